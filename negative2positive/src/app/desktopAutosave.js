@@ -0,0 +1,56 @@
+// Debounced autosave of the desktop session. The backend writes each snapshot
+// atomically, so `recover_last_session` can bring a roll back after the
+// webview crashes.
+
+export const AUTOSAVE_DELAY_MS = 1500;
+
+function createSessionId() {
+  return `${Date.now().toString(36)}-${Math.random().toString(36).slice(2, 10)}`;
+}
+
+// `collectSession` returns the session to save, or null when there is nothing
+// worth recovering; the autosave is then discarded instead.
+export function createAutosaveScheduler({
+  invoke,
+  collectSession,
+  delayMs = AUTOSAVE_DELAY_MS,
+  timers = globalThis
+}) {
+  // Snapshots are ordered per webview load, so a reload never loses to the
+  // stale sequence numbers of the load before it.
+  const sessionId = createSessionId();
+  let sequence = 0;
+  let timer = null;
+
+  function cancel() {
+    if (timer === null) return;
+    timers.clearTimeout(timer);
+    timer = null;
+  }
+
+  async function discard() {
+    cancel();
+    await invoke('discard_autosave_session');
+  }
+
+  async function flush() {
+    cancel();
+    const session = collectSession();
+    if (!session) {
+      await discard();
+      return false;
+    }
+    sequence++;
+    return Boolean(await invoke('autosave_session', { sessionId, sequence, session }));
+  }
+
+  function schedule() {
+    cancel();
+    timer = timers.setTimeout(() => {
+      timer = null;
+      flush().catch((err) => console.warn('Autosave failed:', err));
+    }, delayMs);
+  }
+
+  return { schedule, flush, discard };
+}
@@ -0,0 +1,82 @@
+// Standalone Node test for desktopAutosave.js - run with:
+// node negative2positive/src/app/desktopAutosave.test.mjs
+import assert from 'node:assert/strict';
+import { createAutosaveScheduler } from './desktopAutosave.js';
+
+function createFakeTimers() {
+  let nextId = 1;
+  const pending = new Map();
+  return {
+    setTimeout(fn) {
+      const id = nextId++;
+      pending.set(id, fn);
+      return id;
+    },
+    clearTimeout(id) {
+      pending.delete(id);
+    },
+    runAll() {
+      const callbacks = [...pending.values()];
+      pending.clear();
+      callbacks.forEach((fn) => fn());
+    },
+    get size() {
+      return pending.size;
+    }
+  };
+}
+
+function createFakeInvoke() {
+  const calls = [];
+  const invoke = async (command, args) => {
+    calls.push({ command, args });
+    return command === 'autosave_session' ? true : null;
+  };
+  return { invoke, calls };
+}
+
+// 1. Rapid edits collapse into one write with increasing sequence numbers
+{
+  const timers = createFakeTimers();
+  const { invoke, calls } = createFakeInvoke();
+  let edits = 0;
+  const autosave = createAutosaveScheduler({
+    invoke,
+    timers,
+    collectSession: () => ({ project: { frames: [{ path: '/rolls/01.NEF' }] }, edits })
+  });
+  for (edits = 1; edits <= 5; edits++) autosave.schedule();
+  assert.equal(timers.size, 1);
+  timers.runAll();
+  await new Promise((resolve) => setImmediate(resolve));
+  assert.equal(calls.length, 1);
+  assert.equal(calls[0].command, 'autosave_session');
+  assert.equal(calls[0].args.sequence, 1);
+  assert.equal(calls[0].args.session.edits, 6);
+
+  assert.equal(await autosave.flush(), true);
+  assert.equal(calls[1].args.sequence, 2);
+  assert.equal(calls[1].args.sessionId, calls[0].args.sessionId);
+}
+
+// 2. An empty session discards the autosave instead of writing it
+{
+  const timers = createFakeTimers();
+  const { invoke, calls } = createFakeInvoke();
+  const autosave = createAutosaveScheduler({ invoke, timers, collectSession: () => null });
+  autosave.schedule();
+  assert.equal(await autosave.flush(), false);
+  assert.equal(timers.size, 0, 'flush cancels the pending timer');
+  assert.deepEqual(calls.map((call) => call.command), ['discard_autosave_session']);
+}
+
+// 3. Separate webview loads use separate session ids
+{
+  const { invoke, calls } = createFakeInvoke();
+  const collectSession = () => ({ project: { frames: [] } });
+  await createAutosaveScheduler({ invoke, collectSession }).flush();
+  await createAutosaveScheduler({ invoke, collectSession }).flush();
+  assert.notEqual(calls[0].args.sessionId, calls[1].args.sessionId);
+}
+
+console.log('desktopAutosave tests: all passed');
@@ -0,0 +1,54 @@
+// Desktop-only conversion: the Tauri backend runs the SilverCore pipeline
+// natively through the `convert_frame` command (src-tauri/src/engine), spread
+// across cores and off the webview thread, so roll exports do not stall the UI.
+
+export const FRAME_WIDTH_HEADER = 'x-frame-width';
+export const FRAME_HEIGHT_HEADER = 'x-frame-height';
+export const FRAME_SETTINGS_HEADER = 'x-frame-settings';
+
+// The loader's 16-bit pixels when they still match the frame, otherwise the
+// 8-bit pixels widened (×257) to the RGBA layout of silvercore/util/image16.js,
+// as silverAdapter's `toImage16` does.
+export function frameToImage16(imageData) {
+  const source16 = imageData.__image16;
+  if (
+    source16
+    && source16.data instanceof Uint16Array
+    && source16.width === imageData.width
+    && source16.height === imageData.height
+  ) {
+    return source16;
+  }
+  return {
+    width: imageData.width,
+    height: imageData.height,
+    data: Uint16Array.from(imageData.data, value => value * 257)
+  };
+}
+
+// The response is little-endian 16-bit RGBA, the same size as the request.
+export function parseConvertedFrame(response, width, height) {
+  const buffer = response instanceof ArrayBuffer
+    ? response
+    : response.buffer.slice(response.byteOffset, response.byteOffset + response.byteLength);
+  const data = new Uint16Array(buffer);
+  if (data.length !== width * height * 4) {
+    throw new Error('Converted frame does not match its dimensions.');
+  }
+  return { width, height, data };
+}
+
+// `settings` is a sanitized snapshot (`deepCopySanitizedSettings`) of a frame
+// that is already rotated and cropped. Resolves to the converted Image16.
+export async function convertFrameOnDesktop(invoke, imageData, settings) {
+  const { width, height, data } = frameToImage16(imageData);
+  const bytes = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+  const response = await invoke('convert_frame', bytes, {
+    headers: {
+      [FRAME_WIDTH_HEADER]: String(width),
+      [FRAME_HEIGHT_HEADER]: String(height),
+      [FRAME_SETTINGS_HEADER]: encodeURIComponent(JSON.stringify(settings))
+    }
+  });
+  return parseConvertedFrame(response, width, height);
+}
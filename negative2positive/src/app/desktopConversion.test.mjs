@@ -0,0 +1,59 @@
+// Standalone Node test for desktopConversion.js - run with:
+// node negative2positive/src/app/desktopConversion.test.mjs
+import assert from 'node:assert/strict';
+import {
+  FRAME_HEIGHT_HEADER,
+  FRAME_SETTINGS_HEADER,
+  FRAME_WIDTH_HEADER,
+  convertFrameOnDesktop,
+  frameToImage16,
+  parseConvertedFrame
+} from './desktopConversion.js';
+
+function imageData(width, height, values) {
+  return { width, height, data: Uint8ClampedArray.from(values) };
+}
+
+// 1. The loader's 16-bit pixels are sent as they are while they match the frame
+{
+  const source = imageData(1, 1, [1, 2, 3, 255]);
+  source.__image16 = { width: 1, height: 1, data: Uint16Array.from([300, 600, 900, 65535]) };
+  assert.equal(frameToImage16(source), source.__image16);
+
+  // A rotated or cropped frame no longer matches them and is widened instead.
+  source.__image16 = { width: 2, height: 1, data: new Uint16Array(8) };
+  assert.deepEqual(Array.from(frameToImage16(source).data), [257, 514, 771, 65535]);
+}
+
+// 2. Pixels go over as the raw body, dimensions and settings in headers
+{
+  const calls = [];
+  const invoke = async (command, bytes, options) => {
+    calls.push({ command, bytes, options });
+    const samples = new Uint16Array(bytes.buffer, bytes.byteOffset, bytes.byteLength / 2);
+    // Answer with a Uint8Array view at an odd offset, as a copied IPC body may be.
+    const out = new Uint8Array(bytes.byteLength + 1);
+    out.set(new Uint8Array(Uint16Array.from(samples, value => 65535 - value).buffer), 1);
+    return out.subarray(1);
+  };
+  const frame = imageData(2, 1, [0, 0, 0, 255, 255, 255, 255, 255]);
+  const settings = { filmType: 'color', coreFilmPreset: 'portra-400', cropRegion: null };
+  const converted = await convertFrameOnDesktop(invoke, frame, settings);
+
+  assert.equal(calls.length, 1);
+  assert.equal(calls[0].command, 'convert_frame');
+  assert.equal(calls[0].bytes.byteLength, 2 * 4 * 2);
+  const { headers } = calls[0].options;
+  assert.equal(headers[FRAME_WIDTH_HEADER], '2');
+  assert.equal(headers[FRAME_HEIGHT_HEADER], '1');
+  assert.deepEqual(JSON.parse(decodeURIComponent(headers[FRAME_SETTINGS_HEADER])), settings);
+  assert.equal(converted.width, 2);
+  assert.deepEqual(Array.from(converted.data), [65535, 65535, 65535, 0, 0, 0, 0, 0]);
+}
+
+// 3. A response of the wrong size is refused
+{
+  assert.throws(() => parseConvertedFrame(new ArrayBuffer(6), 1, 1), /dimensions/);
+}
+
+console.log('desktopConversion tests: all passed');
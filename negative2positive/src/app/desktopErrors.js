@@ -0,0 +1,64 @@
+// Desktop commands reject with `{ code, message, retryable }` (see
+// src-tauri/src/error.rs). These helpers turn that into something a user can
+// act on and decide when an operation is worth trying again.
+
+export const DESKTOP_RETRY_ATTEMPTS = 3;
+export const DESKTOP_RETRY_DELAY_MS = 400;
+
+const ERROR_TEXT = {
+  NotFound: ['errorNotFound', 'The file or folder could not be found.'],
+  PermissionDenied: ['errorPermissionDenied', 'Permission denied. Choose a folder you can write to.'],
+  DiskFull: ['errorDiskFull', 'The disk is full. Free up space or choose another drive.'],
+  InvalidPath: ['errorInvalidPath', 'The path is not valid for this file.'],
+  AlreadyExists: ['errorAlreadyExists', 'The file already exists and replacing it was not confirmed.'],
+  DecodeFailed: ['errorDecodeFailed', 'The file could not be read. It may be damaged or unsupported.'],
+  Cancelled: ['errorCancelled', 'The operation was cancelled.'],
+  Io: ['errorIo', 'A read or write failed. Check the drive and try again.']
+};
+
+// Errors that will fail every remaining file of a batch the same way.
+const BATCH_FATAL_CODES = new Set(['DiskFull', 'PermissionDenied', 'InvalidPath']);
+
+// Normalizes anything a command can reject with: structured command errors,
+// plain strings from Tauri itself (e.g. an unknown command) and JS errors.
+export function parseDesktopError(err) {
+  if (err && typeof err === 'object' && typeof err.code === 'string') {
+    return {
+      code: err.code,
+      message: String(err.message || ''),
+      retryable: Boolean(err.retryable)
+    };
+  }
+  const message = err && err.message ? err.message : String(err || 'Unknown error');
+  return { code: 'Internal', message, retryable: false };
+}
+
+// `getText(key, fallback)` looks up localized text. Unclassified errors keep
+// the backend message, which is the most specific thing available.
+export function describeDesktopError(err, getText = (_key, fallback) => fallback) {
+  const { code, message } = parseDesktopError(err);
+  const entry = ERROR_TEXT[code];
+  if (!entry) return message;
+  const hint = getText(entry[0], entry[1]);
+  return message ? `${hint} (${message})` : hint;
+}
+
+export function isBatchFatalDesktopError(err) {
+  return BATCH_FATAL_CODES.has(parseDesktopError(err).code);
+}
+
+// Runs `operation` and retries it only while it fails with a retryable error.
+export async function withDesktopRetry(operation, {
+  attempts = DESKTOP_RETRY_ATTEMPTS,
+  delayMs = DESKTOP_RETRY_DELAY_MS,
+  wait = (ms) => new Promise((resolve) => setTimeout(resolve, ms))
+} = {}) {
+  for (let attempt = 1; ; attempt++) {
+    try {
+      return await operation();
+    } catch (err) {
+      if (attempt >= attempts || !parseDesktopError(err).retryable) throw err;
+      await wait(delayMs * attempt);
+    }
+  }
+}
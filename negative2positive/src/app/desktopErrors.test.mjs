@@ -0,0 +1,76 @@
+// Standalone Node test for desktopErrors.js - run with:
+// node negative2positive/src/app/desktopErrors.test.mjs
+import assert from 'node:assert/strict';
+import {
+  describeDesktopError,
+  isBatchFatalDesktopError,
+  parseDesktopError,
+  withDesktopRetry
+} from './desktopErrors.js';
+
+const diskFull = { code: 'DiskFull', message: 'write file failed: No space left on device', retryable: false };
+const shareDropped = { code: 'Io', message: 'write file failed: timed out', retryable: true };
+
+// ---- parsing ----
+assert.deepEqual(parseDesktopError(diskFull), diskFull);
+assert.deepEqual(
+  parseDesktopError('Command begin_export_upload not found'),
+  { code: 'Internal', message: 'Command begin_export_upload not found', retryable: false }
+);
+assert.deepEqual(
+  parseDesktopError(new Error('boom')),
+  { code: 'Internal', message: 'boom', retryable: false }
+);
+assert.equal(parseDesktopError(null).message, 'Unknown error');
+
+// ---- descriptions ----
+assert.equal(
+  describeDesktopError(diskFull),
+  'The disk is full. Free up space or choose another drive. (write file failed: No space left on device)'
+);
+assert.equal(
+  describeDesktopError(diskFull, (key) => `[${key}]`),
+  '[errorDiskFull] (write file failed: No space left on device)'
+);
+assert.equal(describeDesktopError({ code: 'Internal', message: 'bad header' }), 'bad header');
+assert.equal(describeDesktopError('plain failure'), 'plain failure');
+assert.equal(
+  describeDesktopError({ code: 'AlreadyExists', message: '' }),
+  'The file already exists and replacing it was not confirmed.'
+);
+
+// ---- batch handling ----
+assert.equal(isBatchFatalDesktopError(diskFull), true);
+assert.equal(isBatchFatalDesktopError({ code: 'PermissionDenied', message: '' }), true);
+assert.equal(isBatchFatalDesktopError(shareDropped), false);
+assert.equal(isBatchFatalDesktopError({ code: 'DecodeFailed', message: '' }), false);
+
+// ---- retries ----
+const waits = [];
+const wait = async (ms) => { waits.push(ms); };
+
+let calls = 0;
+const result = await withDesktopRetry(async () => {
+  calls++;
+  if (calls < 3) throw shareDropped;
+  return 'written';
+}, { attempts: 3, delayMs: 10, wait });
+assert.equal(result, 'written');
+assert.equal(calls, 3);
+assert.deepEqual(waits, [10, 20]);
+
+calls = 0;
+await assert.rejects(
+  withDesktopRetry(async () => { calls++; throw diskFull; }, { wait }),
+  (err) => err === diskFull
+);
+assert.equal(calls, 1, 'non-retryable errors are not retried');
+
+calls = 0;
+await assert.rejects(
+  withDesktopRetry(async () => { calls++; throw shareDropped; }, { attempts: 2, wait }),
+  (err) => err === shareDropped
+);
+assert.equal(calls, 2, 'retryable errors give up after the last attempt');
+
+console.log('desktopErrors tests: all passed');
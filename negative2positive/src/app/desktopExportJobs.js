@@ -0,0 +1,99 @@
+// Background export jobs on desktop (see src-tauri/src/export_jobs.rs). Files
+// are queued with `queue_export_file` and written on a backend worker thread;
+// each one reports back through `export://done` or `export://error`, so the
+// webview can move on to the next frame while the last one is written.
+
+export const EXPORT_PROGRESS_EVENT = 'export://progress';
+export const EXPORT_DONE_EVENT = 'export://done';
+export const EXPORT_ERROR_EVENT = 'export://error';
+export const EXPORT_ITEM_HEADER = 'x-export-item';
+
+function isMissingCommand(err, command) {
+  return new RegExp(command).test(String(err && err.message ? err.message : err));
+}
+
+// `invoke` and `listen` are `window.__TAURI__.core.invoke` and
+// `window.__TAURI__.event.listen`. Resolves to null when the backend has no
+// export jobs, so callers can keep writing file by file.
+export async function startDesktopExportJob({ invoke, listen }, total, {
+  onProgress = () => {},
+  onDone = () => {},
+  onError = () => {}
+} = {}) {
+  let jobId;
+  try {
+    jobId = await invoke('begin_export_job', { total });
+  } catch (err) {
+    if (isMissingCommand(err, 'begin_export_job')) return null;
+    throw err;
+  }
+
+  const settled = new Set();
+  const queued = new Set();
+  let waiters = [];
+  let resolveFinished;
+  const finished = new Promise((resolve) => { resolveFinished = resolve; });
+  const pending = () => [...queued].filter((index) => !settled.has(index)).length;
+  const settle = (index) => {
+    settled.add(index);
+    waiters = waiters.filter(({ limit, resolve }) => {
+      if (pending() >= limit) return true;
+      resolve();
+      return false;
+    });
+    if (settled.size >= total) resolveFinished();
+  };
+  const forJob = (handler) => ({ payload }) => {
+    if (!payload || payload.jobId !== jobId) return;
+    handler(payload);
+  };
+
+  const unlisten = await Promise.all([
+    listen(EXPORT_PROGRESS_EVENT, forJob(onProgress)),
+    listen(EXPORT_DONE_EVENT, forJob((payload) => {
+      if (settled.has(payload.index)) return;
+      onDone(payload);
+      settle(payload.index);
+    })),
+    listen(EXPORT_ERROR_EVENT, forJob((payload) => {
+      if (settled.has(payload.index)) return;
+      onError(payload);
+      settle(payload.index);
+    }))
+  ]);
+
+  return {
+    jobId,
+    // `item` carries `target`, `metadata`, `verify` and, for raw TIFF
+    // samples, `tiff`. Resolves once the file is queued, not written.
+    queueFile(index, bytes, item) {
+      queued.add(index);
+      return invoke('queue_export_file', bytes, {
+        headers: {
+          [EXPORT_ITEM_HEADER]: encodeURIComponent(JSON.stringify({ ...item, jobId, index }))
+        }
+      });
+    },
+    // Resolves once fewer than `limit` queued files are still unwritten, so
+    // encoded frames do not pile up in the backend faster than they land.
+    whenPendingBelow(limit) {
+      if (pending() < limit) return Promise.resolve();
+      return new Promise((resolve) => { waiters.push({ limit, resolve }); });
+    },
+    // For files that never reach the queue (failed before encoding, or
+    // dropped after a cancel), so `finished` still resolves and the backend
+    // ends the job. A cancelled job has already ended there.
+    settle(index) {
+      if (settled.has(index)) return;
+      settle(index);
+      invoke('settle_export_file', { jobId, index }).catch(() => {});
+    },
+    cancel() {
+      return invoke('cancel_export_job', { jobId });
+    },
+    finished,
+    dispose() {
+      unlisten.forEach((stop) => stop());
+    }
+  };
+}
@@ -0,0 +1,118 @@
+// Standalone Node test for desktopExportJobs.js - run with:
+// node negative2positive/src/app/desktopExportJobs.test.mjs
+import assert from 'node:assert/strict';
+import {
+  EXPORT_DONE_EVENT,
+  EXPORT_ERROR_EVENT,
+  EXPORT_ITEM_HEADER,
+  EXPORT_PROGRESS_EVENT,
+  startDesktopExportJob
+} from './desktopExportJobs.js';
+
+function createBackend() {
+  const listeners = new Map();
+  const calls = [];
+  return {
+    calls,
+    emit(event, payload) {
+      (listeners.get(event) || []).forEach((handler) => handler({ payload }));
+    },
+    listenerCount() {
+      return [...listeners.values()].reduce((sum, handlers) => sum + handlers.length, 0);
+    },
+    api: {
+      async invoke(command, args, options) {
+        calls.push({ command, args, options });
+        if (command === 'begin_export_job') return 7;
+        if (command === 'cancel_export_job') return true;
+        return null;
+      },
+      async listen(event, handler) {
+        if (!listeners.has(event)) listeners.set(event, []);
+        listeners.get(event).push(handler);
+        return () => {
+          listeners.set(event, listeners.get(event).filter((entry) => entry !== handler));
+        };
+      }
+    }
+  };
+}
+
+// ---- events are routed per job and `finished` waits for every file ----
+{
+  const backend = createBackend();
+  const seen = [];
+  const job = await startDesktopExportJob(backend.api, 3, {
+    onProgress: (payload) => seen.push(['progress', payload.index, payload.stage]),
+    onDone: (payload) => seen.push(['done', payload.index, payload.result.path]),
+    onError: (payload) => seen.push(['error', payload.index, payload.error.code])
+  });
+  assert.equal(job.jobId, 7);
+  assert.deepEqual(backend.calls[0], { command: 'begin_export_job', args: { total: 3 }, options: undefined });
+
+  const bytes = new Uint8Array([1, 2, 3]);
+  await job.queueFile(0, bytes, { target: { kind: 'directory', directory: '/out', suggestedName: 'a.png' } });
+  const queued = backend.calls[1];
+  assert.equal(queued.command, 'queue_export_file');
+  assert.equal(queued.args, bytes);
+  assert.deepEqual(JSON.parse(decodeURIComponent(queued.options.headers[EXPORT_ITEM_HEADER])), {
+    target: { kind: 'directory', directory: '/out', suggestedName: 'a.png' },
+    jobId: 7,
+    index: 0
+  });
+
+  let drained = false;
+  job.whenPendingBelow(1).then(() => { drained = true; });
+  await job.whenPendingBelow(2);
+
+  let finished = false;
+  job.finished.then(() => { finished = true; });
+  backend.emit(EXPORT_PROGRESS_EVENT, { jobId: 7, index: 0, stage: 'writing' });
+  backend.emit(EXPORT_DONE_EVENT, { jobId: 99, index: 1, result: { path: '/elsewhere' } });
+  backend.emit(EXPORT_DONE_EVENT, { jobId: 7, index: 0, result: { path: '/out/a.png' } });
+  backend.emit(EXPORT_ERROR_EVENT, { jobId: 7, index: 1, error: { code: 'DiskFull' } });
+  backend.emit(EXPORT_ERROR_EVENT, { jobId: 7, index: 1, error: { code: 'DiskFull' } });
+  await Promise.resolve();
+  assert.equal(drained, true);
+  assert.equal(finished, false);
+  job.settle(2);
+  await job.finished;
+  assert.deepEqual(backend.calls.at(-1), {
+    command: 'settle_export_file',
+    args: { jobId: 7, index: 2 },
+    options: undefined
+  });
+  // Files the backend already reported are not settled there again.
+  const callCount = backend.calls.length;
+  job.settle(0);
+  assert.equal(backend.calls.length, callCount);
+  assert.deepEqual(seen, [
+    ['progress', 0, 'writing'],
+    ['done', 0, '/out/a.png'],
+    ['error', 1, 'DiskFull']
+  ]);
+
+  assert.equal(await job.cancel(), true);
+  assert.deepEqual(backend.calls.at(-1).args, { jobId: 7 });
+  job.dispose();
+  assert.equal(backend.listenerCount(), 0);
+}
+
+// ---- older backends without export jobs ----
+{
+  const job = await startDesktopExportJob({
+    invoke: async (command) => { throw new Error(`Command ${command} not found`); },
+    listen: async () => () => {}
+  }, 2);
+  assert.equal(job, null);
+
+  await assert.rejects(
+    startDesktopExportJob({
+      invoke: async () => { throw { code: 'Internal', message: 'export job has no files' }; },
+      listen: async () => () => {}
+    }, 0),
+    (err) => err.code === 'Internal'
+  );
+}
+
+console.log('desktopExportJobs tests: all passed');
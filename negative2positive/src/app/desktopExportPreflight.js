@@ -0,0 +1,87 @@
+// Checks a batch export's target folder on desktop before anything is
+// written (see src-tauri/src/export_preflight.rs): free space against an
+// estimate of the batch, writability, filesystem limits and names that only
+// differ by case from files already there.
+
+// Frames of unknown size are assumed to be 24 MP scans.
+const FALLBACK_PIXELS = 6000 * 4000;
+// Atomic writes keep a partial file next to the target, and estimates are rough.
+const HEADROOM_PERCENT = 10;
+
+function bytesPerPixel(format, bitDepth, jpegQuality) {
+  if (format === 'jpeg') return 0.25 + 0.5 * Math.max(0, Math.min(1, Number(jpegQuality) || 0.92));
+  const raw = 3 * (bitDepth === 16 ? 2 : 1);
+  // TIFF compression is not counted on, PNG usually saves about 40%.
+  return format === 'tiff' ? raw : raw * 0.6;
+}
+
+/** Rough size of a batch of `count` frames of `width` x `height` pixels. */
+export function estimateExportBytes({ format, bitDepth, jpegQuality, width, height, count }) {
+  const pixels = width > 0 && height > 0 ? width * height : FALLBACK_PIXELS;
+  const bytes = pixels * bytesPerPixel(format, bitDepth, jpegQuality) * Math.max(0, count);
+  return Math.ceil(bytes * (100 + HEADROOM_PERCENT) / 100);
+}
+
+/** "1.5 GB" style sizes for warnings. */
+export function formatByteSize(bytes) {
+  const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+  let value = Math.max(0, Number(bytes) || 0);
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return `${unit === 0 ? value : value.toFixed(1)} ${units[unit]}`;
+}
+
+// Resolves to null when the backend has no preflight, so exports go ahead
+// unchecked as before.
+export async function runDesktopExportPreflight(invoke, { directory, estimatedBytes, count, files }) {
+  try {
+    return await invoke('preflight_export', { directory, estimatedBytes, count, files });
+  } catch (err) {
+    if (/preflight_export/.test(String(err && err.message ? err.message : err))) return null;
+    throw err;
+  }
+}
+
+/**
+ * One line per warning in `report`. `text(key, vars, fallback)` localizes;
+ * collisions list at most three names.
+ */
+export function describePreflightWarnings(report, text) {
+  if (!report || !Array.isArray(report.warnings)) return [];
+  const baseName = (path) => String(path || '').split(/[\\/]/).pop();
+  return report.warnings.map((warning) => {
+    switch (warning) {
+      case 'insufficient-space':
+        return text('preflightInsufficientSpace', {
+          needed: formatByteSize(report.estimatedBytes),
+          free: formatByteSize(report.freeBytes)
+        }, 'About {needed} is needed but only {free} is free.');
+      case 'not-writable':
+        return text('preflightNotWritable', {}, 'The folder cannot be written to.');
+      case 'file-too-large':
+        return text('preflightFileTooLarge', {
+          fileSystem: report.fileSystem || '',
+          limit: formatByteSize(report.maxFileBytes)
+        }, 'Files may exceed the {limit} limit of {fileSystem}.');
+      case 'name-collisions': {
+        const names = report.collisions.slice(0, 3)
+          .map((collision) => `${baseName(collision.path)} / ${baseName(collision.existing)}`)
+          .join(', ');
+        return text('preflightNameCollisions', {
+          count: report.collisions.length,
+          names
+        }, '{count} name(s) differ only by case from other files: {names}');
+      }
+      case 'name-too-long':
+        return text('preflightNameTooLong', {
+          count: report.longNames.length,
+          limit: report.maxFileNameLength
+        }, '{count} file name(s) exceed the {limit} character limit.');
+      default:
+        return String(warning);
+    }
+  });
+}
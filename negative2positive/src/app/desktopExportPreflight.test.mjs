@@ -0,0 +1,77 @@
+// Standalone Node test for desktopExportPreflight.js - run with:
+// node negative2positive/src/app/desktopExportPreflight.test.mjs
+import assert from 'node:assert/strict';
+import {
+  describePreflightWarnings,
+  estimateExportBytes,
+  formatByteSize,
+  runDesktopExportPreflight
+} from './desktopExportPreflight.js';
+import { interpolateText } from './textUtils.js';
+
+// ---- estimates scale with format, depth and count ----
+{
+  const frame = { width: 1000, height: 1000, count: 1 };
+  assert.equal(estimateExportBytes({ ...frame, format: 'tiff', bitDepth: 8 }), 3300000);
+  assert.equal(estimateExportBytes({ ...frame, format: 'tiff', bitDepth: 16 }), 6600000);
+  assert.equal(
+    estimateExportBytes({ ...frame, format: 'tiff', bitDepth: 16, count: 36 }),
+    36 * estimateExportBytes({ ...frame, format: 'tiff', bitDepth: 16 })
+  );
+  assert.ok(estimateExportBytes({ ...frame, format: 'png', bitDepth: 8 }) < 3300000);
+  assert.ok(estimateExportBytes({ ...frame, format: 'jpeg', jpegQuality: 0.5 })
+    < estimateExportBytes({ ...frame, format: 'jpeg', jpegQuality: 1 }));
+  assert.equal(
+    estimateExportBytes({ format: 'tiff', bitDepth: 8, count: 1 }),
+    estimateExportBytes({ format: 'tiff', bitDepth: 8, width: 6000, height: 4000, count: 1 })
+  );
+}
+
+// ---- sizes ----
+assert.equal(formatByteSize(512), '512 B');
+assert.equal(formatByteSize(1536), '1.5 KB');
+assert.equal(formatByteSize(4 * 1024 ** 3), '4.0 GB');
+
+// ---- warnings read as sentences ----
+{
+  const text = (key, vars, fallback) => interpolateText(fallback, vars);
+  const lines = describePreflightWarnings({
+    warnings: ['insufficient-space', 'name-collisions', 'file-too-large'],
+    estimatedBytes: 4 * 1024 ** 3,
+    freeBytes: 1024 ** 3,
+    fileSystem: 'vfat',
+    maxFileBytes: 4 * 1024 ** 3 - 1,
+    collisions: [{ path: '/out/roll_001.tif', existing: '/out/Roll_001.tif', withinBatch: false }],
+    longNames: []
+  }, text);
+  assert.deepEqual(lines, [
+    'About 4.0 GB is needed but only 1.0 GB is free.',
+    '1 name(s) differ only by case from other files: roll_001.tif / Roll_001.tif',
+    'Files may exceed the 4.0 GB limit of vfat.'
+  ]);
+  assert.deepEqual(describePreflightWarnings(null, text), []);
+}
+
+// ---- older backends skip the check ----
+{
+  assert.equal(await runDesktopExportPreflight(async (command) => {
+    throw new Error(`Command ${command} not found`);
+  }, { directory: '/out', estimatedBytes: 1, count: 1 }), null);
+
+  let args = null;
+  const report = await runDesktopExportPreflight(async (command, payload) => {
+    args = payload;
+    return { warnings: [] };
+  }, { directory: '/out', estimatedBytes: 10, count: 2, files: [] });
+  assert.deepEqual(report, { warnings: [] });
+  assert.deepEqual(args, { directory: '/out', estimatedBytes: 10, count: 2, files: [] });
+
+  await assert.rejects(
+    runDesktopExportPreflight(async () => { throw { code: 'NotFound', message: 'export directory not found' }; }, {
+      directory: '/gone', estimatedBytes: 1, count: 1
+    }),
+    (err) => err.code === 'NotFound'
+  );
+}
+
+console.log('desktopExportPreflight tests: all passed');
@@ -0,0 +1,64 @@
+// User film presets on desktop (see src-tauri/src/preset_store.rs): presets
+// saved from the current look into the app's config folder, next to the
+// bundled ones from film-presets.json. User presets have `user:<slug>` ids.
+
+const USER_PRESET_PREFIX = 'user:';
+
+// Preset settings mirrored by the core sliders, with the ranges the backend
+// accepts.
+const STATE_SETTINGS = [
+  ['saturation', 'coreSaturation', 0, 200],
+  ['glow', 'coreGlow', 0, 100],
+  ['fade', 'coreFade', 0, 100],
+  ['shadows', 'coreShadows', -100, 100],
+  ['highlights', 'coreHighlights', -100, 100],
+  ['blacks', 'coreBlacks', -100, 100],
+  ['whites', 'coreWhites', -100, 100]
+];
+
+const CATEGORIES = new Set(['color', 'bw', 'positive']);
+
+export function isUserPresetId(id) {
+  return typeof id === 'string' && id.startsWith(USER_PRESET_PREFIX) && id.length > USER_PRESET_PREFIX.length;
+}
+
+// Resolves to null when the backend has no preset commands.
+export async function listDesktopFilmPresets(invoke) {
+  try {
+    const presets = await invoke('list_film_presets');
+    return Array.isArray(presets) ? presets : [];
+  } catch (err) {
+    if (/list_film_presets/.test(String(err && err.message ? err.message : err))) return null;
+    throw err;
+  }
+}
+
+/** Presets keyed by id, shaped like `loadFilmPresets()` entries. */
+export function userFilmPresetMap(presets) {
+  const map = {};
+  (Array.isArray(presets) ? presets : []).forEach((preset) => {
+    if (!preset || !isUserPresetId(preset.id)) return;
+    map[preset.id] = { name: preset.name, category: preset.category, settings: preset.settings || {} };
+  });
+  return map;
+}
+
+/**
+ * The preset to save for the current look: the settings of the preset it
+ * started from, with the values the core controls override.
+ */
+export function buildFilmPresetDraft({ id = null, name, filmType, baseSettings = {}, state = {} }) {
+  const settings = { ...baseSettings };
+  const profile = state.coreEnhancedProfile;
+  if (typeof profile === 'string' && profile) settings.enhancedProfile = profile;
+  STATE_SETTINGS.forEach(([key, stateKey, min, max]) => {
+    const value = Number(state[stateKey]);
+    if (Number.isFinite(value)) settings[key] = Math.min(max, Math.max(min, value));
+  });
+  return {
+    id: isUserPresetId(id) ? id : null,
+    name: String(name || '').trim(),
+    category: CATEGORIES.has(filmType) ? filmType : 'color',
+    settings
+  };
+}
@@ -0,0 +1,75 @@
+// Standalone Node test for desktopFilmPresets.js - run with:
+// node negative2positive/src/app/desktopFilmPresets.test.mjs
+import assert from 'node:assert/strict';
+import {
+  buildFilmPresetDraft,
+  isUserPresetId,
+  listDesktopFilmPresets,
+  userFilmPresetMap
+} from './desktopFilmPresets.js';
+
+// ---- ids ----
+assert.equal(isUserPresetId('user:warm-portra'), true);
+assert.equal(isUserPresetId('user:'), false);
+assert.equal(isUserPresetId('frontier-lab'), false);
+assert.equal(isUserPresetId(null), false);
+
+// ---- listed presets look like bundled ones ----
+assert.deepEqual(userFilmPresetMap([
+  { id: 'user:warm', name: 'Warm', category: 'color', settings: { glow: 10 }, updatedAt: 1 },
+  { id: 'frontier-lab', name: 'Frontier Lab', category: 'color', settings: {} }
+]), {
+  'user:warm': { name: 'Warm', category: 'color', settings: { glow: 10 } }
+});
+assert.deepEqual(userFilmPresetMap(undefined), {});
+
+// ---- older backends have no preset commands ----
+assert.equal(await listDesktopFilmPresets(async (command) => {
+  throw new Error(`Command ${command} not found`);
+}), null);
+await assert.rejects(
+  listDesktopFilmPresets(async () => { throw { code: 'Io', message: 'disk gone' }; }),
+  (err) => err.code === 'Io'
+);
+
+// ---- drafts keep the base preset and take the slider values ----
+{
+  const draft = buildFilmPresetDraft({
+    name: '  Warm Portra ',
+    filmType: 'color',
+    baseSettings: { toneProfile: 'base', shadows: -24, glow: 12, layerOrder: 'colorFirst' },
+    state: {
+      coreEnhancedProfile: 'user:Warm.cube',
+      coreSaturation: 250,
+      coreGlow: 20,
+      coreFade: 5,
+      coreShadows: -10,
+      coreHighlights: 0,
+      coreBlacks: 3,
+      coreWhites: 'x'
+    }
+  });
+  assert.deepEqual(draft, {
+    id: null,
+    name: 'Warm Portra',
+    category: 'color',
+    settings: {
+      toneProfile: 'base',
+      layerOrder: 'colorFirst',
+      enhancedProfile: 'user:Warm.cube',
+      saturation: 200,
+      glow: 20,
+      fade: 5,
+      shadows: -10,
+      highlights: 0,
+      blacks: 3
+    }
+  });
+
+  const update = buildFilmPresetDraft({ id: 'user:warm', name: 'Warm', filmType: 'slide', state: {} });
+  assert.equal(update.id, 'user:warm');
+  assert.equal(update.category, 'color');
+  assert.equal(buildFilmPresetDraft({ id: 'frontier-lab', name: 'x' }).id, null);
+}
+
+console.log('desktopFilmPresets tests: all passed');
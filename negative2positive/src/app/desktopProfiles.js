@@ -0,0 +1,42 @@
+// Enhanced profiles on desktop (see src-tauri/src/profiles.rs): the built-in
+// scanner LUTs plus .cube/.3dl/.bin LUTs imported into the app's data folder.
+// Imported profiles have `user:<file name>` ids.
+
+const USER_PROFILE_PREFIX = 'user:';
+
+export function isUserProfileId(id) {
+  return typeof id === 'string' && id.startsWith(USER_PROFILE_PREFIX) && id.length > USER_PROFILE_PREFIX.length;
+}
+
+// Resolves to null when the backend has no profile commands.
+export async function listDesktopProfiles(invoke) {
+  try {
+    const profiles = await invoke('list_profiles');
+    return Array.isArray(profiles) ? profiles : [];
+  } catch (err) {
+    if (/list_profiles/.test(String(err && err.message ? err.message : err))) return null;
+    throw err;
+  }
+}
+
+/** Imported profiles only, in the order the backend lists them. */
+export function userProfileOptions(profiles) {
+  return (Array.isArray(profiles) ? profiles : [])
+    .filter((profile) => profile && !profile.builtin && isUserProfileId(profile.id))
+    .map((profile) => ({ value: profile.id, label: profile.name || profile.id.slice(USER_PROFILE_PREFIX.length) }));
+}
+
+/**
+ * Loads a profile as `EnhancedProfiles.js` applies it. The backend returns
+ * an sRGB-to-sRGB table, so it serves as the baked data as is.
+ */
+export async function readDesktopProfile(invoke, id) {
+  const result = await invoke('read_profile', { id });
+  const bytes = result instanceof ArrayBuffer ? new Uint8Array(result) : Uint8Array.from(result);
+  const data = new Uint16Array(bytes.buffer, bytes.byteOffset, bytes.byteLength >> 1);
+  const size = Math.round(Math.cbrt(data.length / 3));
+  if (size < 2 || size * size * size * 3 !== data.length) {
+    throw new Error(`Profile ${id} is not a 3D LUT`);
+  }
+  return { name: id, data, size, bakedData: data };
+}
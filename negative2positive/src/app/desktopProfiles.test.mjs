@@ -0,0 +1,56 @@
+// Standalone Node test for desktopProfiles.js - run with:
+// node negative2positive/src/app/desktopProfiles.test.mjs
+import assert from 'node:assert/strict';
+import {
+  isUserProfileId,
+  listDesktopProfiles,
+  readDesktopProfile,
+  userProfileOptions
+} from './desktopProfiles.js';
+
+// ---- ids ----
+assert.equal(isUserProfileId('user:Portra.cube'), true);
+assert.equal(isUserProfileId('user:'), false);
+assert.equal(isUserProfileId('frontier'), false);
+assert.equal(isUserProfileId(undefined), false);
+
+// ---- only imported profiles become options ----
+assert.deepEqual(userProfileOptions([
+  { id: 'frontier', name: 'frontier', builtin: true, format: 'bin', size: 32 },
+  { id: 'user:Warm.cube', name: 'Warm', builtin: false, format: 'cube', size: 33 },
+  { id: 'user:Cold.3dl', name: '', builtin: false, format: '3dl', size: 17 }
+]), [
+  { value: 'user:Warm.cube', label: 'Warm' },
+  { value: 'user:Cold.3dl', label: 'Cold.3dl' }
+]);
+assert.deepEqual(userProfileOptions(null), []);
+
+// ---- older backends have no profile commands ----
+assert.equal(await listDesktopProfiles(async (command) => {
+  throw new Error(`Command ${command} not found`);
+}), null);
+await assert.rejects(
+  listDesktopProfiles(async () => { throw { code: 'Io', message: 'disk gone' }; }),
+  (err) => err.code === 'Io'
+);
+
+// ---- profiles arrive as baked u16 cubes ----
+{
+  const samples = new Uint16Array(2 * 2 * 2 * 3).map((_, i) => i * 1000);
+  let args = null;
+  const profile = await readDesktopProfile(async (command, payload) => {
+    args = { command, payload };
+    return samples.buffer.slice(0);
+  }, 'user:Warm.cube');
+  assert.deepEqual(args, { command: 'read_profile', payload: { id: 'user:Warm.cube' } });
+  assert.equal(profile.size, 2);
+  assert.deepEqual(Array.from(profile.bakedData), Array.from(samples));
+  assert.equal(profile.data, profile.bakedData);
+
+  await assert.rejects(
+    readDesktopProfile(async () => new Uint16Array(10).buffer, 'user:bad.cube'),
+    /not a 3D LUT/
+  );
+}
+
+console.log('desktopProfiles tests: all passed');
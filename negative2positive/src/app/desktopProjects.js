@@ -0,0 +1,124 @@
+// `.ncroll` roll projects on desktop: turning the file queue into the payload
+// `save_project` writes, and an `open_project` result back into queue items.
+
+import { toSerializableSettings } from './settingsSnapshot.js';
+
+const EXPORT_FORMATS = ['png', 'jpeg', 'tiff'];
+const TIFF_COMPRESSIONS = ['none', 'lzw', 'deflate'];
+const MAX_FILENAME_TEMPLATE_LENGTH = 160;
+const CONFLICT_POLICIES = ['rename', 'overwrite', 'skip', 'overwrite-if-older', 'ask'];
+
+export function collectExportPreferences(state) {
+  return {
+    exportFormat: state.exportFormat,
+    exportBitDepth: state.exportBitDepth,
+    jpegQuality: state.jpegQuality,
+    tiffCompression: state.tiffCompression,
+    exportSprocketHolesEnabled: Boolean(state.exportSprocketHolesEnabled),
+    exportFilenameTemplate: state.exportFilenameTemplate || '',
+    exportSubfolderPattern: state.exportSubfolderPattern || '',
+    exportConflictPolicy: state.exportConflictPolicy || 'rename'
+  };
+}
+
+// Copies only well-formed values, so a hand-edited project cannot put the
+// export menu into a state the UI has no button for.
+export function applyExportPreferences(state, preferences) {
+  if (!preferences || typeof preferences !== 'object') return;
+  if (EXPORT_FORMATS.includes(preferences.exportFormat)) {
+    state.exportFormat = preferences.exportFormat;
+  }
+  if (preferences.exportBitDepth === 8 || preferences.exportBitDepth === 16) {
+    state.exportBitDepth = preferences.exportBitDepth;
+  }
+  if (Number.isFinite(preferences.jpegQuality)) {
+    state.jpegQuality = Math.min(100, Math.max(1, Math.round(preferences.jpegQuality)));
+  }
+  if (TIFF_COMPRESSIONS.includes(preferences.tiffCompression)) {
+    state.tiffCompression = preferences.tiffCompression;
+  }
+  if (typeof preferences.exportSprocketHolesEnabled === 'boolean') {
+    state.exportSprocketHolesEnabled = preferences.exportSprocketHolesEnabled;
+  }
+  if (typeof preferences.exportFilenameTemplate === 'string') {
+    state.exportFilenameTemplate = preferences.exportFilenameTemplate.trim().slice(0, MAX_FILENAME_TEMPLATE_LENGTH);
+  }
+  if (typeof preferences.exportSubfolderPattern === 'string') {
+    state.exportSubfolderPattern = preferences.exportSubfolderPattern.trim().slice(0, MAX_FILENAME_TEMPLATE_LENGTH);
+  }
+  if (CONFLICT_POLICIES.includes(preferences.exportConflictPolicy)) {
+    state.exportConflictPolicy = preferences.exportConflictPolicy;
+  }
+}
+
+// Only items opened from disk can be reopened later; the rest are counted in
+// `skipped` so the caller can say so.
+export function buildRollProject({ name = '', queue = [], currentIndex = 0, rollReference = null, preferences = null }) {
+  const frames = [];
+  let skipped = 0;
+  let currentFrame = 0;
+  queue.forEach((item, index) => {
+    if (!item || !item.sourcePath) {
+      skipped++;
+      return;
+    }
+    if (index === currentIndex) currentFrame = frames.length;
+    frames.push({
+      path: item.sourcePath,
+      selected: item.selected !== false,
+      settings: item.settings ? toSerializableSettings(item.settings) : null
+    });
+  });
+
+  let reference = null;
+  if (rollReference && rollReference.enabled && rollReference.settingsSnapshot) {
+    const sourceItem = queue.find((item) => item && item.id === rollReference.sourceFileId);
+    reference = {
+      sourcePath: sourceItem && sourceItem.sourcePath ? sourceItem.sourcePath : null,
+      settings: toSerializableSettings(rollReference.settingsSnapshot),
+      applyLock: Boolean(rollReference.applyLock),
+      applyCrop: Boolean(rollReference.applyCrop)
+    };
+  }
+
+  return {
+    project: {
+      name,
+      frames,
+      currentFrame,
+      rollReference: reference,
+      exportPreferences: preferences || {}
+    },
+    skipped
+  };
+}
+
+// `createFile` turns an `InputFileEntry` into a queue file handle.
+export function restoreRollProject(opened, createFile) {
+  const project = opened && opened.project ? opened.project : {};
+  const frames = Array.isArray(opened && opened.frames) ? opened.frames : [];
+  const items = frames.map((frame) => ({
+    file: createFile(frame.file),
+    sourcePath: frame.file.path,
+    selected: frame.selected !== false,
+    settings: frame.settings || null
+  }));
+
+  const savedFrames = Array.isArray(project.frames) ? project.frames : [];
+  const currentPath = savedFrames[project.currentFrame]?.path;
+  const currentIndex = Math.max(0, items.findIndex((item) => item.sourcePath === currentPath));
+
+  const reference = project.rollReference;
+  return {
+    items,
+    currentIndex,
+    rollReference: reference && reference.settings ? {
+      sourcePath: reference.sourcePath || null,
+      settingsSnapshot: reference.settings,
+      applyLock: Boolean(reference.applyLock),
+      applyCrop: Boolean(reference.applyCrop)
+    } : null,
+    exportPreferences: project.exportPreferences || null,
+    missingFrames: Array.isArray(opened && opened.missingFrames) ? opened.missingFrames : []
+  };
+}
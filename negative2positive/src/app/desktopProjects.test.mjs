@@ -0,0 +1,105 @@
+// Standalone Node test for desktopProjects.js - run with:
+// node negative2positive/src/app/desktopProjects.test.mjs
+import assert from 'node:assert/strict';
+import {
+  applyExportPreferences,
+  buildRollProject,
+  collectExportPreferences,
+  restoreRollProject
+} from './desktopProjects.js';
+
+// 1. Only desktop-backed queue items become project frames
+{
+  const queue = [
+    { id: 'a', sourcePath: null, selected: true, settings: null },
+    { id: 'b', sourcePath: '/rolls/02.NEF', selected: false, settings: { rotationAngle: 1 } },
+    { id: 'c', sourcePath: '/rolls/03.NEF', selected: true, settings: null }
+  ];
+  const { project, skipped } = buildRollProject({
+    name: 'Portra 0612',
+    queue,
+    currentIndex: 2,
+    rollReference: { enabled: true, sourceFileId: 'b', settingsSnapshot: { filmType: 'color' }, applyLock: true, applyCrop: false },
+    preferences: { exportFormat: 'tiff' }
+  });
+  assert.equal(skipped, 1);
+  assert.deepEqual(project.frames, [
+    { path: '/rolls/02.NEF', selected: false, settings: { rotationAngle: 1 } },
+    { path: '/rolls/03.NEF', selected: true, settings: null }
+  ]);
+  assert.equal(project.currentFrame, 1);
+  assert.deepEqual(project.rollReference, {
+    sourcePath: '/rolls/02.NEF',
+    settings: { filmType: 'color' },
+    applyLock: true,
+    applyCrop: false
+  });
+  assert.deepEqual(project.exportPreferences, { exportFormat: 'tiff' });
+}
+
+// 2. Opened projects map back to queue items, skipping missing frames
+{
+  const opened = {
+    path: '/rolls/roll.ncroll',
+    project: {
+      currentFrame: 2,
+      frames: [{ path: '/rolls/01.NEF' }, { path: '/gone/02.NEF' }, { path: '/rolls/03.NEF' }],
+      rollReference: { sourcePath: '/rolls/01.NEF', settings: { filmType: 'bw' }, applyLock: false, applyCrop: true },
+      exportPreferences: { exportFormat: 'png' }
+    },
+    frames: [
+      { file: { path: '/rolls/01.NEF', name: '01.NEF' }, selected: true, settings: { rotationAngle: 2 } },
+      { file: { path: '/rolls/03.NEF', name: '03.NEF' }, selected: false, settings: null }
+    ],
+    missingFrames: ['/gone/02.NEF']
+  };
+  const restored = restoreRollProject(opened, (entry) => ({ name: entry.name, sourcePath: entry.path }));
+  assert.equal(restored.items.length, 2);
+  assert.deepEqual(restored.items[0], {
+    file: { name: '01.NEF', sourcePath: '/rolls/01.NEF' },
+    sourcePath: '/rolls/01.NEF',
+    selected: true,
+    settings: { rotationAngle: 2 }
+  });
+  assert.equal(restored.currentIndex, 1);
+  assert.deepEqual(restored.rollReference, {
+    sourcePath: '/rolls/01.NEF',
+    settingsSnapshot: { filmType: 'bw' },
+    applyLock: false,
+    applyCrop: true
+  });
+  assert.deepEqual(restored.missingFrames, ['/gone/02.NEF']);
+}
+
+// 3. Export preferences round-trip and reject malformed values
+{
+  const state = { exportFormat: 'png', exportBitDepth: 8, jpegQuality: 92, tiffCompression: 'deflate', exportSprocketHolesEnabled: false };
+  applyExportPreferences(state, {
+    exportFormat: 'tiff',
+    exportBitDepth: 16,
+    jpegQuality: 250,
+    tiffCompression: 'lzw',
+    exportSprocketHolesEnabled: true,
+    exportFilenameTemplate: ' {roll}_{frame:03} ',
+    exportSubfolderPattern: '{year}/{roll}',
+    exportConflictPolicy: 'overwrite-if-older'
+  });
+  assert.deepEqual(collectExportPreferences(state), {
+    exportFormat: 'tiff',
+    exportBitDepth: 16,
+    jpegQuality: 100,
+    tiffCompression: 'lzw',
+    exportSprocketHolesEnabled: true,
+    exportFilenameTemplate: '{roll}_{frame:03}',
+    exportSubfolderPattern: '{year}/{roll}',
+    exportConflictPolicy: 'overwrite-if-older'
+  });
+  applyExportPreferences(state, { exportFormat: 'gif', exportBitDepth: 12, tiffCompression: 'zip', exportFilenameTemplate: 7, exportConflictPolicy: 'merge' });
+  assert.equal(state.exportFormat, 'tiff');
+  assert.equal(state.exportBitDepth, 16);
+  assert.equal(state.tiffCompression, 'lzw');
+  assert.equal(state.exportFilenameTemplate, '{roll}_{frame:03}');
+  assert.equal(state.exportConflictPolicy, 'overwrite-if-older');
+}
+
+console.log('desktopProjects tests: all passed');
@@ -0,0 +1,82 @@
+// Desktop-only RAW path: the Tauri backend demosaics natively through the
+// `decode_raw_file` command instead of LibRaw-Wasm.
+
+const RAW_HEADER_LENGTH_BYTES = 4;
+
+let bt709Curve = null;
+
+// LibRaw's default output transfer curve; the native decoder returns linear
+// samples, so this keeps downstream processing identical to the Wasm path.
+function getBt709Curve() {
+  if (bt709Curve) return bt709Curve;
+  bt709Curve = new Uint16Array(65536);
+  for (let i = 0; i < 65536; i++) {
+    const linear = i / 65535;
+    const encoded = linear < 0.018 ? 4.5 * linear : 1.099 * Math.pow(linear, 0.45) - 0.099;
+    bt709Curve[i] = Math.min(65535, Math.max(0, Math.round(encoded * 65535)));
+  }
+  return bt709Curve;
+}
+
+// Response layout: u32 LE header length, JSON header, LE u16 RGB samples.
+export function parseDesktopRawResponse(buffer) {
+  const bytes = buffer instanceof ArrayBuffer ? new Uint8Array(buffer) : new Uint8Array(buffer.buffer, buffer.byteOffset, buffer.byteLength);
+  if (bytes.byteLength < RAW_HEADER_LENGTH_BYTES) {
+    throw new Error('RAW response is truncated.');
+  }
+  const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  const headerLength = view.getUint32(0, true);
+  const pixelOffset = RAW_HEADER_LENGTH_BYTES + headerLength;
+  if (pixelOffset > bytes.byteLength) {
+    throw new Error('RAW response header is truncated.');
+  }
+  const header = JSON.parse(new TextDecoder().decode(bytes.subarray(RAW_HEADER_LENGTH_BYTES, pixelOffset)));
+  const sampleCount = header.width * header.height * header.channels;
+  if ((bytes.byteLength - pixelOffset) !== sampleCount * 2) {
+    throw new Error('RAW response pixel data does not match its header.');
+  }
+
+  const rgb16 = new Uint16Array(sampleCount);
+  for (let i = 0; i < sampleCount; i++) {
+    rgb16[i] = view.getUint16(pixelOffset + i * 2, true);
+  }
+  return { ...header, rgb16 };
+}
+
+// Expands RGB to the 16-bit RGBA layout of silvercore/util/image16.js.
+export function desktopRawToImage16(decoded) {
+  const { width, height, rgb16, linear } = decoded;
+  const curve = linear ? getBt709Curve() : null;
+  const pixelCount = width * height;
+  const data = new Uint16Array(pixelCount * 4);
+  for (let p = 0, s = 0, d = 0; p < pixelCount; p++, s += 3, d += 4) {
+    data[d] = curve ? curve[rgb16[s]] : rgb16[s];
+    data[d + 1] = curve ? curve[rgb16[s + 1]] : rgb16[s + 1];
+    data[d + 2] = curve ? curve[rgb16[s + 2]] : rgb16[s + 2];
+    data[d + 3] = 65535;
+  }
+  return { width, height, data };
+}
+
+// The native metadata uses null where extractRawLensMetadata reports NaN.
+export function normalizeDesktopRawMetadata(metadata) {
+  if (!metadata || typeof metadata !== 'object') return null;
+  const toNumber = (value) => (typeof value === 'number' && Number.isFinite(value) ? value : NaN);
+  return {
+    lensModel: String(metadata.lensModel || ''),
+    lensMaker: String(metadata.lensMaker || ''),
+    cameraModel: String(metadata.cameraModel || ''),
+    cameraMaker: String(metadata.cameraMaker || ''),
+    focal: toNumber(metadata.focal),
+    aperture: toNumber(metadata.aperture)
+  };
+}
+
+export async function decodeRawFileOnDesktop(invoke, path) {
+  const response = await invoke('decode_raw_file', { path });
+  const decoded = parseDesktopRawResponse(response);
+  return {
+    image16: desktopRawToImage16(decoded),
+    metadata: normalizeDesktopRawMetadata(decoded.metadata)
+  };
+}
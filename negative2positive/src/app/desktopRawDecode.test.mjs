@@ -0,0 +1,56 @@
+// Standalone Node test for desktopRawDecode.js - run with:
+// node negative2positive/src/app/desktopRawDecode.test.mjs
+import assert from 'node:assert/strict';
+import {
+  desktopRawToImage16,
+  normalizeDesktopRawMetadata,
+  parseDesktopRawResponse
+} from './desktopRawDecode.js';
+
+function buildResponse(header, samples) {
+  const json = new TextEncoder().encode(JSON.stringify(header));
+  const buffer = new ArrayBuffer(4 + json.length + samples.length * 2);
+  const view = new DataView(buffer);
+  view.setUint32(0, json.length, true);
+  new Uint8Array(buffer, 4, json.length).set(json);
+  samples.forEach((value, i) => view.setUint16(4 + json.length + i * 2, value, true));
+  return buffer;
+}
+
+// 1. Header and little-endian samples are split apart
+{
+  const header = { width: 2, height: 1, channels: 3, linear: false, metadata: { cameraMaker: 'Nikon' } };
+  const parsed = parseDesktopRawResponse(buildResponse(header, [1, 2, 3, 65535, 256, 0]));
+  assert.equal(parsed.width, 2);
+  assert.equal(parsed.metadata.cameraMaker, 'Nikon');
+  assert.deepEqual(Array.from(parsed.rgb16), [1, 2, 3, 65535, 256, 0]);
+
+  const image16 = desktopRawToImage16(parsed);
+  assert.deepEqual(Array.from(image16.data), [1, 2, 3, 65535, 65535, 256, 0, 65535]);
+}
+
+// 2. Linear data is encoded with the BT.709 curve, keeping the endpoints
+{
+  const header = { width: 1, height: 1, channels: 3, linear: true, metadata: null };
+  const image16 = desktopRawToImage16(parseDesktopRawResponse(buildResponse(header, [0, 65535, 16384])));
+  assert.equal(image16.data[0], 0);
+  assert.equal(image16.data[1], 65535);
+  assert.ok(image16.data[2] > 16384);
+}
+
+// 3. Mismatched pixel data is rejected
+{
+  const header = { width: 2, height: 2, channels: 3, linear: true, metadata: null };
+  assert.throws(() => parseDesktopRawResponse(buildResponse(header, [1, 2, 3])), /does not match/);
+}
+
+// 4. Missing numbers become NaN like extractRawLensMetadata
+{
+  const metadata = normalizeDesktopRawMetadata({ lensModel: 'AF-S 60mm', focal: 60, aperture: null });
+  assert.equal(metadata.lensModel, 'AF-S 60mm');
+  assert.equal(metadata.focal, 60);
+  assert.ok(Number.isNaN(metadata.aperture));
+  assert.equal(normalizeDesktopRawMetadata(null), null);
+}
+
+console.log('desktopRawDecode tests: all passed');
@@ -0,0 +1,38 @@
+// Per-frame settings sidecars (`frame.NEF` -> `frame.xmp`) kept by the desktop
+// backend, so edits travel with the scans instead of living only in memory.
+
+function isSettingsObject(value) {
+  return Boolean(value && typeof value === 'object' && !Array.isArray(value));
+}
+
+export async function readSettingsSidecar(invoke, sourcePath) {
+  if (!sourcePath) return null;
+  const settings = await invoke('read_settings_sidecar', { path: sourcePath });
+  return isSettingsObject(settings) ? settings : null;
+}
+
+// Resolves to the sidecar path, or null when there is nothing to write.
+export async function writeSettingsSidecar(invoke, sourcePath, settings) {
+  if (!sourcePath || !isSettingsObject(settings)) return null;
+  const path = await invoke('write_settings_sidecar', { path: sourcePath, settings });
+  return typeof path === 'string' && path ? path : null;
+}
+
+// Fills in settings for queue items that have none yet. A broken sidecar only
+// costs its own frame; the rest of the roll still loads.
+export async function hydrateQueueSettingsFromSidecars(invoke, items, normalizeSettings = (value) => value) {
+  let restored = 0;
+  for (const item of items) {
+    if (!item || item.settings || !item.sourcePath) continue;
+    try {
+      const settings = await readSettingsSidecar(invoke, item.sourcePath);
+      if (!settings) continue;
+      item.settings = normalizeSettings(settings);
+      item.isDirty = false;
+      restored++;
+    } catch (err) {
+      console.warn(`Reading sidecar for ${item.sourcePath} failed:`, err);
+    }
+  }
+  return restored;
+}
@@ -0,0 +1,67 @@
+// Standalone Node test for desktopSidecars.js - run with:
+// node negative2positive/src/app/desktopSidecars.test.mjs
+import assert from 'node:assert/strict';
+import {
+  hydrateQueueSettingsFromSidecars,
+  readSettingsSidecar,
+  writeSettingsSidecar
+} from './desktopSidecars.js';
+
+// 1. Reads go through read_settings_sidecar and ignore non-object payloads
+{
+  const calls = [];
+  const invoke = async (cmd, args) => {
+    calls.push([cmd, args]);
+    return args.path.endsWith('a.NEF') ? { filmType: 'color' } : [1, 2];
+  };
+  assert.deepEqual(await readSettingsSidecar(invoke, '/rolls/a.NEF'), { filmType: 'color' });
+  assert.equal(await readSettingsSidecar(invoke, '/rolls/b.NEF'), null);
+  assert.equal(await readSettingsSidecar(invoke, ''), null);
+  assert.deepEqual(calls.map(([cmd]) => cmd), ['read_settings_sidecar', 'read_settings_sidecar']);
+}
+
+// 2. Writes send the snapshot and return the sidecar path
+{
+  const calls = [];
+  const invoke = async (cmd, args) => {
+    calls.push([cmd, args]);
+    return args.path.replace(/\.[^.]+$/, '.xmp');
+  };
+  const settings = { coreFilmPreset: 'portra-400' };
+  assert.equal(await writeSettingsSidecar(invoke, '/rolls/a.NEF', settings), '/rolls/a.xmp');
+  assert.deepEqual(calls, [['write_settings_sidecar', { path: '/rolls/a.NEF', settings }]]);
+  assert.equal(await writeSettingsSidecar(invoke, null, settings), null);
+  assert.equal(await writeSettingsSidecar(invoke, '/rolls/a.NEF', null), null);
+  assert.equal(calls.length, 1);
+}
+
+// 3. Hydration only fills items without settings and survives broken sidecars
+{
+  const invoke = async (cmd, args) => {
+    if (args.path === '/rolls/broken.NEF') throw new Error('parse sidecar settings failed');
+    if (args.path === '/rolls/none.NEF') return null;
+    return { rotationAngle: 1 };
+  };
+  const items = [
+    { sourcePath: '/rolls/a.NEF', settings: null, isDirty: true },
+    { sourcePath: '/rolls/b.NEF', settings: { rotationAngle: 5 } },
+    { sourcePath: '/rolls/broken.NEF', settings: null },
+    { sourcePath: '/rolls/none.NEF', settings: null },
+    { sourcePath: null, settings: null }
+  ];
+  const originalWarn = console.warn;
+  console.warn = () => {};
+  try {
+    const restored = await hydrateQueueSettingsFromSidecars(invoke, items, (value) => ({ ...value, normalized: true }));
+    assert.equal(restored, 1);
+  } finally {
+    console.warn = originalWarn;
+  }
+  assert.deepEqual(items[0].settings, { rotationAngle: 1, normalized: true });
+  assert.equal(items[0].isDirty, false);
+  assert.deepEqual(items[1].settings, { rotationAngle: 5 });
+  assert.equal(items[2].settings, null);
+  assert.equal(items[3].settings, null);
+}
+
+console.log('desktopSidecars tests: all passed');
@@ -0,0 +1,47 @@
+// File-like handles for inputs picked through the desktop `open_input_files`
+// command. Bytes stay on disk until a loader asks for them, so opening a
+// 40-frame roll does not pull every RAW into the webview up front.
+
+const MIME_TYPES_BY_EXTENSION = Object.freeze({
+  png: 'image/png',
+  jpg: 'image/jpeg',
+  jpeg: 'image/jpeg',
+  gif: 'image/gif',
+  webp: 'image/webp',
+  bmp: 'image/bmp',
+  tif: 'image/tiff',
+  tiff: 'image/tiff'
+});
+
+export function guessMimeTypeFromName(name) {
+  const ext = String(name || '').split('.').pop().toLowerCase();
+  return MIME_TYPES_BY_EXTENSION[ext] || '';
+}
+
+export function createDesktopSourceFile(entry, invoke) {
+  const path = String(entry && entry.path ? entry.path : '');
+  const name = String(entry && entry.name ? entry.name : path.split(/[\\/]/).pop() || '');
+  return {
+    isDesktopSourceFile: true,
+    sourcePath: path,
+    name,
+    size: Number(entry && entry.size) || 0,
+    lastModified: Number(entry && entry.lastModified) || 0,
+    type: guessMimeTypeFromName(name),
+    async arrayBuffer() {
+      const bytes = await invoke('read_input_file', { path });
+      return bytes instanceof ArrayBuffer ? bytes : new Uint8Array(bytes).buffer;
+    }
+  };
+}
+
+export function isDesktopSourceFile(file) {
+  return Boolean(file && file.isDesktopSourceFile === true && file.sourcePath);
+}
+
+// Loaders that need a real Blob (createImageBitmap, object URLs) go through here.
+export async function materializeDesktopSourceFile(file) {
+  if (!isDesktopSourceFile(file)) return file;
+  const buffer = await file.arrayBuffer();
+  return new File([buffer], file.name, { type: file.type, lastModified: file.lastModified });
+}
@@ -0,0 +1,53 @@
+// Standalone Node test for desktopSourceFiles.js - run with:
+// node negative2positive/src/app/desktopSourceFiles.test.mjs
+import assert from 'node:assert/strict';
+import {
+  createDesktopSourceFile,
+  guessMimeTypeFromName,
+  isDesktopSourceFile,
+  materializeDesktopSourceFile
+} from './desktopSourceFiles.js';
+
+// 1. Entries from open_input_files become lazy file handles
+{
+  const calls = [];
+  const invoke = async (cmd, args) => {
+    calls.push([cmd, args]);
+    return new Uint8Array([1, 2, 3]).buffer;
+  };
+  const file = createDesktopSourceFile(
+    { path: '/rolls/roll_07/frame_01.NEF', name: 'frame_01.NEF', size: 3, lastModified: 1700000000000 },
+    invoke
+  );
+  assert.ok(isDesktopSourceFile(file));
+  assert.equal(file.name, 'frame_01.NEF');
+  assert.equal(file.type, '');
+  assert.equal(calls.length, 0);
+
+  const buffer = await file.arrayBuffer();
+  assert.deepEqual(Array.from(new Uint8Array(buffer)), [1, 2, 3]);
+  assert.deepEqual(calls, [['read_input_file', { path: '/rolls/roll_07/frame_01.NEF' }]]);
+}
+
+// 2. Name falls back to the last path segment on either separator
+{
+  const file = createDesktopSourceFile({ path: 'C:\\scans\\frame_02.png' }, async () => new ArrayBuffer(0));
+  assert.equal(file.name, 'frame_02.png');
+  assert.equal(file.type, 'image/png');
+}
+
+// 3. MIME guesses cover the webview-decoded formats
+{
+  assert.equal(guessMimeTypeFromName('a.JPG'), 'image/jpeg');
+  assert.equal(guessMimeTypeFromName('a.tiff'), 'image/tiff');
+  assert.equal(guessMimeTypeFromName('a.nef'), '');
+}
+
+// 4. Browser files pass through untouched
+{
+  const browserFile = { name: 'x.png' };
+  assert.equal(isDesktopSourceFile(browserFile), false);
+  assert.equal(await materializeDesktopSourceFile(browserFile), browserFile);
+}
+
+console.log('desktopSourceFiles tests: all passed');
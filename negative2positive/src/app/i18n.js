@@ -152,6 +152,15 @@ export const i18n = {
         applyCropWithReference: "同时套用参考裁剪",
         rollReferenceSet: "已将当前图片设为整卷参考。",
         rollReferenceCleared: "已清除整卷参考。",
+        matchRollFilmBase: "整卷统一片基",
+        rollFilmBaseNeedsFrames: "至少添加两张图像才能统一整卷片基。",
+        rollFilmBaseNone: "没有可比较片基的图像。",
+        rollFilmBaseFrom: "整卷片基取自 {total} 张中的 {used} 张。",
+        rollFilmBaseOutliers: "与整卷偏差较大：{files}",
+        rollFilmBaseUnsure: "未找到清晰片基：{files}",
+        rollFilmBaseMissing: "无法读取：{files}",
+        rollFilmBaseConfirm: "将整卷片基应用到所有已保存设置的图像？",
+        rollFilmBaseApplied: "已将整卷片基应用到 {count} 张图像。",
         rollReferenceApplied: "已将整卷参考应用到 {count} 张图片。",
         rollReferenceMissing: "尚未设置整卷参考。",
         rollReferenceAppliedCurrent: "已将整卷参考应用到当前图片。",
@@ -215,6 +224,12 @@ export const i18n = {
         coreProfileCrystal: "Crystal",
         coreProfileNatural: "Natural",
         coreProfilePakon: "Pakon",
+        coreProfileNoritsu: "Noritsu",
+        coreProfileImported: "已导入",
+        importProfile: "导入 LUT…",
+        exportProfileCube: "导出 .cube…",
+        profileImported: "已导入 {name}",
+        profileExportNone: "请先选择要导出的配置。",
         coreModelStandard: "标准",
         coreModelWarm: "暖调",
         coreModelMono: "单色",
@@ -228,6 +243,20 @@ export const i18n = {
         presetGroupColor: "彩色",
         presetGroupBW: "黑白",
         presetGroupPositive: "正片",
+        presetGroupUser: "我的预设",
+        presetNamePlaceholder: "预设名称",
+        savePreset: "保存",
+        renamePreset: "重命名",
+        deletePreset: "删除",
+        importPresets: "导入…",
+        exportPresets: "导出…",
+        presetSaved: "已保存预设 {name}",
+        presetNameRequired: "请先输入预设名称。",
+        presetUserOnly: "请先选择一个自己的预设。",
+        presetDeleteConfirm: "删除预设 {name}？",
+        presetDeleted: "已删除预设 {name}",
+        presetsImported: "已导入 {count} 个预设",
+        presetsUnchanged: "{count} 个预设已在库中",
         corePreSaturation: "预饱和度",
         coreBorderBuffer: "边框缓冲 %",
         coreBrightness: "亮度",
@@ -378,6 +407,7 @@ export const i18n = {
         desktopBatchExportFolderCancelled: "已取消选择导出文件夹。",
         desktopBatchExportSummary: "已导出 {success} / {total} 张到 {folder}。",
         desktopBatchExportSummaryErrors: "已导出 {success} / {total} 张，失败 {failed} 张。目标：{folder}。",
+        desktopBatchExportSkipped: "已跳过 {skipped} 个已存在的文件。",
         lensSectionTitle: "镜头矫正（可选）",
         lensEnable: "启用镜头矫正",
         lensSkipBtn: "跳过镜头矫正",
@@ -414,6 +444,49 @@ export const i18n = {
         lensStepAuto: "自动",
         lensStepManual: "手动",
         bitDepthJpegLocked: "JPEG 仅支持 8-bit 导出。",
+        exportTiffCompression: "TIFF 压缩",
+        tiffCompressionNone: "无",
+        openRoll: "打开胶卷",
+        saveRoll: "保存胶卷",
+        recentRolls: "最近的胶卷",
+        rollSaved: "胶卷已保存：{path}",
+        rollNoFrames: "只有从磁盘打开的文件才能保存到胶卷中。",
+        rollSkippedFrames: "{count} 个未从磁盘打开的文件未保存到胶卷中。",
+        rollSaveFailed: "保存胶卷失败：{error}",
+        rollOpenFailed: "打开胶卷失败：{error}",
+        rollMissingFrames: "找不到 {count} 个画幅：\n{paths}",
+        errorNotFound: "找不到文件或文件夹。",
+        errorPermissionDenied: "没有权限。请选择一个可写入的文件夹。",
+        errorDiskFull: "磁盘已满。请释放空间或选择其他磁盘。",
+        errorInvalidPath: "该路径对此文件无效。",
+        errorAlreadyExists: "文件已存在，未确认替换。",
+        errorDecodeFailed: "无法读取文件，可能已损坏或格式不受支持。",
+        errorCancelled: "操作已取消。",
+        errorIo: "读写失败。请检查磁盘后重试。",
+        desktopBatchExportStopped: "批量导出已停止：{error}",
+        desktopBatchExportCancel: "停止",
+        desktopBatchExportCancelled: "已停止导出，已写入 {success} / {total} 个文件。",
+        desktopSavedTo: "已保存到 {path}",
+        revealInFileManager: "在文件夹中显示",
+        openWithDefaultApp: "打开",
+        preflightTitle: "导出前请注意：",
+        preflightContinue: "仍要导出吗？",
+        preflightInsufficientSpace: "预计需要约 {needed}，但仅剩 {free} 可用空间。",
+        preflightNotWritable: "无法写入该文件夹。",
+        preflightFileTooLarge: "文件可能超过 {fileSystem} 的 {limit} 单文件上限。",
+        preflightNameCollisions: "{count} 个文件名与其他文件仅大小写不同：{names}",
+        preflightNameTooLong: "{count} 个文件名超过 {limit} 字符的上限。",
+        exportFilenameTemplate: "批量文件名",
+        exportFilenameTemplateHint: "可用标记：{stem} {roll} {frame:03} {preset} {film} {date} {year} {month} {day} {format} {bits}。留空则沿用源文件名。",
+        exportSubfolderPattern: "子文件夹",
+        exportSubfolderPatternHint: "在所选导出文件夹内创建的文件夹，用 / 分隔。留空则全部导出到同一文件夹。",
+        exportConflictPolicy: "文件已存在时",
+        exportConflictRename: "保留两者（加 _1）",
+        exportConflictOverwrite: "替换",
+        exportConflictSkip: "跳过",
+        exportConflictOverwriteIfOlder: "画幅有改动时替换",
+        exportConflictAsk: "询问",
+        recoverSessionPrompt: "是否恢复上次未完成的胶卷（{count} 个画幅，自动保存于 {time}）？",
         zoomIn: "放大",
         zoomOut: "缩小",
         zoomReset: "重置缩放",
@@ -580,6 +653,15 @@ export const i18n = {
         applyCropWithReference: "Also apply crop from reference",
         rollReferenceSet: "Current image has been set as the roll reference.",
         rollReferenceCleared: "Roll reference cleared.",
+        matchRollFilmBase: "Match Film Base Across Roll",
+        rollFilmBaseNeedsFrames: "Add at least two frames to match the film base across the roll.",
+        rollFilmBaseNone: "No frame had a film base to compare.",
+        rollFilmBaseFrom: "Roll film base from {used} of {total} frames.",
+        rollFilmBaseOutliers: "Far from the rest of the roll: {files}",
+        rollFilmBaseUnsure: "No clear film base found: {files}",
+        rollFilmBaseMissing: "Could not be read: {files}",
+        rollFilmBaseConfirm: "Apply the roll film base to every frame with saved settings?",
+        rollFilmBaseApplied: "Applied the roll film base to {count} frame(s).",
         rollReferenceApplied: "Applied roll reference to {count} image(s).",
         rollReferenceMissing: "No roll reference is set.",
         rollReferenceAppliedCurrent: "Roll reference applied to current image.",
@@ -643,6 +725,12 @@ export const i18n = {
         coreProfileCrystal: "Crystal",
         coreProfileNatural: "Natural",
         coreProfilePakon: "Pakon",
+        coreProfileNoritsu: "Noritsu",
+        coreProfileImported: "Imported",
+        importProfile: "Import LUT…",
+        exportProfileCube: "Export .cube…",
+        profileImported: "Imported {name}",
+        profileExportNone: "Choose a profile to export first.",
         coreModelStandard: "Standard",
         coreModelWarm: "Warm",
         coreModelMono: "Mono",
@@ -656,6 +744,20 @@ export const i18n = {
         presetGroupColor: "Color",
         presetGroupBW: "B&W",
         presetGroupPositive: "Positive",
+        presetGroupUser: "My Presets",
+        presetNamePlaceholder: "Preset name",
+        savePreset: "Save",
+        renamePreset: "Rename",
+        deletePreset: "Delete",
+        importPresets: "Import…",
+        exportPresets: "Export…",
+        presetSaved: "Saved preset {name}",
+        presetNameRequired: "Enter a preset name first.",
+        presetUserOnly: "Choose one of your presets first.",
+        presetDeleteConfirm: "Delete preset {name}?",
+        presetDeleted: "Deleted preset {name}",
+        presetsImported: "Imported {count} presets",
+        presetsUnchanged: "{count} already in your library",
         corePreSaturation: "Pre-Saturation",
         coreBorderBuffer: "Border Buffer %",
         coreBrightness: "Brightness",
@@ -806,6 +908,7 @@ export const i18n = {
         desktopBatchExportFolderCancelled: "Folder selection cancelled. No files were exported.",
         desktopBatchExportSummary: "Exported {success} / {total} files to {folder}.",
         desktopBatchExportSummaryErrors: "Exported {success} / {total} files, {failed} failed. Target: {folder}.",
+        desktopBatchExportSkipped: "Skipped {skipped} existing files.",
         lensSectionTitle: "Lens Correction (Optional)",
         lensEnable: "Enable Lens Correction",
         lensSkipBtn: "Skip Lens Correction",
@@ -842,6 +945,49 @@ export const i18n = {
         lensStepAuto: "Auto",
         lensStepManual: "Manual",
         bitDepthJpegLocked: "JPEG export is limited to 8-bit.",
+        exportTiffCompression: "TIFF Compression",
+        tiffCompressionNone: "None",
+        openRoll: "Open Roll",
+        saveRoll: "Save Roll",
+        recentRolls: "Recent Rolls",
+        rollSaved: "Roll saved: {path}",
+        rollNoFrames: "Only files opened from disk can be saved in a roll.",
+        rollSkippedFrames: "{count} file(s) not opened from disk were left out of the roll.",
+        rollSaveFailed: "Saving the roll failed: {error}",
+        rollOpenFailed: "Opening the roll failed: {error}",
+        rollMissingFrames: "{count} frame(s) could not be found:\n{paths}",
+        errorNotFound: "The file or folder could not be found.",
+        errorPermissionDenied: "Permission denied. Choose a folder you can write to.",
+        errorDiskFull: "The disk is full. Free up space or choose another drive.",
+        errorInvalidPath: "The path is not valid for this file.",
+        errorAlreadyExists: "The file already exists and replacing it was not confirmed.",
+        errorDecodeFailed: "The file could not be read. It may be damaged or unsupported.",
+        errorCancelled: "The operation was cancelled.",
+        errorIo: "A read or write failed. Check the drive and try again.",
+        desktopBatchExportStopped: "Batch export stopped: {error}",
+        desktopBatchExportCancel: "Stop",
+        desktopBatchExportCancelled: "Export stopped after {success} / {total} files.",
+        desktopSavedTo: "Saved to {path}",
+        revealInFileManager: "Show in folder",
+        openWithDefaultApp: "Open",
+        preflightTitle: "Before exporting:",
+        preflightContinue: "Export anyway?",
+        preflightInsufficientSpace: "About {needed} is needed but only {free} is free.",
+        preflightNotWritable: "The folder cannot be written to.",
+        preflightFileTooLarge: "Files may exceed the {limit} limit of {fileSystem}.",
+        preflightNameCollisions: "{count} name(s) differ only by case from other files: {names}",
+        preflightNameTooLong: "{count} file name(s) exceed the {limit} character limit.",
+        exportFilenameTemplate: "Batch File Names",
+        exportFilenameTemplateHint: "Tokens: {stem} {roll} {frame:03} {preset} {film} {date} {year} {month} {day} {format} {bits}. Leave empty to keep source names.",
+        exportSubfolderPattern: "Subfolders",
+        exportSubfolderPatternHint: "Folders created inside the chosen export folder, separated by /. Leave empty to export flat.",
+        exportConflictPolicy: "If a File Exists",
+        exportConflictRename: "Keep both (add _1)",
+        exportConflictOverwrite: "Replace",
+        exportConflictSkip: "Skip",
+        exportConflictOverwriteIfOlder: "Replace if the frame changed since",
+        exportConflictAsk: "Ask",
+        recoverSessionPrompt: "Restore the roll you were working on ({count} frame(s), autosaved {time})?",
         zoomIn: "Zoom In",
         zoomOut: "Zoom Out",
         zoomReset: "Reset Zoom",
@@ -1008,6 +1154,15 @@ export const i18n = {
         applyCropWithReference: "参照のトリミングも適用",
         rollReferenceSet: "現在の画像をロール参照に設定しました。",
         rollReferenceCleared: "ロール参照をクリアしました。",
+        matchRollFilmBase: "ロール全体でフィルムベースを揃える",
+        rollFilmBaseNeedsFrames: "ロール全体で揃えるには 2 枚以上追加してください。",
+        rollFilmBaseNone: "比較できるフィルムベースがありません。",
+        rollFilmBaseFrom: "{total} 枚中 {used} 枚からロールのフィルムベースを算出しました。",
+        rollFilmBaseOutliers: "ロールの他の画像と大きく異なる：{files}",
+        rollFilmBaseUnsure: "フィルムベースが明確でない：{files}",
+        rollFilmBaseMissing: "読み込めない：{files}",
+        rollFilmBaseConfirm: "設定を保存したすべての画像にロールのフィルムベースを適用しますか？",
+        rollFilmBaseApplied: "{count} 枚にロールのフィルムベースを適用しました。",
         rollReferenceApplied: "ロール参照を {count} 枚に適用しました。",
         rollReferenceMissing: "ロール参照が設定されていません。",
         rollReferenceAppliedCurrent: "ロール参照を現在の画像に適用しました。",
@@ -1071,6 +1226,12 @@ export const i18n = {
         coreProfileCrystal: "Crystal",
         coreProfileNatural: "Natural",
         coreProfilePakon: "Pakon",
+        coreProfileNoritsu: "Noritsu",
+        coreProfileImported: "インポート済み",
+        importProfile: "LUT をインポート…",
+        exportProfileCube: ".cube を書き出す…",
+        profileImported: "{name} をインポートしました",
+        profileExportNone: "書き出すプロファイルを先に選択してください。",
         coreModelStandard: "標準",
         coreModelWarm: "ウォーム",
         coreModelMono: "モノクロ",
@@ -1084,6 +1245,20 @@ export const i18n = {
         presetGroupColor: "カラー",
         presetGroupBW: "白黒",
         presetGroupPositive: "ポジ",
+        presetGroupUser: "マイプリセット",
+        presetNamePlaceholder: "プリセット名",
+        savePreset: "保存",
+        renamePreset: "名前を変更",
+        deletePreset: "削除",
+        importPresets: "インポート…",
+        exportPresets: "書き出し…",
+        presetSaved: "プリセット {name} を保存しました",
+        presetNameRequired: "先にプリセット名を入力してください。",
+        presetUserOnly: "先に自分のプリセットを選択してください。",
+        presetDeleteConfirm: "プリセット {name} を削除しますか？",
+        presetDeleted: "プリセット {name} を削除しました",
+        presetsImported: "{count} 件のプリセットをインポートしました",
+        presetsUnchanged: "{count} 件はすでにライブラリにあります",
         corePreSaturation: "事前彩度",
         coreBorderBuffer: "境界バッファ %",
         coreBrightness: "明るさ",
@@ -1234,6 +1409,7 @@ export const i18n = {
         desktopBatchExportFolderCancelled: "出力フォルダの選択をキャンセルしました。",
         desktopBatchExportSummary: "{total}枚中{success}枚を書き出しました。保存先: {folder}",
         desktopBatchExportSummaryErrors: "{total}枚中{success}枚を書き出し、{failed}枚は失敗しました。保存先: {folder}",
+        desktopBatchExportSkipped: "既存の {skipped} 件のファイルをスキップしました。",
         lensSectionTitle: "レンズ補正（任意）",
         lensEnable: "レンズ補正を有効化",
         lensSkipBtn: "レンズ補正をスキップ",
@@ -1270,6 +1446,49 @@ export const i18n = {
         lensStepAuto: "自動",
         lensStepManual: "手動",
         bitDepthJpegLocked: "JPEG は 8-bit 出力のみ対応です。",
+        exportTiffCompression: "TIFF 圧縮",
+        tiffCompressionNone: "なし",
+        openRoll: "ロールを開く",
+        saveRoll: "ロールを保存",
+        recentRolls: "最近のロール",
+        rollSaved: "ロールを保存しました: {path}",
+        rollNoFrames: "ディスクから開いたファイルのみロールに保存できます。",
+        rollSkippedFrames: "ディスクから開いていない {count} 件のファイルはロールに含まれていません。",
+        rollSaveFailed: "ロールの保存に失敗しました: {error}",
+        rollOpenFailed: "ロールを開けませんでした: {error}",
+        rollMissingFrames: "{count} 件のコマが見つかりません:\n{paths}",
+        errorNotFound: "ファイルまたはフォルダが見つかりません。",
+        errorPermissionDenied: "アクセスが拒否されました。書き込み可能なフォルダを選択してください。",
+        errorDiskFull: "ディスクがいっぱいです。空き容量を確保するか、別のドライブを選択してください。",
+        errorInvalidPath: "このファイルには無効なパスです。",
+        errorAlreadyExists: "ファイルは既に存在し、置き換えが確認されていません。",
+        errorDecodeFailed: "ファイルを読み込めませんでした。破損しているか、未対応の形式です。",
+        errorCancelled: "操作はキャンセルされました。",
+        errorIo: "読み書きに失敗しました。ドライブを確認してもう一度お試しください。",
+        desktopBatchExportStopped: "一括書き出しを中止しました: {error}",
+        desktopBatchExportCancel: "停止",
+        desktopBatchExportCancelled: "{success} / {total} 件を書き出した時点で停止しました。",
+        desktopSavedTo: "{path} に保存しました",
+        revealInFileManager: "フォルダーで表示",
+        openWithDefaultApp: "開く",
+        preflightTitle: "書き出し前の確認:",
+        preflightContinue: "このまま書き出しますか？",
+        preflightInsufficientSpace: "約 {needed} が必要ですが、空き容量は {free} です。",
+        preflightNotWritable: "このフォルダーには書き込めません。",
+        preflightFileTooLarge: "ファイルが {fileSystem} の上限 {limit} を超える可能性があります。",
+        preflightNameCollisions: "{count} 件のファイル名が他のファイルと大文字・小文字だけ異なります: {names}",
+        preflightNameTooLong: "{count} 件のファイル名が上限の {limit} 文字を超えています。",
+        exportFilenameTemplate: "一括書き出しのファイル名",
+        exportFilenameTemplateHint: "使用できるトークン: {stem} {roll} {frame:03} {preset} {film} {date} {year} {month} {day} {format} {bits}。空欄の場合は元のファイル名を使います。",
+        exportSubfolderPattern: "サブフォルダ",
+        exportSubfolderPatternHint: "選択した書き出し先フォルダ内に作成するフォルダを / で区切って指定します。空欄の場合はフォルダ分けしません。",
+        exportConflictPolicy: "同名ファイルがある場合",
+        exportConflictRename: "両方残す（_1 を付加）",
+        exportConflictOverwrite: "置き換える",
+        exportConflictSkip: "スキップ",
+        exportConflictOverwriteIfOlder: "コマが変更されていれば置き換える",
+        exportConflictAsk: "確認する",
+        recoverSessionPrompt: "作業中だったロールを復元しますか？（{count} コマ、{time} に自動保存）",
         zoomIn: "拡大",
         zoomOut: "縮小",
         zoomReset: "ズームリセット",
@@ -19,6 +19,19 @@ export async function loadRawImageDataPreview(buffer, fileName, options) {
   return loadRawFile(buffer, fileName, { ...options, preview: true });
 }
 
+// Desktop builds decode RAWs natively when the file has a path on disk.
+export async function loadRawImageDataFromDesktopPath(invoke, path, options = {}) {
+  const [{ decodeRawFileOnDesktop }, { toImageData8 }] = await Promise.all([
+    import('./desktopRawDecode.js'),
+    import('../silvercore/util/image16.js')
+  ]);
+  const { image16, metadata } = await decodeRawFileOnDesktop(invoke, path);
+  if (typeof options.onMetadata === 'function') options.onMetadata(metadata);
+  const imageData = toImageData8(image16);
+  imageData.__image16 = image16;
+  return imageData;
+}
+
 export async function loadPngImageData(buffer) {
   const { loadPngFile } = await import('./pngFileLoader.js');
   return loadPngFile(buffer);
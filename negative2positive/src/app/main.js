@@ -8,7 +8,7 @@
     import { i18n } from './i18n.js';
     import { interpolateText, summarizePathForUi } from './textUtils.js';
     import { computeSpline, buildCurveLut, getCurvePresetPoints, insertCurvePoint, moveCurvePoint, findNearPointIndex } from './curveMath.js';
-    import { deepCopySanitizedSettings } from './settingsSnapshot.js';
+    import { deepCopySanitizedSettings, toSerializableSettings } from './settingsSnapshot.js';
     import { computeZoomGeometry, clampPanValues } from './zoomGeometry.js';
     import { showToast } from '../ui/toast.js';
 
@@ -48,11 +48,46 @@
       isRawLikeFileName,
       loadPngImageData,
       loadRawImageData,
+      loadRawImageDataFromDesktopPath,
       loadRawImageDataPreview,
       loadStandardImage
     } from './imageFileLoaders.js';
+    import {
+      createDesktopSourceFile,
+      isDesktopSourceFile,
+      materializeDesktopSourceFile
+    } from './desktopSourceFiles.js';
+    import {
+      hydrateQueueSettingsFromSidecars,
+      writeSettingsSidecar
+    } from './desktopSidecars.js';
+    import {
+      applyExportPreferences,
+      buildRollProject,
+      collectExportPreferences,
+      restoreRollProject
+    } from './desktopProjects.js';
+    import { createAutosaveScheduler } from './desktopAutosave.js';
+    import { describeDesktopError, isBatchFatalDesktopError, withDesktopRetry } from './desktopErrors.js';
+    import { startDesktopExportJob } from './desktopExportJobs.js';
+    import { convertFrameOnDesktop } from './desktopConversion.js';
+    import { buildRollFilmBaseFrames, summarizeRollFilmBase } from './rollFilmBase.js';
+    import { isUserProfileId, listDesktopProfiles, readDesktopProfile, userProfileOptions } from './desktopProfiles.js';
+    import {
+      buildFilmPresetDraft,
+      isUserPresetId,
+      listDesktopFilmPresets,
+      userFilmPresetMap
+    } from './desktopFilmPresets.js';
+    import {
+      describePreflightWarnings,
+      estimateExportBytes,
+      runDesktopExportPreflight
+    } from './desktopExportPreflight.js';
     import { Histogram } from '../silvercore/ui/Histogram.js';
+    import { toImageData8 } from '../silvercore/util/image16.js';
     import { loadFilmPresets } from '../silvercore/engine/filmPresetsLoader.js';
+    import { registerProfileSource } from '../silvercore/engine/EnhancedProfiles.js';
     import {
       detectDust, updateDustStrength, inpaintMasked,
       refineMaskIntelligent, refineMaskDirect, refineMaskRemove
@@ -129,7 +164,7 @@
       centerPrior: 0.08,
       aspect: 0.12
     };
-    const CORE_ENHANCED_PROFILE_OPTIONS = new Set(['none', 'frontier', 'crystal', 'natural', 'pakon']);
+    const CORE_ENHANCED_PROFILE_OPTIONS = new Set(['none', 'frontier', 'crystal', 'natural', 'pakon', 'noritsu']);
     const CORE_COLOR_MODEL_OPTIONS = new Set(['frontier', 'standard', 'warm', 'mono', 'noritsu', 'cine-log', 'cine-rich', 'cine-flat', 'neutral']);
     const CORE_COLOR_MODEL_MIGRATION_MAP = Object.freeze({});
     const SPROCKET_EDGE_CONTROL_IDS = Object.freeze({
@@ -225,6 +260,10 @@
       fileName: '',
       targetDirectory: ''
     };
+    // Set while a desktop batch export can be stopped from the header.
+    let cancelDesktopBatchExport = null;
+    // Desktop user presets by id, shaped like `loadFilmPresets()` entries.
+    let userFilmPresets = {};
     const desktopUpdateState = {
       visible: false,
       currentVersion: '',
@@ -438,6 +477,13 @@
       setFrontierGuidePopupVisible(true);
     }
 
+    // Bundled presets, or the desktop library's for `user:` ids.
+    async function getFilmPreset(presetId) {
+      if (isUserPresetId(presetId)) return userFilmPresets[presetId] || null;
+      const filmPresets = await loadFilmPresets();
+      return filmPresets[presetId] || null;
+    }
+
     async function applyFilmPresetSettingsToState(presetId) {
       const nextPresetId = String(presetId || 'none');
       state.coreFilmPreset = nextPresetId;
@@ -446,8 +492,7 @@
         return false;
       }
 
-      const filmPresets = await loadFilmPresets();
-      const preset = filmPresets[nextPresetId];
+      const preset = await getFilmPreset(nextPresetId);
       if (!preset || !preset.settings) {
         syncAllSelectsFromState();
         return false;
@@ -621,6 +666,10 @@
       startWhiteBalanceSampling();
     });
 
+    document.getElementById('headerExportProgressCancel')?.addEventListener('click', () => {
+      if (cancelDesktopBatchExport) cancelDesktopBatchExport();
+    });
+
     document.getElementById('frontierGuidePopupCloseBtn')?.addEventListener('click', () => {
       closeFrontierGuidePopup();
     });
@@ -1587,10 +1636,13 @@
       return 'color';
     }
 
+    // Imported desktop profiles (`user:<file>`) pass as well; one that has
+    // since been removed renders without a profile.
     function sanitizeCoreEnhancedProfile(value, fallback = 'none') {
-      const normalizedFallback = CORE_ENHANCED_PROFILE_OPTIONS.has(fallback) ? fallback : 'none';
+      const isKnown = (id) => CORE_ENHANCED_PROFILE_OPTIONS.has(id) || isUserProfileId(id);
+      const normalizedFallback = isKnown(fallback) ? fallback : 'none';
       const normalized = String(value || normalizedFallback);
-      return CORE_ENHANCED_PROFILE_OPTIONS.has(normalized) ? normalized : normalizedFallback;
+      return isKnown(normalized) ? normalized : normalizedFallback;
     }
 
     function sanitizeCoreColorModel(value, fallback = 'standard') {
@@ -1859,6 +1911,9 @@
         applyCrop: false
       },
 
+      // `.ncroll` file the queue was opened from or last saved to (desktop)
+      projectPath: null,
+
       // Dust removal
       dustRemoval: {
         enabled: false,
@@ -1876,6 +1931,10 @@
       // Export settings
       exportFormat: 'png',  // 'png' | 'jpeg' | 'tiff'
       exportBitDepth: 8,    // 8 | 16
+      tiffCompression: 'deflate', // 'none' | 'lzw' | 'deflate' (desktop TIFF writer)
+      exportFilenameTemplate: '', // desktop batch export names, e.g. '{roll}_{frame:03}_{preset}'
+      exportSubfolderPattern: '', // desktop batch export folders, e.g. '{year}/{roll}/{format}'
+      exportConflictPolicy: 'rename', // 'rename' | 'overwrite' | 'skip' | 'overwrite-if-older' | 'ask'
       jpegQuality: 92,      // 1-100
       sprocketPreviewEnabled: false,
       exportSprocketHolesEnabled: false,
@@ -1891,6 +1950,15 @@
     updateGuideModeUI();
     updateGrayPointGuideUI();
 
+    // Debounced crash-recovery autosave of the desktop session (see the
+    // Session Autosave section)
+    const sessionAutosave = isTauriDesktop()
+      ? createAutosaveScheduler({ invoke: window.__TAURI__.core.invoke, collectSession: collectAutosaveSession })
+      : null;
+    // Held until the previous session has been recovered or declined, so an
+    // empty startup queue cannot discard it first.
+    let sessionRecoveryPending = Boolean(sessionAutosave);
+
     let fullResolutionRenderTimer = null;
 
     function clearFullResolutionRenderState() {
@@ -1915,11 +1983,13 @@
       const label = document.getElementById('headerExportProgressLabel');
       const file = document.getElementById('headerExportProgressFile');
       const fill = document.getElementById('headerExportProgressFill');
+      const cancel = document.getElementById('headerExportProgressCancel');
       if (!container || !label || !file || !fill) return;
 
       const show = isTauriDesktop() && desktopBatchExportState.active;
       container.classList.toggle('visible', show);
       container.setAttribute('aria-hidden', show ? 'false' : 'true');
+      if (cancel) cancel.disabled = !show || !cancelDesktopBatchExport;
 
       if (!show) {
         fill.style.width = '0%';
@@ -1995,7 +2065,9 @@
         'addFilesToolbarBtn',
         'clearFileListBtn',
         'saveSettingsBtn',
-        'applyToSelectedBtn'
+        'applyToSelectedBtn',
+        'saveProjectBtn',
+        'openProjectBtn'
       ].forEach((id) => {
         const el = document.getElementById(id);
         if (el) el.disabled = locked;
@@ -2009,6 +2081,30 @@
       }
     }
 
+    function runDesktopFileCommand(command, path) {
+      window.__TAURI__.core.invoke(command, { path }).catch((err) => {
+        console.warn(`${command} failed:`, err);
+        showToast(describeDesktopError(err, getLocalizedText), 4000);
+      });
+    }
+
+    // Toast buttons for a file the desktop app just wrote: show it in the
+    // system file manager and, unless `open` is false, open it.
+    function getDesktopFileToastActions(path, { open = true } = {}) {
+      if (!isTauriDesktop() || !path) return [];
+      const actions = [{
+        label: getLocalizedText('revealInFileManager', 'Show in folder'),
+        onClick: () => runDesktopFileCommand('reveal_in_file_manager', path)
+      }];
+      if (open) {
+        actions.push({
+          label: getLocalizedText('openWithDefaultApp', 'Open'),
+          onClick: () => runDesktopFileCommand('open_with_default_app', path)
+        });
+      }
+      return actions;
+    }
+
     function handleSaveResult(result, {
       cancelledKey,
       cancelledFallback,
@@ -2023,8 +2119,11 @@
         return false;
       }
 
-      if (result.path && savedPathKey) {
-        alert(getInterpolatedText(savedPathKey, { path: result.path }, savedPathFallback));
+      if (result.path) {
+        const message = savedPathKey
+          ? getInterpolatedText(savedPathKey, { path: result.path }, savedPathFallback)
+          : getInterpolatedText('desktopSavedTo', { path: result.path }, 'Saved to {path}');
+        showToast(message, 8000, getDesktopFileToastActions(result.path));
       } else if (browserSuccessKey) {
         showToast(getLocalizedText(browserSuccessKey, browserSuccessFallback), toastDurationMs);
       }
@@ -3207,9 +3306,11 @@
         includeCurves: false
       });
 
+      const userPreset = isUserPresetId(safe.coreFilmPreset) ? userFilmPresets[safe.coreFilmPreset] : null;
       return {
         ...safe,
         filmPreset: safe.coreFilmPreset || 'none',
+        filmPresetSettings: userPreset ? userPreset.settings : undefined,
         colorModel: safe.coreColorModel,
         enhancedProfile: safe.coreEnhancedProfile,
         profileStrength: safe.coreProfileStrength,
@@ -4401,7 +4502,9 @@
     // main thread. Falls back to the main thread if the worker fails.
     let conversionWorkerBroken = false;
     async function convertFrameOffMainThread({ imageData, settings, options }) {
-      if (!conversionWorkerBroken && usesSilverCoreConversion(state)) {
+      // Imported profiles are read over IPC, which the worker cannot reach.
+      const usesImportedProfile = isUserProfileId(settings && settings.enhancedProfile);
+      if (!conversionWorkerBroken && usesSilverCoreConversion(state) && !usesImportedProfile) {
         try {
           return await convertFrameInWorker({ imageData, settings, options });
         } catch (err) {
@@ -5398,7 +5501,13 @@
         let imageData;
         let extractedRawMeta = null;
 
-        if (isRawLikeFile) {
+        if (isRawLikeFile && isDesktopSourceFile(file)) {
+          overlay.updateProgress(30, lang.loadingProcessing);
+          imageData = await loadDesktopRawImageData(file, (meta) => {
+            extractedRawMeta = meta;
+          });
+          overlay.updateProgress(90, lang.loadingProcessing);
+        } else if (isRawLikeFile) {
           const arrayBuffer = await file.arrayBuffer();
           const isHeavy = arrayBuffer.byteLength > 100 * 1024 * 1024;
 
@@ -5429,7 +5538,7 @@
           const arrayBuffer = await file.arrayBuffer();
           imageData = await loadPngImageData(arrayBuffer);
         } else {
-          imageData = await loadStandardImage(file);
+          imageData = await loadStandardImage(await materializeDesktopSourceFile(file));
         }
 
         if (imageData) {
@@ -5875,12 +5984,14 @@
       updateBeforeAfterButtonState();
     });
 
-    document.getElementById('autoDetectBtn').addEventListener('click', () => {
+    document.getElementById('autoDetectBtn').addEventListener('click', async () => {
       if (!requiresFilmBase()) return;
       const sourceData = state.croppedImageData || state.originalImageData;
       if (!sourceData) return;
+      const filmBase = await detectFilmBaseForSource(sourceData, state.coreBorderBuffer);
+      if (sourceData !== (state.croppedImageData || state.originalImageData)) return;
       pushUndo('autoDetectBase');
-      state.filmBase = autoDetectFilmBase(sourceData, state.coreBorderBuffer);
+      state.filmBase = filmBase;
       state.filmBaseSet = true;
       updateFilmBasePreview();
       updateStep2GuideCard();
@@ -6845,8 +6956,45 @@
       return 'low';
     }
 
+    // Desktop builds find the frame natively on the full-resolution source, so
+    // auto frame needs no OpenCV.js download and works offline. 8-bit sources
+    // are widened to the 16-bit layout the command reads.
+    async function detectFrameOnDesktop(imageData) {
+      const source16 = imageData.__image16;
+      const image16 = source16
+        && source16.data instanceof Uint16Array
+        && source16.width === imageData.width
+        && source16.height === imageData.height
+        ? source16
+        : {
+          width: imageData.width,
+          height: imageData.height,
+          data: Uint16Array.from(imageData.data, value => value * 257)
+        };
+      const { width, height, data } = image16;
+      const bytes = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+      const settings = { ...state.autoFrame, filmType: state.filmType };
+      delete settings.lastDiagnostics;
+      const result = await window.__TAURI__.core.invoke('detect_frame', bytes, {
+        headers: {
+          'x-frame-width': String(width),
+          'x-frame-height': String(height),
+          'x-auto-frame-settings': encodeURIComponent(JSON.stringify(settings))
+        }
+      });
+      if (!result) return null;
+      return { ...result, rotatedImageData: applyRotationToImageData(imageData, result.angle) };
+    }
+
     async function detectFrameAndRotation(imageData) {
       if (!imageData) return null;
+      if (isTauriDesktop()) {
+        try {
+          return await detectFrameOnDesktop(imageData);
+        } catch (err) {
+          console.warn('Native frame detection failed:', err);
+        }
+      }
       const ready = await ensureOpenCvReady();
       if (!ready) return null;
 
@@ -7113,7 +7261,7 @@
       }
 
       try {
-        const ready = await ensureOpenCvReady();
+        const ready = isTauriDesktop() || await ensureOpenCvReady();
         if (!ready) {
           alert(i18n[currentLang].autoFrameCvLoadError || 'OpenCV failed to load. Auto frame is unavailable.');
           return;
@@ -7189,7 +7337,7 @@
       const selectedItems = state.fileQueue.filter(item => item.selected);
       if (selectedItems.length < 1) return;
 
-      const ready = await ensureOpenCvReady();
+      const ready = isTauriDesktop() || await ensureOpenCvReady();
       if (!ready) {
         alert(i18n[currentLang].autoFrameCvLoadError || 'OpenCV failed to load. Auto frame is unavailable.');
         return;
@@ -8440,6 +8588,7 @@
       state.batchSessionActive = false;
       state.batchMode = false;
       state.lensCorrection = createInitialLensCorrectionState();
+      state.projectPath = null;
       resetFrontierGuideImageState();
       resetRollReferenceState();
       fullAdjustedBuffer = null;
@@ -8471,12 +8620,18 @@
       document.getElementById('appFooter').style.display = 'none';
       updateBeforeAfterButtonState();
       updateSprocketControlsUI();
+      refreshRecentRollsList();
+      scheduleSessionAutosave();
 
       // Reset adjustments
       document.getElementById('resetBtn').click();
       syncBatchUIState({ reason: 'newImageBtn' });
 
       // Trigger file selection
+      if (isTauriDesktop() && uploadBtn) {
+        uploadBtn.click();
+        return;
+      }
       fileInput.value = '';
       fileInput.click();
     });
@@ -8571,7 +8726,9 @@
     function normalizeSaveResult(result) {
       return {
         saved: Boolean(result && result.saved),
-        path: result && result.path ? result.path : null
+        path: result && result.path ? result.path : null,
+        skipped: Boolean(result && result.skipped),
+        conflict: result && result.conflict ? result.conflict : null
       };
     }
 
@@ -8589,44 +8746,175 @@
       return typeof path === 'string' && path ? path : null;
     }
 
-    async function writeBlobToDesktopPath(blob, targetPath, mimeType = 'application/octet-stream') {
+    const DESKTOP_UPLOAD_CHUNK_BYTES = 8 * 1024 * 1024;
+
+    // Streams the blob to the backend as raw-body chunks. Resolves to null when
+    // the running backend has no upload commands so callers can fall back to base64.
+    async function uploadBlobToDesktop(blob, target, metadata = null) {
+      const { invoke } = window.__TAURI__.core;
+      let began;
+      try {
+        began = await invoke('begin_export_upload', { target });
+      } catch (err) {
+        if (/begin_export_upload/.test(String(err && err.message ? err.message : err))) {
+          return null;
+        }
+        throw err;
+      }
+      // No upload id: the conflict policy kept the existing file.
+      if (began.uploadId == null) {
+        return normalizeSaveResult({ ...began, skipped: true });
+      }
+      const { uploadId } = began;
+
+      try {
+        for (let offset = 0; offset < blob.size; offset += DESKTOP_UPLOAD_CHUNK_BYTES) {
+          const chunk = blob.slice(offset, offset + DESKTOP_UPLOAD_CHUNK_BYTES);
+          const bytes = new Uint8Array(await chunk.arrayBuffer());
+          await invoke('append_export_chunk', bytes, {
+            headers: { 'x-upload-id': String(uploadId) }
+          });
+        }
+        const result = await invoke('finish_export_upload', { uploadId, metadata });
+        return normalizeSaveResult({ ...result, conflict: began.conflict });
+      } catch (err) {
+        await invoke('abort_export_upload', { uploadId }).catch(() => {});
+        throw err;
+      }
+    }
+
+    async function writeBlobToDesktopPath(blob, targetPath, mimeType = 'application/octet-stream', metadata = null) {
       if (!isTauriDesktop()) {
         throw new Error('Desktop path writes require the Tauri runtime.');
       }
 
       const normalizedBlob = normalizeExportBlob(blob, mimeType);
+      const uploaded = await uploadBlobToDesktop(normalizedBlob, { kind: 'path', path: targetPath }, metadata);
+      if (uploaded) return uploaded;
+
       const bytesBase64 = await blobToBase64(normalizedBlob);
       const result = await window.__TAURI__.core.invoke('write_export_file_to_path', {
         path: targetPath,
-        bytesBase64
+        bytesBase64,
+        metadata
       });
       return normalizeSaveResult(result);
     }
 
-    async function writeBlobToDesktopDirectory(blob, directory, fileName, mimeType = 'application/octet-stream') {
+    async function writeBlobToDesktopDirectory(
+      blob,
+      directory,
+      fileName,
+      mimeType = 'application/octet-stream',
+      metadata = null,
+      naming = null,
+      conflict = null
+    ) {
       if (!isTauriDesktop()) {
         throw new Error('Desktop directory writes require the Tauri runtime.');
       }
 
       const normalizedBlob = normalizeExportBlob(blob, mimeType);
+      const uploaded = await uploadBlobToDesktop(normalizedBlob, {
+        kind: 'directory',
+        directory,
+        suggestedName: fileName,
+        naming,
+        conflict
+      }, metadata);
+      if (uploaded) return uploaded;
+
       const bytesBase64 = await blobToBase64(normalizedBlob);
       const result = await window.__TAURI__.core.invoke('write_export_file_to_directory', {
         directory,
         suggestedName: fileName,
-        bytesBase64
+        bytesBase64,
+        metadata,
+        naming,
+        conflict
       });
       return normalizeSaveResult(result);
     }
 
-    async function saveBlob(blob, fileName, mimeType = 'application/octet-stream') {
+    // Source EXIF and film settings the backend embeds into desktop exports.
+    // EXIF is re-read from `sourcePath` when the file came from the native picker.
+    function buildExportMetadata(settings, queueItem = null, rawMetadata = null) {
+      const source = rawMetadata && typeof rawMetadata === 'object' ? rawMetadata : {};
+      const text = (value) => (typeof value === 'string' && value.trim() ? value.trim() : null);
+      const number = (value) => (Number.isFinite(value) && value > 0 ? value : null);
+      return {
+        sourcePath: queueItem?.sourcePath || queueItem?.file?.sourcePath || null,
+        source: {
+          cameraMaker: text(source.cameraMaker),
+          cameraModel: text(source.cameraModel),
+          lensMaker: text(source.lensMaker),
+          lensModel: text(source.lensModel),
+          focal: number(source.focal),
+          aperture: number(source.aperture)
+        },
+        film: settings ? {
+          coreFilmPreset: settings.coreFilmPreset || 'none',
+          coreEnhancedProfile: settings.coreEnhancedProfile || 'none',
+          filmType: settings.filmType || 'color',
+          filmBase: settings.filmBase || null
+        } : null
+      };
+    }
+
+    // Lets the backend name a batch export and pick its subfolder from the
+    // user's patterns. `frame` is the 1-based position in the queue, so
+    // numbering matches the roll.
+    function buildExportNaming(metadata, queueIndex, exportInfo) {
+      const template = String(state.exportFilenameTemplate || '').trim();
+      const subfolder = String(state.exportSubfolderPattern || '').trim();
+      if (!template && !subfolder) return null;
+      return {
+        template,
+        subfolder,
+        roll: getRollProjectName(),
+        frame: queueIndex + 1,
+        bitDepth: exportInfo.bitDepth,
+        metadata
+      };
+    }
+
+    // Encodes and writes a TIFF in the backend, which can compress it. Resolves to
+    // null when the running backend has no TIFF writer so callers can encode in JS.
+    async function writeTiffToDesktop(imageData, target, exportInfo, metadata = null) {
+      const { invoke } = window.__TAURI__.core;
+      const { width, height, data } = imageData;
+      const bytes = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+      const options = {
+        target,
+        width,
+        height,
+        channels: 4,
+        sampleBits: 8,
+        bitDepth: exportInfo.bitDepth,
+        compression: state.tiffCompression,
+        predictor: state.tiffCompression !== 'none',
+        metadata
+      };
+      try {
+        return normalizeSaveResult(await invoke('write_tiff_export', bytes, {
+          headers: { 'x-tiff-options': encodeURIComponent(JSON.stringify(options)) }
+        }));
+      } catch (err) {
+        if (/write_tiff_export/.test(String(err && err.message ? err.message : err))) {
+          return null;
+        }
+        throw err;
+      }
+    }
+
+    async function saveBlob(blob, fileName, mimeType = 'application/octet-stream', metadata = null) {
       const normalizedBlob = normalizeExportBlob(blob, mimeType);
       if (isTauriDesktop()) {
-        const bytesBase64 = await blobToBase64(normalizedBlob);
-        const result = await window.__TAURI__.core.invoke('save_export_file', {
-          suggestedName: fileName,
-          bytesBase64
-        });
-        return normalizeSaveResult(result);
+        const targetPath = await pickDesktopSavePath(fileName);
+        if (!targetPath) {
+          return { saved: false, path: null };
+        }
+        return writeBlobToDesktopPath(normalizedBlob, targetPath, mimeType, metadata);
       }
 
       downloadBlobInBrowser(normalizedBlob, fileName);
@@ -8755,15 +9043,17 @@
 
     function notifyExportError(err) {
       console.error('Export failed:', err);
-      const message = err && err.message ? err.message : String(err || 'Unknown error');
-      alert(`Export failed: ${message}`);
+      alert(`Export failed: ${describeDesktopError(err, getLocalizedText)}`);
     }
 
     async function exportSingle() {
       const lang = i18n[currentLang];
       const overlay = getLoadingOverlay();
       const exportInfo = getExportInfo();
+      const nativeTiff = isTauriDesktop() && exportInfo.format === 'tiff';
       let fileName = buildActiveExportFileName(null, exportInfo);
+      let outputImageData = null;
+      let metadata = null;
       let blob;
 
       await overlay.show({ title: lang.loadingExporting });
@@ -8771,24 +9061,29 @@
         overlay.updateProgress(5, lang.loadingAdjusting);
 
         const currentItem = getCurrentQueueItem();
+        metadata = buildExportMetadata(extractCurrentSettings(), currentItem, state.rawMetadata);
         if (state.currentStep >= 3 && state.processedImageData) {
           persistCurrentFileSettings({ silent: true, force: true });
           const imageData = await renderCurrentImageDataForExport();
-          const outputImageData = applySprocketFrameForExport(imageData, exportInfo);
-          overlay.updateProgress(60, lang.loadingEncoding);
-          blob = await imageDataToBlob(outputImageData, exportInfo.format, state.jpegQuality, exportInfo.bitDepth, (pct) => {
-            overlay.updateProgress(60 + pct * 0.35, lang.loadingEncoding);
-          });
+          outputImageData = applySprocketFrameForExport(imageData, exportInfo);
+          if (!nativeTiff) {
+            overlay.updateProgress(60, lang.loadingEncoding);
+            blob = await imageDataToBlob(outputImageData, exportInfo.format, state.jpegQuality, exportInfo.bitDepth, (pct) => {
+              overlay.updateProgress(60 + pct * 0.35, lang.loadingEncoding);
+            });
+          }
           if (currentItem?.file?.name) {
             fileName = buildActiveExportFileName(currentItem.file.name, exportInfo);
           }
         } else {
           overlay.updateProgress(50, lang.loadingEncoding);
           const imageData = await renderCurrentImageDataForExport();
-          const outputImageData = applySprocketFrameForExport(imageData, exportInfo);
-          blob = await imageDataToBlob(outputImageData, exportInfo.format, state.jpegQuality, exportInfo.bitDepth, (pct) => {
-            overlay.updateProgress(50 + pct * 0.45, lang.loadingEncoding);
-          });
+          outputImageData = applySprocketFrameForExport(imageData, exportInfo);
+          if (!nativeTiff) {
+            blob = await imageDataToBlob(outputImageData, exportInfo.format, state.jpegQuality, exportInfo.bitDepth, (pct) => {
+              overlay.updateProgress(50 + pct * 0.45, lang.loadingEncoding);
+            });
+          }
         }
 
         overlay.updateProgress(100, lang.loadingComplete);
@@ -8797,7 +9092,18 @@
         overlay.hide();
       }
 
-      return await saveBlob(blob, fileName, exportInfo.mimeType);
+      if (nativeTiff) {
+        const targetPath = await pickDesktopSavePath(fileName);
+        if (!targetPath) {
+          return { saved: false, path: null };
+        }
+        const written = await writeTiffToDesktop(outputImageData, { kind: 'path', path: targetPath }, exportInfo, metadata);
+        if (written) return written;
+        blob = await imageDataToBlob(outputImageData, exportInfo.format, state.jpegQuality, exportInfo.bitDepth);
+        return await writeBlobToDesktopPath(blob, targetPath, exportInfo.mimeType, metadata);
+      }
+
+      return await saveBlob(blob, fileName, exportInfo.mimeType, metadata);
     }
 
     document.getElementById('exportSingleBtn').addEventListener('click', async () => {
@@ -8849,6 +9155,26 @@
       });
     });
 
+    document.getElementById('exportFilenameTemplateInput').addEventListener('change', (e) => {
+      state.exportFilenameTemplate = e.target.value.trim();
+    });
+
+    document.getElementById('exportSubfolderPatternInput').addEventListener('change', (e) => {
+      state.exportSubfolderPattern = e.target.value.trim();
+    });
+
+    document.getElementById('exportConflictPolicySelect').addEventListener('change', (e) => {
+      state.exportConflictPolicy = e.target.value;
+    });
+
+    // TIFF compression toggle buttons (desktop TIFF writer)
+    document.querySelectorAll('.tiff-compression-btn').forEach(btn => {
+      btn.addEventListener('click', () => {
+        state.tiffCompression = btn.dataset.compression;
+        updateExportUI();
+      });
+    });
+
     // Quality slider
     document.getElementById('exportQualitySlider').addEventListener('input', (e) => {
       state.jpegQuality = parseInt(e.target.value);
@@ -8861,7 +9187,6 @@
       if (!zipBtn || !exportAllBtn) return;
 
       const desktop = isTauriDesktop();
-      zipBtn.style.display = desktop ? 'none' : '';
       const exportAllKey = desktop ? 'exportIndividualDesktop' : 'exportIndividual';
       exportAllBtn.textContent = getLocalizedText(exportAllKey, exportAllBtn.textContent || 'Export All Individually');
       exportAllBtn.setAttribute('data-i18n', exportAllKey);
@@ -8884,6 +9209,18 @@
         btn.classList.toggle('active', depth === state.exportBitDepth);
       });
 
+      const tiffCompressionSection = document.getElementById('exportTiffCompressionSection');
+      if (tiffCompressionSection) {
+        tiffCompressionSection.classList.toggle('show', format === 'tiff' && isTauriDesktop());
+        document.querySelectorAll('.tiff-compression-btn').forEach(btn => {
+          btn.classList.toggle('active', btn.dataset.compression === state.tiffCompression);
+        });
+      }
+      const filenameTemplateSection = document.getElementById('exportFilenameTemplateSection');
+      if (filenameTemplateSection) {
+        filenameTemplateSection.classList.toggle('show', isTauriDesktop());
+      }
+
       // Update export button text
       const exportBtn = document.getElementById('exportBtn');
       const exportKey = isJpeg ? 'exportJpeg' : (format === 'tiff' ? 'exportTiff' : 'exportPng');
@@ -8929,9 +9266,37 @@
       return deepCopySanitizedSettings(safe);
     }
 
+    // Film base detection on the full 16-bit frame with the native detector,
+    // which also reports every candidate region it considered. Falls back to
+    // the preview-based detector in the browser or without 16-bit data.
+    async function detectFilmBaseForSource(sourceData, borderBufferPct) {
+      const image16 = sourceData && sourceData.__image16;
+      if (!isTauriDesktop() || !image16 || !(image16.data instanceof Uint16Array)) {
+        return autoDetectFilmBase(sourceData, borderBufferPct);
+      }
+      try {
+        const { width, height, data } = image16;
+        const bytes = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+        const result = await window.__TAURI__.core.invoke('detect_film_base', bytes, {
+          headers: {
+            'x-frame-width': String(width),
+            'x-frame-height': String(height),
+            'x-border-buffer': String(borderBufferPct)
+          }
+        });
+        // The per-region breakdown is not part of the film base settings.
+        delete result.regions;
+        return result;
+      } catch (err) {
+        console.warn('Native film base detection failed:', err);
+        return autoDetectFilmBase(sourceData, borderBufferPct);
+      }
+    }
+
     function markCurrentFileDirty() {
       const item = getCurrentQueueItem();
       if (!item) return;
+      scheduleSessionAutosave();
       if (item.isDirty) return;
       item.isDirty = true;
       if (state.batchSessionActive) {
@@ -8941,6 +9306,16 @@
       }
     }
 
+    // Mirrors a queue item's settings into its `.xmp` sidecar on desktop. Fire and
+    // forget: a read-only roll folder should not block editing.
+    function saveSettingsSidecar(item) {
+      if (!isTauriDesktop() || !item || !item.sourcePath || !item.settings) return;
+      const settings = toSerializableSettings(item.settings);
+      writeSettingsSidecar(window.__TAURI__.core.invoke, item.sourcePath, settings).catch((err) => {
+        console.warn(`Writing sidecar for ${item.sourcePath} failed:`, err);
+      });
+    }
+
     function persistCurrentFileSettings(options = {}) {
       const { silent = false, force = false } = options;
       const item = getCurrentQueueItem();
@@ -8950,6 +9325,7 @@
 
       item.settings = extractCurrentSettings();
       item.isDirty = false;
+      saveSettingsSidecar(item);
       updateFileListUI();
 
       if (!silent) {
@@ -8976,6 +9352,7 @@
         }
         item.settings = next;
         item.isDirty = false;
+        saveSettingsSidecar(item);
         count++;
       });
       return count;
@@ -9045,6 +9422,78 @@
       alert(template.replace('{count}', String(applied)));
     }
 
+    // Samples every queued frame's film base natively and, after showing
+    // which frames disagree with the rest, applies the roll's consensus base
+    // to every frame that has settings.
+    async function matchFilmBaseAcrossRoll() {
+      if (state.fileQueue.length < 2) {
+        showToast(getLocalizedText('rollFilmBaseNeedsFrames', 'Add at least two frames to match the film base across the roll.'), 4000);
+        return;
+      }
+      persistCurrentFileSettings({ silent: true });
+      const frames = buildRollFilmBaseFrames(state.fileQueue, state.coreBorderBuffer);
+      const method = getEffectiveFilmType() === 'color' ? 'density' : 'linear';
+      let report = null;
+      try {
+        report = await window.__TAURI__.core.invoke('analyze_roll_film_base', { frames, method });
+      } catch (err) {
+        console.warn('Roll film base analysis failed:', err);
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+        return;
+      }
+      if (!report || !report.consensus) {
+        showToast(getLocalizedText('rollFilmBaseNone', 'No frame had a film base to compare.'), 4000);
+        return;
+      }
+
+      const summary = summarizeRollFilmBase(report);
+      const nameOf = (index) => {
+        const item = state.fileQueue[index];
+        return item && item.file ? item.file.name : `#${index + 1}`;
+      };
+      const lines = [
+        getInterpolatedText(
+          'rollFilmBaseFrom',
+          { used: summary.used.length, total: state.fileQueue.length },
+          'Roll film base from {used} of {total} frames.'
+        )
+      ];
+      [
+        ['outliers', 'rollFilmBaseOutliers', 'Far from the rest of the roll: {files}'],
+        ['lowConfidence', 'rollFilmBaseUnsure', 'No clear film base found: {files}'],
+        ['missing', 'rollFilmBaseMissing', 'Could not be read: {files}']
+      ].forEach(([key, textKey, fallback]) => {
+        if (summary[key].length) {
+          lines.push(getInterpolatedText(textKey, { files: summary[key].map(nameOf).join(', ') }, fallback));
+        }
+      });
+      lines.push('', getLocalizedText('rollFilmBaseConfirm', 'Apply the roll film base to every frame with saved settings?'));
+      if (!window.confirm(lines.join('\n'))) return;
+
+      const filmBase = sanitizeFilmBase(report.consensus);
+      const currentItem = getCurrentQueueItem();
+      let applied = 0;
+      state.fileQueue.forEach((item) => {
+        if (!item.settings || item === currentItem) return;
+        item.settings = { ...item.settings, filmBase: { ...filmBase } };
+        saveSettingsSidecar(item);
+        applied += 1;
+      });
+      if (currentItem && state.originalImageData) {
+        pushUndo('autoDetectBase');
+        state.filmBase = { ...filmBase };
+        state.filmBaseSet = true;
+        updateFilmBasePreview();
+        updateStep2GuideCard();
+        markCurrentFileDirty();
+        persistCurrentFileSettings({ silent: true, force: true });
+        scheduleSilverSourceRefresh({ immediate: true });
+        applied += 1;
+      }
+      updateFileListUI();
+      showToast(getInterpolatedText('rollFilmBaseApplied', { count: applied }, 'Applied the roll film base to {count} frame(s).'), 4000);
+    }
+
     function clearRollReference() {
       resetRollReferenceState();
       updateRollReferenceUI();
@@ -9129,17 +9578,33 @@
       return cropImageDataRegion(imageData, sanitized);
     }
 
+    // Desktop inputs with a path on disk decode natively; the Wasm decoder
+    // stays as the fallback for files the native backend cannot read.
+    async function loadDesktopRawImageData(file, onMetadata = null) {
+      try {
+        return await loadRawImageDataFromDesktopPath(window.__TAURI__.core.invoke, file.sourcePath, {
+          onMetadata
+        });
+      } catch (err) {
+        console.warn('[RAW] native decode failed, falling back to LibRaw-Wasm:', err);
+        const arrayBuffer = await file.arrayBuffer();
+        return await loadRawImageData(arrayBuffer, file.name.toLowerCase(), { onMetadata });
+      }
+    }
+
     async function loadFileToImageData(file) {
       const fileName = file.name.toLowerCase();
 
-      if (isRawLikeFileName(fileName)) {
+      if (isRawLikeFileName(fileName) && isDesktopSourceFile(file)) {
+        return await loadDesktopRawImageData(file);
+      } else if (isRawLikeFileName(fileName)) {
         const arrayBuffer = await file.arrayBuffer();
         return await loadRawImageData(arrayBuffer, fileName);
       } else if (file.type === 'image/png') {
         const arrayBuffer = await file.arrayBuffer();
         return await loadPngImageData(arrayBuffer);
       } else {
-        return await loadStandardImage(file);
+        return await loadStandardImage(await materializeDesktopSourceFile(file));
       }
     }
 
@@ -9272,6 +9737,32 @@
     }
 
     // Process a file with its own settings or auto-detect
+    // Desktop builds convert export frames on the native engine; the browser,
+    // or a frame the backend fails on, goes through the unified conversion
+    // router (in a worker when available — keeps batch export from freezing
+    // the page).
+    async function convertFrameForExport(imageData, settings) {
+      if (isTauriDesktop() && usesSilverCoreConversion(settings)) {
+        try {
+          const converted16 = await convertFrameOnDesktop(
+            window.__TAURI__.core.invoke,
+            imageData,
+            cloneSettings(settings)
+          );
+          const result = toImageData8(converted16);
+          result.__image16 = converted16;
+          return result;
+        } catch (err) {
+          console.warn('Native conversion failed, converting in the webview:', err);
+        }
+      }
+      return convertFrameOffMainThread({
+        imageData,
+        settings: buildRouterSettings(settings),
+        options: {}
+      });
+    }
+
     async function processFileWithSettings(file, savedSettings, options = {}) {
       const trace = createPerfTrace('processFileWithSettings', {
         file: file?.name || '',
@@ -9305,13 +9796,7 @@
         pixels: getImageDataPixelCount(workingData)
       });
 
-      // Convert negative/positive via unified conversion router (in a worker
-      // when available — keeps batch export from freezing the page).
-      let processed = await convertFrameOffMainThread({
-        imageData: workingData,
-        settings: buildRouterSettings(settings),
-        options: {}
-      });
+      let processed = await convertFrameForExport(workingData, settings);
       trace.mark('convert', {
         pixels: getImageDataPixelCount(processed)
       });
@@ -9358,24 +9843,134 @@
       );
     }
 
-    async function exportBatchAsZipDesktop(selectedFiles, zipFileName) {
-      const JSZipCtor = await getJSZipCtor();
-      if (typeof JSZipCtor !== 'function') {
-        throw new Error('JSZip module is unavailable');
+    // Streams one encoded frame into a native ZIP archive as raw-body chunks.
+    async function addDesktopZipEntry(zipId, blob, name, naming) {
+      const { invoke } = window.__TAURI__.core;
+      let offset = 0;
+      do {
+        const chunk = blob.slice(offset, offset + DESKTOP_UPLOAD_CHUNK_BYTES);
+        offset += DESKTOP_UPLOAD_CHUNK_BYTES;
+        const entry = { zipId, name, last: offset >= blob.size, naming };
+        await invoke('add_zip_entry', new Uint8Array(await chunk.arrayBuffer()), {
+          headers: { 'x-zip-entry': encodeURIComponent(JSON.stringify(entry)) }
+        });
+      } while (offset < blob.size);
+    }
+
+    // Writes the archive entry by entry in the backend, so a roll of 16-bit
+    // TIFFs never has to fit in webview memory. Resolves to null when the
+    // running backend has no ZIP commands so the caller can use JSZip.
+    async function exportBatchAsZipNative(selectedFiles, targetPath) {
+      const { invoke } = window.__TAURI__.core;
+      const exportInfo = getExportInfo();
+      let zipId;
+      try {
+        zipId = await invoke('begin_zip_export', {
+          target: { kind: 'path', path: targetPath },
+          // PNG and JPEG are already compressed; JS-encoded TIFFs are not
+          deflate: exportInfo.format === 'tiff'
+        });
+      } catch (err) {
+        if (/begin_zip_export/.test(String(err && err.message ? err.message : err))) {
+          return null;
+        }
+        throw err;
+      }
+
+      const lang = i18n[currentLang];
+      const overlay = getLoadingOverlay();
+      const total = selectedFiles.length;
+      let processedCount = 0;
+      await overlay.show({ title: lang.loadingExporting });
+      try {
+        for (const { item, index } of selectedFiles) {
+          item.status = 'processing';
+          updateFileListUI();
+          processedCount++;
+          const fileProgress = ((processedCount - 1) / total) * 95;
+          const fileSlice = 95 / total;
+          overlay.updateProgress(fileProgress, lang.loadingBatchFile.replace('{current}', processedCount).replace('{total}', total));
+
+          let blob;
+          let settingsForFile;
+          try {
+            settingsForFile = getSettingsForExport(index, item);
+            const adjusted = await processFileWithSettings(item.file, settingsForFile);
+            const outputImageData = applySprocketFrameForExport(adjusted, exportInfo);
+            overlay.updateProgress(fileProgress + fileSlice * 0.6, lang.loadingEncoding);
+            blob = await imageDataToBlob(
+              outputImageData,
+              exportInfo.format,
+              state.jpegQuality,
+              exportInfo.bitDepth
+            );
+          } catch (err) {
+            console.error(`Error processing ${item.file.name}:`, err);
+            item.status = 'error';
+            item.error = describeDesktopError(err, getLocalizedText);
+            updateFileListUI();
+            continue;
+          }
+
+          // A failed write leaves the archive unusable, so it ends the export
+          const name = buildActiveExportFileName(item.file.name, exportInfo);
+          const naming = buildExportNaming(buildExportMetadata(settingsForFile, item), index, exportInfo);
+          try {
+            await addDesktopZipEntry(zipId, blob, name, naming);
+          } catch (err) {
+            item.status = 'error';
+            item.error = describeDesktopError(err, getLocalizedText);
+            updateFileListUI();
+            throw err;
+          }
+          item.status = 'done';
+          item.error = null;
+          overlay.updateProgress(fileProgress + fileSlice, lang.loadingBatchFile.replace('{current}', processedCount).replace('{total}', total));
+          updateFileListUI();
+        }
+
+        overlay.updateProgress(97, lang.loadingBatchZip);
+        return normalizeSaveResult(await invoke('finish_zip_export', { zipId }));
+      } catch (err) {
+        await invoke('abort_zip_export', { zipId }).catch(() => {});
+        throw err;
+      } finally {
+        overlay.hide();
       }
+    }
 
-      let desktopZipTargetPath = null;
+    async function exportBatchAsZipDesktop(selectedFiles, zipFileName) {
+      const zipSaveMessages = {
+        cancelledKey: 'zipSaveCancelled',
+        cancelledFallback: 'ZIP save cancelled. No file was written.',
+        savedPathKey: 'zipSavedTo',
+        savedPathFallback: 'ZIP saved to:\n{path}',
+        browserSuccessKey: 'zipDownloadStarted',
+        browserSuccessFallback: 'ZIP download started. Check your Downloads folder.'
+      };
       if (isTauriDesktop()) {
         // Pick the destination before the long-running batch starts so macOS can
         // surface the save panel immediately instead of after processing finishes.
-        desktopZipTargetPath = await pickDesktopSavePath(zipFileName);
-        if (!desktopZipTargetPath) {
-          handleSaveResult({ saved: false, path: null }, {
-            cancelledKey: 'zipSaveCancelled',
-            cancelledFallback: 'ZIP save cancelled. No file was written.'
-          });
+        const targetPath = await pickDesktopSavePath(zipFileName);
+        if (!targetPath) {
+          handleSaveResult({ saved: false, path: null }, zipSaveMessages);
+          return;
+        }
+        const result = await exportBatchAsZipNative(selectedFiles, targetPath);
+        if (result) {
+          handleSaveResult(result, zipSaveMessages);
           return;
         }
+        await exportBatchAsZipInMemory(selectedFiles, zipFileName, targetPath);
+        return;
+      }
+      await exportBatchAsZipInMemory(selectedFiles, zipFileName, null);
+    }
+
+    async function exportBatchAsZipInMemory(selectedFiles, zipFileName, desktopZipTargetPath) {
+      const JSZipCtor = await getJSZipCtor();
+      if (typeof JSZipCtor !== 'function') {
+        throw new Error('JSZip module is unavailable');
       }
 
       const zip = new JSZipCtor();
@@ -9628,21 +10223,73 @@
       return new Promise((resolve) => requestAnimationFrame(() => resolve()));
     }
 
-    function showDesktopBatchExportSummary({ successCount, failCount, total, targetDirectory }) {
+    function showDesktopBatchExportSummary({
+      successCount,
+      failCount,
+      skippedCount = 0,
+      total,
+      targetDirectory,
+      lastWrittenPath = null
+    }) {
       const folder = summarizePathForUi(targetDirectory) || targetDirectory || 'selected folder';
       const key = failCount > 0 ? 'desktopBatchExportSummaryErrors' : 'desktopBatchExportSummary';
       const fallback = failCount > 0
         ? `Exported ${successCount} / ${total} files, ${failCount} failed. Target: ${folder}.`
         : `Exported ${successCount} / ${total} files to ${folder}.`;
-      showToast(
-        getInterpolatedText(key, {
-          success: successCount,
-          total,
-          failed: failCount,
-          folder
-        }, fallback),
-        4500
-      );
+      let message = getInterpolatedText(key, {
+        success: successCount,
+        total,
+        failed: failCount,
+        folder
+      }, fallback);
+      if (skippedCount > 0) {
+        message += ' ' + getInterpolatedText('desktopBatchExportSkipped', {
+          skipped: skippedCount
+        }, `Skipped ${skippedCount} existing files.`);
+      }
+      if (lastWrittenPath) {
+        showToast(message, 8000, getDesktopFileToastActions(lastWrittenPath, { open: false }));
+        return;
+      }
+      showToast(message, 4500);
+    }
+
+    // Checks the target folder before a batch starts. Resolves to false when
+    // it cannot be written or the user backs out of the warnings.
+    async function confirmDesktopExportPreflight(targetDirectory, jobs, exportInfo) {
+      const frame = state.originalImageData;
+      let report;
+      try {
+        report = await runDesktopExportPreflight(window.__TAURI__.core.invoke, {
+          directory: targetDirectory,
+          estimatedBytes: estimateExportBytes({
+            format: exportInfo.format,
+            bitDepth: exportInfo.bitDepth,
+            jpegQuality: state.jpegQuality,
+            width: frame ? frame.width : 0,
+            height: frame ? frame.height : 0,
+            count: jobs.length
+          }),
+          count: jobs.length,
+          files: jobs.map(({ item, index, outputName, settings }) => ({
+            suggestedName: outputName,
+            naming: buildExportNaming(buildExportMetadata(settings, item), index, exportInfo)
+          }))
+        });
+      } catch (err) {
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+        return false;
+      }
+
+      const warnings = describePreflightWarnings(report, getInterpolatedText);
+      if (!warnings.length) return true;
+      const title = getLocalizedText('preflightTitle', 'Before exporting:');
+      if (report.warnings.includes('not-writable')) {
+        showToast(`${title} ${warnings.join(' ')}`, 6000);
+        return false;
+      }
+      const question = getLocalizedText('preflightContinue', 'Export anyway?');
+      return window.confirm(`${title}\n${warnings.join('\n')}\n\n${question}`);
     }
 
     async function exportBatchIndividuallyDesktop() {
@@ -9663,6 +10310,7 @@
 
       const exportInfo = getExportInfo();
       const jobs = createBatchExportJobs(selectedFiles, exportInfo);
+      if (!await confirmDesktopExportPreflight(targetDirectory, jobs, exportInfo)) return;
       const total = jobs.length;
       const jpegQuality = state.jpegQuality;
       const dustRemoval = {
@@ -9671,6 +10319,80 @@
       };
       let successCount = 0;
       let failCount = 0;
+      let skippedCount = 0;
+      let settledCount = 0;
+      // `ask` can be answered once for the whole batch.
+      let conflictPolicy = state.exportConflictPolicy;
+      let cancelled = false;
+      let stopped = false;
+      let exportJob = null;
+      let lastWrittenPath = null;
+
+      const stopBatch = () => {
+        stopped = true;
+        if (exportJob) exportJob.cancel().catch(() => {});
+      };
+      const recordWritten = (item, written) => {
+        if (written.conflict && written.conflict.applyToAll) {
+          conflictPolicy = written.conflict.policy;
+        }
+        item.status = 'done';
+        item.error = null;
+        if (written.skipped) {
+          skippedCount++;
+        } else {
+          successCount++;
+          lastWrittenPath = written.path || lastWrittenPath;
+        }
+      };
+      const recordFailed = (item, err) => {
+        // Files dropped because the batch stopped were never attempted.
+        if (stopped && err && err.code === 'Cancelled') {
+          item.status = 'pending';
+          item.error = null;
+          return;
+        }
+        console.error(`Error processing ${item.file.name}:`, err);
+        item.status = 'error';
+        item.error = describeDesktopError(err, getLocalizedText);
+        failCount++;
+        // A full disk or unwritable folder fails every remaining file too.
+        if (isBatchFatalDesktopError(err) && !stopped) {
+          stopBatch();
+          showToast(getInterpolatedText(
+            'desktopBatchExportStopped',
+            { error: item.error },
+            'Batch export stopped: {error}'
+          ), 6000);
+        }
+      };
+      // Files are written on a backend worker while the next one converts;
+      // older backends write them one at a time below.
+      const onSettled = () => {
+        settledCount++;
+        updateFileListUI();
+        if (desktopBatchExportState.fileName) return;
+        setDesktopBatchExportState({ percent: (settledCount / total) * 100 });
+      };
+      if (window.__TAURI__.event) {
+        try {
+          exportJob = await startDesktopExportJob({
+            invoke: window.__TAURI__.core.invoke,
+            listen: window.__TAURI__.event.listen
+          }, total, {
+            onDone: ({ index, result }) => {
+              recordWritten(jobs[index].item, normalizeSaveResult(result));
+              onSettled();
+            },
+            onError: ({ index, error }) => {
+              recordFailed(jobs[index].item, error);
+              onSettled();
+            }
+          });
+        } catch (err) {
+          console.warn('Export jobs unavailable, writing files one at a time:', err);
+        }
+      }
 
       resetBatchExportStatuses(jobs);
       setDesktopBatchExportState({
@@ -9681,11 +10403,20 @@
         fileName: '',
         targetDirectory
       });
+      cancelDesktopBatchExport = () => {
+        cancelled = true;
+        cancelDesktopBatchExport = null;
+        stopBatch();
+        updateDesktopBatchExportUI();
+      };
+      updateDesktopBatchExportUI();
       await waitForNextFrame();
 
+      let i = 0;
       try {
-        for (let i = 0; i < jobs.length; i++) {
-          const { item, file, outputName, settings } = jobs[i];
+        for (; i < jobs.length; i++) {
+          if (stopped) break;
+          const { item, index, file, outputName, settings } = jobs[i];
           const fileBaseProgress = (i / total) * 100;
           const fileSlice = 100 / total;
 
@@ -9714,32 +10445,89 @@
               targetDirectory
             });
 
-            const blob = await imageDataToBlob(
-              outputImageData,
-              exportInfo.format,
-              jpegQuality,
-              exportInfo.bitDepth,
-              (pct) => {
-                setDesktopBatchExportState({
-                  active: true,
-                  current: i + 1,
-                  total,
-                  percent: fileBaseProgress + fileSlice * (0.62 + pct * 0.3),
-                  fileName: file.name,
-                  targetDirectory
-                });
+            const metadata = buildExportMetadata(settings, item);
+            const naming = buildExportNaming(metadata, index, exportInfo);
+            const conflict = {
+              policy: conflictPolicy,
+              remaining: total - i - 1,
+              sourcePath: item.sourcePath || file.sourcePath || null
+            };
+            const target = {
+              kind: 'directory',
+              directory: targetDirectory,
+              suggestedName: outputName,
+              naming,
+              conflict
+            };
+            const reportEncoding = (pct) => {
+              setDesktopBatchExportState({
+                active: true,
+                current: i + 1,
+                total,
+                percent: fileBaseProgress + fileSlice * (0.62 + pct * 0.3),
+                fileName: file.name,
+                targetDirectory
+              });
+            };
+
+            if (exportJob) {
+              // TIFFs go over as raw samples so the backend keeps its own
+              // compression and metadata, as `write_tiff_export` does.
+              let bytes;
+              let tiff = null;
+              if (exportInfo.format === 'tiff') {
+                const { width, height, data } = outputImageData;
+                bytes = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+                tiff = {
+                  width,
+                  height,
+                  channels: 4,
+                  sampleBits: 8,
+                  bitDepth: exportInfo.bitDepth,
+                  compression: state.tiffCompression,
+                  predictor: state.tiffCompression !== 'none'
+                };
+              } else {
+                const blob = await imageDataToBlob(
+                  outputImageData,
+                  exportInfo.format,
+                  jpegQuality,
+                  exportInfo.bitDepth,
+                  reportEncoding
+                );
+                bytes = new Uint8Array(await blob.arrayBuffer());
               }
-            );
+              // Stay at most one file ahead of the writer.
+              await exportJob.whenPendingBelow(2);
+              await exportJob.queueFile(i, bytes, { target, metadata, tiff });
+            } else {
+              let written = exportInfo.format === 'tiff'
+                ? await withDesktopRetry(() => writeTiffToDesktop(outputImageData, target, exportInfo, metadata))
+                : null;
+              if (!written) {
+                const blob = await imageDataToBlob(
+                  outputImageData,
+                  exportInfo.format,
+                  jpegQuality,
+                  exportInfo.bitDepth,
+                  reportEncoding
+                );
 
-            await writeBlobToDesktopDirectory(blob, targetDirectory, outputName, exportInfo.mimeType);
-            item.status = 'done';
-            item.error = null;
-            successCount++;
+                written = await withDesktopRetry(() => writeBlobToDesktopDirectory(
+                  blob,
+                  targetDirectory,
+                  outputName,
+                  exportInfo.mimeType,
+                  metadata,
+                  naming,
+                  conflict
+                ));
+              }
+              recordWritten(item, written);
+            }
           } catch (err) {
-            console.error(`Error processing ${file.name}:`, err);
-            item.status = 'error';
-            item.error = err && err.message ? err.message : String(err || 'Unknown error');
-            failCount++;
+            if (exportJob) exportJob.settle(i);
+            recordFailed(item, err);
           }
 
           updateFileListUI();
@@ -9753,11 +10541,37 @@
           });
           await waitForNextFrame();
         }
+
+        if (exportJob) {
+          for (let rest = i; rest < total; rest++) exportJob.settle(rest);
+          setDesktopBatchExportState({
+            current: total,
+            percent: (settledCount / total) * 100,
+            fileName: ''
+          });
+          await exportJob.finished;
+        }
       } finally {
+        if (exportJob) exportJob.dispose();
+        cancelDesktopBatchExport = null;
         resetDesktopBatchExportState();
       }
 
-      showDesktopBatchExportSummary({ successCount, failCount, total, targetDirectory });
+      if (cancelled) {
+        showToast(getInterpolatedText('desktopBatchExportCancelled', {
+          success: successCount,
+          total
+        }, `Export stopped after ${successCount} / ${total} files.`), 4500);
+        return;
+      }
+      showDesktopBatchExportSummary({
+        successCount,
+        failCount,
+        skippedCount,
+        total,
+        targetDirectory,
+        lastWrittenPath
+      });
     }
 
     // Streaming individual download: process → download → free → next
@@ -9807,7 +10621,7 @@
 
             name = buildActiveExportFileName(item.file.name, exportInfo);
             overlay.hide(); // Hide overlay before save dialog
-            const result = await saveBlob(blob, name, exportInfo.mimeType);
+            const result = await saveBlob(blob, name, exportInfo.mimeType, buildExportMetadata(settingsForFile, item));
             if (!result.saved) {
               cancelledByUser = true;
               item.status = 'pending';
@@ -9888,6 +10702,7 @@
 
       updateAutoFrameButtons();
       syncBatchUIState({ reason: 'updateFileListUI' });
+      scheduleSessionAutosave();
     }
 
     async function switchToFile(index) {
@@ -10231,6 +11046,12 @@
       clearRollReference();
     });
 
+    const matchRollFilmBaseBtn = document.getElementById('matchRollFilmBaseBtn');
+    if (matchRollFilmBaseBtn && isTauriDesktop()) {
+      matchRollFilmBaseBtn.style.display = '';
+      matchRollFilmBaseBtn.addEventListener('click', () => matchFilmBaseAcrossRoll());
+    }
+
     document.getElementById('lockRollReference').addEventListener('change', (e) => {
       state.rollReference.applyLock = Boolean(e.target.checked);
       updateRollReferenceUI();
@@ -10284,6 +11105,7 @@
       state.fileQueue = [];
       state.currentFileIndex = 0;
       state.batchSessionActive = false;
+      state.projectPath = null;
       resetRollReferenceState();
       updateFileListUI();
       syncBatchUIState({ reason: 'clearFileListBtn' });
@@ -10312,6 +11134,7 @@
           const newItem = {
             id,
             file: file,
+            sourcePath: isDesktopSourceFile(file) ? file.sourcePath : null,
             selected: true,  // Selected by default
             status: 'pending',
             error: null,
@@ -10358,6 +11181,11 @@
       if (e.key !== 'Enter' && e.key !== ' ') return;
       const label = e.currentTarget;
       if (!label || label.getAttribute('aria-disabled') === 'true') return;
+      if (label === uploadBtn && isTauriDesktop()) {
+        e.preventDefault();
+        label.click();
+        return;
+      }
       const inputId = label.getAttribute('for');
       if (!inputId) return;
       const input = document.getElementById(inputId);
@@ -10391,6 +11219,562 @@
 
     applyFolderPickerAvailability();
 
+    async function pickDesktopInputFiles() {
+      const { invoke } = window.__TAURI__.core;
+      const entries = await invoke('open_input_files');
+      return Array.isArray(entries) ? entries.map((entry) => createDesktopSourceFile(entry, invoke)) : [];
+    }
+
+    // On desktop the native picker replaces the <input type="file"> so queue
+    // items keep their source paths on disk.
+    if (uploadBtn && isTauriDesktop()) {
+      uploadBtn.addEventListener('click', async (e) => {
+        e.preventDefault();
+        if (isDesktopBatchExportLocked()) return;
+        let files;
+        try {
+          files = await pickDesktopInputFiles();
+        } catch (err) {
+          console.warn('Native file picker failed, using the browser picker:', err);
+          fileInput.value = '';
+          fileInput.click();
+          return;
+        }
+        if (files.length === 0) return;
+
+        // Reset state for new batch
+        state.fileQueue = [];
+        state.currentFileIndex = 0;
+        state.cropRegion = null;
+        state.rotationAngle = 0;
+        state.loadedBaseImageData = null;
+        state.batchSessionActive = false;
+        state.projectPath = null;
+        resetRollReferenceState();
+        syncBatchUIState({ reason: 'desktopPicker_reset' });
+
+        addFilesToQueue(files);
+        await hydrateQueueSettingsFromSidecars(window.__TAURI__.core.invoke, state.fileQueue, cloneSettings);
+        updateFileListUI();
+
+        // Load the first file, picking up its sidecar settings like switchToFile does
+        const firstItem = state.fileQueue[0];
+        if (firstItem) {
+          await loadFile(firstItem.file);
+          if (firstItem.settings) {
+            restoreSettings(firstItem.settings);
+            firstItem.isDirty = false;
+            updateFileListUI();
+          }
+        }
+      });
+    }
+
+    // ===========================================
+    // Roll Projects (.ncroll, desktop)
+    // ===========================================
+    const openProjectBtn = document.getElementById('openProjectBtn');
+    const saveProjectBtn = document.getElementById('saveProjectBtn');
+    const recentRollsSection = document.getElementById('recentRollsSection');
+    const recentRollsList = document.getElementById('recentRollsList');
+
+    function getRollProjectName() {
+      if (state.projectPath) {
+        return state.projectPath.split(/[\\/]/).pop().replace(/\.ncroll$/i, '');
+      }
+      // Default to the roll folder's name, which is how most scans are organised
+      const firstPath = state.fileQueue.find(item => item.sourcePath)?.sourcePath || '';
+      const segments = firstPath.split(/[\\/]/).filter(Boolean);
+      return segments.length > 1 ? segments[segments.length - 2] : 'roll';
+    }
+
+    function syncExportPreferenceControls() {
+      document.querySelectorAll('.format-btn').forEach(btn => {
+        btn.classList.toggle('active', btn.dataset.format === state.exportFormat);
+      });
+      document.getElementById('exportQualitySlider').value = String(state.jpegQuality);
+      document.getElementById('exportQualityValue').textContent = state.jpegQuality + '%';
+      document.getElementById('exportFilenameTemplateInput').value = state.exportFilenameTemplate;
+      document.getElementById('exportSubfolderPatternInput').value = state.exportSubfolderPattern;
+      document.getElementById('exportConflictPolicySelect').value = state.exportConflictPolicy;
+      updateSprocketControlsUI();
+      updateExportUI();
+    }
+
+    async function saveRollProject() {
+      if (!isTauriDesktop() || isDesktopBatchExportLocked()) return;
+      persistCurrentFileSettings({ silent: true });
+      const { project, skipped } = buildRollProject({
+        name: getRollProjectName(),
+        queue: state.fileQueue,
+        currentIndex: state.currentFileIndex,
+        rollReference: state.rollReference,
+        preferences: collectExportPreferences(state)
+      });
+      if (project.frames.length === 0) {
+        showToast(getLocalizedText('rollNoFrames', 'Only files opened from disk can be saved in a roll.'));
+        return;
+      }
+
+      let result;
+      try {
+        result = await window.__TAURI__.core.invoke('save_project', { path: state.projectPath, project });
+      } catch (err) {
+        console.error('Saving roll failed:', err);
+        alert(getInterpolatedText(
+          'rollSaveFailed',
+          { error: describeDesktopError(err, getLocalizedText) },
+          'Saving the roll failed: {error}'
+        ));
+        return;
+      }
+      if (!result || !result.saved) return;
+
+      state.projectPath = result.path;
+      showToast(getInterpolatedText('rollSaved', { path: result.path }, 'Roll saved: {path}'));
+      if (skipped > 0) {
+        showToast(getInterpolatedText(
+          'rollSkippedFrames',
+          { count: skipped },
+          '{count} file(s) not opened from disk were left out of the roll.'
+        ), 5000);
+      }
+    }
+
+    // Opens a roll from `path`, or asks for one when `path` is null.
+    async function openRollProject(path = null) {
+      if (!isTauriDesktop() || isDesktopBatchExportLocked()) return;
+      const { invoke } = window.__TAURI__.core;
+      let opened;
+      try {
+        opened = await invoke('open_project', { path });
+      } catch (err) {
+        console.error('Opening roll failed:', err);
+        alert(getInterpolatedText(
+          'rollOpenFailed',
+          { error: describeDesktopError(err, getLocalizedText) },
+          'Opening the roll failed: {error}'
+        ));
+        refreshRecentRollsList();
+        return;
+      }
+      if (!opened) return;
+
+      if (await loadOpenedRoll(opened, opened.path)) {
+        refreshRecentRollsList();
+      }
+    }
+
+    // Replaces the queue with an `open_project` or `recover_last_session`
+    // result. Returns false when none of its frames could be found.
+    async function loadOpenedRoll(opened, projectPath) {
+      const { invoke } = window.__TAURI__.core;
+      const restored = restoreRollProject(opened, (entry) => createDesktopSourceFile(entry, invoke));
+      if (restored.missingFrames.length > 0) {
+        alert(getInterpolatedText('rollMissingFrames', {
+          count: restored.missingFrames.length,
+          paths: restored.missingFrames.join('\n')
+        }, '{count} frame(s) could not be found:\n{paths}'));
+      }
+      if (restored.items.length === 0) return false;
+
+      // Reset state for the reopened roll, the same way the file pickers do
+      state.fileQueue = [];
+      state.currentFileIndex = 0;
+      state.cropRegion = null;
+      state.rotationAngle = 0;
+      state.loadedBaseImageData = null;
+      state.batchSessionActive = false;
+      resetRollReferenceState();
+      syncBatchUIState({ reason: 'openRollProject_reset' });
+
+      addFilesToQueue(restored.items.map(item => item.file));
+      state.fileQueue.forEach((queueItem) => {
+        const saved = restored.items.find(item => item.sourcePath === queueItem.sourcePath);
+        if (!saved) return;
+        queueItem.selected = saved.selected;
+        queueItem.settings = saved.settings ? cloneSettings(saved.settings) : null;
+      });
+
+      if (restored.rollReference) {
+        const referenceItem = state.fileQueue.find(item => item.sourcePath === restored.rollReference.sourcePath);
+        state.rollReference.enabled = true;
+        state.rollReference.sourceFileId = referenceItem ? referenceItem.id : null;
+        state.rollReference.settingsSnapshot = cloneSettings(restored.rollReference.settingsSnapshot);
+        state.rollReference.applyLock = restored.rollReference.applyLock;
+        state.rollReference.applyCrop = restored.rollReference.applyCrop;
+      }
+      updateRollReferenceUI();
+
+      applyExportPreferences(state, restored.exportPreferences);
+      syncExportPreferenceControls();
+      state.projectPath = projectPath || null;
+      updateFileListUI();
+
+      const currentIndex = Math.min(restored.currentIndex, state.fileQueue.length - 1);
+      const currentItem = state.fileQueue[currentIndex];
+      state.currentFileIndex = currentIndex;
+      await loadFile(currentItem.file);
+      if (currentItem.settings) {
+        restoreSettings(currentItem.settings);
+        currentItem.isDirty = false;
+      }
+      updateFileListUI();
+      return true;
+    }
+
+    async function refreshRecentRollsList() {
+      if (!recentRollsSection || !recentRollsList || !isTauriDesktop()) return;
+      let recent = [];
+      try {
+        recent = await window.__TAURI__.core.invoke('list_recent_projects');
+      } catch (err) {
+        console.warn('Listing recent rolls failed:', err);
+      }
+      recentRollsList.replaceChildren();
+      (Array.isArray(recent) ? recent : []).forEach((entry) => {
+        const button = document.createElement('button');
+        button.type = 'button';
+        button.className = 'recent-roll-btn';
+        button.title = entry.path;
+        const name = document.createElement('span');
+        name.textContent = entry.name || entry.path.split(/[\\/]/).pop();
+        const meta = document.createElement('span');
+        meta.className = 'recent-roll-meta';
+        meta.textContent = `${entry.frameCount} · ${new Date(entry.savedAt).toLocaleDateString()}`;
+        button.append(name, meta);
+        button.addEventListener('click', () => openRollProject(entry.path));
+        const item = document.createElement('li');
+        item.appendChild(button);
+        recentRollsList.appendChild(item);
+      });
+      recentRollsSection.style.display = recentRollsList.childElementCount > 0 ? 'block' : 'none';
+    }
+
+    if (isTauriDesktop()) {
+      if (openProjectBtn) {
+        openProjectBtn.style.display = 'inline-flex';
+        openProjectBtn.addEventListener('click', () => openRollProject());
+      }
+      if (saveProjectBtn) {
+        saveProjectBtn.style.display = '';
+        saveProjectBtn.addEventListener('click', () => saveRollProject());
+      }
+      refreshRecentRollsList();
+    }
+
+    // ===========================================
+    // Enhanced Profiles (desktop)
+    // ===========================================
+    const coreEnhancedProfileSelect = document.getElementById('coreEnhancedProfile');
+    const coreUserProfileGroup = document.getElementById('coreUserProfileGroup');
+    const coreProfileFileButtons = document.getElementById('coreProfileFileButtons');
+    const importProfileBtn = document.getElementById('importProfileBtn');
+    const exportProfileCubeBtn = document.getElementById('exportProfileCubeBtn');
+
+    async function refreshDesktopProfileOptions() {
+      if (!coreUserProfileGroup || !coreEnhancedProfileSelect) return false;
+      let profiles = null;
+      try {
+        profiles = await listDesktopProfiles(window.__TAURI__.core.invoke);
+      } catch (err) {
+        console.warn('Listing profiles failed:', err);
+        return true;
+      }
+      if (!profiles) return false;
+      coreUserProfileGroup.replaceChildren(...userProfileOptions(profiles).map(({ value, label }) => {
+        const option = document.createElement('option');
+        option.value = value;
+        option.textContent = label;
+        return option;
+      }));
+      coreUserProfileGroup.hidden = coreUserProfileGroup.childElementCount === 0;
+      if (coreEnhancedProfileSelect.value !== state.coreEnhancedProfile) {
+        coreEnhancedProfileSelect.value = state.coreEnhancedProfile;
+      }
+      return true;
+    }
+
+    async function importDesktopProfile() {
+      try {
+        const imported = await window.__TAURI__.core.invoke('import_profile');
+        if (!imported) return;
+        await refreshDesktopProfileOptions();
+        coreEnhancedProfileSelect.value = imported.id;
+        coreEnhancedProfileSelect.dispatchEvent(new Event('change'));
+        showToast(getInterpolatedText('profileImported', { name: imported.name }, 'Imported {name}'), 3500);
+      } catch (err) {
+        console.warn('Importing profile failed:', err);
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+      }
+    }
+
+    async function exportDesktopProfileAsCube() {
+      const id = coreEnhancedProfileSelect && coreEnhancedProfileSelect.value;
+      if (!id || id === 'none') {
+        showToast(getLocalizedText('profileExportNone', 'Choose a profile to export first.'), 3500);
+        return;
+      }
+      try {
+        const result = await window.__TAURI__.core.invoke('export_profile_as_cube', { id });
+        if (result && result.saved && result.path) {
+          const message = getInterpolatedText('desktopSavedTo', { path: result.path }, 'Saved to {path}');
+          showToast(message, 8000, getDesktopFileToastActions(result.path, { open: false }));
+        }
+      } catch (err) {
+        console.warn('Exporting profile failed:', err);
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+      }
+    }
+
+    if (isTauriDesktop()) {
+      registerProfileSource((id) => (
+        isUserProfileId(id) ? readDesktopProfile(window.__TAURI__.core.invoke, id) : null
+      ));
+      refreshDesktopProfileOptions().then((available) => {
+        if (!available || !coreProfileFileButtons) return;
+        coreProfileFileButtons.style.display = '';
+        if (importProfileBtn) importProfileBtn.addEventListener('click', importDesktopProfile);
+        if (exportProfileCubeBtn) exportProfileCubeBtn.addEventListener('click', exportDesktopProfileAsCube);
+      });
+    }
+
+    // ===========================================
+    // Film Preset Library (desktop)
+    // ===========================================
+    const filmPresetSelect = document.getElementById('filmPreset');
+    const userFilmPresetGroup = document.getElementById('userFilmPresetGroup');
+    const filmPresetLibrary = document.getElementById('filmPresetLibrary');
+    const filmPresetNameInput = document.getElementById('filmPresetNameInput');
+
+    async function refreshUserFilmPresets() {
+      if (!userFilmPresetGroup || !filmPresetSelect) return false;
+      let presets = null;
+      try {
+        presets = await listDesktopFilmPresets(window.__TAURI__.core.invoke);
+      } catch (err) {
+        console.warn('Listing film presets failed:', err);
+        return true;
+      }
+      if (!presets) return false;
+      userFilmPresets = userFilmPresetMap(presets);
+      userFilmPresetGroup.replaceChildren(...Object.entries(userFilmPresets).map(([id, preset]) => {
+        const option = document.createElement('option');
+        option.value = id;
+        option.textContent = preset.name;
+        return option;
+      }));
+      userFilmPresetGroup.hidden = userFilmPresetGroup.childElementCount === 0;
+      if (filmPresetSelect.value !== state.coreFilmPreset) {
+        filmPresetSelect.value = state.coreFilmPreset;
+      }
+      return true;
+    }
+
+    function syncFilmPresetNameInput() {
+      if (!filmPresetNameInput) return;
+      const preset = userFilmPresets[state.coreFilmPreset];
+      filmPresetNameInput.value = preset ? preset.name : '';
+    }
+
+    // Saving under the selected user preset's name updates it; any other
+    // name adds a new preset.
+    async function saveUserFilmPreset() {
+      const name = filmPresetNameInput ? filmPresetNameInput.value.trim() : '';
+      if (!name) {
+        showToast(getLocalizedText('presetNameRequired', 'Enter a preset name first.'), 3500);
+        return;
+      }
+      const current = state.coreFilmPreset;
+      const base = current === 'none' ? null : await getFilmPreset(current);
+      const updating = isUserPresetId(current) && base && base.name === name;
+      const draft = buildFilmPresetDraft({
+        id: updating ? current : null,
+        name,
+        filmType: getEffectiveFilmType(),
+        baseSettings: base ? base.settings : {},
+        state
+      });
+      try {
+        const saved = await window.__TAURI__.core.invoke('save_film_preset', { preset: draft });
+        await refreshUserFilmPresets();
+        state.coreFilmPreset = saved.id;
+        syncAllSelectsFromState();
+        syncFilmPresetNameInput();
+        scheduleSessionAutosave();
+        showToast(getInterpolatedText('presetSaved', { name: saved.name }, 'Saved preset {name}'), 3500);
+      } catch (err) {
+        console.warn('Saving film preset failed:', err);
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+      }
+    }
+
+    async function renameUserFilmPreset() {
+      const id = state.coreFilmPreset;
+      if (!isUserPresetId(id) || !userFilmPresets[id]) {
+        showToast(getLocalizedText('presetUserOnly', 'Choose one of your presets first.'), 3500);
+        return;
+      }
+      const name = filmPresetNameInput ? filmPresetNameInput.value.trim() : '';
+      if (!name) {
+        showToast(getLocalizedText('presetNameRequired', 'Enter a preset name first.'), 3500);
+        return;
+      }
+      try {
+        await window.__TAURI__.core.invoke('rename_film_preset', { id, name });
+        await refreshUserFilmPresets();
+        syncFilmPresetNameInput();
+      } catch (err) {
+        console.warn('Renaming film preset failed:', err);
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+      }
+    }
+
+    async function deleteUserFilmPreset() {
+      const id = state.coreFilmPreset;
+      const preset = userFilmPresets[id];
+      if (!isUserPresetId(id) || !preset) {
+        showToast(getLocalizedText('presetUserOnly', 'Choose one of your presets first.'), 3500);
+        return;
+      }
+      const question = getInterpolatedText('presetDeleteConfirm', { name: preset.name }, 'Delete preset {name}?');
+      if (!window.confirm(question)) return;
+      try {
+        await window.__TAURI__.core.invoke('delete_film_preset', { id });
+        // Frames keep the look they were given; only the selection goes.
+        state.coreFilmPreset = 'none';
+        await refreshUserFilmPresets();
+        syncAllSelectsFromState();
+        syncFilmPresetNameInput();
+        scheduleSessionAutosave();
+        showToast(getInterpolatedText('presetDeleted', { name: preset.name }, 'Deleted preset {name}'), 3500);
+      } catch (err) {
+        console.warn('Deleting film preset failed:', err);
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+      }
+    }
+
+    async function importUserFilmPresets() {
+      try {
+        const report = await window.__TAURI__.core.invoke('import_film_presets');
+        if (!report) return;
+        await refreshUserFilmPresets();
+        const messages = [
+          getInterpolatedText('presetsImported', { count: report.imported.length }, 'Imported {count} presets')
+        ];
+        if (report.unchanged.length) {
+          messages.push(getInterpolatedText(
+            'presetsUnchanged',
+            { count: report.unchanged.length },
+            '{count} already in your library'
+          ));
+        }
+        showToast(messages.join(' · '), 4000);
+      } catch (err) {
+        console.warn('Importing film presets failed:', err);
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+      }
+    }
+
+    // Exports the selected user preset, or the whole library otherwise.
+    async function exportUserFilmPresets() {
+      const id = state.coreFilmPreset;
+      const ids = isUserPresetId(id) && userFilmPresets[id] ? [id] : null;
+      try {
+        const result = await window.__TAURI__.core.invoke('export_film_presets', { ids });
+        if (result && result.saved && result.path) {
+          const message = getInterpolatedText('desktopSavedTo', { path: result.path }, 'Saved to {path}');
+          showToast(message, 8000, getDesktopFileToastActions(result.path, { open: false }));
+        }
+      } catch (err) {
+        console.warn('Exporting film presets failed:', err);
+        showToast(describeDesktopError(err, getLocalizedText), 5000);
+      }
+    }
+
+    if (isTauriDesktop()) {
+      refreshUserFilmPresets().then((available) => {
+        if (!available || !filmPresetLibrary) return;
+        filmPresetLibrary.style.display = '';
+        syncFilmPresetNameInput();
+        filmPresetSelect.addEventListener('change', syncFilmPresetNameInput);
+        const actions = [
+          ['saveFilmPresetBtn', saveUserFilmPreset],
+          ['renameFilmPresetBtn', renameUserFilmPreset],
+          ['deleteFilmPresetBtn', deleteUserFilmPreset],
+          ['importFilmPresetsBtn', importUserFilmPresets],
+          ['exportFilmPresetsBtn', exportUserFilmPresets]
+        ];
+        actions.forEach(([buttonId, action]) => {
+          const button = document.getElementById(buttonId);
+          if (button) button.addEventListener('click', action);
+        });
+      });
+    }
+
+    // ===========================================
+    // Session Autosave (desktop)
+    // ===========================================
+    function scheduleSessionAutosave() {
+      if (sessionAutosave && !sessionRecoveryPending) sessionAutosave.schedule();
+    }
+
+    // The queue as an unsaved roll project, plus the current frame's edits
+    // that have not been persisted into its queue item yet.
+    function collectAutosaveSession() {
+      if (!state.fileQueue.some(item => item.sourcePath)) return null;
+      const { project } = buildRollProject({
+        name: getRollProjectName(),
+        queue: state.fileQueue,
+        currentIndex: state.currentFileIndex,
+        rollReference: state.rollReference,
+        preferences: collectExportPreferences(state)
+      });
+      const currentItem = getCurrentQueueItem();
+      const hasLiveEdits = Boolean(currentItem && currentItem.isDirty && state.originalImageData);
+      return {
+        projectPath: state.projectPath,
+        project,
+        currentSettings: hasLiveEdits ? toSerializableSettings(extractCurrentSettings()) : null
+      };
+    }
+
+    async function offerSessionRecovery() {
+      let recovered;
+      try {
+        recovered = await window.__TAURI__.core.invoke('recover_last_session');
+      } catch (err) {
+        console.warn('Reading the autosaved session failed:', err);
+      }
+      if (!recovered) {
+        sessionRecoveryPending = false;
+        return;
+      }
+
+      const prompt = getInterpolatedText('recoverSessionPrompt', {
+        count: recovered.frames.length,
+        time: new Date(recovered.savedAt).toLocaleString()
+      }, 'Restore the roll you were working on ({count} frame(s), autosaved {time})?');
+      if (!window.confirm(prompt)) {
+        sessionRecoveryPending = false;
+        sessionAutosave.discard().catch((err) => console.warn('Discarding autosave failed:', err));
+        return;
+      }
+      try {
+        if (await loadOpenedRoll(recovered, recovered.projectPath) && recovered.currentSettings) {
+          restoreSettings(recovered.currentSettings);
+          markCurrentFileDirty();
+        }
+      } finally {
+        sessionRecoveryPending = false;
+        scheduleSessionAutosave();
+      }
+    }
+
+    if (sessionAutosave) {
+      offerSessionRecovery();
+    }
+
     fileInput.addEventListener('click', () => {
       fileInput.value = '';
     });
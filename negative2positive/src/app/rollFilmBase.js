@@ -0,0 +1,40 @@
+// Roll-wide film base consensus on desktop (see src-tauri/src/roll_film_base.rs):
+// the backend samples every queued frame again and reports one base for the
+// roll plus how far each frame's own base is from it.
+
+/** The queue as `analyze_roll_film_base` frames, in queue order. */
+export function buildRollFilmBaseFrames(queue, fallbackBorderBuffer = 10) {
+  return (Array.isArray(queue) ? queue : []).map((item) => {
+    const settings = (item && item.settings) || {};
+    const borderBuffer = Number(settings.coreBorderBuffer);
+    return {
+      sourcePath: (item && item.sourcePath) || null,
+      borderBuffer: Number.isFinite(borderBuffer) ? borderBuffer : fallbackBorderBuffer,
+      filmBase: settings.filmBase && typeof settings.filmBase === 'object' ? { ...settings.filmBase } : null
+    };
+  });
+}
+
+/**
+ * Groups the per-frame reports for display: which frames went into the
+ * consensus and which were left out, by queue index.
+ */
+export function summarizeRollFilmBase(report) {
+  const summary = { used: [], outliers: [], lowConfidence: [], missing: [], worstDeviation: 0 };
+  const frames = report && Array.isArray(report.frames) ? report.frames : [];
+  frames.forEach((frame) => {
+    if (frame.source === 'missing') {
+      summary.missing.push(frame.index);
+    } else if (frame.rejected === 'outlier') {
+      summary.outliers.push(frame.index);
+    } else if (frame.rejected === 'lowConfidence') {
+      summary.lowConfidence.push(frame.index);
+    } else {
+      summary.used.push(frame.index);
+    }
+    if (Number.isFinite(frame.deviation)) {
+      summary.worstDeviation = Math.max(summary.worstDeviation, frame.deviation);
+    }
+  });
+  return summary;
+}
@@ -0,0 +1,37 @@
+// Standalone Node test for rollFilmBase.js - run with:
+// node negative2positive/src/app/rollFilmBase.test.mjs
+import assert from 'node:assert/strict';
+import { buildRollFilmBaseFrames, summarizeRollFilmBase } from './rollFilmBase.js';
+
+// ---- frames carry their path, border buffer and snapshot base ----
+assert.deepEqual(buildRollFilmBaseFrames([
+  { sourcePath: '/roll/01.dng', settings: { coreBorderBuffer: 6, filmBase: { r: 200, g: 130, b: 80, confidence: 0.7 } } },
+  { sourcePath: '/roll/02.dng', settings: null },
+  { file: { name: 'dropped.png' } }
+], 12), [
+  { sourcePath: '/roll/01.dng', borderBuffer: 6, filmBase: { r: 200, g: 130, b: 80, confidence: 0.7 } },
+  { sourcePath: '/roll/02.dng', borderBuffer: 12, filmBase: null },
+  { sourcePath: null, borderBuffer: 12, filmBase: null }
+]);
+assert.deepEqual(buildRollFilmBaseFrames(undefined), []);
+
+// ---- reports are grouped by why frames were left out ----
+assert.deepEqual(summarizeRollFilmBase({
+  consensus: { r: 200, g: 130, b: 80 },
+  frames: [
+    { index: 0, source: 'detected', deviation: 0.004 },
+    { index: 1, source: 'settings', deviation: 0.01 },
+    { index: 2, source: 'detected', deviation: 0.12, rejected: 'outlier' },
+    { index: 3, source: 'detected', deviation: 0.02, rejected: 'lowConfidence' },
+    { index: 4, source: 'missing', error: 'unsupported input format' }
+  ]
+}), {
+  used: [0, 1],
+  outliers: [2],
+  lowConfidence: [3],
+  missing: [4],
+  worstDeviation: 0.12
+});
+assert.deepEqual(summarizeRollFilmBase(null).used, []);
+
+console.log('rollFilmBase tests: all passed');
@@ -69,3 +69,19 @@ export function deepCopySanitizedSettings(safe, { autoFrameMeta = safe.autoFrame
     }
   };
 }
+
+// JSON turns the Uint8Array curve LUTs into `{"0": …}` objects, which
+// sanitizeSettings cannot read back, so snapshots leaving the webview
+// (sidecars, roll projects, autosave) carry them as plain arrays.
+export function toSerializableSettings(snapshot) {
+  if (!snapshot || typeof snapshot !== 'object') return snapshot;
+  if (!snapshot.curves) return { ...snapshot };
+  return {
+    ...snapshot,
+    curves: {
+      r: Array.from(snapshot.curves.r || []),
+      g: Array.from(snapshot.curves.g || []),
+      b: Array.from(snapshot.curves.b || [])
+    }
+  };
+}
@@ -1,7 +1,7 @@
 // Standalone Node test for settingsSnapshot.js - run with:
 // node negative2positive/src/app/settingsSnapshot.test.mjs
 import assert from 'node:assert/strict';
-import { deepCopySanitizedSettings } from './settingsSnapshot.js';
+import { deepCopySanitizedSettings, toSerializableSettings } from './settingsSnapshot.js';
 
 function makeSafeSettings() {
   return {
@@ -116,4 +116,16 @@ function makeSafeSettings() {
   assert.equal(copy.lensCorrection.selectedLens, null);
 }
 
+// 5. Serializable snapshots keep their curve LUTs through JSON
+{
+  const copy = deepCopySanitizedSettings(makeSafeSettings());
+  const parsed = JSON.parse(JSON.stringify(toSerializableSettings(copy)));
+  assert.ok(Array.isArray(parsed.curves.r));
+  assert.equal(parsed.curves.r.length, 256);
+  assert.equal(parsed.curves.r[10], 10);
+  assert.deepEqual(parsed.cropRegion, copy.cropRegion);
+  assert.ok(copy.curves.r instanceof Uint8Array, 'source snapshot is not modified');
+  assert.equal(toSerializableSettings(null), null);
+}
+
 console.log('settingsSnapshot tests: all passed');
@@ -8,7 +8,7 @@ import {
 } from '../silvercore/util/image16.js';
 import { applyFilmBaseCompensationToBuffer } from './filmBaseCompensation.js';
 
-const ENHANCED_PROFILE_SET = new Set(['none', 'frontier', 'crystal', 'natural', 'pakon']);
+const ENHANCED_PROFILE_SET = new Set(['none', 'frontier', 'crystal', 'natural', 'pakon', 'noritsu']);
 
 function clamp(value, min, max) {
   return Math.max(min, Math.min(max, value));
@@ -43,11 +43,18 @@ function normalizeCurvePrecision(value) {
 
 function normalizeEnhancedProfile(value) {
   const normalized = String(value || 'none');
+  // Imported desktop profiles load through `registerProfileSource`.
+  if (normalized.startsWith('user:')) return normalized;
   return ENHANCED_PROFILE_SET.has(normalized) ? normalized : 'none';
 }
 
 async function applyFilmPreset(baseSettings, presetId) {
   if (!presetId || presetId === 'none') return baseSettings;
+  // User presets live in the desktop app's store and travel with the
+  // settings, so conversion workers do not need to reach it.
+  if (baseSettings.filmPresetSettings) {
+    return { ...baseSettings, ...baseSettings.filmPresetSettings };
+  }
   const filmPresets = await loadFilmPresets();
   const preset = filmPresets[presetId];
   if (!preset) return baseSettings;
@@ -10,6 +10,7 @@ export const PROFILES = ['none', 'frontier', 'crystal', 'natural', 'pakon', 'nor
 export const LUT_SIZE = 32
 
 const profileCache = new Map()
+let profileSource = null
 const PROFILE_URLS = {
   frontier: new URL('../resources/profiles/frontier.bin', import.meta.url).href,
   crystal: new URL('../resources/profiles/crystal.bin', import.meta.url).href,
@@ -18,6 +19,16 @@ const PROFILE_URLS = {
   noritsu: new URL('../resources/profiles/noritsu.bin', import.meta.url).href,
 }
 
+/**
+ * Register a loader for profiles that are not built in (the desktop app's
+ * imported LUTs). It resolves to `{ name, data, size, bakedData }` with
+ * `bakedData` already an sRGB-to-sRGB table, or null for unknown names.
+ * @param {((name: string) => Promise<object|null>)|null} loader
+ */
+export function registerProfileSource(loader) {
+  profileSource = typeof loader === 'function' ? loader : null
+}
+
 /**
  * Load a 3D LUT profile from binary file.
  * @param {string} name - Profile name (e.g., 'frontier')
@@ -28,7 +39,12 @@ export async function loadProfile(name) {
   if (profileCache.has(name)) return profileCache.get(name)
 
   const url = PROFILE_URLS[name]
-  if (!url) throw new Error(`Unknown profile: ${name}`)
+  if (!url) {
+    const profile = profileSource ? await profileSource(name) : null
+    if (!profile) throw new Error(`Unknown profile: ${name}`)
+    profileCache.set(name, profile)
+    return profile
+  }
   const resp = await fetch(url)
   if (!resp.ok) throw new Error(`Failed to load profile: ${name}`)
   const buf = await resp.arrayBuffer()
@@ -1,11 +1,27 @@
 // Toast notifications. Renders into #toastContainer (see index.html).
+// `actions` adds buttons, e.g. `[{ label: 'Show in folder', onClick }]`.
 
-export function showToast(message, durationMs = 2000) {
+export function showToast(message, durationMs = 2000, actions = []) {
   const container = document.getElementById('toastContainer');
   if (!container) return;
   const el = document.createElement('div');
   el.className = 'toast-message';
-  el.textContent = message;
+  if (actions.length) {
+    el.classList.add('toast-with-actions');
+    const text = document.createElement('span');
+    text.textContent = message;
+    el.appendChild(text);
+    actions.forEach(({ label, onClick }) => {
+      const button = document.createElement('button');
+      button.type = 'button';
+      button.className = 'toast-action';
+      button.textContent = label;
+      button.addEventListener('click', () => onClick());
+      el.appendChild(button);
+    });
+  } else {
+    el.textContent = message;
+  }
   container.appendChild(el);
   requestAnimationFrame(() => el.classList.add('toast-visible'));
   setTimeout(() => {
@@ -0,0 +1,15 @@
+// Writes the bundled film presets as JSON for the native engine, which embeds
+// src-tauri/resources/film-presets.json at compile time. Re-run after editing
+// negative2positive/src/silvercore/engine/FilmPresets.js.
+import { mkdirSync, writeFileSync } from 'node:fs';
+import { dirname, join } from 'node:path';
+import { fileURLToPath } from 'node:url';
+import { filmPresets } from '../negative2positive/src/silvercore/engine/FilmPresets.js';
+
+const repoRoot = join(dirname(fileURLToPath(import.meta.url)), '..');
+const target = join(repoRoot, 'src-tauri', 'resources', 'film-presets.json');
+
+mkdirSync(dirname(target), { recursive: true });
+writeFileSync(target, `${JSON.stringify(filmPresets, null, 2)}\n`);
+
+console.log(`Exported ${Object.keys(filmPresets).length} film presets -> ${target}`);
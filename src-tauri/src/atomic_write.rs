@@ -0,0 +1,254 @@
+//! Crash-safe file writes. Bytes go to a hidden sibling temp file, are fsynced,
+//! optionally read back and checked against the SHA-256 of what was written,
+//! and only then renamed over the target. An interrupted write (crash, full
+//! disk, dropped network share) leaves at most a stray temp file, never a
+//! truncated file under the final name.
+
+use crate::error::{CommandError, CommandResult};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteReport {
+    pub bytes_written: u64,
+    /// Lowercase hex SHA-256, comparable with `sha256sum` output.
+    pub checksum: String,
+}
+
+/// A file being written under a temp name until [`PartialFile::commit`].
+pub struct PartialFile {
+    final_path: PathBuf,
+    temp_path: PathBuf,
+    file: File,
+    hasher: Sha256,
+    bytes_written: u64,
+}
+
+fn temp_path_for(path: &Path) -> CommandResult<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| CommandError::InvalidPath(format!("invalid path: {}", path.display())))?;
+    let id = NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed);
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}-{id}.partial", std::process::id()));
+    Ok(path.with_file_name(temp_name))
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn file_checksum(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Makes the rename itself durable. Not every platform or filesystem lets a
+/// directory be opened for syncing, so failures are ignored.
+fn sync_parent_directory(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        if let Ok(directory) = File::open(parent) {
+            let _ = directory.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+impl PartialFile {
+    pub fn create(final_path: PathBuf) -> CommandResult<Self> {
+        let temp_path = temp_path_for(&final_path)?;
+        let file = File::create(&temp_path).map_err(|err| CommandError::io("create file", err))?;
+        Ok(Self {
+            final_path,
+            temp_path,
+            file,
+            hasher: Sha256::new(),
+            bytes_written: 0,
+        })
+    }
+
+    pub fn final_path(&self) -> &Path {
+        &self.final_path
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> CommandResult<u64> {
+        self.file
+            .write_all(bytes)
+            .map_err(|err| CommandError::io("write file", err))?;
+        self.hasher.update(bytes);
+        self.bytes_written += bytes.len() as u64;
+        Ok(self.bytes_written)
+    }
+
+    /// Fsyncs, verifies when asked, and renames the temp file into place.
+    /// On any failure the temp file is removed and the target is untouched.
+    pub fn commit(self, verify: bool) -> CommandResult<WriteReport> {
+        let Self {
+            final_path,
+            temp_path,
+            mut file,
+            hasher,
+            bytes_written,
+        } = self;
+        let checksum = hex(&hasher.finalize());
+
+        let result = (|| {
+            file.flush()
+                .and_then(|()| file.sync_all())
+                .map_err(|err| CommandError::io("write file", err))?;
+            drop(file);
+            if verify {
+                let on_disk = file_checksum(&temp_path)
+                    .map_err(|err| CommandError::io("verify file", err))?;
+                if on_disk != checksum {
+                    return Err(CommandError::Io(format!(
+                        "verify file failed: {} does not match what was written",
+                        final_path.display()
+                    )));
+                }
+            }
+            std::fs::rename(&temp_path, &final_path)
+                .map_err(|err| CommandError::io("move file into place", err))
+        })();
+        if let Err(err) = result {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(err);
+        }
+        sync_parent_directory(&final_path);
+
+        Ok(WriteReport {
+            bytes_written,
+            checksum,
+        })
+    }
+
+    /// Ends the write without moving it into place and returns the target
+    /// path with the bytes written so far.
+    pub fn into_bytes(self) -> CommandResult<(PathBuf, Vec<u8>)> {
+        let Self {
+            final_path,
+            temp_path,
+            file,
+            ..
+        } = self;
+        drop(file);
+        let bytes = std::fs::read(&temp_path).map_err(|err| CommandError::io("read file", err));
+        let _ = std::fs::remove_file(&temp_path);
+        Ok((final_path, bytes?))
+    }
+
+    /// Drops the partial write, leaving any existing target untouched.
+    pub fn abort(self) -> CommandResult<()> {
+        drop(self.file);
+        std::fs::remove_file(&self.temp_path)
+            .map_err(|err| CommandError::io("remove partial file", err))
+    }
+}
+
+/// Lets streaming encoders (e.g. the ZIP writer) write straight into the temp
+/// file; errors stay raw so callers can classify them.
+impl Write for PartialFile {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let written = self.file.write(bytes)?;
+        self.hasher.update(&bytes[..written]);
+        self.bytes_written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Writes `bytes` to `path` through a temp file and a rename.
+pub fn write_atomic(path: &Path, bytes: &[u8], verify: bool) -> CommandResult<WriteReport> {
+    let mut partial = PartialFile::create(path.to_path_buf())?;
+    if let Err(err) = partial.write(bytes) {
+        let _ = partial.abort();
+        return Err(err);
+    }
+    partial.commit(verify)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_atomic, PartialFile};
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "negative-converter-atomic-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn writes_replace_the_target_and_report_sha256() {
+        let dir = scratch_dir("replace");
+        let path = dir.join("frame.tif");
+        std::fs::write(&path, b"old contents").unwrap();
+
+        let report = write_atomic(&path, b"abc", true).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"abc");
+        assert_eq!(report.bytes_written, 3);
+        assert_eq!(
+            report.checksum,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn target_is_untouched_until_commit_and_after_abort() {
+        let dir = scratch_dir("abort");
+        let path = dir.join("frame.png");
+        std::fs::write(&path, b"keep me").unwrap();
+
+        let mut partial = PartialFile::create(path.clone()).unwrap();
+        partial.write(b"half").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"keep me");
+        partial.abort().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"keep me");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let mut partial = PartialFile::create(path.clone()).unwrap();
+        partial.write(b"new ").unwrap();
+        partial.write(b"frame").unwrap();
+        assert_eq!(partial.commit(false).unwrap().bytes_written, 9);
+        assert_eq!(std::fs::read(&path).unwrap(), b"new frame");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_writes_leave_no_temp_file() {
+        let dir = scratch_dir("missing");
+        assert!(write_atomic(&dir.join("no-such-dir").join("frame.jpg"), b"x", true).is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
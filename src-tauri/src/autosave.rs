@@ -0,0 +1,225 @@
+//! Crash-safe autosave of the webview's working session.
+//!
+//! The webview sends debounced snapshots of its queue as an unsaved roll
+//! project (per-frame settings in the `settingsSnapshot.js` shape, roll
+//! reference, export preferences) plus the current frame's live edits. Each
+//! one replaces the previous file through a temp file and a rename, so a crash
+//! mid-write leaves the last complete snapshot on disk for
+//! `recover_last_session`.
+
+use crate::atomic_write::write_atomic;
+use crate::error::{CommandError, CommandResult};
+use crate::project::RollProject;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const SESSION_FILE: &str = "autosave-session.json";
+const SESSION_FORMAT: &str = "negative-converter-session";
+const SESSION_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutosaveSession {
+    /// `.ncroll` the queue came from, so recovery can keep saving to it.
+    #[serde(default)]
+    pub project_path: Option<String>,
+    pub project: RollProject,
+    /// Edits on the current frame not yet persisted into its queue item.
+    #[serde(default)]
+    pub current_settings: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    pub format: String,
+    pub schema_version: u32,
+    pub saved_at: u64,
+    pub session: AutosaveSession,
+}
+
+/// Orders snapshots from one webview load. Commands may run concurrently, so
+/// an older snapshot can arrive after a newer one; it is dropped instead of
+/// overwriting newer work. A reloaded webview starts a new `session_id` and
+/// counts from zero again.
+#[derive(Default)]
+pub struct Autosave {
+    latest: Mutex<Option<(String, u64)>>,
+}
+
+impl Autosave {
+    /// Writes `session` unless a newer snapshot of the same webview load has
+    /// already been written. Returns whether it was written.
+    pub fn save(
+        &self,
+        dir: &Path,
+        session_id: &str,
+        sequence: u64,
+        session: AutosaveSession,
+    ) -> CommandResult<bool> {
+        let mut latest = self
+            .latest
+            .lock()
+            .map_err(|_| CommandError::Internal("autosave state is poisoned".to_string()))?;
+        if let Some((id, last)) = latest.as_ref() {
+            if id == session_id && sequence <= *last {
+                return Ok(false);
+            }
+        }
+        let snapshot = SessionSnapshot {
+            format: SESSION_FORMAT.to_string(),
+            schema_version: SESSION_SCHEMA_VERSION,
+            saved_at: now_millis(),
+            session,
+        };
+        let bytes = serde_json::to_vec(&snapshot)
+            .map_err(|err| CommandError::Internal(format!("serialize session failed: {err}")))?;
+        std::fs::create_dir_all(dir)
+            .map_err(|err| CommandError::io("create autosave directory", err))?;
+        write_atomic(&session_path(dir), &bytes, false)?;
+        *latest = Some((session_id.to_string(), sequence));
+        Ok(true)
+    }
+
+    /// Forgets the autosave, e.g. after the user cleared the queue or chose
+    /// not to recover it.
+    pub fn discard(&self, dir: &Path) -> CommandResult<()> {
+        let _latest = self
+            .latest
+            .lock()
+            .map_err(|_| CommandError::Internal("autosave state is poisoned".to_string()))?;
+        match std::fs::remove_file(session_path(dir)) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(CommandError::io("remove autosave", err)),
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn session_path(dir: &Path) -> PathBuf {
+    dir.join(SESSION_FILE)
+}
+
+/// Returns the last autosaved session, or `None` when there is none.
+pub fn recover(dir: &Path) -> CommandResult<Option<SessionSnapshot>> {
+    let bytes = match std::fs::read(session_path(dir)) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(CommandError::io("read autosave", err)),
+    };
+    let snapshot: SessionSnapshot = serde_json::from_slice(&bytes)
+        .map_err(|err| CommandError::DecodeFailed(format!("parse autosave failed: {err}")))?;
+    if snapshot.format != SESSION_FORMAT {
+        return Err(CommandError::DecodeFailed(
+            "autosave is not a Negative Converter session".to_string(),
+        ));
+    }
+    if snapshot.schema_version > SESSION_SCHEMA_VERSION {
+        return Err(CommandError::DecodeFailed(format!(
+            "autosave schema {} is newer than this version supports ({SESSION_SCHEMA_VERSION})",
+            snapshot.schema_version
+        )));
+    }
+    Ok(Some(snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recover, session_path, Autosave, AutosaveSession};
+    use crate::project::{ProjectFrame, RollProject};
+    use serde_json::json;
+
+    fn session(frame: &str) -> AutosaveSession {
+        AutosaveSession {
+            project_path: None,
+            project: RollProject {
+                frames: vec![ProjectFrame {
+                    path: frame.to_string(),
+                    relative_path: None,
+                    selected: true,
+                    settings: Some(json!({ "rotationAngle": 1.5 })),
+                }],
+                ..RollProject::default()
+            },
+            current_settings: Some(json!({ "rotationAngle": 2.0 })),
+        }
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("nc-autosave-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn saved_session_is_recovered_until_discarded() {
+        let dir = temp_dir("recover");
+        let autosave = Autosave::default();
+        assert_eq!(recover(&dir).unwrap(), None);
+
+        assert!(autosave
+            .save(&dir, "load-a", 1, session("/rolls/01.NEF"))
+            .unwrap());
+        let recovered = recover(&dir).unwrap().unwrap();
+        assert_eq!(recovered.session, session("/rolls/01.NEF"));
+        assert!(recovered.saved_at > 0);
+
+        autosave.discard(&dir).unwrap();
+        assert_eq!(recover(&dir).unwrap(), None);
+        autosave.discard(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stale_snapshots_are_dropped_but_a_reloaded_webview_can_write() {
+        let dir = temp_dir("order");
+        let autosave = Autosave::default();
+        assert!(autosave
+            .save(&dir, "load-a", 5, session("/rolls/05.NEF"))
+            .unwrap());
+        assert!(!autosave
+            .save(&dir, "load-a", 4, session("/rolls/04.NEF"))
+            .unwrap());
+        assert_eq!(
+            recover(&dir).unwrap().unwrap().session,
+            session("/rolls/05.NEF")
+        );
+
+        assert!(autosave
+            .save(&dir, "load-b", 1, session("/rolls/01.NEF"))
+            .unwrap());
+        assert_eq!(
+            recover(&dir).unwrap().unwrap().session,
+            session("/rolls/01.NEF")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn foreign_or_newer_files_are_rejected() {
+        let dir = temp_dir("reject");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            session_path(&dir),
+            b"{\"format\":\"other\",\"schemaVersion\":1,\"savedAt\":0,\"session\":{\"project\":{}}}",
+        )
+        .unwrap();
+        assert!(recover(&dir).is_err());
+        std::fs::write(
+            session_path(&dir),
+            b"{\"format\":\"negative-converter-session\",\"schemaVersion\":99,\"savedAt\":0,\"session\":{\"project\":{}}}",
+        )
+        .unwrap();
+        assert!(recover(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
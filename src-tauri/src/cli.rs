@@ -0,0 +1,499 @@
+//! Headless `convert` mode, so rolls can be converted from scripts without
+//! starting the webview:
+//!
+//! ```text
+//! negative-converter convert --preset frontier-lab --profile frontier --out ./positives *.png
+//! ```
+
+use crate::atomic_write::write_atomic;
+use crate::engine::auto_frame::AutoFrameSettings;
+use crate::engine::{self, film_base, geometry, lut3d, presets, FrameSettings, Library};
+use crate::image_io;
+use crate::preset_store::PresetStore;
+use crate::profiles::ProfileStore;
+use crate::{build_unique_export_path, AppLibrary};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "\
+Usage: negative-converter convert [options] <input>...
+
+Inputs are camera RAW files, TIFF, PNG or JPEG scans.
+
+Options:
+  --preset <id>          Film preset, e.g. frontier-lab, or a saved user:
+                         preset (default: none)
+  --profile <id>         Enhanced scanner profile: frontier, noritsu, ...,
+                         or an imported user:<file> LUT (default: none)
+  --color-model <model>  Color model: frontier, standard, warm, ...
+  --film-type <type>     color, bw, or positive (default: color)
+  --settings <file>      Settings snapshot JSON exported from the app
+  --auto-film-base       Detect each frame's film base instead of using the
+                         one in the settings
+  --auto-frame           Detect each frame's crop and straightening angle
+                         instead of using the ones in the settings
+  --out <dir>            Output directory (default: current directory)
+  -h, --help             Show this help
+";
+
+const FILM_TYPES: &[&str] = &["color", "bw", "positive"];
+
+/// `identifier` in tauri.conf.json, which names the app's config and data
+/// folders.
+const APP_IDENTIFIER: &str = "com.neoanaloglab.negativeconverter";
+
+/// The folders Tauri's `app_config_dir` and `app_data_dir` resolve to, so
+/// the CLI sees the presets and profiles saved from the app.
+fn app_dirs() -> (Option<PathBuf>, Option<PathBuf>) {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+    };
+    let home = env_dir("HOME");
+    let (config, data) = if cfg!(target_os = "windows") {
+        (env_dir("APPDATA"), env_dir("APPDATA"))
+    } else if cfg!(target_os = "macos") {
+        let support = home.map(|home| home.join("Library/Application Support"));
+        (support.clone(), support)
+    } else {
+        (
+            env_dir("XDG_CONFIG_HOME").or_else(|| home.as_ref().map(|home| home.join(".config"))),
+            env_dir("XDG_DATA_HOME").or_else(|| home.map(|home| home.join(".local/share"))),
+        )
+    };
+    (
+        config.map(|dir| dir.join(APP_IDENTIFIER)),
+        data.map(|dir| dir.join(APP_IDENTIFIER)),
+    )
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ConvertArgs {
+    preset: Option<String>,
+    profile: Option<String>,
+    color_model: Option<String>,
+    film_type: Option<String>,
+    settings: Option<PathBuf>,
+    auto_film_base: bool,
+    auto_frame: bool,
+    out_dir: Option<PathBuf>,
+    inputs: Vec<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+enum CliCommand {
+    Convert(ConvertArgs),
+    Help,
+}
+
+fn parse_convert_args(args: &[OsString]) -> Result<CliCommand, String> {
+    let mut parsed = ConvertArgs::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let flag = arg.to_string_lossy();
+        let mut value = |name: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match flag.as_ref() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "--preset" => parsed.preset = Some(value("--preset")?.to_string_lossy().into_owned()),
+            "--profile" => {
+                parsed.profile = Some(value("--profile")?.to_string_lossy().into_owned())
+            }
+            "--color-model" => {
+                parsed.color_model = Some(value("--color-model")?.to_string_lossy().into_owned())
+            }
+            "--film-type" => {
+                parsed.film_type = Some(value("--film-type")?.to_string_lossy().into_owned())
+            }
+            "--settings" => parsed.settings = Some(PathBuf::from(value("--settings")?)),
+            "--auto-film-base" => parsed.auto_film_base = true,
+            "--auto-frame" => parsed.auto_frame = true,
+            "--out" | "-o" => parsed.out_dir = Some(PathBuf::from(value("--out")?)),
+            other if other.starts_with('-') && other.len() > 1 => {
+                return Err(format!("unknown option: {other}"));
+            }
+            _ => parsed.inputs.push(PathBuf::from(arg)),
+        }
+    }
+
+    if parsed.inputs.is_empty() {
+        return Err("no input files given".to_string());
+    }
+    Ok(CliCommand::Convert(parsed))
+}
+
+fn build_frame_settings(args: &ConvertArgs, library: &AppLibrary) -> Result<FrameSettings, String> {
+    let mut settings = match &args.settings {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .map_err(|err| format!("read settings {} failed: {err}", path.display()))?;
+            serde_json::from_str(&json)
+                .map_err(|err| format!("parse settings {} failed: {err}", path.display()))?
+        }
+        None => FrameSettings::default(),
+    };
+
+    if let Some(preset) = &args.preset {
+        if preset != "none" && library.film_preset(preset).is_none() {
+            let known: Vec<&str> = presets::film_presets().keys().map(String::as_str).collect();
+            return Err(format!(
+                "unknown preset: {preset} (available: {}, or a saved user: preset)",
+                known.join(", ")
+            ));
+        }
+        settings.core_film_preset = preset.clone();
+    }
+    if let Some(profile) = &args.profile {
+        if profile != "none" && library.enhanced_profile(profile).is_none() {
+            let mut known: Vec<String> = match &library.data_dir {
+                Some(data_dir) => library
+                    .profiles
+                    .list(data_dir)
+                    .into_iter()
+                    .map(|info| info.id)
+                    .collect(),
+                None => lut3d::BUILTIN_PROFILES
+                    .iter()
+                    .map(|(name, _)| name.to_string())
+                    .collect(),
+            };
+            known.insert(0, "none".to_string());
+            return Err(format!(
+                "unknown profile: {profile} (available: {})",
+                known.join(", ")
+            ));
+        }
+        settings.core_enhanced_profile = profile.clone();
+    }
+    if let Some(model) = &args.color_model {
+        if !presets::COLOR_MODELS.contains(&model.as_str()) {
+            return Err(format!(
+                "unknown color model: {model} (available: {})",
+                presets::COLOR_MODELS.join(", ")
+            ));
+        }
+        settings.core_color_model = model.clone();
+    }
+    if let Some(film_type) = &args.film_type {
+        if !FILM_TYPES.contains(&film_type.as_str()) {
+            return Err(format!(
+                "unknown film type: {film_type} (expected one of: {})",
+                FILM_TYPES.join(", ")
+            ));
+        }
+        settings.film_type = film_type.clone();
+    }
+    Ok(settings)
+}
+
+fn convert_file(
+    input: &Path,
+    out_dir: &Path,
+    settings: &FrameSettings,
+    args: &ConvertArgs,
+    library: &AppLibrary,
+) -> Result<PathBuf, String> {
+    let image = image_io::read_input_image(input)?;
+    let mut settings = settings.clone();
+    if args.auto_frame {
+        let auto = AutoFrameSettings {
+            film_type: settings.film_type.clone(),
+            ..AutoFrameSettings::default()
+        };
+        geometry::auto_frame(&image, &mut settings, &auto);
+    }
+    let mut image = geometry::apply_frame_geometry(image, &settings);
+    if args.auto_film_base && settings.film_type != "positive" {
+        let detection = film_base::detect_film_base(&image, settings.core_border_buffer);
+        settings.film_base = Some(detection.result.to_film_base());
+    }
+    engine::convert_frame_with(&mut image, &settings, library);
+    let bytes = image_io::encode_png16(&image)?;
+
+    let stem = input
+        .file_stem()
+        .map(|value| value.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let target = build_unique_export_path(out_dir, &format!("{stem}.png"));
+    write_atomic(&target, &bytes, false).map_err(|err| err.to_string())?;
+    Ok(target)
+}
+
+fn run_convert(args: &ConvertArgs) -> i32 {
+    let (config_dir, data_dir) = app_dirs();
+    let presets = PresetStore::default();
+    let profiles = ProfileStore::default();
+    let library = AppLibrary {
+        config_dir,
+        data_dir,
+        presets: &presets,
+        profiles: &profiles,
+    };
+    let settings = match build_frame_settings(args, &library) {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return 2;
+        }
+    };
+    let out_dir = args.out_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+    if let Err(err) = std::fs::create_dir_all(&out_dir) {
+        eprintln!(
+            "error: create output directory {} failed: {err}",
+            out_dir.display()
+        );
+        return 1;
+    }
+
+    let mut failed = 0usize;
+    for input in &args.inputs {
+        match convert_file(input, &out_dir, &settings, args, &library) {
+            Ok(target) => println!("{} -> {}", input.display(), target.display()),
+            Err(err) => {
+                failed += 1;
+                eprintln!("error: {}: {err}", input.display());
+            }
+        }
+    }
+
+    if failed > 0 {
+        eprintln!("{failed} of {} frames failed", args.inputs.len());
+        1
+    } else {
+        0
+    }
+}
+
+/// Runs the CLI when the first argument is a known subcommand and returns the
+/// process exit code. Returns `None` to let the caller start the GUI.
+pub fn run_cli(args: &[OsString]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    if command.to_str() != Some("convert") {
+        return None;
+    }
+
+    let exit_code = match parse_convert_args(rest) {
+        Ok(CliCommand::Help) => {
+            print!("{USAGE}");
+            0
+        }
+        Ok(CliCommand::Convert(convert)) => run_convert(&convert),
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            2
+        }
+    };
+    Some(exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_frame_settings, convert_file, parse_convert_args, run_cli, CliCommand, ConvertArgs,
+    };
+    use crate::engine::auto_frame::tests::scan;
+    use crate::engine::geometry::FrameCrop;
+    use crate::engine::{FrameSettings, Image16};
+    use crate::image_io;
+    use crate::preset_store::PresetStore;
+    use crate::profiles::{self, ProfileStore};
+    use crate::AppLibrary;
+    use std::ffi::OsString;
+    use std::path::{Path, PathBuf};
+
+    fn os_args(values: &[&str]) -> Vec<OsString> {
+        values.iter().map(OsString::from).collect()
+    }
+
+    fn library<'a>(
+        presets: &'a PresetStore,
+        profiles: &'a ProfileStore,
+        data_dir: Option<&Path>,
+    ) -> AppLibrary<'a> {
+        AppLibrary {
+            config_dir: None,
+            data_dir: data_dir.map(Path::to_path_buf),
+            presets,
+            profiles,
+        }
+    }
+
+    #[test]
+    fn convert_args_collect_options_and_inputs() {
+        let command = parse_convert_args(&os_args(&[
+            "--preset",
+            "frontier-lab",
+            "--profile",
+            "frontier",
+            "--color-model",
+            "warm",
+            "--out",
+            "./positives",
+            "--auto-film-base",
+            "--auto-frame",
+            "a.png",
+            "b.png",
+        ]))
+        .unwrap();
+        assert_eq!(
+            command,
+            CliCommand::Convert(ConvertArgs {
+                preset: Some("frontier-lab".to_string()),
+                profile: Some("frontier".to_string()),
+                color_model: Some("warm".to_string()),
+                auto_film_base: true,
+                auto_frame: true,
+                out_dir: Some(PathBuf::from("./positives")),
+                inputs: vec![PathBuf::from("a.png"), PathBuf::from("b.png")],
+                ..ConvertArgs::default()
+            })
+        );
+    }
+
+    #[test]
+    fn convert_args_reject_missing_values_and_inputs() {
+        assert!(parse_convert_args(&os_args(&["--preset"])).is_err());
+        assert!(parse_convert_args(&os_args(&["--out", "dir"])).is_err());
+        assert!(parse_convert_args(&os_args(&["--bogus", "a.png"])).is_err());
+    }
+
+    #[test]
+    fn unknown_preset_and_profile_are_rejected() {
+        let (presets, profiles) = (PresetStore::default(), ProfileStore::default());
+        let library = library(&presets, &profiles, None);
+        let args = ConvertArgs {
+            preset: Some("no-such-film".to_string()),
+            ..ConvertArgs::default()
+        };
+        assert!(build_frame_settings(&args, &library).is_err());
+        let args = ConvertArgs {
+            profile: Some("no-such-lab".to_string()),
+            ..ConvertArgs::default()
+        };
+        assert!(build_frame_settings(&args, &library).is_err());
+        let args = ConvertArgs {
+            profile: Some("user:missing.cube".to_string()),
+            ..ConvertArgs::default()
+        };
+        assert!(build_frame_settings(&args, &library).is_err());
+        let args = ConvertArgs {
+            color_model: Some("frontier-ish".to_string()),
+            ..ConvertArgs::default()
+        };
+        assert!(build_frame_settings(&args, &library).is_err());
+    }
+
+    #[test]
+    fn profile_selects_the_enhanced_profile() {
+        let (presets, profiles) = (PresetStore::default(), ProfileStore::default());
+        let library = library(&presets, &profiles, None);
+        let args = ConvertArgs {
+            profile: Some("frontier".to_string()),
+            color_model: Some("warm".to_string()),
+            ..ConvertArgs::default()
+        };
+        let settings = build_frame_settings(&args, &library).unwrap();
+        assert_eq!(settings.core_enhanced_profile, "frontier");
+        assert_eq!(settings.core_color_model, "warm");
+    }
+
+    #[test]
+    fn imported_profiles_resolve_from_the_data_dir() {
+        let dir = std::env::temp_dir().join(format!(
+            "negative-converter-cli-profiles-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let profiles_dir = profiles::profiles_dir(&dir);
+        std::fs::create_dir_all(&profiles_dir).unwrap();
+        let mut cube = String::from("LUT_3D_SIZE 2\n");
+        for index in 0..8 {
+            let (r, g, b) = (index & 1, (index >> 1) & 1, (index >> 2) & 1);
+            cube.push_str(&format!("{} {} {}\n", 1 - r, 1 - g, 1 - b));
+        }
+        std::fs::write(profiles_dir.join("lab.cube"), cube).unwrap();
+
+        let (presets, profiles) = (PresetStore::default(), ProfileStore::default());
+        let library = library(&presets, &profiles, Some(&dir));
+        let args = ConvertArgs {
+            profile: Some("user:lab.cube".to_string()),
+            ..ConvertArgs::default()
+        };
+        let settings = build_frame_settings(&args, &library).unwrap();
+        assert_eq!(settings.core_enhanced_profile, "user:lab.cube");
+
+        let input = dir.join("frame.png");
+        let frame = Image16::new(8, 6, vec![30000; 8 * 6 * 4]).unwrap();
+        std::fs::write(&input, image_io::encode_png16(&frame).unwrap()).unwrap();
+        let output = convert_file(&input, &dir, &settings, &args, &library).unwrap();
+        assert!(output.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn gui_launch_arguments_are_left_alone() {
+        assert_eq!(run_cli(&[]), None);
+        assert_eq!(run_cli(&os_args(&["-psn_0_12345"])), None);
+    }
+
+    #[test]
+    fn settings_rotation_and_crop_shape_the_output() {
+        let dir = std::env::temp_dir().join(format!(
+            "negative-converter-cli-geometry-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("frame.png");
+        let frame = Image16::new(40, 30, vec![30000; 40 * 30 * 4]).unwrap();
+        std::fs::write(&input, image_io::encode_png16(&frame).unwrap()).unwrap();
+
+        let settings = FrameSettings {
+            rotation_angle: 90.0,
+            crop_region: Some(FrameCrop {
+                left: 2.0,
+                top: 4.0,
+                width: 20.0,
+                height: 25.0,
+            }),
+            ..FrameSettings::default()
+        };
+        let (presets, profiles) = (PresetStore::default(), ProfileStore::default());
+        let library = library(&presets, &profiles, None);
+        let output =
+            convert_file(&input, &dir, &settings, &ConvertArgs::default(), &library).unwrap();
+        let converted = image_io::read_input_image(&output).unwrap();
+        assert_eq!((converted.width, converted.height), (20, 25));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn auto_frame_crops_to_the_detected_frame() {
+        let dir = std::env::temp_dir().join(format!(
+            "negative-converter-cli-auto-frame-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("frame.png");
+        let scan = scan(1200, 800, 900.0, 600.0, 0.0);
+        std::fs::write(&input, image_io::encode_png16(&scan).unwrap()).unwrap();
+
+        let args = ConvertArgs {
+            auto_frame: true,
+            ..ConvertArgs::default()
+        };
+        let (presets, profiles) = (PresetStore::default(), ProfileStore::default());
+        let library = library(&presets, &profiles, None);
+        let output =
+            convert_file(&input, &dir, &FrameSettings::default(), &args, &library).unwrap();
+        let converted = image_io::read_input_image(&output).unwrap();
+        assert!(converted.width.abs_diff(900) <= 24, "{}", converted.width);
+        assert!(converted.height.abs_diff(600) <= 24, "{}", converted.height);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,316 @@
+//! Per-pixel passes over 16-bit RGBA rows: LUT application, HSL and
+//! saturation adjustments, grayscale mixing, and film base compensation.
+//! Each pass is split across rows with rayon.
+
+use super::curves::CurveLuts;
+use super::presets::HslAdjustments;
+use super::Image16;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::sync::OnceLock;
+
+const MAX_16: f64 = 65535.0;
+const HUE_TABLE_SIZE: usize = 3600;
+
+pub(super) fn for_each_pixel(image: &mut Image16, f: impl Fn(&mut [u16]) + Sync) {
+    let row_len = image.width as usize * 4;
+    image.data.par_chunks_mut(row_len.max(4)).for_each(|row| {
+        for px in row.chunks_exact_mut(4) {
+            f(px);
+        }
+    });
+}
+
+fn to_u16(value: f64) -> u16 {
+    (value + 0.5).clamp(0.0, MAX_16) as u16
+}
+
+pub fn apply_luts(image: &mut Image16, luts: &CurveLuts) {
+    for_each_pixel(image, |px| {
+        px[0] = luts.r[px[0] as usize];
+        px[1] = luts.g[px[1] as usize];
+        px[2] = luts.b[px[2] as usize];
+    });
+}
+
+fn hue_weight(h: f64, center: f64) -> f32 {
+    let mut dist = (h - center).abs();
+    if dist > 0.5 {
+        dist = 1.0 - dist;
+    }
+    if dist > 1.0 / 6.0 {
+        0.0
+    } else {
+        (0.5 + 0.5 * (dist * 6.0 * PI).cos()) as f32
+    }
+}
+
+fn hue_weight_tables() -> &'static [[f32; 3]] {
+    static TABLES: OnceLock<Vec<[f32; 3]>> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        (0..HUE_TABLE_SIZE)
+            .map(|i| {
+                let h = i as f64 / HUE_TABLE_SIZE as f64;
+                [
+                    hue_weight(h, 0.0),
+                    hue_weight(h, 1.0 / 3.0),
+                    hue_weight(h, 2.0 / 3.0),
+                ]
+            })
+            .collect()
+    })
+}
+
+fn hue_to_rgb(p: f64, q: f64, mut t: f64) -> f64 {
+    if t < 0.0 {
+        t += 1.0;
+    }
+    if t > 1.0 {
+        t -= 1.0;
+    }
+    if t < 1.0 / 6.0 {
+        return p + (q - p) * 6.0 * t;
+    }
+    if t < 0.5 {
+        return q;
+    }
+    if t < 2.0 / 3.0 {
+        return p + (q - p) * (2.0 / 3.0 - t) * 6.0;
+    }
+    p
+}
+
+/// Lightroom-style hue/saturation shifts for the red, green, and blue regions.
+pub fn apply_hsl_adjustments(image: &mut Image16, hsl: &HslAdjustments) {
+    if *hsl == HslAdjustments::default() {
+        return;
+    }
+
+    let hue_shift = [
+        hsl.red_hue / 360.0,
+        hsl.green_hue / 360.0,
+        hsl.blue_hue / 360.0,
+    ];
+    let sat_factor = [
+        hsl.red_saturation / 100.0,
+        hsl.green_saturation / 100.0,
+        hsl.blue_saturation / 100.0,
+    ];
+    let tables = hue_weight_tables();
+
+    for_each_pixel(image, |px| {
+        let r = px[0] as f64 / MAX_16;
+        let g = px[1] as f64 / MAX_16;
+        let b = px[2] as f64 / MAX_16;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+        let l = (max + min) * 0.5;
+        if d < 1e-6 {
+            return;
+        }
+
+        let mut h = if max == r {
+            ((g - b) / d + if g < b { 6.0 } else { 0.0 }) / 6.0
+        } else if max == g {
+            ((b - r) / d + 2.0) / 6.0
+        } else {
+            ((r - g) / d + 4.0) / 6.0
+        };
+        let mut s = d / if l > 0.5 { 2.0 - max - min } else { max + min };
+
+        let weights = tables[((h * HUE_TABLE_SIZE as f64) as usize).min(HUE_TABLE_SIZE - 1)];
+        let weights = weights.map(f64::from);
+        h += weights[0] * hue_shift[0] + weights[1] * hue_shift[1] + weights[2] * hue_shift[2];
+        h -= h.floor();
+        let sat_adj =
+            weights[0] * sat_factor[0] + weights[1] * sat_factor[1] + weights[2] * sat_factor[2];
+        s = (s * (1.0 + sat_adj)).clamp(0.0, 1.0);
+
+        let (r2, g2, b2) = if s < 1e-6 {
+            (l, l, l)
+        } else {
+            let q = if l < 0.5 {
+                l * (1.0 + s)
+            } else {
+                l + s - l * s
+            };
+            let p = 2.0 * l - q;
+            (
+                hue_to_rgb(p, q, h + 1.0 / 3.0),
+                hue_to_rgb(p, q, h),
+                hue_to_rgb(p, q, h - 1.0 / 3.0),
+            )
+        };
+        px[0] = to_u16(r2 * MAX_16);
+        px[1] = to_u16(g2 * MAX_16);
+        px[2] = to_u16(b2 * MAX_16);
+    });
+}
+
+/// 0 = grayscale, 100 = unchanged, 200 = double saturation.
+pub fn adjust_saturation(image: &mut Image16, amount: f64) {
+    if amount == 100.0 {
+        return;
+    }
+    let factor = amount / 100.0;
+    for_each_pixel(image, |px| {
+        let (r, g, b) = (px[0] as f64, px[1] as f64, px[2] as f64);
+        let lum = 0.299 * r + 0.587 * g + 0.114 * b;
+        // Truncates like assigning a float into a Uint16Array.
+        px[0] = (lum + factor * (r - lum)).clamp(0.0, MAX_16) as u16;
+        px[1] = (lum + factor * (g - lum)).clamp(0.0, MAX_16) as u16;
+        px[2] = (lum + factor * (b - lum)).clamp(0.0, MAX_16) as u16;
+    });
+}
+
+pub fn to_grayscale(image: &mut Image16, weights: (f64, f64, f64)) {
+    for_each_pixel(image, |px| {
+        let y = px[0] as f64 * weights.0 + px[1] as f64 * weights.1 + px[2] as f64 * weights.2;
+        let y = to_u16(y);
+        px[0] = y;
+        px[1] = y;
+        px[2] = y;
+    });
+}
+
+/// Film base color as stored in settings snapshots; `r16`/`g16`/`b16` win
+/// over the 8-bit values when present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FilmBase {
+    pub r: Option<f64>,
+    pub g: Option<f64>,
+    pub b: Option<f64>,
+    pub r16: Option<f64>,
+    pub g16: Option<f64>,
+    pub b16: Option<f64>,
+}
+
+impl FilmBase {
+    pub fn to_16bit(self) -> Option<[f64; 3]> {
+        let clamp16 = |value: f64| value.round().clamp(1.0, MAX_16);
+        if let (Some(r), Some(g), Some(b)) = (self.r16, self.g16, self.b16) {
+            if r.is_finite() && g.is_finite() && b.is_finite() {
+                return Some([clamp16(r), clamp16(g), clamp16(b)]);
+            }
+        }
+        match (self.r, self.g, self.b) {
+            (Some(r), Some(g), Some(b)) if r.is_finite() && g.is_finite() && b.is_finite() => {
+                Some([clamp16(r * 257.0), clamp16(g * 257.0), clamp16(b * 257.0)])
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilmBaseMethod {
+    Linear,
+    Density,
+}
+
+impl FilmBaseMethod {
+    pub fn parse(value: &str) -> Self {
+        if value == "linear" {
+            Self::Linear
+        } else {
+            Self::Density
+        }
+    }
+}
+
+/// Per-channel gains that neutralize the film base. Mirrors
+/// `computeFilmBaseGains` in `pipeline/filmBaseCompensation.js`.
+pub fn compute_film_base_gains(
+    base: &FilmBase,
+    method: FilmBaseMethod,
+    strength: f64,
+) -> Option<[f64; 3]> {
+    let [r, g, b] = base.to_16bit()?;
+    let strength = if strength.is_finite() { strength } else { 1.0 }.clamp(0.0, 1.5);
+    let target = match method {
+        FilmBaseMethod::Linear => (r + g + b) / 3.0,
+        FilmBaseMethod::Density => {
+            { ((r / MAX_16).ln() + (g / MAX_16).ln() + (b / MAX_16).ln()) / 3.0 }.exp() * MAX_16
+        }
+    };
+    if !target.is_finite() || target <= 0.0 {
+        return None;
+    }
+    Some([r, g, b].map(|channel| (target / channel).powf(strength).clamp(0.25, 4.0)))
+}
+
+pub fn apply_channel_gains(image: &mut Image16, gains: [f64; 3]) {
+    for_each_pixel(image, |px| {
+        for ch in 0..3 {
+            px[ch] = to_u16(px[ch] as f64 * gains[ch]);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        adjust_saturation, apply_hsl_adjustments, compute_film_base_gains, FilmBase, FilmBaseMethod,
+    };
+    use crate::engine::presets::HslAdjustments;
+    use crate::engine::Image16;
+
+    fn single_pixel(r: u16, g: u16, b: u16) -> Image16 {
+        Image16::new(1, 1, vec![r, g, b, 65535]).unwrap()
+    }
+
+    #[test]
+    fn film_base_gains_equalize_channels() {
+        let base = FilmBase {
+            r16: Some(50000.0),
+            g16: Some(30000.0),
+            b16: Some(20000.0),
+            ..FilmBase::default()
+        };
+        let gains = compute_film_base_gains(&base, FilmBaseMethod::Linear, 1.0).unwrap();
+        let target = (50000.0 + 30000.0 + 20000.0) / 3.0;
+        assert!((50000.0 * gains[0] - target).abs() < 1e-6);
+        assert!((20000.0 * gains[2] - target).abs() < 1e-6);
+    }
+
+    #[test]
+    fn film_base_falls_back_to_8_bit_values() {
+        let base = FilmBase {
+            r: Some(210.0),
+            g: Some(140.0),
+            b: Some(90.0),
+            ..FilmBase::default()
+        };
+        assert_eq!(
+            base.to_16bit(),
+            Some([210.0 * 257.0, 140.0 * 257.0, 90.0 * 257.0])
+        );
+        assert!(
+            compute_film_base_gains(&FilmBase::default(), FilmBaseMethod::Density, 1.0).is_none()
+        );
+    }
+
+    #[test]
+    fn zero_saturation_produces_gray() {
+        let mut image = single_pixel(60000, 30000, 10000);
+        adjust_saturation(&mut image, 0.0);
+        assert_eq!(image.data[0], image.data[1]);
+        assert_eq!(image.data[1], image.data[2]);
+        assert_eq!(image.data[3], 65535);
+    }
+
+    #[test]
+    fn hsl_leaves_neutral_pixels_untouched() {
+        let mut image = single_pixel(30000, 30000, 30000);
+        apply_hsl_adjustments(
+            &mut image,
+            &HslAdjustments {
+                blue_hue: -10.0,
+                ..HslAdjustments::default()
+            },
+        );
+        assert_eq!(image.data, vec![30000, 30000, 30000, 65535]);
+    }
+}
@@ -0,0 +1,214 @@
+//! Histogram analysis and auto color. Mirrors `analyzeImage` in
+//! `ImageProcessor.js` and `computeAutoColor` in `WhiteBalance.js`.
+
+use super::{presets, Image16};
+use rayon::prelude::*;
+
+const HIST_BINS: usize = 256;
+const HIST_MAX: usize = HIST_BINS - 1;
+const BIN_TO_16: f64 = 257.0;
+
+/// Per-channel levels in the 16-bit domain. `white_point_origin` is the dark
+/// end of the negative (white of the positive) and vice versa.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelLevels {
+    pub white_point_origin: f64,
+    pub black_point_origin: f64,
+    pub mean_point: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AutoColor {
+    pub temp_correction: f64,
+    pub tint_correction: f64,
+    pub cyan_correction: f64,
+}
+
+type Histograms = [[u64; HIST_BINS]; 3];
+
+fn build_histograms(image: &Image16, border_pct: f64) -> (Histograms, u64) {
+    let width = image.width as usize;
+    let height = image.height as usize;
+    let crop_x = (width as f64 * border_pct).round() as usize;
+    let crop_y = (height as f64 * border_pct).round() as usize;
+    if crop_x * 2 >= width || crop_y * 2 >= height {
+        return ([[0; HIST_BINS]; 3], 0);
+    }
+
+    let row_len = width * 4;
+    image.data[crop_y * row_len..(height - crop_y) * row_len]
+        .par_chunks(row_len)
+        .fold(
+            || ([[0u64; HIST_BINS]; 3], 0u64),
+            |(mut hist, mut total), row| {
+                for px in row[crop_x * 4..(width - crop_x) * 4].chunks_exact(4) {
+                    hist[0][(px[0] >> 8) as usize] += 1;
+                    hist[1][(px[1] >> 8) as usize] += 1;
+                    hist[2][(px[2] >> 8) as usize] += 1;
+                    total += 1;
+                }
+                (hist, total)
+            },
+        )
+        .reduce(
+            || ([[0u64; HIST_BINS]; 3], 0u64),
+            |(mut a, a_total), (b, b_total)| {
+                for ch in 0..3 {
+                    for bin in 0..HIST_BINS {
+                        a[ch][bin] += b[ch][bin];
+                    }
+                }
+                (a, a_total + b_total)
+            },
+        )
+}
+
+fn compute_channel_levels(
+    hist: &[u64; HIST_BINS],
+    total_pixels: u64,
+    black_threshold: f64,
+    white_threshold: f64,
+    is_negative: bool,
+) -> ChannelLevels {
+    let total = total_pixels as f64;
+
+    let mut cumulative = 0.0;
+    let mut white_bin = 0usize;
+    for (i, count) in hist.iter().enumerate() {
+        cumulative += *count as f64 / total;
+        if cumulative > white_threshold {
+            white_bin = i.saturating_sub(1);
+            break;
+        }
+    }
+
+    cumulative = 0.0;
+    let mut black_bin = HIST_MAX;
+    for i in (0..HIST_BINS).rev() {
+        cumulative += hist[i] as f64 / total;
+        if cumulative > black_threshold {
+            black_bin = (i + 1).min(HIST_MAX);
+            break;
+        }
+    }
+
+    let adj_black = (white_bin as f64 * 1.005).round() as usize;
+    let adj_white = (black_bin as f64 * 0.995).round() as usize;
+    let adj_range = adj_white as f64 - adj_black as f64;
+    let adj_scale = if adj_range > 0.0 {
+        HIST_BINS as f64 / adj_range
+    } else {
+        1.0
+    };
+
+    let mut weighted_sum = 0.0;
+    for (offset, count) in hist.iter().enumerate().take(adj_white + 1).skip(adj_black) {
+        weighted_sum += *count as f64 * (offset - adj_black) as f64 * adj_scale;
+    }
+
+    let mut mean_point = if total_pixels > 0 {
+        weighted_sum / total / HIST_BINS as f64
+    } else {
+        0.5
+    };
+    if is_negative {
+        mean_point = 1.0 - mean_point;
+    }
+
+    ChannelLevels {
+        white_point_origin: white_bin as f64 * BIN_TO_16,
+        black_point_origin: black_bin as f64 * BIN_TO_16,
+        mean_point,
+    }
+}
+
+/// Histogram black/white/mean points per channel over the center crop that
+/// excludes `border_buffer` percent of each edge.
+pub fn analyze_image(
+    image: &Image16,
+    border_buffer: f64,
+    color_model: &str,
+    is_negative: bool,
+) -> [ChannelLevels; 3] {
+    let border_pct = if border_buffer > 0.0 {
+        border_buffer
+    } else {
+        10.0
+    } / 100.0;
+    let (hist, total) = build_histograms(image, border_pct);
+    let model = presets::color_model(color_model);
+    [0, 1, 2].map(|ch| {
+        compute_channel_levels(
+            &hist[ch],
+            total,
+            model.black_threshold,
+            model.white_threshold,
+            is_negative,
+        )
+    })
+}
+
+pub fn compute_auto_color(levels: &[ChannelLevels; 3]) -> AutoColor {
+    let r_mean = levels[0].mean_point;
+    let g_mean = levels[1].mean_point;
+    let b_mean = levels[2].mean_point;
+    let n_gray = (r_mean + g_mean + b_mean) / 3.0;
+
+    AutoColor {
+        temp_correction: js_round((n_gray - b_mean) * -100.0),
+        tint_correction: js_round((n_gray - g_mean) * -100.0),
+        cyan_correction: js_round((n_gray - r_mean) * -100.0),
+    }
+}
+
+/// `Math.round` semantics: halves round towards positive infinity.
+pub fn js_round(value: f64) -> f64 {
+    (value + 0.5).floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze_image, compute_auto_color, js_round};
+    use crate::engine::Image16;
+
+    fn gradient_image() -> Image16 {
+        let width = 64u32;
+        let height = 32u32;
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for _y in 0..height {
+            for x in 0..width {
+                let v = (x * 1024) as u16;
+                data.extend_from_slice(&[v, v / 2, v / 4, 65535]);
+            }
+        }
+        Image16::new(width, height, data).unwrap()
+    }
+
+    #[test]
+    fn analyze_image_reports_levels_in_16_bit_domain() {
+        let levels = analyze_image(&gradient_image(), 10.0, "standard", true);
+        for channel in &levels {
+            assert!(channel.white_point_origin < channel.black_point_origin);
+            assert_eq!(channel.white_point_origin % 257.0, 0.0);
+            assert!((0.0..=1.0).contains(&channel.mean_point));
+        }
+        assert!(levels[0].black_point_origin > levels[2].black_point_origin);
+    }
+
+    #[test]
+    fn auto_color_is_neutral_for_equal_means() {
+        let levels = analyze_image(&gradient_image(), 10.0, "standard", true);
+        let neutral = [levels[0]; 3];
+        let auto = compute_auto_color(&neutral);
+        assert_eq!(auto.temp_correction, 0.0);
+        assert_eq!(auto.tint_correction, 0.0);
+        assert_eq!(auto.cyan_correction, 0.0);
+    }
+
+    #[test]
+    fn js_round_matches_math_round_for_halves() {
+        assert_eq!(js_round(2.5), 3.0);
+        assert_eq!(js_round(-2.5), -2.0);
+        assert_eq!(js_round(-2.6), -3.0);
+    }
+}
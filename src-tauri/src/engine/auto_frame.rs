@@ -0,0 +1,1452 @@
+//! Automatic frame and rotation detection. Mirrors `detectFrameAndRotation`
+//! in `app/autoFrameAnalyzer.js` along its density-template path, which needs
+//! no OpenCV.js, and replaces the `HoughLinesP` tilt pass with a
+//! gradient-constrained Hough transform so tilted scans are still straightened
+//! offline and in batch mode.
+
+use super::Image16;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// `AUTO_FRAME_MAX_SIDE`: candidates and angles are found on a preview.
+const PREVIEW_MAX_SIDE: usize = 1600;
+const DENSITY_TEMPLATE_MAX_PIXELS: usize = 2_700_000;
+const DENSITY_TEMPLATE_SCALE_FACTORS: &[f64] = &[0.98, 0.94, 0.90, 0.84, 0.78, 0.70, 0.62];
+const DENSITY_TEMPLATE_OFFSETS: &[f64] = &[-0.055, -0.025, 0.0, 0.025, 0.055];
+/// Frame heights, as a share of the scan's short side, tried for a single
+/// 135 frame on a longer strip.
+const SPROCKET_SHORT_RATIOS: &[f64] = &[0.46, 0.52, 0.58, 0.64, 0.70, 0.76];
+const FORMAT_RATIOS: &[(&str, f64)] = &[
+    ("135", 1.5),
+    ("120-6x4.5", 1.33),
+    ("120-6x6", 1.0),
+    ("120-6x7", 1.17),
+    ("120-6x9", 1.5),
+];
+const DEFAULT_120_FORMATS: &[&str] = &["6x4.5", "6x6", "6x7", "6x9"];
+const MAX_DENSITY_CANDIDATES: usize = 18;
+const MAX_FRAME_CANDIDATES: usize = 12;
+const MIN_CANDIDATE_AREA_RATIO: f64 = 0.04;
+const ASPECT_TOLERANCE: f64 = 0.34;
+/// Blurred Sobel magnitude (|gx| + |gy|) a pixel needs to vote for a line.
+const STRONG_EDGE: f64 = 100.0;
+const MAX_TILT: f64 = 35.0;
+/// How far, in degrees, a pixel's vote spreads around its gradient's angle.
+const TILT_VOTE_SPREAD: f64 = 2.0;
+const MIN_TILT_SHARE: f64 = 0.16;
+
+/// `state.autoFrame` plus the film type, as the webview sends them.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AutoFrameSettings {
+    pub min_confidence: f64,
+    pub high_confidence: f64,
+    /// `auto`, `135` or `120`.
+    pub format_preference: String,
+    /// 120 sub-formats mapped to `false` are not considered.
+    pub allowed_120_formats: HashMap<String, bool>,
+    pub film_type: String,
+}
+
+impl Default for AutoFrameSettings {
+    fn default() -> Self {
+        Self {
+            min_confidence: 0.55,
+            high_confidence: 0.72,
+            format_preference: "auto".to_string(),
+            allowed_120_formats: HashMap::new(),
+            film_type: "color".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CropRegion {
+    pub left: usize,
+    pub top: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FrameMode {
+    ColorNegative,
+    ColorPositive,
+    BwNegative,
+    BwPositive,
+}
+
+impl FrameMode {
+    /// `inferFrameMaterialMode`: the film type decides, and chroma only
+    /// separates color from black and white for generic positives.
+    fn infer(film_type: &str, global_chroma: f64) -> Self {
+        let positive = matches!(film_type, "positive" | "slide" | "bwPositive");
+        let bw = matches!(film_type, "bw" | "blackWhite" | "bwNegative" | "bwPositive");
+        let low_chroma = global_chroma < 18.0;
+        match film_type {
+            "bwPositive" => Self::BwPositive,
+            _ if bw && positive => Self::BwPositive,
+            _ if bw => Self::BwNegative,
+            "color" | "colorNegative" => Self::ColorNegative,
+            "colorPositive" => Self::ColorPositive,
+            _ if positive && low_chroma => Self::BwPositive,
+            _ if positive => Self::ColorPositive,
+            _ if low_chroma => Self::BwNegative,
+            _ => Self::ColorNegative,
+        }
+    }
+
+    /// Edge, contrast, chroma and outside weights of the density score.
+    fn weights(self) -> [f64; 4] {
+        match self {
+            Self::ColorPositive => [0.29, 0.25, 0.09, 0.10],
+            Self::BwNegative => [0.34, 0.27, 0.0, 0.12],
+            Self::BwPositive => [0.36, 0.29, 0.0, 0.10],
+            Self::ColorNegative => [0.30, 0.24, 0.06, 0.12],
+        }
+    }
+}
+
+/// The density score's terms, rounded like the webview's `scoreBreakdown`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreBreakdown {
+    pub area: f64,
+    pub edge_support: f64,
+    pub center_prior: f64,
+    pub aspect: f64,
+    pub border_contrast: f64,
+    pub boundary_completeness: f64,
+    pub boundary_edge_contrast: f64,
+    pub content_texture: f64,
+    pub outside_clean: f64,
+    pub chroma_contrast: f64,
+    pub sprocket_lane: f64,
+    pub interior_gap: f64,
+    pub frame_mode: FrameMode,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CropValidation {
+    pub is_valid: bool,
+    pub area_ratio: f64,
+    pub aspect_delta: f64,
+    pub aspect_score: f64,
+    pub area_score: f64,
+    pub edge_support: f64,
+    pub short_edge_coverage: f64,
+    pub aspect_tolerance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameDiagnostics {
+    /// `density-template` or `density-sprocket-template`.
+    pub method: &'static str,
+    pub score_breakdown: ScoreBreakdown,
+    pub frame_mode: FrameMode,
+    pub angle_penalty: f64,
+    pub crop_validation: CropValidation,
+}
+
+/// `detectFrameAndRotation`'s result without `rotatedImageData`: the crop is
+/// in the coordinates of the source rotated by `angle` as the webview's
+/// `applyRotationToImageData` does, which is `rotated_width` by
+/// `rotated_height`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameDetection {
+    pub angle: f64,
+    pub crop_region: CropRegion,
+    pub confidence: f64,
+    /// `high`, `medium` or `low`.
+    pub confidence_level: &'static str,
+    pub detected_format: &'static str,
+    pub rotated_width: usize,
+    pub rotated_height: usize,
+    pub diagnostics: FrameDiagnostics,
+}
+
+/// `inferAutoFrameConfidenceLevel`.
+pub fn confidence_level(confidence: f64, settings: &AutoFrameSettings) -> &'static str {
+    if confidence >= settings.high_confidence {
+        "high"
+    } else if confidence >= settings.min_confidence {
+        "medium"
+    } else {
+        "low"
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Target {
+    key: &'static str,
+    ratio: f64,
+}
+
+#[derive(Debug, Clone)]
+struct DensityCandidate {
+    sprocket: bool,
+    crop: CropRegion,
+    score: f64,
+    area_ratio: f64,
+    target: Target,
+    breakdown: ScoreBreakdown,
+}
+
+impl DensityCandidate {
+    fn method(&self) -> &'static str {
+        if self.sprocket {
+            "density-sprocket-template"
+        } else {
+            "density-template"
+        }
+    }
+}
+
+/// An 8-bit RGB image, the webview's `ImageData` without alpha.
+#[derive(Debug, Clone)]
+struct Rgb8 {
+    width: usize,
+    height: usize,
+    data: Vec<[u8; 3]>,
+}
+
+struct DensityAnalysis {
+    width: usize,
+    height: usize,
+    stride: usize,
+    luma: Vec<f64>,
+    edge: Vec<f64>,
+    chroma: Vec<f64>,
+    global_chroma: f64,
+}
+
+struct BandStats {
+    inner_luma: f64,
+    outer_luma: f64,
+    inner_edge: f64,
+    outer_edge: f64,
+    inner_chroma: f64,
+    outer_chroma: f64,
+    outside_band_count: usize,
+}
+
+struct CropPick {
+    crop: CropRegion,
+    confidence: f64,
+    confidence_cap: f64,
+    aspect_score: f64,
+    candidate: DensityCandidate,
+}
+
+struct PreviewPick {
+    angle: f64,
+    /// Where the line pass ranked `angle`, `usize::MAX` when it did not.
+    line_rank: usize,
+    score: f64,
+    crop: CropPick,
+    width: usize,
+    height: usize,
+}
+
+/// `clampBetween`, which unlike `f64::clamp` tolerates `min > max`.
+fn clamp(v: f64, min: f64, max: f64) -> f64 {
+    if v < min {
+        min
+    } else if v > max {
+        max
+    } else {
+        v
+    }
+}
+
+/// `Math.round`, which rounds halves up rather than away from zero.
+fn js_round(v: f64) -> f64 {
+    (v + 0.5).floor()
+}
+
+fn round_to(v: f64, digits: i32) -> f64 {
+    let scale = 10f64.powi(digits);
+    (v * scale).round() / scale
+}
+
+/// `normalizeAngleDegrees`: folds any angle into (-180, 180].
+pub fn normalize_angle(angle: f64) -> f64 {
+    let mut normalized = if angle.is_finite() { angle } else { 0.0 };
+    while normalized > 180.0 {
+        normalized -= 360.0;
+    }
+    while normalized <= -180.0 {
+        normalized += 360.0;
+    }
+    normalized
+}
+
+/// Folds a line direction into its tilt away from the nearest axis.
+fn normalize_axis_delta(angle: f64) -> f64 {
+    let mut normalized = normalize_angle(angle);
+    while normalized > 90.0 {
+        normalized -= 180.0;
+    }
+    while normalized <= -90.0 {
+        normalized += 180.0;
+    }
+    if normalized > 45.0 {
+        normalized -= 90.0;
+    }
+    if normalized <= -45.0 {
+        normalized += 90.0;
+    }
+    normalized
+}
+
+fn format_ratio(key: &str) -> Option<Target> {
+    FORMAT_RATIOS
+        .iter()
+        .find(|(candidate, _)| *candidate == key)
+        .map(|&(key, ratio)| Target { key, ratio })
+}
+
+/// `getAutoFrameAspectTargets`: locking to 135 or 120 is exclusive.
+fn aspect_targets(settings: &AutoFrameSettings) -> Vec<Target> {
+    let preference = settings.format_preference.as_str();
+    let enabled_120 = DEFAULT_120_FORMATS
+        .iter()
+        .filter(|fmt| settings.allowed_120_formats.get(**fmt) != Some(&false))
+        .copied()
+        .collect::<Vec<_>>();
+    let safe_120 = if !enabled_120.is_empty() {
+        enabled_120
+    } else if preference == "120" {
+        vec!["6x6"]
+    } else {
+        Vec::new()
+    };
+    let mut keys = Vec::new();
+    if preference != "120" {
+        keys.push("135".to_string());
+    }
+    if preference != "135" {
+        keys.extend(safe_120.iter().map(|fmt| format!("120-{fmt}")));
+    }
+    let targets = keys
+        .iter()
+        .filter_map(|key| format_ratio(key))
+        .collect::<Vec<_>>();
+    if targets.is_empty() {
+        vec![Target {
+            key: "135",
+            ratio: 1.5,
+        }]
+    } else {
+        targets
+    }
+}
+
+fn to_8bit(sum: u64, count: u64) -> u8 {
+    ((sum * 255 + count * 32767) / (count * 65535)) as u8
+}
+
+/// Box-averages the frame down to `max_side`, as `resizeImageDataToMaxSide`
+/// does for the webview's preview.
+fn preview(image: &Image16, max_side: usize) -> Rgb8 {
+    let (width, height) = (image.width as usize, image.height as usize);
+    let scale = (max_side as f64 / width.max(height) as f64).min(1.0);
+    let out_width = ((width as f64 * scale).round() as usize).max(1);
+    let out_height = ((height as f64 * scale).round() as usize).max(1);
+    let mut data = Vec::with_capacity(out_width * out_height);
+    for y in 0..out_height {
+        let y0 = y * height / out_height;
+        let y1 = ((y + 1) * height / out_height).max(y0 + 1);
+        for x in 0..out_width {
+            let x0 = x * width / out_width;
+            let x1 = ((x + 1) * width / out_width).max(x0 + 1);
+            let mut sum = [0u64; 3];
+            for row in y0..y1 {
+                let start = (row * width + x0) * 4;
+                let end = (row * width + x1) * 4;
+                for pixel in image.data[start..end].chunks_exact(4) {
+                    for (total, &value) in sum.iter_mut().zip(pixel) {
+                        *total += u64::from(value);
+                    }
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u64;
+            data.push(sum.map(|total| to_8bit(total, count)));
+        }
+    }
+    Rgb8 {
+        width: out_width,
+        height: out_height,
+        data,
+    }
+}
+
+/// The size of a `width` by `height` image after `applyRotationToImageData`:
+/// right angles swap or keep the sides, other angles expand the canvas to
+/// hold the rotated corners.
+pub fn rotated_size(width: usize, height: usize, angle: f64) -> (usize, usize) {
+    let normalized = normalize_angle(angle);
+    if normalized.abs() < 0.001 {
+        return (width, height);
+    }
+    let right_angle = (normalized / 90.0).round() * 90.0;
+    if (normalized - right_angle).abs() < 0.001 {
+        return if right_angle.abs() == 90.0 {
+            (height, width)
+        } else {
+            (width, height)
+        };
+    }
+    let rad = normalized.to_radians();
+    let (sin, cos) = (rad.sin().abs(), rad.cos().abs());
+    let (w, h) = (width as f64, height as f64);
+    (
+        ((w * cos + h * sin).ceil() as usize).max(1),
+        ((w * sin + h * cos).ceil() as usize).max(1),
+    )
+}
+
+/// Rotates clockwise for positive angles like the webview's canvas, filling
+/// the uncovered corners with black.
+fn rotate(image: &Rgb8, angle: f64) -> Rgb8 {
+    let normalized = normalize_angle(angle);
+    let (width, height) = (image.width, image.height);
+    let (out_width, out_height) = rotated_size(width, height, normalized);
+    let right_angle = (normalized / 90.0).round() * 90.0;
+    let mut data = Vec::with_capacity(out_width * out_height);
+    if (normalized - right_angle).abs() < 0.001 {
+        for y in 0..out_height {
+            for x in 0..out_width {
+                let (sx, sy) = match right_angle as i32 {
+                    90 => (y, height - 1 - x),
+                    -90 => (width - 1 - y, x),
+                    180 => (width - 1 - x, height - 1 - y),
+                    _ => (x, y),
+                };
+                data.push(image.data[sy * width + sx]);
+            }
+        }
+        return Rgb8 {
+            width: out_width,
+            height: out_height,
+            data,
+        };
+    }
+
+    let rad = normalized.to_radians();
+    let (sin, cos) = (rad.sin(), rad.cos());
+    let sample = |x: isize, y: isize| -> [f64; 3] {
+        if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+            return [0.0; 3];
+        }
+        image.data[y as usize * width + x as usize].map(f64::from)
+    };
+    for y in 0..out_height {
+        let v = y as f64 + 0.5 - out_height as f64 / 2.0;
+        for x in 0..out_width {
+            let u = x as f64 + 0.5 - out_width as f64 / 2.0;
+            let sx = u * cos + v * sin + width as f64 / 2.0 - 0.5;
+            let sy = -u * sin + v * cos + height as f64 / 2.0 - 0.5;
+            let (x0, y0) = (sx.floor(), sy.floor());
+            let (fx, fy) = (sx - x0, sy - y0);
+            let (x0, y0) = (x0 as isize, y0 as isize);
+            let corners = [
+                (sample(x0, y0), (1.0 - fx) * (1.0 - fy)),
+                (sample(x0 + 1, y0), fx * (1.0 - fy)),
+                (sample(x0, y0 + 1), (1.0 - fx) * fy),
+                (sample(x0 + 1, y0 + 1), fx * fy),
+            ];
+            let mut pixel = [0.0; 3];
+            for (value, weight) in corners {
+                for (out, channel) in pixel.iter_mut().zip(value) {
+                    *out += channel * weight;
+                }
+            }
+            data.push(pixel.map(|v| v.round().clamp(0.0, 255.0) as u8));
+        }
+    }
+    Rgb8 {
+        width: out_width,
+        height: out_height,
+        data,
+    }
+}
+
+/// `buildDensityAnalysis`: integral images of luma, local edge strength and
+/// chroma, so any rectangle's means cost four lookups.
+fn density_analysis(image: &Rgb8) -> Option<DensityAnalysis> {
+    let (width, height) = (image.width, image.height);
+    let total = width * height;
+    if !(16..=DENSITY_TEMPLATE_MAX_PIXELS).contains(&total) {
+        return None;
+    }
+    let stride = width + 1;
+    let luma = image
+        .data
+        .iter()
+        .map(|&[r, g, b]| {
+            js_round(f64::from(r) * 0.299 + f64::from(g) * 0.587 + f64::from(b) * 0.114)
+        })
+        .collect::<Vec<_>>();
+    let chroma_of = |[r, g, b]: [u8; 3]| f64::from(r.max(g).max(b) - r.min(g).min(b));
+
+    let mut luma_integral = vec![0.0; stride * (height + 1)];
+    let mut edge_integral = vec![0.0; stride * (height + 1)];
+    let mut chroma_integral = vec![0.0; stride * (height + 1)];
+    let mut chroma_sum = 0.0;
+    for y in 0..height {
+        let mut row = [0.0; 3];
+        for x in 0..width {
+            let idx = y * width + x;
+            let v = luma[idx];
+            let left = if x > 0 { luma[idx - 1] } else { v };
+            let up = if y > 0 { luma[idx - width] } else { v };
+            let edge = ((v - left).abs() + (v - up).abs()).min(255.0);
+            let chroma = chroma_of(image.data[idx]);
+            chroma_sum += chroma;
+            let above = y * stride + x + 1;
+            let at = (y + 1) * stride + x + 1;
+            row[0] += v;
+            row[1] += edge;
+            row[2] += chroma;
+            luma_integral[at] = luma_integral[above] + row[0];
+            edge_integral[at] = edge_integral[above] + row[1];
+            chroma_integral[at] = chroma_integral[above] + row[2];
+        }
+    }
+    Some(DensityAnalysis {
+        width,
+        height,
+        stride,
+        luma: luma_integral,
+        edge: edge_integral,
+        chroma: chroma_integral,
+        global_chroma: chroma_sum / total as f64,
+    })
+}
+
+/// `getRegionMean`, rounding the rectangle to the integral's grid.
+fn region_mean(integral: &[f64], stride: usize, x: f64, y: f64, width: f64, height: f64) -> f64 {
+    if width <= 0.0 || height <= 0.0 {
+        return 0.0;
+    }
+    let max_x = (stride - 1) as f64;
+    let max_y = (integral.len() / stride).saturating_sub(1) as f64;
+    let x1 = clamp(js_round(x), 0.0, max_x);
+    let y1 = clamp(js_round(y), 0.0, max_y);
+    let x2 = clamp(js_round(x + width), x1, max_x);
+    let y2 = clamp(js_round(y + height), y1, max_y);
+    let area = ((x2 - x1) * (y2 - y1)).max(1.0);
+    let at = |x: f64, y: f64| integral[y as usize * stride + x as usize];
+    (at(x2, y2) - at(x2, y1) - at(x1, y2) + at(x1, y1)) / area
+}
+
+/// `sanitizeBound`: floors the rectangle and keeps at least one pixel of it
+/// inside the image.
+fn sanitize_bound(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    image_width: usize,
+    image_height: usize,
+) -> Option<CropRegion> {
+    if image_width < 1 || image_height < 1 {
+        return None;
+    }
+    let finite = |v: f64| if v.is_finite() { v.floor() } else { 0.0 };
+    let left = clamp(finite(x), 0.0, (image_width - 1) as f64) as usize;
+    let top = clamp(finite(y), 0.0, (image_height - 1) as f64) as usize;
+    let width = clamp(finite(width), 1.0, (image_width - left) as f64) as usize;
+    let height = clamp(finite(height), 1.0, (image_height - top) as f64) as usize;
+    Some(CropRegion {
+        left,
+        top,
+        width,
+        height,
+    })
+}
+
+/// `computeBandStats`: means just inside and just outside the rectangle's
+/// four sides. Outside bands that leave the image are dropped.
+fn band_stats(crop: CropRegion, analysis: &DensityAnalysis) -> BandStats {
+    let (left, top) = (crop.left as f64, crop.top as f64);
+    let (width, height) = (crop.width as f64, crop.height as f64);
+    let min_side = width.min(height).max(1.0);
+    let band = clamp(js_round(min_side * 0.045), 4.0, 42.0);
+    let outer = clamp(js_round(min_side * 0.06), 5.0, 56.0);
+    let (right, bottom) = (left + width, top + height);
+
+    let inside = [
+        (left, top, width, band),
+        (left, bottom - band, width, band),
+        (left, top, band, height),
+        (right - band, top, band, height),
+    ];
+    let outside = [
+        (left, top - outer, width, outer),
+        (left, bottom, width, outer),
+        (left - outer, top, outer, height),
+        (right, top, outer, height),
+    ]
+    .into_iter()
+    .filter(|&(x, y, w, h)| {
+        x >= 0.0 && y >= 0.0 && x + w <= analysis.width as f64 && y + h <= analysis.height as f64
+    })
+    .collect::<Vec<_>>();
+
+    let mean_for = |integral: &[f64], bands: &[(f64, f64, f64, f64)]| {
+        if bands.is_empty() {
+            return 0.0;
+        }
+        let mut weighted = 0.0;
+        let mut area = 0.0;
+        for &(x, y, w, h) in bands {
+            let a = (w * h).max(1.0);
+            weighted += region_mean(integral, analysis.stride, x, y, w, h) * a;
+            area += a;
+        }
+        weighted / area.max(1.0)
+    };
+
+    BandStats {
+        inner_luma: mean_for(&analysis.luma, &inside),
+        outer_luma: mean_for(&analysis.luma, &outside),
+        inner_edge: mean_for(&analysis.edge, &inside),
+        outer_edge: mean_for(&analysis.edge, &outside),
+        inner_chroma: mean_for(&analysis.chroma, &inside),
+        outer_chroma: mean_for(&analysis.chroma, &outside),
+        outside_band_count: outside.len(),
+    }
+}
+
+/// `scoreSprocketLaneSupport`: busy, balanced lanes on both long sides of
+/// the frame, where a 135 strip's perforations run.
+fn sprocket_lane_support(analysis: &DensityAnalysis, crop: CropRegion) -> f64 {
+    let (x, y) = (crop.left as f64, crop.top as f64);
+    let (width, height) = (crop.width as f64, crop.height as f64);
+    let short_side = width.min(height).max(1.0);
+    let lane_depth = clamp(js_round(short_side * 0.16), 5.0, 56.0);
+    let min_lane = js_round(short_side * 0.045).max(4.0);
+    let mean =
+        |x: f64, y: f64, w: f64, h: f64| region_mean(&analysis.edge, analysis.stride, x, y, w, h);
+
+    let (near, far, center) = if width >= height {
+        let top_height = lane_depth.min(y);
+        let bottom_height = lane_depth.min(analysis.height as f64 - (y + height));
+        if top_height < min_lane || bottom_height < min_lane {
+            return 0.0;
+        }
+        (
+            mean(x, y - top_height, width, top_height),
+            mean(x, y + height, width, bottom_height),
+            mean(
+                x + width * 0.10,
+                y + height * 0.28,
+                width * 0.80,
+                height * 0.44,
+            ),
+        )
+    } else {
+        let left_width = lane_depth.min(x);
+        let right_width = lane_depth.min(analysis.width as f64 - (x + width));
+        if left_width < min_lane || right_width < min_lane {
+            return 0.0;
+        }
+        (
+            mean(x - left_width, y, left_width, height),
+            mean(x + width, y, right_width, height),
+            mean(
+                x + width * 0.28,
+                y + height * 0.10,
+                width * 0.44,
+                height * 0.80,
+            ),
+        )
+    };
+    let balance = 1.0 - clamp((near - far).abs() / near.max(far).max(1.0), 0.0, 1.0);
+    clamp(((near + far) * 0.5 - center * 0.52) / 18.0, 0.0, 1.0) * balance
+}
+
+/// `scoreInteriorGapPresence`: 0 when activity runs across the whole crop,
+/// up to 1 when a quiet strip of a different density (the blank film between
+/// two frames) sits inside it.
+fn interior_gap_presence(crop: CropRegion, analysis: &DensityAnalysis) -> f64 {
+    let landscape = crop.width >= crop.height;
+    let (x, y) = (crop.left as f64, crop.top as f64);
+    let (width, height) = (crop.width as f64, crop.height as f64);
+    let (along, across) = if landscape {
+        (width, height)
+    } else {
+        (height, width)
+    };
+    let inset = along * 0.05;
+    let band_inset = across * 0.26;
+    let band_size = across * 0.48;
+    let window = js_round(along * 0.035).max(3.0);
+    let step = js_round(window / 2.0).max(2.0);
+    let start = if landscape { x } else { y } + inset;
+    let end = if landscape { x + width } else { y + height } - inset - window;
+    if end <= start {
+        return 0.0;
+    }
+
+    let mut edges = Vec::new();
+    let mut lumas = Vec::new();
+    let mut pos = start;
+    while pos <= end {
+        let (rx, ry, rw, rh) = if landscape {
+            (pos, y + band_inset, window, band_size)
+        } else {
+            (x + band_inset, pos, band_size, window)
+        };
+        edges.push(region_mean(&analysis.edge, analysis.stride, rx, ry, rw, rh));
+        lumas.push(region_mean(&analysis.luma, analysis.stride, rx, ry, rw, rh));
+        pos += step;
+    }
+    if edges.len() < 6 {
+        return 0.0;
+    }
+
+    let median = |values: &[f64]| {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        sorted[sorted.len() / 2]
+    };
+    let median_edge = median(&edges);
+    let median_luma = median(&lumas);
+    if median_edge < 2.5 {
+        return 0.0;
+    }
+    let quiet_edge = median_edge * 0.85;
+    edges
+        .iter()
+        .zip(&lumas)
+        .map(|(&edge, &luma)| {
+            let edge_quiet = clamp((quiet_edge - edge) / quiet_edge.max(1e-6), 0.0, 1.0);
+            let luma_deviation = clamp(((luma - median_luma).abs() - 8.0) / 26.0, 0.0, 1.0);
+            edge_quiet * luma_deviation
+        })
+        .fold(0.0, f64::max)
+}
+
+/// `scoreDensityRect`: how much a rectangle of `target`'s aspect looks like
+/// the frame's border. `None` below the webview's 0.34 score floor.
+fn score_density_rect(
+    rect: (f64, f64, f64, f64),
+    target: Target,
+    analysis: &DensityAnalysis,
+    frame_mode: FrameMode,
+    sprocket: bool,
+) -> Option<DensityCandidate> {
+    let (x, y, width, height) = rect;
+    let crop = sanitize_bound(x, y, width, height, analysis.width, analysis.height)?;
+    let (crop_w, crop_h) = (crop.width as f64, crop.height as f64);
+    let image_area = (analysis.width * analysis.height).max(1) as f64;
+    let area_ratio = crop_w * crop_h / image_area;
+    if !(0.12..=0.965).contains(&area_ratio) {
+        return None;
+    }
+    let ratio = crop_w.max(crop_h) / crop_w.min(crop_h).max(1.0);
+    let aspect_delta = (ratio - target.ratio).abs() / target.ratio;
+    let aspect_score = 1.0 - clamp(aspect_delta / 0.24, 0.0, 1.0);
+    if aspect_score <= 0.0 {
+        return None;
+    }
+
+    let bands = band_stats(crop, analysis);
+    let boundary_completeness = clamp(bands.outside_band_count as f64 / 4.0, 0.0, 1.0);
+    if boundary_completeness < 0.5 {
+        return None;
+    }
+
+    let [edge_weight, contrast_weight, chroma_weight, outside_weight] = frame_mode.weights();
+    let inside_edge = region_mean(
+        &analysis.edge,
+        analysis.stride,
+        crop.left as f64 + crop_w * 0.08,
+        crop.top as f64 + crop_h * 0.08,
+        crop_w * 0.84,
+        crop_h * 0.84,
+    );
+    let outside_edge = bands.outer_edge;
+    let content_texture =
+        clamp((inside_edge - outside_edge * 0.72) / 24.0, 0.0, 1.0) * boundary_completeness;
+    let boundary_edge_contrast =
+        clamp((bands.inner_edge - bands.outer_edge).abs() / 18.0, 0.0, 1.0) * boundary_completeness;
+    let edge_delta = content_texture.max(boundary_edge_contrast * 0.72);
+    let border_contrast =
+        clamp((bands.inner_luma - bands.outer_luma).abs() / 34.0, 0.0, 1.0) * boundary_completeness;
+    let chroma_contrast = if chroma_weight > 0.0 {
+        clamp(
+            (bands.inner_chroma - bands.outer_chroma).abs() / 22.0,
+            0.0,
+            1.0,
+        ) * boundary_completeness
+    } else {
+        0.0
+    };
+    let outside_clean =
+        clamp(1.0 - outside_edge / (inside_edge + 1.0).max(12.0), 0.0, 1.0) * boundary_completeness;
+    let (image_w, image_h) = (analysis.width as f64, analysis.height as f64);
+    let center_dist = (crop.left as f64 + crop_w / 2.0 - image_w / 2.0)
+        .hypot(crop.top as f64 + crop_h / 2.0 - image_h / 2.0);
+    let center_prior = 1.0 - clamp(center_dist / (image_w.hypot(image_h) * 0.34), 0.0, 1.0);
+    // A sprocket candidate is one 135 frame on a longer strip, which covers
+    // much less of the scan than a lone frame.
+    let area_target = if sprocket { 0.30 } else { 0.62 };
+    let area_score = 1.0 - clamp((area_ratio - area_target).abs() / 0.48, 0.0, 1.0);
+    let lane_score = if sprocket {
+        sprocket_lane_support(analysis, crop)
+    } else {
+        0.0
+    };
+    let interior_gap = interior_gap_presence(crop, analysis);
+
+    let score = clamp(
+        aspect_score * 0.19
+            + edge_delta * edge_weight
+            + border_contrast * contrast_weight
+            + chroma_contrast * chroma_weight
+            + outside_clean * outside_weight
+            + center_prior * 0.10
+            + area_score * 0.08
+            + lane_score * 0.08
+            - interior_gap * 0.16,
+        0.0,
+        1.0,
+    );
+    if score < 0.34 {
+        return None;
+    }
+
+    Some(DensityCandidate {
+        sprocket,
+        crop,
+        score,
+        area_ratio,
+        target,
+        breakdown: ScoreBreakdown {
+            area: round_to(area_score, 3),
+            edge_support: round_to(edge_delta.max(border_contrast), 3),
+            center_prior: round_to(center_prior, 3),
+            aspect: round_to(aspect_score, 3),
+            border_contrast: round_to(border_contrast, 3),
+            boundary_completeness: round_to(boundary_completeness, 3),
+            boundary_edge_contrast: round_to(boundary_edge_contrast, 3),
+            content_texture: round_to(content_texture, 3),
+            outside_clean: round_to(outside_clean, 3),
+            chroma_contrast: round_to(chroma_contrast, 3),
+            sprocket_lane: round_to(lane_score, 3),
+            interior_gap: round_to(interior_gap, 3),
+            frame_mode,
+        },
+    })
+}
+
+/// `buildDensityTemplateCandidates`: every target aspect in both
+/// orientations at a range of scales and offsets around the center, plus
+/// single 135 frames on a strip. Best first, near-duplicates dropped.
+fn density_candidates(
+    image: &Rgb8,
+    settings: &AutoFrameSettings,
+    targets: &[Target],
+) -> Vec<DensityCandidate> {
+    let Some(analysis) = density_analysis(image) else {
+        return Vec::new();
+    };
+    let frame_mode = FrameMode::infer(&settings.film_type, analysis.global_chroma);
+    let (image_w, image_h) = (analysis.width as f64, analysis.height as f64);
+    let (max_w, max_h) = (image_w * 0.985, image_h * 0.985);
+    let (center_x, center_y) = (image_w / 2.0, image_h / 2.0);
+    let mut candidates = Vec::new();
+    let mut push_centered = |width: f64, height: f64, target: Target, sprocket: bool| {
+        for offset_x in DENSITY_TEMPLATE_OFFSETS {
+            for offset_y in DENSITY_TEMPLATE_OFFSETS {
+                let x = center_x - width / 2.0 + image_w * offset_x;
+                let y = center_y - height / 2.0 + image_h * offset_y;
+                let rect = (x, y, width, height);
+                candidates.extend(score_density_rect(
+                    rect, target, &analysis, frame_mode, sprocket,
+                ));
+            }
+        }
+    };
+
+    for &target in targets {
+        for portrait in [false, true] {
+            let ratio = if portrait {
+                1.0 / target.ratio
+            } else {
+                target.ratio
+            };
+            let fit_w = max_w.min(max_h * ratio);
+            let fit_h = if ratio >= 1.0 {
+                fit_w / ratio
+            } else {
+                max_h.min(fit_w / ratio)
+            };
+            if fit_w < image_w * 0.22 || fit_h < image_h * 0.22 {
+                continue;
+            }
+            for scale in DENSITY_TEMPLATE_SCALE_FACTORS {
+                let (width, height) = (fit_w * scale, fit_h * scale);
+                if width < image_w * 0.22 || height < image_h * 0.22 {
+                    continue;
+                }
+                push_centered(width, height, target, false);
+            }
+            if target.key == "135" {
+                for short_ratio in SPROCKET_SHORT_RATIOS {
+                    let short_side = if portrait { image_w } else { image_h } * short_ratio;
+                    let (width, height) = if portrait {
+                        (short_side, short_side * target.ratio)
+                    } else {
+                        (short_side * target.ratio, short_side)
+                    };
+                    if width > max_w || height > max_h {
+                        continue;
+                    }
+                    push_centered(width, height, target, true);
+                }
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut seen = HashSet::new();
+    candidates.retain(|candidate| {
+        let quarter = |v: usize| js_round(v as f64 / 4.0) as i64;
+        seen.insert((
+            candidate.target.key,
+            quarter(candidate.crop.left),
+            quarter(candidate.crop.top),
+            quarter(candidate.crop.width),
+            quarter(candidate.crop.height),
+        ))
+    });
+    candidates.truncate(MAX_DENSITY_CANDIDATES);
+    candidates
+}
+
+/// The density candidates `detectFrameCandidatesWithCv` falls back to.
+fn frame_candidates(
+    image: &Rgb8,
+    settings: &AutoFrameSettings,
+    targets: &[Target],
+) -> Vec<DensityCandidate> {
+    let mut candidates = density_candidates(image, settings, targets);
+    candidates.retain(|candidate| candidate.area_ratio >= MIN_CANDIDATE_AREA_RATIO);
+    candidates.truncate(MAX_FRAME_CANDIDATES);
+    candidates
+}
+
+/// `evaluateAutoFrameCropRegion`.
+fn evaluate_crop(
+    crop: Option<CropRegion>,
+    width: usize,
+    height: usize,
+    candidate: &DensityCandidate,
+) -> CropValidation {
+    let Some(crop) = crop else {
+        return CropValidation {
+            is_valid: false,
+            area_ratio: 0.0,
+            aspect_delta: 1.0,
+            aspect_score: 0.0,
+            area_score: 0.0,
+            edge_support: 0.0,
+            short_edge_coverage: 0.0,
+            aspect_tolerance: ASPECT_TOLERANCE,
+        };
+    };
+    let (crop_w, crop_h) = (crop.width as f64, crop.height as f64);
+    let total_area = (width * height).max(1) as f64;
+    let area_ratio = (crop_w * crop_h).max(1.0) / total_area;
+    let ratio = crop_w.max(crop_h) / crop_w.min(crop_h).max(1.0);
+    let aspect_delta = (ratio - candidate.target.ratio).abs() / candidate.target.ratio;
+    let area_score = clamp((area_ratio - 0.08) / 0.86, 0.0, 1.0);
+    let aspect_score = 1.0 - clamp(aspect_delta / ASPECT_TOLERANCE, 0.0, 1.0);
+    let short_edge_coverage = (crop_w / width as f64).min(crop_h / height as f64);
+    let edge_support = clamp(candidate.breakdown.edge_support, 0.0, 1.0);
+    let is_valid = (0.10..=0.975).contains(&area_ratio)
+        && aspect_delta <= ASPECT_TOLERANCE
+        && short_edge_coverage >= 0.22
+        && edge_support >= 0.12;
+
+    CropValidation {
+        is_valid,
+        area_ratio: round_to(area_ratio, 4),
+        aspect_delta: round_to(aspect_delta, 4),
+        aspect_score: round_to(aspect_score, 3),
+        area_score: round_to(area_score, 3),
+        edge_support: round_to(edge_support, 3),
+        short_edge_coverage: round_to(short_edge_coverage, 3),
+        aspect_tolerance: ASPECT_TOLERANCE,
+    }
+}
+
+/// `getDensityTemplateReliability`: a density candidate is only trusted with
+/// clear sprocket lanes or a clean, textured image window, and its confidence
+/// is capped by which of those it showed.
+fn density_confidence_cap(
+    candidate: &DensityCandidate,
+    validation: &CropValidation,
+) -> Option<f64> {
+    let breakdown = &candidate.breakdown;
+    let boundary_completeness = clamp(breakdown.boundary_completeness, 0.0, 1.0);
+    let boundary_strength = breakdown
+        .border_contrast
+        .max(breakdown.edge_support)
+        .max(breakdown.boundary_edge_contrast);
+    let content_texture = clamp(breakdown.content_texture, 0.0, 1.0);
+    let outside_clean = clamp(breakdown.outside_clean, 0.0, 1.0);
+    let sprocket_lane = clamp(breakdown.sprocket_lane, 0.0, 1.0);
+    let aspect_score = validation.aspect_score;
+    let area_ratio = validation.area_ratio;
+
+    let strong_35mm = candidate.target.key == "135"
+        && candidate.sprocket
+        && sprocket_lane >= 0.24
+        && boundary_strength >= 0.24
+        && outside_clean >= 0.12
+        && boundary_completeness >= 0.5;
+    let strong_window = boundary_completeness >= 0.75
+        && boundary_strength >= 0.44
+        && outside_clean >= 0.20
+        && content_texture >= 0.08
+        && aspect_score >= 0.78
+        && area_ratio <= 0.93;
+    let moderate_window = boundary_completeness >= 0.75
+        && boundary_strength >= 0.34
+        && outside_clean >= 0.18
+        && content_texture >= 0.06
+        && aspect_score >= 0.84
+        && area_ratio <= 0.88;
+    if !strong_35mm && !strong_window && !moderate_window {
+        return None;
+    }
+
+    let mut cap: f64 = 0.66;
+    if strong_35mm {
+        cap = 0.78;
+    }
+    if strong_window {
+        cap = cap.max(0.74);
+    }
+    if !candidate.sprocket {
+        cap = cap.min(0.68);
+    }
+    Some(cap)
+}
+
+/// `detectAxisAlignedCropRegion`: the best candidate that passes validation
+/// and is reliable enough.
+fn pick_crop(candidates: &[DensityCandidate], width: usize, height: usize) -> Option<CropPick> {
+    candidates.iter().find_map(|candidate| {
+        let validation = evaluate_crop(Some(candidate.crop), width, height, candidate);
+        if !validation.is_valid {
+            return None;
+        }
+        let cap = density_confidence_cap(candidate, &validation)?;
+        let confidence = clamp(
+            candidate.score * 0.62
+                + (validation.area_ratio / 0.92).min(1.0) * 0.16
+                + validation.aspect_score * 0.16
+                + validation.edge_support * 0.06,
+            0.0,
+            1.0,
+        );
+        Some(CropPick {
+            crop: candidate.crop,
+            confidence: confidence.min(cap),
+            confidence_cap: cap,
+            aspect_score: validation.aspect_score,
+            candidate: candidate.clone(),
+        })
+    })
+}
+
+/// `computeAutoFrameAnglePenalty`: straightening by a few degrees costs
+/// more than a right-angle turn.
+fn angle_penalty(angle: f64) -> f64 {
+    let abs_angle = normalize_angle(angle).abs();
+    if abs_angle <= 0.12 {
+        return 0.0;
+    }
+    let remainder = abs_angle % 90.0;
+    let to_right_angle = remainder.min(90.0 - remainder);
+    let off_axis = clamp(to_right_angle / 22.0, 0.0, 1.0) * 0.16;
+    let magnitude = clamp(abs_angle / 120.0, 0.0, 1.0) * 0.07;
+    clamp(off_axis + magnitude, 0.0, 0.25)
+}
+
+/// Blurs with a separable [1, 4, 6, 4, 1] kernel, the 5x5 Gaussian the
+/// webview applies before finding lines, clamping at the borders.
+fn blur(values: &[f64], width: usize, height: usize) -> Vec<f64> {
+    const KERNEL: [f64; 5] = [1.0, 4.0, 6.0, 4.0, 1.0];
+    let pass = |src: &[f64], horizontal: bool| {
+        let mut out = vec![0.0; src.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for (k, weight) in KERNEL.iter().enumerate() {
+                    let offset = k as isize - 2;
+                    let idx = if horizontal {
+                        let sx = (x as isize + offset).clamp(0, width as isize - 1) as usize;
+                        y * width + sx
+                    } else {
+                        let sy = (y as isize + offset).clamp(0, height as isize - 1) as usize;
+                        sy * width + x
+                    };
+                    sum += src[idx] * weight;
+                }
+                out[y * width + x] = sum / 16.0;
+            }
+        }
+        out
+    };
+    pass(&pass(values, true), false)
+}
+
+/// Replaces `buildLineOrientationRotationCandidates`' `HoughLinesP` pass: a
+/// Hough transform over tilts within 35° of either axis, where each strong
+/// edge pixel only votes near its own gradient's angle. Lines at least as
+/// long as the webview's `minLineLength` weigh in at their length, and up to
+/// three tilts holding 16% of that weight are returned as the angles that
+/// would straighten them.
+fn tilt_candidates(image: &Rgb8) -> Vec<f64> {
+    let (width, height) = (image.width, image.height);
+    let min_dim = width.min(height);
+    if min_dim < 80 {
+        return Vec::new();
+    }
+    let luma = image
+        .data
+        .iter()
+        .map(|&[r, g, b]| f64::from(r) * 0.299 + f64::from(g) * 0.587 + f64::from(b) * 0.114)
+        .collect::<Vec<_>>();
+    let luma = blur(&luma, width, height);
+
+    let bins = (MAX_TILT * 4.0) as usize + 1;
+    let diagonal = (width as f64).hypot(height as f64).ceil() as usize;
+    let rhos = diagonal * 2 + 1;
+    // One accumulator per axis: lines running across, then lines running down.
+    let mut votes = vec![0u32; 2 * bins * rhos];
+    let spread = (TILT_VOTE_SPREAD * 2.0) as isize;
+    let tilt_of = |bin: usize| bin as f64 / 2.0 - MAX_TILT;
+    let trig = (0..bins)
+        .map(|bin| tilt_of(bin).to_radians().sin_cos())
+        .collect::<Vec<_>>();
+
+    let at = |x: usize, y: usize| luma[y * width + x];
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                - at(x - 1, y - 1)
+                - 2.0 * at(x - 1, y)
+                - at(x - 1, y + 1);
+            let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                - at(x - 1, y - 1)
+                - 2.0 * at(x, y - 1)
+                - at(x + 1, y - 1);
+            if gx.abs() + gy.abs() < STRONG_EDGE {
+                continue;
+            }
+            // The edge runs across the gradient.
+            let direction = gy.atan2(gx).to_degrees() + 90.0;
+            let tilt = normalize_axis_delta(direction);
+            let across = (normalize_angle(direction - tilt) % 180.0).abs() < 1.0;
+            let axis = usize::from(!across);
+            let center = js_round((tilt + MAX_TILT) * 2.0) as isize;
+            for bin in center - spread..=center + spread {
+                if bin < 0 || bin >= bins as isize {
+                    continue;
+                }
+                let bin = bin as usize;
+                let (sin, cos) = trig[bin];
+                let (fx, fy) = (x as f64, y as f64);
+                let rho = if across {
+                    fy * cos - fx * sin
+                } else {
+                    fx * cos + fy * sin
+                };
+                let cell = (rho.round() as isize + diagonal as isize) as usize;
+                votes[(axis * bins + bin) * rhos + cell] += 1;
+            }
+        }
+    }
+
+    let min_length = (js_round(min_dim as f64 * 0.18) as u32).max(34);
+    let mut weights = vec![0.0; bins];
+    for (row, cells) in votes.chunks_exact(rhos).enumerate() {
+        let length = cells
+            .iter()
+            .filter(|&&count| count >= min_length)
+            .map(|&count| f64::from(count))
+            .sum::<f64>();
+        let tilt = tilt_of(row % bins);
+        weights[row % bins] += length * (1.0 - clamp(tilt.abs() / 42.0, 0.0, 0.45));
+    }
+    let total = weights.iter().sum::<f64>();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+    let mut ranked = weights.iter().copied().enumerate().collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked
+        .into_iter()
+        .take(3)
+        .filter(|&(_, weight)| weight / total >= MIN_TILT_SHARE)
+        .map(|(bin, _)| -tilt_of(bin))
+        .filter(|angle| angle.abs() >= 0.2)
+        .collect()
+}
+
+/// `buildRotationCandidates` and `mergeAngleCandidates`: density candidates
+/// are axis-aligned, so they only propose right-angle turns; line tilts are
+/// added and the smallest turns are tried first.
+fn angle_candidates(has_candidates: bool, tilts: &[f64]) -> Vec<f64> {
+    let turns: &[f64] = if has_candidates {
+        &[90.0, -90.0, 180.0]
+    } else {
+        &[]
+    };
+    let mut angles = vec![0.0];
+    for &angle in turns.iter().chain(tilts) {
+        let normalized = normalize_angle(angle);
+        let quantized = if normalized.abs() < 0.12 {
+            0.0
+        } else {
+            round_to(normalized, 1)
+        };
+        if !angles.contains(&quantized) {
+            angles.push(quantized);
+        }
+    }
+    angles.sort_by(|a: &f64, b: &f64| a.abs().total_cmp(&b.abs()));
+    angles
+}
+
+fn scale_crop(crop: CropRegion, from: (usize, usize), to: (usize, usize)) -> Option<CropRegion> {
+    let scale_x = to.0 as f64 / from.0 as f64;
+    let scale_y = to.1 as f64 / from.1 as f64;
+    sanitize_bound(
+        js_round(crop.left as f64 * scale_x),
+        js_round(crop.top as f64 * scale_y),
+        js_round(crop.width as f64 * scale_x),
+        js_round(crop.height as f64 * scale_y),
+        to.0,
+        to.1,
+    )
+}
+
+/// Finds the frame's crop and the rotation that straightens it. Candidates
+/// and angles are scored on a 1600px preview like the webview does, then the
+/// crop is scaled to the full-resolution frame. `None` when no border is
+/// reliable enough.
+pub fn detect_frame(image: &Image16, settings: &AutoFrameSettings) -> Option<FrameDetection> {
+    let targets = aspect_targets(settings);
+    let preview = preview(image, PREVIEW_MAX_SIDE);
+    let candidates = frame_candidates(&preview, settings, &targets);
+    let tilts = tilt_candidates(&preview);
+    if candidates.is_empty() && tilts.is_empty() {
+        return None;
+    }
+    let base_score = candidates.first().map_or(0.5, |candidate| candidate.score);
+
+    let mut best: Option<PreviewPick> = None;
+    for angle in angle_candidates(!candidates.is_empty(), &tilts) {
+        let rotated;
+        let (frame, frame_candidates) = if angle.abs() < 0.001 {
+            (&preview, candidates.clone())
+        } else {
+            rotated = rotate(&preview, angle);
+            (&rotated, frame_candidates(&rotated, settings, &targets))
+        };
+        let Some(crop) = pick_crop(&frame_candidates, frame.width, frame.height) else {
+            continue;
+        };
+        // The webview's contour candidates score higher once a tilted frame
+        // is straightened, but density scores saturate. A tilt backed by long
+        // lines is not charged for leaving the axis here, and wins ties in
+        // the order the line pass ranked it.
+        let line_rank = tilts
+            .iter()
+            .position(|tilt| (tilt - angle).abs() < 0.05)
+            .unwrap_or(usize::MAX);
+        let penalty = if line_rank < usize::MAX {
+            0.0
+        } else {
+            angle_penalty(angle)
+        };
+        let score = clamp(
+            base_score * 0.24 + crop.confidence * 0.66 + crop.aspect_score * 0.10 - penalty,
+            0.0,
+            1.0,
+        );
+        let better = best.as_ref().is_none_or(|best| {
+            score > best.score + 0.001
+                || ((score - best.score).abs() <= 0.001
+                    && (line_rank, angle.abs()) < (best.line_rank, best.angle.abs()))
+        });
+        if better {
+            best = Some(PreviewPick {
+                angle,
+                line_rank,
+                score,
+                crop,
+                width: frame.width,
+                height: frame.height,
+            });
+        }
+    }
+
+    let best = best?;
+    let angle = if best.angle.abs() < 0.15 {
+        0.0
+    } else {
+        round_to(best.angle, 2)
+    };
+    let (width, height) = (image.width as usize, image.height as usize);
+    let (rotated_width, rotated_height) = rotated_size(width, height, angle);
+    let candidate = &best.crop.candidate;
+    let crop_region = scale_crop(
+        best.crop.crop,
+        (best.width, best.height),
+        (rotated_width, rotated_height),
+    );
+    let validation = evaluate_crop(crop_region, rotated_width, rotated_height, candidate);
+    if !validation.is_valid {
+        return None;
+    }
+    let crop_region = crop_region?;
+
+    let penalty = angle_penalty(angle);
+    let confidence = round_to(
+        best.crop.confidence_cap.min(clamp(
+            best.score * 0.34 + best.crop.confidence * 0.56 + validation.aspect_score * 0.10
+                - penalty * 0.4,
+            0.0,
+            1.0,
+        )),
+        2,
+    );
+    Some(FrameDetection {
+        angle,
+        crop_region,
+        confidence,
+        confidence_level: confidence_level(confidence, settings),
+        detected_format: candidate.target.key,
+        rotated_width,
+        rotated_height,
+        diagnostics: FrameDiagnostics {
+            method: candidate.method(),
+            score_breakdown: candidate.breakdown.clone(),
+            frame_mode: candidate.breakdown.frame_mode,
+            angle_penalty: round_to(penalty, 3),
+            crop_validation: validation,
+        },
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{aspect_targets, confidence_level, detect_frame, rotated_size, AutoFrameSettings};
+    use crate::engine::Image16;
+
+    /// A textured `frame_w` by `frame_h` frame centered on a dark scan and
+    /// turned clockwise by `tilt` degrees, supersampled so its edges are
+    /// anti-aliased like a real scan's.
+    pub(crate) fn scan(width: u32, height: u32, frame_w: f64, frame_h: f64, tilt: f64) -> Image16 {
+        let (sin, cos) = tilt.to_radians().sin_cos();
+        let (cx, cy) = (f64::from(width) / 2.0, f64::from(height) / 2.0);
+        let texture = |u: f64, v: f64| {
+            let cell = ((u / 12.0).floor() as i64 * 7919 + (v / 12.0).floor() as i64 * 104_729)
+                .rem_euclid(9973);
+            20000.0 + (cell % 160) as f64 * 250.0
+        };
+        let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for (sx, sy) in [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)] {
+                    let (dx, dy) = (f64::from(x) + sx - cx, f64::from(y) + sy - cy);
+                    let u = dx * cos + dy * sin + frame_w / 2.0;
+                    let v = -dx * sin + dy * cos + frame_h / 2.0;
+                    let inside = (0.0..frame_w).contains(&u) && (0.0..frame_h).contains(&v);
+                    sum += if inside { texture(u, v) } else { 2500.0 };
+                }
+                let value = (sum / 4.0) as u16;
+                data.extend([value, value, value, 65535]);
+            }
+        }
+        Image16::new(width, height, data).unwrap()
+    }
+
+    #[test]
+    fn confidence_levels_follow_the_settings_thresholds() {
+        let settings = AutoFrameSettings::default();
+        assert_eq!(confidence_level(0.72, &settings), "high");
+        assert_eq!(confidence_level(0.6, &settings), "medium");
+        assert_eq!(confidence_level(0.54, &settings), "low");
+        let strict: AutoFrameSettings = serde_json::from_str(
+            r#"{"minConfidence":0.65,"allowed120Formats":{"6x6":false},"lastDiagnostics":null}"#,
+        )
+        .unwrap();
+        assert_eq!(confidence_level(0.6, &strict), "low");
+        let formats = aspect_targets(&strict)
+            .iter()
+            .map(|target| target.key)
+            .collect::<Vec<_>>();
+        assert_eq!(formats, ["135", "120-6x4.5", "120-6x7", "120-6x9"]);
+    }
+
+    #[test]
+    fn rotated_size_matches_the_canvas_rotation() {
+        assert_eq!(rotated_size(300, 200, 0.0), (300, 200));
+        assert_eq!(rotated_size(300, 200, -90.0), (200, 300));
+        assert_eq!(rotated_size(300, 200, 180.0), (300, 200));
+        let (width, height) = rotated_size(300, 200, 30.0);
+        assert_eq!((width, height), (360, 324));
+    }
+
+    #[test]
+    fn level_frame_is_cropped_without_rotation() {
+        let image = scan(1200, 800, 900.0, 600.0, 0.0);
+        let detection = detect_frame(&image, &AutoFrameSettings::default()).unwrap();
+        assert_eq!(detection.angle, 0.0);
+        assert_eq!(detection.detected_format, "135");
+        assert_eq!(
+            (detection.rotated_width, detection.rotated_height),
+            (1200, 800)
+        );
+        let crop = detection.crop_region;
+        assert!(
+            crop.left.abs_diff(150) <= 12 && crop.top.abs_diff(100) <= 12,
+            "{crop:?}"
+        );
+        assert!(
+            crop.width.abs_diff(900) <= 24 && crop.height.abs_diff(600) <= 24,
+            "{crop:?}"
+        );
+        assert!(detection.confidence >= 0.55, "{detection:?}");
+        assert_ne!(detection.confidence_level, "low");
+    }
+
+    #[test]
+    fn tilted_frame_is_straightened() {
+        let image = scan(1200, 800, 840.0, 560.0, 3.0);
+        let detection = detect_frame(&image, &AutoFrameSettings::default()).unwrap();
+        assert!((detection.angle + 3.0).abs() <= 0.5, "{detection:?}");
+        let crop = detection.crop_region;
+        let ratio = crop.width as f64 / crop.height as f64;
+        assert!((ratio - 1.5).abs() < 0.1, "{crop:?}");
+        assert!(crop.width.abs_diff(840) <= 40, "{crop:?}");
+    }
+}
@@ -0,0 +1,871 @@
+//! Tone curve generation. Mirrors `generateCurves` in `CurveEngine.js`:
+//! tanh/atanh S-curves applied as layers over a sparse grid, inverted for
+//! negatives, then interpolated into 65536-entry LUTs.
+
+use super::analysis::{js_round, ChannelLevels};
+use super::presets;
+
+const PIXEL_MAX: f64 = 65535.0;
+const LUT_SIZE: usize = 65536;
+const WB_LOW: f64 = 0.1;
+const WB_HIGH: f64 = 0.8;
+
+pub type Lut = Vec<u16>;
+type Channels = [Vec<f64>; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerOrder {
+    ColorFirst,
+    TonesFirst,
+}
+
+/// Variant names follow the `wbTonality` keys used by the presets.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WbTonality {
+    AddDensity,
+    NeutralDensity,
+    SubtractDensity,
+    TempTintDensity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WbMethod {
+    LinearFixed,
+    LinearDynamic,
+    ShadowWeighted,
+    HighlightWeighted,
+    MidtoneWeighted,
+}
+
+impl LayerOrder {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "tonesFirst" => Self::TonesFirst,
+            _ => Self::ColorFirst,
+        }
+    }
+}
+
+impl WbTonality {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "neutralDensity" => Self::NeutralDensity,
+            "subtractDensity" => Self::SubtractDensity,
+            "tempTintDensity" => Self::TempTintDensity,
+            _ => Self::AddDensity,
+        }
+    }
+}
+
+impl WbMethod {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "linearDynamic" => Self::LinearDynamic,
+            "shadowWeighted" => Self::ShadowWeighted,
+            "highlightWeighted" => Self::HighlightWeighted,
+            "midtoneWeighted" => Self::MidtoneWeighted,
+            _ => Self::LinearFixed,
+        }
+    }
+}
+
+/// Engine settings as produced by `Engine.buildSettings`.
+#[derive(Debug, Clone)]
+pub struct CurveSettings {
+    pub tone_profile: &'static str,
+    pub is_negative: bool,
+    pub brightness: f64,
+    pub exposure: f64,
+    pub contrast: f64,
+    pub highlights: f64,
+    pub shadows: f64,
+    pub whites: f64,
+    pub blacks: f64,
+    pub glow: f64,
+    pub fade: f64,
+    pub temp: f64,
+    pub tint: f64,
+    pub temperature: f64,
+    pub wb_cyan: f64,
+    pub wb_temp: f64,
+    pub wb_tint: f64,
+    pub wb_tonality: WbTonality,
+    pub wb_method: WbMethod,
+    pub layer_order: LayerOrder,
+    pub soft_highlights: bool,
+    pub soft_shadows: bool,
+    pub shadow_range: f64,
+    pub highlight_range: f64,
+    pub shadow_cyan: f64,
+    pub shadow_tint: f64,
+    pub shadow_temp: f64,
+    pub highlight_cyan: f64,
+    pub highlight_tint: f64,
+    pub highlight_temp: f64,
+    pub mid_cyan: f64,
+    pub mid_tint: f64,
+    pub mid_temp: f64,
+    pub curve_precision: String,
+    pub auto_tone_level: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CurveLuts {
+    pub r: Lut,
+    pub g: Lut,
+    pub b: Lut,
+}
+
+struct Prepared {
+    cyan: f64,
+    tint: f64,
+    temp: f64,
+    brightness: f64,
+    exposure: f64,
+    contrast: f64,
+    highlights: f64,
+    shadows: f64,
+    blacks: f64,
+    whites: f64,
+    shadow_range: f64,
+}
+
+struct ClipPoints {
+    whites: [f64; 3],
+    widths: [f64; 3],
+    white_overflow: [f64; 3],
+    black_underflow: [f64; 3],
+}
+
+fn clamp01(x: f64) -> f64 {
+    x.clamp(0.0, 1.0)
+}
+
+fn atanh(x: f64) -> f64 {
+    let x = x.clamp(-0.9999999, 0.9999999);
+    0.5 * ((1.0 + x) / (1.0 - x)).ln()
+}
+
+fn tanh_basis(steepness: f64, midpoint: f64, x: f64) -> f64 {
+    (0.5 * steepness * (x - midpoint)).tanh()
+}
+
+fn tanh_interpolate(steepness: f64, midpoint: f64, x: f64, scale: f64) -> f64 {
+    let t = x / scale;
+    let num = tanh_basis(steepness, midpoint, t) - tanh_basis(steepness, midpoint, 0.0);
+    let den = tanh_basis(steepness, midpoint, 1.0) - tanh_basis(steepness, midpoint, 0.0);
+    (num / den) * scale
+}
+
+fn atanh_interpolate(steepness: f64, midpoint: f64, x: f64, scale: f64) -> f64 {
+    let range = tanh_basis(steepness, midpoint, 1.0) - tanh_basis(steepness, midpoint, 0.0);
+    let t =
+        (range * (x / scale) + tanh_basis(steepness, midpoint, 0.0)).clamp(-0.9999999, 0.9999999);
+    ((2.0 / steepness) * atanh(t) + midpoint) * scale
+}
+
+fn map_channels(channels: &Channels, f: impl Fn(f64, usize, usize) -> f64) -> Channels {
+    [0, 1, 2].map(|ch| {
+        channels[ch]
+            .iter()
+            .enumerate()
+            .map(|(pt, &x)| f(x, ch, pt))
+            .collect()
+    })
+}
+
+fn apply_difference_layer(
+    base: &Channels,
+    transformed: &Channels,
+    reference: &Channels,
+) -> Channels {
+    map_channels(base, |x, ch, pt| {
+        clamp01(x + transformed[ch][pt] - reference[ch][pt])
+    })
+}
+
+fn auto_tone_gamma(levels: &[ChannelLevels; 3]) -> f64 {
+    let avg_mean = (levels[0].mean_point + levels[1].mean_point + levels[2].mean_point) / 3.0;
+    if avg_mean <= 0.0 || avg_mean >= 1.0 {
+        return 1.0;
+    }
+    let gamma = 1.0 / (avg_mean.ln() / 0.5f64.ln());
+    gamma.clamp(0.8, 1.1)
+}
+
+fn prepare_settings(levels: &[ChannelLevels; 3], settings: &CurveSettings) -> Prepared {
+    let profile = presets::tone_profile(settings.tone_profile);
+
+    let mut auto_gamma = 1.0;
+    if profile.auto_tone {
+        let raw_gamma = auto_tone_gamma(levels);
+        auto_gamma = 1.0 + (raw_gamma - 1.0) * settings.auto_tone_level;
+    }
+
+    Prepared {
+        cyan: 1.0 - settings.temp * 0.01,
+        tint: 1.0 - settings.tint * 0.01,
+        temp: 1.0 - settings.temperature * 0.01,
+        brightness: (1.0 / (1.0 + settings.brightness * 0.02))
+            * (1.0 / profile.default_gamma)
+            * (1.0 / auto_gamma),
+        exposure: 1.0 / (1.0 + settings.exposure * 0.02),
+        contrast: settings.contrast + profile.default_contrast,
+        highlights: settings.highlights + profile.default_highlights + settings.glow / 1.5,
+        shadows: settings.shadows + profile.default_shadows - settings.fade / 1.5,
+        blacks: settings.blacks + profile.default_blacks + settings.fade,
+        whites: settings.whites + profile.default_whites - settings.glow,
+        shadow_range: settings.shadow_range,
+    }
+}
+
+fn curve_resolution(curve_width: f64, precision: &str) -> usize {
+    match precision {
+        "auto" => {
+            if curve_width <= 30.0 {
+                3
+            } else if curve_width <= 70.0 {
+                5
+            } else if curve_width < 128.0 {
+                7
+            } else {
+                9
+            }
+        }
+        "smooth" => 4,
+        "precise" => 14,
+        _ => 8,
+    }
+}
+
+fn base_grid(resolution: usize) -> Channels {
+    let row: Vec<f64> = (0..resolution)
+        .map(|i| i as f64 / (resolution - 1) as f64)
+        .collect();
+    [row.clone(), row.clone(), row]
+}
+
+fn exposure_layer(channels: Channels, exposure: f64) -> Channels {
+    if exposure == 1.0 {
+        return channels;
+    }
+    map_channels(&channels, |x, _, _| {
+        if exposure < 1.0 {
+            return 1.0 - (1.0 - x).powf(1.0 / exposure);
+        }
+        let dark_factor = 2.0 - exposure;
+        x * (1.0 - (1.0 - dark_factor) * 0.4)
+    })
+}
+
+fn gamma_layer(channels: Channels, gamma: f64) -> Channels {
+    if gamma == 1.0 {
+        return channels;
+    }
+    map_channels(&channels, |x, _, _| {
+        if x <= 0.0 {
+            0.0
+        } else if x >= 1.0 {
+            1.0
+        } else {
+            x.powf(gamma)
+        }
+    })
+}
+
+fn contrast_layer(channels: Channels, contrast: f64) -> Channels {
+    if contrast.abs() < 1.0 {
+        return channels;
+    }
+    if contrast >= 1.0 {
+        let steepness = 1.0 + contrast * 0.2;
+        return map_channels(&channels, |x, _, _| {
+            tanh_interpolate(steepness, 0.5, x, 1.0)
+        });
+    }
+    let steepness = 1.0 + contrast.abs() * 0.1;
+    map_channels(&channels, |x, _, _| {
+        atanh_interpolate(steepness, 0.5, x, 1.0)
+    })
+}
+
+fn highlights_layer(channels: Channels, highlights: f64) -> Channels {
+    if highlights.abs() < 1.0 {
+        return channels;
+    }
+    let threshold = 0.9;
+    let steepness = 0.5 + highlights.abs() * 0.1;
+    map_channels(&channels, |x, _, _| {
+        if x <= 1.0 - threshold {
+            return x;
+        }
+        if highlights >= 1.0 {
+            1.0 - tanh_interpolate(steepness, 0.75, 1.0 - x, threshold)
+        } else {
+            1.0 - atanh_interpolate(steepness, 0.75, 1.0 - x, threshold)
+        }
+    })
+}
+
+fn shadows_layer(channels: Channels, shadows: f64) -> Channels {
+    if shadows.abs() < 1.0 {
+        return channels;
+    }
+    let threshold = 0.9;
+    let steepness = 0.5 + shadows.abs() * 0.1;
+    map_channels(&channels, |x, _, _| {
+        if x >= threshold {
+            return x;
+        }
+        if shadows >= 1.0 {
+            atanh_interpolate(steepness, 0.75, x, threshold)
+        } else {
+            tanh_interpolate(steepness, 0.75, x, threshold)
+        }
+    })
+}
+
+fn blacks_layer(channels: Channels, prepared: &Prepared) -> Channels {
+    let blacks = prepared.blacks;
+    if blacks.abs() < 1.0 {
+        return channels;
+    }
+    let decay = (-prepared.shadow_range + 1.0) * 0.33 + 5.0;
+
+    if blacks >= 1.0 {
+        let lift = blacks / 255.0;
+        return map_channels(&channels, |x, _, _| {
+            if x >= 0.9 {
+                x
+            } else {
+                lift * (-x * decay).exp() + x
+            }
+        });
+    }
+    let steepness = 0.5 + blacks.abs() * 0.1;
+    map_channels(&channels, |x, _, _| {
+        if x > 0.5 {
+            x
+        } else {
+            tanh_interpolate(steepness, 0.75, x, 0.5)
+        }
+    })
+}
+
+fn whites_layer(channels: Channels, settings: &CurveSettings, prepared: &Prepared) -> Channels {
+    let mut whites = prepared.whites;
+    let decay = (-settings.highlight_range + 1.0) * 0.33 + 5.0;
+
+    if settings.highlights < -5.0 {
+        whites += settings.highlights / 5.0;
+    }
+
+    if whites.abs() < 1.0 {
+        return channels;
+    }
+
+    if whites <= -1.0 {
+        let clip = -whites / 255.0;
+        return map_channels(&channels, |x, _, _| {
+            if x < 0.1 {
+                x
+            } else {
+                1.0 - (clip * (-(1.0 - x) * decay).exp() + (1.0 - x))
+            }
+        });
+    }
+    let steepness = 0.5 + whites.abs() * 0.1;
+    map_channels(&channels, |x, _, _| {
+        if x < 0.5 {
+            x
+        } else {
+            1.0 - tanh_interpolate(steepness, 0.75, 1.0 - x, 0.5)
+        }
+    })
+}
+
+fn weighted_gamma(x: f64, offset: f64, f: impl Fn(f64, f64) -> f64) -> f64 {
+    if offset == 0.0 || x <= 0.0 || x >= 1.0 {
+        return x;
+    }
+    let gamma = 1.0 + offset * 4.0;
+    if gamma <= 0.01 {
+        return x;
+    }
+    f(x, gamma)
+}
+
+fn color_linear_layer(channels: Channels, settings: &CurveSettings) -> Channels {
+    let wb_tint = -settings.wb_tint / 255.0;
+    let wb_temp = -settings.wb_temp / 255.0;
+    let wb_cyan = -settings.wb_cyan / 255.0;
+
+    let mut brightness_multiplier = 1.0;
+    let color_offset = match settings.wb_tonality {
+        WbTonality::NeutralDensity => [
+            -(wb_temp / 2.0 + wb_tint / 2.0),
+            -(wb_temp / 2.0 - wb_tint / 2.0),
+            wb_temp / 2.0 - wb_tint / 2.0,
+        ],
+        WbTonality::SubtractDensity => {
+            let denom = (1.0 - wb_temp * 0.5) * (1.0 - wb_tint * 0.5);
+            brightness_multiplier = if denom.abs() > 0.001 {
+                1.0 / denom
+            } else {
+                1.0
+            };
+            [-(wb_temp + wb_tint), 0.0, 0.0]
+        }
+        WbTonality::TempTintDensity => [-wb_tint / 2.0, 0.0, wb_temp - wb_tint / 2.0],
+        WbTonality::AddDensity => [wb_cyan, wb_tint, wb_temp],
+    };
+
+    if color_offset == [0.0; 3] && brightness_multiplier == 1.0 {
+        return channels;
+    }
+
+    let channels = if brightness_multiplier != 1.0 {
+        map_channels(&channels, |x, _, _| clamp01(x * brightness_multiplier))
+    } else {
+        channels
+    };
+
+    let high_compl = 1.0 - WB_HIGH;
+    let linear = |x: f64, offset: f64| {
+        if x > WB_HIGH {
+            clamp01(x + offset * ((1.0 - x) / high_compl))
+        } else if x < WB_LOW {
+            clamp01(x + offset * (x / WB_LOW))
+        } else {
+            clamp01(x + offset)
+        }
+    };
+
+    map_channels(&channels, |x, ch, _| {
+        let offset = color_offset[ch];
+        match settings.wb_method {
+            WbMethod::LinearFixed | WbMethod::LinearDynamic => {
+                if offset == 0.0 {
+                    return x;
+                }
+                if x <= 0.0 || x >= 1.0 {
+                    return clamp01(x);
+                }
+                let scaled = if settings.wb_method == WbMethod::LinearDynamic {
+                    offset * (0.2 + 0.8 * x)
+                } else {
+                    offset
+                };
+                linear(x, scaled)
+            }
+            WbMethod::ShadowWeighted => {
+                weighted_gamma(x, offset, |x, gamma| clamp01(x.powf(1.0 / gamma)))
+            }
+            WbMethod::HighlightWeighted => {
+                weighted_gamma(x, offset, |x, gamma| clamp01(1.0 - (1.0 - x).powf(gamma)))
+            }
+            WbMethod::MidtoneWeighted => weighted_gamma(x, offset, |x, gamma| {
+                let shadow = clamp01(x.powf(1.0 / gamma));
+                let highlight = clamp01(1.0 - (1.0 - x).powf(gamma));
+                (shadow + highlight) / 2.0
+            }),
+        }
+    })
+}
+
+fn color_gamma_layer(channels: &Channels, multipliers: [f64; 3]) -> Channels {
+    map_channels(channels, |x, ch, _| {
+        let mult = multipliers[ch];
+        if mult == 1.0 {
+            return x;
+        }
+        let shift = (1.0 - mult) / 4.0;
+        let adjusted = x - shift;
+        if x <= 0.0 || x >= 1.0 {
+            return x;
+        }
+        if adjusted >= 1.0 {
+            return 1.0;
+        }
+        if x > 0.8 {
+            return x - shift * ((1.0 - x) / 0.2);
+        }
+        if adjusted <= 0.0 {
+            return 0.0;
+        }
+        adjusted
+    })
+}
+
+fn shadow_color_layer(channels: Channels, settings: &CurveSettings) -> Channels {
+    let shadow_bound = 0.9 - (10.0 - settings.shadow_range) * 0.0444;
+    let range_factor = 1.0 + (10.0 - settings.shadow_range) / 18.0;
+    let color = [
+        -settings.shadow_cyan,
+        -settings.shadow_tint,
+        -settings.shadow_temp,
+    ];
+
+    map_channels(&channels, |x, ch, _| {
+        let value = color[ch];
+        if value == 0.0 || x >= shadow_bound {
+            return x;
+        }
+        let steepness = 0.75 + value.abs() * range_factor * 0.125;
+        if value > 0.0 {
+            atanh_interpolate(steepness, 0.75, x, shadow_bound)
+        } else {
+            tanh_interpolate(steepness, 0.75, x, shadow_bound)
+        }
+    })
+}
+
+fn midtone_color_layer(channels: Channels, settings: &CurveSettings) -> Channels {
+    let color = [-settings.mid_cyan, -settings.mid_tint, -settings.mid_temp];
+
+    map_channels(&channels, |x, ch, _| {
+        let value = color[ch];
+        if value == 0.0 {
+            return x;
+        }
+        let dist = (x - 0.5) / 0.35;
+        let weight = (-0.5 * dist * dist).exp();
+        if weight < 0.01 {
+            return x;
+        }
+        clamp01(x + value * 0.1 * weight / 255.0)
+    })
+}
+
+fn highlight_color_layer(channels: Channels, settings: &CurveSettings) -> Channels {
+    let highlight_bound = 0.9 - (10.0 - settings.highlight_range) * 0.0444;
+    let range_factor = 1.0 + (10.0 - settings.highlight_range) / 18.0;
+    let color = [
+        -settings.highlight_cyan,
+        -settings.highlight_tint,
+        -settings.highlight_temp,
+    ];
+
+    map_channels(&channels, |x, ch, _| {
+        let value = color[ch];
+        if value == 0.0 || x <= 1.0 - highlight_bound {
+            return x;
+        }
+        let steepness = 0.75 + value.abs() * range_factor * 0.125;
+        if value > 0.0 {
+            1.0 - tanh_interpolate(steepness, 0.75, 1.0 - x, highlight_bound)
+        } else {
+            1.0 - atanh_interpolate(steepness, 0.75, 1.0 - x, highlight_bound)
+        }
+    })
+}
+
+fn invert_curve(channels: &Channels) -> Channels {
+    let len = channels[0].len();
+    map_channels(channels, |_, ch, pt| channels[ch][len - 1 - pt])
+}
+
+fn soft_clip_layer(channels: Channels, white_clips: [f64; 3], black_clips: [f64; 3]) -> Channels {
+    map_channels(&channels, |x, ch, _| {
+        let wc = white_clips[ch];
+        let bc = black_clips[ch];
+        if wc == 0.0 && bc == 0.0 {
+            return x;
+        }
+        let white_scale = (255.0 - wc) / 255.0;
+        let black_scale = bc / 255.0;
+        x * (white_scale - black_scale) + black_scale
+    })
+}
+
+fn color_protected_clamp(mut channels: Channels) -> Channels {
+    for pt in 0..channels[0].len() {
+        let mut r = channels[0][pt];
+        let mut g = channels[1][pt];
+        let mut b = channels[2][pt];
+
+        let max_val = r.max(g).max(b);
+        let min_val = r.min(g).min(b);
+        if max_val <= 1.0 && min_val >= 0.0 {
+            continue;
+        }
+
+        let avg = (r + g + b) / 3.0;
+        if max_val > 1.0 && avg < max_val {
+            let ratio = (1.0 - avg) / (max_val - avg);
+            r = avg + (r - avg) * ratio;
+            g = avg + (g - avg) * ratio;
+            b = avg + (b - avg) * ratio;
+        }
+        if min_val < 0.0 && avg > min_val {
+            let new_min = r.min(g).min(b);
+            if new_min < 0.0 {
+                let ratio = avg / (avg - new_min);
+                r = avg + (r - avg) * ratio;
+                g = avg + (g - avg) * ratio;
+                b = avg + (b - avg) * ratio;
+            }
+        }
+
+        channels[0][pt] = clamp01(r);
+        channels[1][pt] = clamp01(g);
+        channels[2][pt] = clamp01(b);
+    }
+    channels
+}
+
+fn compute_clip_points(levels: &[ChannelLevels; 3], settings: &CurveSettings) -> ClipPoints {
+    let profile = presets::tone_profile(settings.tone_profile);
+    let soft_high = profile.default_soft_high;
+    let soft_low = profile.default_soft_low;
+
+    let mut whites = [0.0; 3];
+    let mut blacks = [0.0; 3];
+    let mut white_overflow = [0.0; 3];
+    let mut black_underflow = [0.0; 3];
+
+    for ch in 0..3 {
+        let mut wp = levels[ch].white_point_origin + soft_high;
+        let mut bp = levels[ch].black_point_origin - soft_low;
+        if wp < 0.0 {
+            white_overflow[ch] = -wp;
+            wp = 0.0;
+        }
+        if bp > PIXEL_MAX {
+            black_underflow[ch] = bp - PIXEL_MAX;
+            bp = PIXEL_MAX;
+        }
+        whites[ch] = wp;
+        blacks[ch] = bp;
+    }
+
+    ClipPoints {
+        whites,
+        widths: [0, 1, 2].map(|ch| blacks[ch] - whites[ch]),
+        white_overflow,
+        black_underflow,
+    }
+}
+
+fn build_raw_curve(clip: &ClipPoints, settings: &CurveSettings, grid: &Channels) -> Channels {
+    const SOFT_CLIP: f64 = 6.0 * 257.0;
+    let num_points = grid[0].len();
+    let mut raw: Channels = [0, 1, 2].map(|ch| {
+        grid[ch]
+            .iter()
+            .map(|&x| clip.whites[ch] + x * clip.widths[ch])
+            .collect()
+    });
+
+    let (use_soft_high, use_soft_low) = if settings.is_negative {
+        (settings.soft_highlights, settings.soft_shadows)
+    } else {
+        (settings.soft_shadows, settings.soft_highlights)
+    };
+
+    if use_soft_high && raw.iter().all(|channel| channel[0] > SOFT_CLIP) {
+        for channel in raw.iter_mut() {
+            channel[0] -= SOFT_CLIP;
+        }
+    }
+    let last = num_points - 1;
+    if use_soft_low
+        && raw
+            .iter()
+            .all(|channel| channel[last] < PIXEL_MAX - SOFT_CLIP)
+    {
+        for channel in raw.iter_mut() {
+            channel[last] += SOFT_CLIP;
+        }
+    }
+
+    raw
+}
+
+/// Interpolate sparse `(x, y)` points to a full 65536-entry LUT.
+fn interpolate_to_lut(mut points: Vec<(f64, f64)>) -> Lut {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let first = points[0];
+    let last = points[points.len() - 1];
+    let first_y = first.1.clamp(0.0, PIXEL_MAX) as u16;
+    let last_y = last.1.clamp(0.0, PIXEL_MAX) as u16;
+
+    let mut lut = vec![0u16; LUT_SIZE];
+    let mut lo = 0usize;
+    for (i, entry) in lut.iter_mut().enumerate() {
+        let x = i as f64;
+        if x <= first.0 {
+            *entry = first_y;
+        } else if x >= last.0 {
+            *entry = last_y;
+        } else {
+            while lo < points.len() - 2 && points[lo + 1].0 < x {
+                lo += 1;
+            }
+            let (x0, y0) = points[lo];
+            let (x1, y1) = points[lo + 1];
+            let t = if x1 == x0 { 0.0 } else { (x - x0) / (x1 - x0) };
+            let v = y0 + t * (y1 - y0);
+            *entry = (v + 0.5).clamp(0.0, PIXEL_MAX) as u16;
+        }
+    }
+    lut
+}
+
+pub fn generate_curves(levels: &[ChannelLevels; 3], settings: &CurveSettings) -> CurveLuts {
+    let prepared = prepare_settings(levels, settings);
+    let clip = compute_clip_points(levels, settings);
+    let min_width = clip.widths.iter().copied().fold(f64::INFINITY, f64::min);
+    let resolution = curve_resolution(min_width, &settings.curve_precision);
+
+    let mut curve = base_grid(resolution);
+    let raw = build_raw_curve(&clip, settings, &curve);
+    let color_mults = [prepared.cyan, prepared.tint, prepared.temp];
+    let has_wb_color =
+        settings.wb_tint != 0.0 || settings.wb_temp != 0.0 || settings.wb_cyan != 0.0;
+
+    if settings.layer_order == LayerOrder::ColorFirst {
+        if has_wb_color {
+            curve = color_linear_layer(curve, settings);
+        }
+        curve = color_gamma_layer(&curve, color_mults);
+    }
+
+    curve = exposure_layer(curve, prepared.exposure);
+    curve = gamma_layer(curve, prepared.brightness);
+    curve = contrast_layer(curve, prepared.contrast);
+    curve = highlights_layer(curve, prepared.highlights);
+    curve = shadows_layer(curve, prepared.shadows);
+    curve = blacks_layer(curve, &prepared);
+    curve = whites_layer(curve, settings, &prepared);
+    curve = soft_clip_layer(curve, clip.white_overflow, clip.black_underflow);
+
+    if settings.layer_order == LayerOrder::TonesFirst {
+        let reference = base_grid(resolution);
+        if has_wb_color {
+            let color_curve = color_linear_layer(reference.clone(), settings);
+            curve = apply_difference_layer(&curve, &color_curve, &reference);
+        }
+        let gamma_curve = color_gamma_layer(&reference, color_mults);
+        curve = apply_difference_layer(&curve, &gamma_curve, &reference);
+    }
+
+    if settings.highlight_cyan != 0.0
+        || settings.highlight_tint != 0.0
+        || settings.highlight_temp != 0.0
+    {
+        curve = highlight_color_layer(curve, settings);
+    }
+    if settings.shadow_cyan != 0.0 || settings.shadow_tint != 0.0 || settings.shadow_temp != 0.0 {
+        curve = shadow_color_layer(curve, settings);
+    }
+    if settings.mid_cyan != 0.0 || settings.mid_tint != 0.0 || settings.mid_temp != 0.0 {
+        curve = midtone_color_layer(curve, settings);
+    }
+
+    curve = color_protected_clamp(curve);
+    let final_curve = if settings.is_negative {
+        invert_curve(&curve)
+    } else {
+        curve
+    };
+
+    let [r, g, b] = [0, 1, 2].map(|ch| {
+        interpolate_to_lut(
+            (0..resolution)
+                .map(|pt| {
+                    (
+                        js_round(raw[ch][pt]),
+                        js_round(final_curve[ch][pt] * PIXEL_MAX),
+                    )
+                })
+                .collect(),
+        )
+    });
+    CurveLuts { r, g, b }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        generate_curves, interpolate_to_lut, CurveSettings, LayerOrder, WbMethod, WbTonality,
+    };
+    use crate::engine::analysis::ChannelLevels;
+
+    fn neutral_settings(is_negative: bool) -> CurveSettings {
+        CurveSettings {
+            tone_profile: "base",
+            is_negative,
+            brightness: 0.0,
+            exposure: 0.0,
+            contrast: 0.0,
+            highlights: 0.0,
+            shadows: 0.0,
+            whites: 0.0,
+            blacks: 0.0,
+            glow: 0.0,
+            fade: 0.0,
+            temp: 0.0,
+            tint: 0.0,
+            temperature: 0.0,
+            wb_cyan: 0.0,
+            wb_temp: 0.0,
+            wb_tint: 0.0,
+            wb_tonality: WbTonality::AddDensity,
+            wb_method: WbMethod::LinearFixed,
+            layer_order: LayerOrder::ColorFirst,
+            soft_highlights: false,
+            soft_shadows: false,
+            shadow_range: 5.0,
+            highlight_range: 5.0,
+            shadow_cyan: 0.0,
+            shadow_tint: 0.0,
+            shadow_temp: 0.0,
+            highlight_cyan: 0.0,
+            highlight_tint: 0.0,
+            highlight_temp: 0.0,
+            mid_cyan: 0.0,
+            mid_tint: 0.0,
+            mid_temp: 0.0,
+            curve_precision: "auto".to_string(),
+            auto_tone_level: 1.0,
+        }
+    }
+
+    fn levels(white: f64, black: f64) -> [ChannelLevels; 3] {
+        [ChannelLevels {
+            white_point_origin: white,
+            black_point_origin: black,
+            mean_point: 0.5,
+        }; 3]
+    }
+
+    #[test]
+    fn interpolate_to_lut_holds_ends_and_interpolates_between_points() {
+        let lut = interpolate_to_lut(vec![(100.0, 0.0), (200.0, 1000.0)]);
+        assert_eq!(lut.len(), 65536);
+        assert_eq!(lut[0], 0);
+        assert_eq!(lut[150], 500);
+        assert_eq!(lut[65535], 1000);
+    }
+
+    #[test]
+    fn negative_curves_are_monotonically_decreasing() {
+        let luts = generate_curves(&levels(4.0 * 257.0, 240.0 * 257.0), &neutral_settings(true));
+        for lut in [&luts.r, &luts.g, &luts.b] {
+            assert!(lut.windows(2).all(|pair| pair[0] >= pair[1]));
+            assert!(lut[0] > lut[65535]);
+        }
+    }
+
+    #[test]
+    fn positive_curves_keep_orientation() {
+        let luts = generate_curves(
+            &levels(4.0 * 257.0, 240.0 * 257.0),
+            &neutral_settings(false),
+        );
+        assert!(luts.g.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(luts.g[0] < luts.g[65535]);
+    }
+}
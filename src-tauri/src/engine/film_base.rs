@@ -0,0 +1,799 @@
+//! Automatic film base (orange mask) detection. Mirrors `autoDetectFilmBase`
+//! and `sampleFilmBase` in `app/filmBaseDetection.js`, but reads the
+//! full-resolution 16-bit source instead of the webview's preview, and keeps
+//! every candidate region so a detection can be explained.
+
+use super::adjust::{self, FilmBase, FilmBaseMethod};
+use super::Image16;
+use serde::Serialize;
+
+const MAX_8: f64 = 255.0;
+const MAX_16: f64 = 65535.0;
+/// 8-bit fallback when a region has no opaque pixels.
+const DEFAULT_FILM_BASE: [f64; 3] = [210.0, 140.0, 90.0];
+const REGION_MAX_SAMPLES: usize = 18000;
+const GRID_MAX_SAMPLES: usize = 9000;
+const REGION_TRIM: f64 = 0.1;
+const EDGE_FRACTIONS_WITH_BORDER: &[f64] = &[0.08, 0.2, 0.34, 0.5, 0.66, 0.8, 0.92];
+const EDGE_FRACTIONS: &[f64] = &[0.12, 0.28, 0.5, 0.72, 0.88];
+const GRID_FRACTIONS: &[f64] = &[0.18, 0.34, 0.5, 0.66, 0.82];
+
+/// The `makeFilmBaseResult` shape the webview stores in `state.filmBase`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilmBaseResult {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub r16: u16,
+    pub g16: u16,
+    pub b16: u16,
+    /// `manual`, `auto-edge`, `auto-grid` or `auto`; `settings` for a base
+    /// read from a settings snapshot and `roll` for a roll's consensus.
+    pub method: &'static str,
+    pub precision: u8,
+    pub samples: usize,
+    /// Spread of the region's luma between its 10th and 90th percentiles,
+    /// in 8-bit steps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spread: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orange_bias: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipped: Option<bool>,
+    pub confidence: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidates: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected: Option<usize>,
+}
+
+impl FilmBaseResult {
+    /// A `filmBase` from a settings snapshot, for frames that cannot be
+    /// sampled again.
+    pub fn from_film_base(base: &FilmBase, confidence: f64) -> Option<Self> {
+        let rgb = base.to_16bit()?;
+        Some(Self {
+            confidence: round_confidence(confidence),
+            ..make_result(rgb, MAX_16, "settings", 16)
+        })
+    }
+
+    pub fn to_film_base(&self) -> FilmBase {
+        FilmBase {
+            r: Some(self.r as f64),
+            g: Some(self.g as f64),
+            b: Some(self.b as f64),
+            r16: Some(self.r16 as f64),
+            g16: Some(self.g16 as f64),
+            b16: Some(self.b16 as f64),
+        }
+    }
+}
+
+/// Why a candidate region was not trusted as film base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RejectReason {
+    /// No opaque pixels to sample.
+    Empty,
+    /// Not orange enough to be unexposed color negative.
+    WeakMask,
+    /// Too much tonal variation, likely image content.
+    Uneven,
+    /// Channels at or near full scale.
+    Clipped,
+    /// Channels not ordered red ≥ green ≥ blue like a mask.
+    ChannelOrder,
+}
+
+/// One sampled region, in source pixels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub sample: FilmBaseResult,
+    pub score: f64,
+    /// Whether the region went into the detected color.
+    pub selected: bool,
+    /// Empty for eligible regions. When no region is eligible the best ones
+    /// are used anyway, so a region can be both selected and rejected.
+    pub rejected: Vec<RejectReason>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilmBaseDetection {
+    #[serde(flatten)]
+    pub result: FilmBaseResult,
+    /// Every candidate, best score first.
+    pub regions: Vec<CandidateRegion>,
+}
+
+/// Inclusive pixel bounds, as `makeRegionBounds` builds them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    start_x: u32,
+    end_x: u32,
+    start_y: u32,
+    end_y: u32,
+}
+
+fn to_8bit(value: f64, max: f64) -> u8 {
+    (value / max * MAX_8).round().clamp(1.0, MAX_8) as u8
+}
+
+fn to_16bit(value: f64, max: f64) -> u16 {
+    (value / max * MAX_16).round().clamp(1.0, MAX_16) as u16
+}
+
+fn round_confidence(value: f64) -> f64 {
+    (value.clamp(0.0, 1.0) * 1000.0).round() / 1000.0
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let pos = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+fn trimmed_mean(sorted: &[f64], trim: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let len = sorted.len();
+    let start = ((len as f64 * trim).floor() as usize).min(len - 1);
+    let end = ((len as f64 * (1.0 - trim)).ceil() as usize).max(start + 1);
+    sorted[start..end].iter().sum::<f64>() / (end - start) as f64
+}
+
+fn sorted(mut values: Vec<f64>) -> Vec<f64> {
+    values.sort_by(f64::total_cmp);
+    values
+}
+
+fn make_result(rgb: [f64; 3], max: f64, method: &'static str, precision: u8) -> FilmBaseResult {
+    FilmBaseResult {
+        r: to_8bit(rgb[0], max),
+        g: to_8bit(rgb[1], max),
+        b: to_8bit(rgb[2], max),
+        r16: to_16bit(rgb[0], max),
+        g16: to_16bit(rgb[1], max),
+        b16: to_16bit(rgb[2], max),
+        method,
+        precision,
+        samples: 0,
+        spread: None,
+        orange_bias: None,
+        clipped: None,
+        confidence: 0.0,
+        candidates: None,
+        selected: None,
+    }
+}
+
+fn region_bounds(image: &Image16, x: f64, y: f64, radius: f64) -> Bounds {
+    let radius = if radius.is_finite() { radius } else { 10.0 }
+        .round()
+        .max(1.0);
+    let max_x = (image.width - 1) as f64;
+    let max_y = (image.height - 1) as f64;
+    Bounds {
+        start_x: (x - radius).floor().clamp(0.0, max_x) as u32,
+        end_x: (x + radius).ceil().clamp(0.0, max_x) as u32,
+        start_y: (y - radius).floor().clamp(0.0, max_y) as u32,
+        end_y: (y + radius).ceil().clamp(0.0, max_y) as u32,
+    }
+}
+
+fn summarize_region(
+    image: &Image16,
+    bounds: Bounds,
+    max_samples: usize,
+    method: &'static str,
+) -> FilmBaseResult {
+    let region_w = (bounds.end_x - bounds.start_x + 1) as f64;
+    let region_h = (bounds.end_y - bounds.start_y + 1) as f64;
+    let step = ((region_w * region_h / max_samples.max(64) as f64)
+        .sqrt()
+        .ceil() as usize)
+        .max(1);
+
+    let mut channels = [Vec::new(), Vec::new(), Vec::new()];
+    let mut luma = Vec::new();
+    for y in (bounds.start_y..=bounds.end_y).step_by(step) {
+        for x in (bounds.start_x..=bounds.end_x).step_by(step) {
+            let idx = (y as usize * image.width as usize + x as usize) * 4;
+            let px = &image.data[idx..idx + 4];
+            if px[3] == 0 {
+                continue;
+            }
+            let [r, g, b] = [px[0] as f64, px[1] as f64, px[2] as f64];
+            channels[0].push(r);
+            channels[1].push(g);
+            channels[2].push(b);
+            luma.push(0.299 * r + 0.587 * g + 0.114 * b);
+        }
+    }
+
+    if luma.is_empty() {
+        return make_result(DEFAULT_FILM_BASE, MAX_8, method, 8);
+    }
+
+    let samples = luma.len();
+    let [r_vals, g_vals, b_vals] = channels.map(sorted);
+    let luma = sorted(luma);
+    let rgb = [
+        trimmed_mean(&r_vals, REGION_TRIM),
+        trimmed_mean(&g_vals, REGION_TRIM),
+        trimmed_mean(&b_vals, REGION_TRIM),
+    ];
+    let spread8 = (percentile(&luma, 0.9) - percentile(&luma, 0.1)) / MAX_16 * MAX_8;
+    let [r8, g8, b8] = rgb.map(|value| to_8bit(value, MAX_16) as f64);
+    let orange_bias = (r8 - b8) + (r8 - g8) * 0.5;
+    let clipped = [&r_vals, &g_vals, &b_vals]
+        .iter()
+        .any(|values| percentile(values, 0.98) >= MAX_16 * 0.995);
+
+    let confidence = (0.18
+        + ((orange_bias - 6.0) / 75.0).clamp(0.0, 1.0) * 0.42
+        + (1.0 - spread8 / 55.0).clamp(0.0, 1.0) * 0.28
+        + (samples as f64 / 800.0).clamp(0.0, 1.0) * 0.12
+        - if clipped { 0.22 } else { 0.0 })
+    .clamp(0.0, 0.98);
+
+    FilmBaseResult {
+        samples,
+        spread: Some(spread8.round().max(0.0) as i64),
+        orange_bias: Some(orange_bias.round() as i64),
+        clipped: Some(clipped),
+        confidence: round_confidence(confidence),
+        ..make_result(rgb, MAX_16, method, 16)
+    }
+}
+
+/// The film base around a picked point, like a click with the sampler.
+pub fn sample_film_base(image: &Image16, x: f64, y: f64, radius: f64) -> FilmBaseResult {
+    let bounds = region_bounds(image, x, y, radius);
+    summarize_region(image, bounds, REGION_MAX_SAMPLES, "manual")
+}
+
+fn orange_bias_of(sample: &FilmBaseResult) -> f64 {
+    let [r, g, b] = [sample.r, sample.g, sample.b].map(f64::from);
+    sample
+        .orange_bias
+        .map_or((r - b) + (r - g) * 0.5, |bias| bias as f64)
+}
+
+fn score(sample: &FilmBaseResult) -> f64 {
+    let [r, g, b] = [sample.r, sample.g, sample.b].map(f64::from);
+    let brightness = (r + g + b) / 3.0;
+    let spread = sample.spread.map_or(45.0, |spread| spread as f64);
+    let order_bonus = if r >= g && g >= b { 12.0 } else { -10.0 };
+    let clip_penalty = if sample.clipped == Some(true) || r > 252.0 || g > 252.0 || b > 252.0 {
+        45.0
+    } else {
+        0.0
+    };
+    brightness * 0.18 + orange_bias_of(sample) * 1.45 + order_bonus - spread * 1.15 - clip_penalty
+}
+
+fn reject_reasons(sample: &FilmBaseResult) -> Vec<RejectReason> {
+    let (Some(orange_bias), Some(spread)) = (sample.orange_bias, sample.spread) else {
+        return vec![RejectReason::Empty];
+    };
+    let [r, g, b] = [sample.r, sample.g, sample.b].map(i32::from);
+    let mut reasons = Vec::new();
+    if orange_bias < 7 {
+        reasons.push(RejectReason::WeakMask);
+    }
+    if spread > 48 {
+        reasons.push(RejectReason::Uneven);
+    }
+    if sample.clipped == Some(true) {
+        reasons.push(RejectReason::Clipped);
+    }
+    if r < g - 6 || g < b - 10 {
+        reasons.push(RejectReason::ChannelOrder);
+    }
+    reasons
+}
+
+fn combine(regions: &[CandidateRegion], had_eligible: bool) -> FilmBaseResult {
+    let selected = regions
+        .iter()
+        .filter(|region| region.selected)
+        .map(|region| &region.sample)
+        .collect::<Vec<_>>();
+    let count = selected.len().max(1) as f64;
+    let trim = if selected.len() >= 5 { 0.15 } else { 0.0 };
+    let channel = |pick: fn(&FilmBaseResult) -> u16| {
+        sorted(selected.iter().map(|sample| pick(sample) as f64).collect())
+    };
+    let channels = [
+        channel(|sample| sample.r16),
+        channel(|sample| sample.g16),
+        channel(|sample| sample.b16),
+    ];
+    let mean = |value: fn(&FilmBaseResult) -> f64| {
+        selected.iter().map(|sample| value(sample)).sum::<f64>() / count
+    };
+    let avg_confidence = mean(|sample| sample.confidence);
+    let avg_spread = mean(|sample| sample.spread.map_or(45.0, |spread| spread as f64));
+    let avg_orange = mean(|sample| sample.orange_bias.unwrap_or(0) as f64);
+    let consistency = channels
+        .iter()
+        .map(|values| percentile(values, 0.9) - percentile(values, 0.1))
+        .sum::<f64>()
+        / MAX_16
+        * MAX_8
+        / 3.0;
+
+    let confidence = (avg_confidence * 0.68
+        + (1.0 - consistency / 35.0).clamp(0.0, 1.0) * 0.18
+        + (count / (regions.len() as f64 * 0.25).max(6.0)).clamp(0.0, 1.0) * 0.14
+        - if had_eligible { 0.0 } else { 0.22 })
+    .clamp(0.02, 0.98);
+
+    FilmBaseResult {
+        samples: selected.iter().map(|sample| sample.samples).sum(),
+        spread: Some(avg_spread.round().max(0.0) as i64),
+        orange_bias: Some(avg_orange.round() as i64),
+        clipped: None,
+        confidence: round_confidence(confidence),
+        candidates: Some(regions.len()),
+        selected: Some(selected.len()),
+        ..make_result(
+            channels.map(|values| trimmed_mean(&values, trim)),
+            MAX_16,
+            "auto",
+            16,
+        )
+    }
+}
+
+/// Scans the frame edges (and, without a border hint, an interior grid) for
+/// unexposed film and combines the most mask-like regions.
+/// `border_buffer_pct` is the `coreBorderBuffer` setting.
+pub fn detect_film_base(image: &Image16, border_buffer_pct: f64) -> FilmBaseDetection {
+    let min_side = image.width.min(image.height).max(1) as f64;
+    let buffer_pct = if border_buffer_pct.is_finite() {
+        border_buffer_pct
+    } else {
+        10.0
+    }
+    .clamp(0.0, 30.0);
+    let has_border_hint = buffer_pct > 0.5;
+    let edge_pct = if has_border_hint { buffer_pct } else { 6.0 };
+    let edge_band = (min_side * edge_pct / 100.0).round().max(4.0);
+    let radius = (edge_band * 0.42).round().clamp(3.0, 72.0);
+    let edge_offset = (edge_band * 0.5)
+        .round()
+        .clamp(radius, radius.max((min_side / 2.0).floor()));
+    let max_x = (image.width - 1) as f64;
+    let max_y = (image.height - 1) as f64;
+
+    let mut points = Vec::new();
+    let fractions = if has_border_hint {
+        EDGE_FRACTIONS_WITH_BORDER
+    } else {
+        EDGE_FRACTIONS
+    };
+    for f in fractions {
+        let x = (max_x * f).round();
+        points.push((x, edge_offset, radius, "auto-edge"));
+        points.push((x, max_y - edge_offset, radius, "auto-edge"));
+    }
+    for f in fractions {
+        let y = (max_y * f).round();
+        points.push((edge_offset, y, radius, "auto-edge"));
+        points.push((max_x - edge_offset, y, radius, "auto-edge"));
+    }
+    if !has_border_hint {
+        let grid_radius = (radius * 0.8).round().max(3.0);
+        for fy in GRID_FRACTIONS {
+            for fx in GRID_FRACTIONS {
+                points.push((
+                    (max_x * fx).round(),
+                    (max_y * fy).round(),
+                    grid_radius,
+                    "auto-grid",
+                ));
+            }
+        }
+    }
+
+    let mut regions = points
+        .into_iter()
+        .map(|(x, y, radius, method)| {
+            let bounds = region_bounds(image, x, y, radius);
+            let max_samples = if method == "auto-grid" {
+                GRID_MAX_SAMPLES
+            } else {
+                REGION_MAX_SAMPLES
+            };
+            let sample = summarize_region(image, bounds, max_samples, method);
+            CandidateRegion {
+                x: bounds.start_x,
+                y: bounds.start_y,
+                width: bounds.end_x - bounds.start_x + 1,
+                height: bounds.end_y - bounds.start_y + 1,
+                score: score(&sample),
+                rejected: reject_reasons(&sample),
+                selected: false,
+                sample,
+            }
+        })
+        .collect::<Vec<_>>();
+    regions.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let eligible = regions
+        .iter()
+        .filter(|region| region.rejected.is_empty())
+        .count();
+    let had_eligible = eligible > 0;
+    let pool = if had_eligible {
+        eligible
+    } else {
+        regions.len()
+    };
+    let selected_count = ((pool as f64 * 0.25).ceil() as usize)
+        .max(3)
+        .min(pool.min(9))
+        .max(1);
+    regions
+        .iter_mut()
+        .filter(|region| !had_eligible || region.rejected.is_empty())
+        .take(selected_count)
+        .for_each(|region| region.selected = true);
+
+    FilmBaseDetection {
+        result: combine(&regions, had_eligible),
+        regions,
+    }
+}
+
+/// Frames whose own detection is less sure than this only join a roll
+/// consensus when no frame is surer.
+const MIN_ROLL_FRAME_CONFIDENCE: f64 = 0.35;
+/// Density differences below this are mask drift too small to matter, so
+/// they never make a frame an outlier however tight the rest of the roll is.
+const MIN_OUTLIER_DENSITY: f64 = 0.04;
+/// Outliers are this many robust standard deviations (MAD × 1.4826) away
+/// from the roll's median base.
+const OUTLIER_MADS: f64 = 3.0;
+
+/// Why a frame's base was left out of a roll consensus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RollRejectReason {
+    LowConfidence,
+    Outlier,
+}
+
+/// How far one frame's base is from the roll consensus.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameDeviation {
+    /// Per-channel optical density of the frame's base minus the consensus';
+    /// positive means a denser (darker) mask on that channel.
+    pub density_offset: [f64; 3],
+    /// The largest absolute channel offset.
+    pub deviation: f64,
+    /// The frame's own film base gains over the consensus gains, i.e. how
+    /// differently `computeFilmBaseGains` would have balanced it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gain_ratio: Option<[f64; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected: Option<RollRejectReason>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollConsensus {
+    /// `None` when no frame had a base.
+    pub consensus: Option<FilmBaseResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gains: Option<[f64; 3]>,
+    /// One entry per input frame; `None` for frames without a base.
+    pub frames: Vec<Option<FrameDeviation>>,
+}
+
+fn density(rgb16: [u16; 3]) -> [f64; 3] {
+    rgb16.map(|value| -(value.max(1) as f64 / MAX_16).log10())
+}
+
+fn median(values: &[f64]) -> f64 {
+    percentile(&sorted(values.to_vec()), 0.5)
+}
+
+/// Combines the film bases of a roll's frames into one. Frames are compared
+/// in optical density, where mask drift between frames is additive; frames
+/// far from the roll's median are left out, and the rest are averaged by
+/// their detection confidence.
+pub fn roll_consensus(frames: &[Option<FilmBaseResult>], method: FilmBaseMethod) -> RollConsensus {
+    let densities = frames
+        .iter()
+        .map(|frame| {
+            frame
+                .as_ref()
+                .map(|base| density([base.r16, base.g16, base.b16]))
+        })
+        .collect::<Vec<_>>();
+    let present = (0..frames.len())
+        .filter(|&i| frames[i].is_some())
+        .collect::<Vec<_>>();
+    let confident = present
+        .iter()
+        .copied()
+        .filter(|&i| frames[i].as_ref().unwrap().confidence >= MIN_ROLL_FRAME_CONFIDENCE)
+        .collect::<Vec<_>>();
+    let mut rejected = vec![None; frames.len()];
+    let candidates = if confident.is_empty() {
+        present.clone()
+    } else {
+        for &i in present.iter().filter(|i| !confident.contains(i)) {
+            rejected[i] = Some(RollRejectReason::LowConfidence);
+        }
+        confident
+    };
+    if candidates.is_empty() {
+        return RollConsensus {
+            consensus: None,
+            gains: None,
+            frames: vec![None; frames.len()],
+        };
+    }
+
+    let medians: [f64; 3] = std::array::from_fn(|ch| {
+        median(
+            &candidates
+                .iter()
+                .map(|&i| densities[i].unwrap()[ch])
+                .collect::<Vec<_>>(),
+        )
+    });
+    let distance = |d: [f64; 3]| {
+        (0..3)
+            .map(|ch| (d[ch] - medians[ch]).abs())
+            .fold(0.0, f64::max)
+    };
+    let distances = candidates
+        .iter()
+        .map(|&i| distance(densities[i].unwrap()))
+        .collect::<Vec<_>>();
+    let threshold = (median(&distances) * 1.4826 * OUTLIER_MADS).max(MIN_OUTLIER_DENSITY);
+    let inliers = candidates
+        .iter()
+        .zip(&distances)
+        .filter_map(|(&i, &dist)| {
+            if dist > threshold {
+                rejected[i] = Some(RollRejectReason::Outlier);
+                None
+            } else {
+                Some(i)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let inlier_bases = inliers
+        .iter()
+        .map(|&i| frames[i].as_ref().unwrap())
+        .collect::<Vec<_>>();
+    let weights = inlier_bases
+        .iter()
+        .map(|base| base.confidence.max(0.05))
+        .collect::<Vec<_>>();
+    let total_weight = weights.iter().sum::<f64>();
+    let consensus_density: [f64; 3] = std::array::from_fn(|ch| {
+        inliers
+            .iter()
+            .zip(&weights)
+            .map(|(&i, weight)| densities[i].unwrap()[ch] * weight)
+            .sum::<f64>()
+            / total_weight
+    });
+    let count = inliers.len() as f64;
+    let avg = |value: fn(&FilmBaseResult) -> Option<f64>| {
+        let values = inlier_bases
+            .iter()
+            .filter_map(|base| value(base))
+            .collect::<Vec<_>>();
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    };
+    let avg_confidence = inlier_bases.iter().map(|base| base.confidence).sum::<f64>() / count;
+    let agreement = count / present.len() as f64;
+    let consensus = FilmBaseResult {
+        samples: inlier_bases.iter().map(|base| base.samples).sum(),
+        spread: avg(|base| base.spread.map(|spread| spread as f64)).map(|v| v.round() as i64),
+        orange_bias: avg(|base| base.orange_bias.map(|bias| bias as f64)).map(|v| v.round() as i64),
+        confidence: round_confidence((avg_confidence * 0.75 + agreement * 0.25).clamp(0.02, 0.98)),
+        candidates: Some(present.len()),
+        selected: Some(inliers.len()),
+        ..make_result(
+            consensus_density.map(|d| 10f64.powf(-d) * MAX_16),
+            MAX_16,
+            "roll",
+            16,
+        )
+    };
+
+    let gains = adjust::compute_film_base_gains(&consensus.to_film_base(), method, 1.0);
+    let reports = frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let base = frame.as_ref()?;
+            let d = densities[i]?;
+            let density_offset: [f64; 3] = std::array::from_fn(|ch| d[ch] - consensus_density[ch]);
+            let frame_gains = adjust::compute_film_base_gains(&base.to_film_base(), method, 1.0);
+            Some(FrameDeviation {
+                deviation: density_offset.iter().fold(0.0, |max, d| d.abs().max(max)),
+                density_offset,
+                gain_ratio: frame_gains
+                    .zip(gains)
+                    .map(|(own, roll)| std::array::from_fn(|ch| own[ch] / roll[ch])),
+                rejected: rejected[i],
+            })
+        })
+        .collect();
+
+    RollConsensus {
+        consensus: Some(consensus),
+        gains,
+        frames: reports,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        detect_film_base, roll_consensus, sample_film_base, FilmBaseResult, RejectReason,
+        RollRejectReason,
+    };
+    use crate::engine::adjust::{FilmBase, FilmBaseMethod};
+    use crate::engine::Image16;
+
+    const MASK: [u16; 3] = [52000, 33000, 20000];
+
+    /// A 200x150 frame with an orange border, gray-blue image content and a
+    /// blown-out light leak on the top edge.
+    fn bordered_frame() -> Image16 {
+        let (width, height) = (200u32, 150u32);
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let border = x < 15 || x >= width - 15 || y < 15 || y >= height - 15;
+                let px = if border && (90..110).contains(&x) && y < 15 {
+                    [65535, 65535, 65535]
+                } else if border {
+                    let noise = ((x * 7 + y * 13) % 5) as u16 * 40;
+                    [MASK[0] + noise, MASK[1] + noise, MASK[2] + noise]
+                } else {
+                    let level = ((x + y) * 173 % 40000) as u16;
+                    [level, level + 3000, level + 9000]
+                };
+                data.extend_from_slice(&[px[0], px[1], px[2], 65535]);
+            }
+        }
+        Image16::new(width, height, data).unwrap()
+    }
+
+    #[test]
+    fn detection_finds_the_mask_and_explains_rejected_regions() {
+        let detection = detect_film_base(&bordered_frame(), 10.0);
+        let result = &detection.result;
+        assert_eq!(result.method, "auto");
+        assert_eq!(result.precision, 16);
+        for (found, expected) in [result.r16, result.g16, result.b16].iter().zip(MASK) {
+            assert!(found.abs_diff(expected) < 300, "{found} vs {expected}");
+        }
+        assert!(result.confidence > 0.6, "{}", result.confidence);
+        assert_eq!(result.candidates, Some(detection.regions.len()));
+        assert_eq!(
+            result.selected,
+            Some(detection.regions.iter().filter(|r| r.selected).count())
+        );
+
+        let leak = detection
+            .regions
+            .iter()
+            .find(|region| region.y < 15 && region.x <= 100 && region.x + region.width > 100)
+            .unwrap();
+        assert!(!leak.selected);
+        assert!(leak.rejected.contains(&RejectReason::Clipped));
+        assert!(detection
+            .regions
+            .windows(2)
+            .all(|w| w[0].score >= w[1].score));
+
+        let json = serde_json::to_value(&detection).unwrap();
+        assert!(json["orangeBias"].as_i64().unwrap() > 7);
+        assert_eq!(json["regions"][0]["sample"]["method"], "auto-edge");
+    }
+
+    #[test]
+    fn sampling_reads_the_picked_region_and_interior_grid_is_rejected() {
+        let image = bordered_frame();
+        let sample = sample_film_base(&image, 5.0, 75.0, 4.0);
+        assert_eq!(sample.method, "manual");
+        assert_eq!(sample.samples, 81);
+        assert!(sample.r16.abs_diff(MASK[0]) < 200);
+
+        let detection = detect_film_base(&image, 0.0);
+        let grid = detection
+            .regions
+            .iter()
+            .filter(|region| region.sample.method == "auto-grid")
+            .collect::<Vec<_>>();
+        assert_eq!(grid.len(), 25);
+        assert!(grid.iter().all(|region| !region.selected));
+        assert!(grid
+            .iter()
+            .all(|region| region.rejected.contains(&RejectReason::WeakMask)));
+    }
+
+    fn frame_base(rgb16: [f64; 3], confidence: f64) -> Option<FilmBaseResult> {
+        let base = FilmBase {
+            r16: Some(rgb16[0]),
+            g16: Some(rgb16[1]),
+            b16: Some(rgb16[2]),
+            ..FilmBase::default()
+        };
+        FilmBaseResult::from_film_base(&base, confidence)
+    }
+
+    #[test]
+    fn roll_consensus_rejects_drifted_and_unsure_frames() {
+        let frames = vec![
+            frame_base([52000.0, 33000.0, 20000.0], 0.9),
+            frame_base([52300.0, 33100.0, 20100.0], 0.8),
+            frame_base([51800.0, 32900.0, 19900.0], 0.85),
+            None,
+            frame_base([40000.0, 33000.0, 26000.0], 0.9),
+            frame_base([30000.0, 20000.0, 10000.0], 0.2),
+        ];
+        let roll = roll_consensus(&frames, FilmBaseMethod::Density);
+        let consensus = roll.consensus.unwrap();
+        assert_eq!(consensus.method, "roll");
+        assert_eq!(consensus.candidates, Some(5));
+        assert_eq!(consensus.selected, Some(3));
+        assert!(consensus.r16.abs_diff(52030) < 100, "{}", consensus.r16);
+        assert!(consensus.b16.abs_diff(20000) < 100, "{}", consensus.b16);
+
+        let rejected = roll
+            .frames
+            .iter()
+            .map(|frame| frame.as_ref().and_then(|frame| frame.rejected))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rejected,
+            vec![
+                None,
+                None,
+                None,
+                None,
+                Some(RollRejectReason::Outlier),
+                Some(RollRejectReason::LowConfidence),
+            ]
+        );
+        assert!(roll.frames[3].is_none());
+        let drifted = roll.frames[4].as_ref().unwrap();
+        assert!(drifted.density_offset[0] > 0.05 && drifted.density_offset[2] < -0.05);
+        assert!(drifted.gain_ratio.unwrap()[0] > 1.0);
+        assert!(roll.frames[0].as_ref().unwrap().deviation < 0.01);
+        assert!(roll.gains.is_some());
+
+        assert_eq!(
+            roll_consensus(&[None], FilmBaseMethod::Density).consensus,
+            None
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Rotation and crop of a full-resolution frame, applied before conversion
+//! the way `processFileForExport` does: `rotationAngle` first, then
+//! `cropRegion`, whose coordinates are in the rotated frame.
+
+use super::auto_frame::{
+    self, normalize_angle, rotated_size, AutoFrameSettings, CropRegion, FrameDetection,
+};
+use super::{FrameSettings, Image16};
+use serde::{Deserialize, Deserializer};
+
+/// `cropRegion` as a settings snapshot stores it. Sides may be fractional or
+/// out of bounds; [`sanitize_crop`] settles them against the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct FrameCrop {
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl From<CropRegion> for FrameCrop {
+    fn from(region: CropRegion) -> Self {
+        Self {
+            left: region.left as f64,
+            top: region.top as f64,
+            width: region.width as f64,
+            height: region.height as f64,
+        }
+    }
+}
+
+/// Reads `cropRegion` as `None` when it is missing sides or not an object,
+/// which the webview treats as no crop, instead of failing the snapshot.
+pub fn deserialize_crop<'de, D>(deserializer: D) -> Result<Option<FrameCrop>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+/// `sanitizeCropRegionForImage`: floors the sides and clamps them to a
+/// `width` by `height` frame. `None` when a side is not finite.
+pub fn sanitize_crop(crop: &FrameCrop, width: u32, height: u32) -> Option<CropRegion> {
+    let sides = [crop.left, crop.top, crop.width, crop.height];
+    if width == 0 || height == 0 || !sides.iter().all(|side| side.is_finite()) {
+        return None;
+    }
+    let (width, height) = (width as f64, height as f64);
+    let left = crop.left.floor().clamp(0.0, width - 1.0);
+    let top = crop.top.floor().clamp(0.0, height - 1.0);
+    Some(CropRegion {
+        left: left as usize,
+        top: top as usize,
+        width: crop.width.floor().clamp(1.0, width - left) as usize,
+        height: crop.height.floor().clamp(1.0, height - top) as usize,
+    })
+}
+
+pub fn crop(image: &Image16, region: CropRegion) -> Image16 {
+    let stride = image.width as usize * 4;
+    let mut data = Vec::with_capacity(region.width * region.height * 4);
+    for row in image
+        .data
+        .chunks_exact(stride)
+        .skip(region.top)
+        .take(region.height)
+    {
+        data.extend_from_slice(&row[region.left * 4..(region.left + region.width) * 4]);
+    }
+    Image16 {
+        width: region.width as u32,
+        height: region.height as u32,
+        data,
+    }
+}
+
+/// `applyRotationToImageData` on 16-bit pixels: clockwise for positive
+/// angles, right angles exact, other angles bilinear on an expanded canvas
+/// whose uncovered corners stay transparent black.
+pub fn rotate(image: &Image16, angle: f64) -> Image16 {
+    let normalized = normalize_angle(angle);
+    let (width, height) = (image.width as usize, image.height as usize);
+    let (out_width, out_height) = rotated_size(width, height, normalized);
+    let right_angle = (normalized / 90.0).round() * 90.0;
+    let mut data = Vec::with_capacity(out_width * out_height * 4);
+    if (normalized - right_angle).abs() < 0.001 {
+        for y in 0..out_height {
+            for x in 0..out_width {
+                let (sx, sy) = match right_angle as i32 {
+                    90 => (y, height - 1 - x),
+                    -90 => (width - 1 - y, x),
+                    180 => (width - 1 - x, height - 1 - y),
+                    _ => (x, y),
+                };
+                let offset = (sy * width + sx) * 4;
+                data.extend_from_slice(&image.data[offset..offset + 4]);
+            }
+        }
+    } else {
+        let rad = normalized.to_radians();
+        let (sin, cos) = (rad.sin(), rad.cos());
+        let sample = |x: isize, y: isize| -> [f64; 4] {
+            if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+                return [0.0; 4];
+            }
+            let offset = (y as usize * width + x as usize) * 4;
+            let pixel = &image.data[offset..offset + 4];
+            [pixel[0], pixel[1], pixel[2], pixel[3]].map(f64::from)
+        };
+        for y in 0..out_height {
+            let v = y as f64 + 0.5 - out_height as f64 / 2.0;
+            for x in 0..out_width {
+                let u = x as f64 + 0.5 - out_width as f64 / 2.0;
+                let sx = u * cos + v * sin + width as f64 / 2.0 - 0.5;
+                let sy = -u * sin + v * cos + height as f64 / 2.0 - 0.5;
+                let (x0, y0) = (sx.floor(), sy.floor());
+                let (fx, fy) = (sx - x0, sy - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                let corners = [
+                    (sample(x0, y0), (1.0 - fx) * (1.0 - fy)),
+                    (sample(x0 + 1, y0), fx * (1.0 - fy)),
+                    (sample(x0, y0 + 1), (1.0 - fx) * fy),
+                    (sample(x0 + 1, y0 + 1), fx * fy),
+                ];
+                let mut pixel = [0.0; 4];
+                for (value, weight) in corners {
+                    for (out, channel) in pixel.iter_mut().zip(value) {
+                        *out += channel * weight;
+                    }
+                }
+                data.extend(pixel.map(|v| v.round().clamp(0.0, 65535.0) as u16));
+            }
+        }
+    }
+    Image16 {
+        width: out_width as u32,
+        height: out_height as u32,
+        data,
+    }
+}
+
+/// Auto frame for unattended conversions: detects the frame on the unrotated
+/// `image` and replaces the angle and crop in `settings` with it. A
+/// low-confidence detection, which the webview only suggests, leaves them
+/// untouched.
+pub fn auto_frame(
+    image: &Image16,
+    settings: &mut FrameSettings,
+    auto: &AutoFrameSettings,
+) -> Option<FrameDetection> {
+    let detection = auto_frame::detect_frame(image, auto)?;
+    if detection.confidence_level != "low" {
+        settings.rotation_angle = detection.angle;
+        settings.crop_region = Some(detection.crop_region.into());
+    }
+    Some(detection)
+}
+
+/// Rotates and crops `image` as `settings` ask; returns it unchanged when
+/// they ask for neither.
+pub fn apply_frame_geometry(image: Image16, settings: &FrameSettings) -> Image16 {
+    let mut image = image;
+    if normalize_angle(settings.rotation_angle).abs() > 0.001 {
+        image = rotate(&image, settings.rotation_angle);
+    }
+    if let Some(region) = settings
+        .crop_region
+        .as_ref()
+        .and_then(|crop| sanitize_crop(crop, image.width, image.height))
+    {
+        image = crop(&image, region);
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_frame_geometry, auto_frame, rotate, sanitize_crop, FrameCrop};
+    use crate::engine::auto_frame::{tests::scan, AutoFrameSettings, CropRegion};
+    use crate::engine::{FrameSettings, Image16};
+
+    fn numbered(width: u32, height: u32) -> Image16 {
+        let data = (0..width * height)
+            .flat_map(|index| {
+                let value = index as u16;
+                [value, value, value, 65535]
+            })
+            .collect();
+        Image16::new(width, height, data).unwrap()
+    }
+
+    #[test]
+    fn right_angles_move_pixels_exactly() {
+        let image = numbered(3, 2);
+        let rotated = rotate(&image, 90.0);
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        let firsts: Vec<u16> = rotated.data.chunks(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(firsts, [3, 0, 4, 1, 5, 2]);
+        assert_eq!(rotate(&rotate(&image, 180.0), -180.0), image);
+    }
+
+    #[test]
+    fn crops_are_clamped_to_the_frame() {
+        let crop = FrameCrop {
+            left: 1.6,
+            top: -4.0,
+            width: 50.0,
+            height: 1.2,
+        };
+        assert_eq!(
+            sanitize_crop(&crop, 4, 3),
+            Some(CropRegion {
+                left: 1,
+                top: 0,
+                width: 3,
+                height: 1,
+            })
+        );
+        let crop = FrameCrop {
+            left: f64::NAN,
+            ..crop
+        };
+        assert_eq!(sanitize_crop(&crop, 4, 3), None);
+    }
+
+    #[test]
+    fn crop_applies_after_rotation() {
+        let settings = FrameSettings {
+            rotation_angle: 2.0,
+            crop_region: Some(FrameCrop {
+                left: 10.0,
+                top: 5.0,
+                width: 60.0,
+                height: 40.0,
+            }),
+            ..FrameSettings::default()
+        };
+        let image = apply_frame_geometry(numbered(80, 50), &settings);
+        assert_eq!((image.width, image.height), (60, 40));
+        assert_eq!(image.data.len(), 60 * 40 * 4);
+
+        let untouched = apply_frame_geometry(numbered(8, 5), &FrameSettings::default());
+        assert_eq!(untouched, numbered(8, 5));
+    }
+
+    #[test]
+    fn auto_frame_replaces_the_snapshot_geometry() {
+        let mut settings = FrameSettings {
+            rotation_angle: 12.0,
+            ..FrameSettings::default()
+        };
+        let image = scan(1200, 800, 900.0, 600.0, 0.0);
+        let detection = auto_frame(&image, &mut settings, &AutoFrameSettings::default()).unwrap();
+        assert_eq!(settings.rotation_angle, detection.angle);
+        assert_eq!(
+            settings.crop_region,
+            Some(FrameCrop::from(detection.crop_region))
+        );
+        let framed = apply_frame_geometry(image, &settings);
+        assert!(framed.width.abs_diff(900) <= 24, "{}", framed.width);
+        assert!(framed.height.abs_diff(600) <= 24, "{}", framed.height);
+    }
+}
@@ -0,0 +1,650 @@
+//! 3D LUTs for enhanced scanner profiles. Mirrors
+//! `silvercore/engine/EnhancedProfiles.js` for the built-in `.bin` profiles
+//! and also reads `.cube` and `.3dl` LUTs of any size.
+//!
+//! LUTs are applied to sRGB-encoded pixels. The built-in profiles were
+//! sampled in ProPhoto RGB (gamma 1.8), so they are baked into an
+//! sRGB-to-sRGB table on load, as `bakeLutToSRGB` does; `.cube` and `.3dl`
+//! files are taken to be sRGB already.
+
+use super::adjust::for_each_pixel;
+use super::Image16;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, OnceLock};
+
+const MAX_16: f64 = 65535.0;
+/// Grid points per axis of the built-in profiles.
+pub const BUILTIN_SIZE: usize = 32;
+/// Beyond this a LUT is more likely a malformed file than a real profile
+/// (129³ is already 25 MB of samples).
+pub const MAX_SIZE: usize = 129;
+
+pub const BUILTIN_PROFILES: &[(&str, &[u8])] = &[
+    (
+        "frontier",
+        include_bytes!("../../../negative2positive/src/silvercore/resources/profiles/frontier.bin"),
+    ),
+    (
+        "crystal",
+        include_bytes!("../../../negative2positive/src/silvercore/resources/profiles/crystal.bin"),
+    ),
+    (
+        "natural",
+        include_bytes!("../../../negative2positive/src/silvercore/resources/profiles/natural.bin"),
+    ),
+    (
+        "pakon",
+        include_bytes!("../../../negative2positive/src/silvercore/resources/profiles/pakon.bin"),
+    ),
+    (
+        "noritsu",
+        include_bytes!("../../../negative2positive/src/silvercore/resources/profiles/noritsu.bin"),
+    ),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpolation {
+    /// What the webview pipeline uses.
+    #[default]
+    Trilinear,
+    /// Splits each cell into six tetrahedra: fewer samples and no hue
+    /// shifts along the neutral axis.
+    Tetrahedral,
+}
+
+impl Interpolation {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "tetrahedral" => Self::Tetrahedral,
+            _ => Self::Trilinear,
+        }
+    }
+}
+
+/// A cube of RGB output samples in [0, 1], blue varying fastest like the
+/// `.bin` files: sample `(r, g, b)` starts at `((r * size + g) * size + b) * 3`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3D {
+    size: usize,
+    data: Vec<f32>,
+    /// Input range mapped onto the grid, from `.cube` `DOMAIN_MIN`/`MAX`.
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+}
+
+fn check_size(size: usize) -> Result<usize, String> {
+    if (2..=MAX_SIZE).contains(&size) {
+        Ok(size)
+    } else {
+        Err(format!("LUT size {size} is outside 2..={MAX_SIZE}"))
+    }
+}
+
+impl Lut3D {
+    pub fn new(size: usize, data: Vec<f32>) -> Result<Self, String> {
+        check_size(size)?;
+        if data.len() != size * size * size * 3 {
+            return Err(format!(
+                "LUT has {} samples, a {size}³ LUT needs {}",
+                data.len() / 3,
+                size * size * size
+            ));
+        }
+        if data.iter().any(|value| !value.is_finite()) {
+            return Err("LUT has non-numeric samples".to_string());
+        }
+        Ok(Self {
+            size,
+            data,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+        })
+    }
+
+    pub fn identity(size: usize) -> Result<Self, String> {
+        let max = (size.max(2) - 1) as f32;
+        let mut data = Vec::with_capacity(size * size * size * 3);
+        for r in 0..size {
+            for g in 0..size {
+                for b in 0..size {
+                    data.extend_from_slice(&[r as f32 / max, g as f32 / max, b as f32 / max]);
+                }
+            }
+        }
+        Self::new(size, data)
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// A built-in profile file: little-endian `u16` samples of a cube whose
+    /// size follows from the length.
+    pub fn from_bin(bytes: &[u8]) -> Result<Self, String> {
+        if !bytes.len().is_multiple_of(6) {
+            return Err("LUT .bin length is not a whole number of RGB samples".to_string());
+        }
+        let samples = bytes.len() / 6;
+        let size = (samples as f64).cbrt().round() as usize;
+        if size * size * size != samples {
+            return Err(format!("LUT .bin holds {samples} samples, not a cube"));
+        }
+        let data = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]) as f32 / MAX_16 as f32)
+            .collect();
+        Self::new(size, data)
+    }
+
+    /// An Adobe/Resolve `.cube` file. Its samples list red fastest.
+    pub fn parse_cube(text: &str) -> Result<Self, String> {
+        let mut size = None;
+        let mut domain_min = [0.0f32; 3];
+        let mut domain_max = [1.0f32; 3];
+        let mut samples: Vec<[f32; 3]> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut tokens = line.split_whitespace();
+            let first = tokens.next().unwrap_or("");
+            let rest = tokens.collect::<Vec<_>>();
+            let floats = |values: &[&str]| -> Result<[f32; 3], String> {
+                let parsed = values
+                    .iter()
+                    .map(|value| value.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("line {}: expected numbers", number + 1))?;
+                <[f32; 3]>::try_from(parsed)
+                    .map_err(|_| format!("line {}: expected three values", number + 1))
+            };
+            match first {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    let value = rest
+                        .first()
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .ok_or_else(|| format!("line {}: bad LUT_3D_SIZE", number + 1))?;
+                    size = Some(check_size(value)?);
+                }
+                "LUT_1D_SIZE" => return Err("1D .cube LUTs are not supported".to_string()),
+                "DOMAIN_MIN" => domain_min = floats(&rest)?,
+                "DOMAIN_MAX" => domain_max = floats(&rest)?,
+                "LUT_3D_INPUT_RANGE" => {
+                    let [min, max] = <[&str; 2]>::try_from(rest.as_slice())
+                        .map_err(|_| format!("line {}: bad LUT_3D_INPUT_RANGE", number + 1))?;
+                    let parse = |value: &str| {
+                        value
+                            .parse::<f32>()
+                            .map_err(|_| format!("line {}: bad LUT_3D_INPUT_RANGE", number + 1))
+                    };
+                    domain_min = [parse(min)?; 3];
+                    domain_max = [parse(max)?; 3];
+                }
+                _ if first.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+                _ => {
+                    let mut values = vec![first];
+                    values.extend(rest);
+                    samples.push(floats(&values)?);
+                }
+            }
+        }
+        let size = size.ok_or("not a 3D .cube file: LUT_3D_SIZE is missing")?;
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err("DOMAIN_MAX must be above DOMAIN_MIN".to_string());
+        }
+        let mut lut = Self::from_red_fastest(size, &samples)?;
+        lut.domain_min = domain_min;
+        lut.domain_max = domain_max;
+        Ok(lut)
+    }
+
+    /// A Lustre/Nuke `.3dl` file: a line with the input mesh (whose length
+    /// is the size), then integer samples with blue fastest. Output values
+    /// are scaled by the `Mesh` line's bit depth, or by the smallest usual
+    /// depth that holds them.
+    pub fn parse_3dl(text: &str) -> Result<Self, String> {
+        let mut size = None;
+        let mut output_bits = None;
+        let mut samples: Vec<[f64; 3]> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            let Some(first) = tokens.first() else {
+                continue;
+            };
+            // Keywords such as `3DMESH` and `Mesh`.
+            if first.parse::<f64>().is_err() {
+                if first.eq_ignore_ascii_case("mesh") {
+                    output_bits = tokens.get(2).and_then(|bits| bits.parse::<u32>().ok());
+                }
+                continue;
+            }
+            let values = tokens
+                .iter()
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("line {}: expected numbers", number + 1))?;
+            match (size, values.len()) {
+                (None, count) if count >= 2 => size = Some(count),
+                (Some(_), 3) => samples.push([values[0], values[1], values[2]]),
+                _ => return Err(format!("line {}: expected three values", number + 1)),
+            }
+        }
+        let size = size.ok_or("not a .3dl file: the input mesh line is missing")?;
+        let largest = samples
+            .iter()
+            .flatten()
+            .fold(0.0f64, |largest, value| largest.max(*value));
+        let scale = match output_bits {
+            Some(bits @ 1..=16) => ((1u32 << bits) - 1) as f64,
+            _ => [1023.0, 4095.0, 16383.0, 65535.0]
+                .into_iter()
+                .find(|scale| largest <= *scale)
+                .ok_or("3dl samples exceed 16 bits")?,
+        };
+        let data = samples
+            .iter()
+            .flatten()
+            .map(|value| (value / scale) as f32)
+            .collect();
+        Self::new(size, data)
+    }
+
+    fn from_red_fastest(size: usize, samples: &[[f32; 3]]) -> Result<Self, String> {
+        let needed = size
+            .checked_mul(size)
+            .and_then(|square| square.checked_mul(size))
+            .ok_or_else(|| format!("LUT size {size} is outside 2..={MAX_SIZE}"))?;
+        if samples.len() != needed {
+            return Err(format!(
+                "LUT has {} samples, a {size}³ LUT needs {needed}",
+                samples.len()
+            ));
+        }
+        let mut data = vec![0.0f32; samples.len() * 3];
+        for (index, sample) in samples.iter().enumerate() {
+            let r = index % size;
+            let g = (index / size) % size;
+            let b = index / (size * size);
+            let at = ((r * size + g) * size + b) * 3;
+            data[at..at + 3].copy_from_slice(sample);
+        }
+        Self::new(size, data)
+    }
+
+    /// Writes the LUT as a `.cube` file, red fastest.
+    pub fn to_cube(&self, title: &str) -> String {
+        let size = self.size;
+        let mut out = String::with_capacity(size * size * size * 27 + 128);
+        let title = title.replace(['"', '\n', '\r'], "");
+        let _ = writeln!(out, "TITLE \"{title}\"");
+        let _ = writeln!(out, "LUT_3D_SIZE {size}");
+        if self.domain_min != [0.0; 3] || self.domain_max != [1.0; 3] {
+            let [r, g, b] = self.domain_min;
+            let _ = writeln!(out, "DOMAIN_MIN {r} {g} {b}");
+            let [r, g, b] = self.domain_max;
+            let _ = writeln!(out, "DOMAIN_MAX {r} {g} {b}");
+        }
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let [red, green, blue] = self.at(r, g, b);
+                    let _ = writeln!(out, "{red:.6} {green:.6} {blue:.6}");
+                }
+            }
+        }
+        out
+    }
+
+    /// The LUT resampled over [0, 1] as little-endian `u16` samples, blue
+    /// fastest: the baked table `EnhancedProfiles.js` applies.
+    pub fn to_le_u16_bytes(&self) -> Vec<u8> {
+        let size = self.size;
+        let max = (size - 1) as f64;
+        let mut bytes = Vec::with_capacity(self.data.len() * 2);
+        for r in 0..size {
+            for g in 0..size {
+                for b in 0..size {
+                    let rgb = [r as f64 / max, g as f64 / max, b as f64 / max];
+                    for value in self.sample(rgb, Interpolation::Trilinear) {
+                        let sample = (value * MAX_16).round().clamp(0.0, MAX_16) as u16;
+                        bytes.extend_from_slice(&sample.to_le_bytes());
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    fn at(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        let index = ((r * self.size + g) * self.size + b) * 3;
+        [self.data[index], self.data[index + 1], self.data[index + 2]]
+    }
+
+    /// Looks up an RGB triple in the LUT's domain (normally [0, 1]).
+    pub fn sample(&self, rgb: [f64; 3], interpolation: Interpolation) -> [f64; 3] {
+        let max = (self.size - 1) as f64;
+        let mut cell = [0usize; 3];
+        let mut frac = [0.0f64; 3];
+        for c in 0..3 {
+            let min = self.domain_min[c] as f64;
+            let span = self.domain_max[c] as f64 - min;
+            let scaled = (((rgb[c] - min) / span) * max).clamp(0.0, max);
+            // The last cell also covers the top edge, as in `sampleLut3D`.
+            let index = (scaled as usize).min(self.size - 2);
+            cell[c] = index;
+            frac[c] = scaled - index as f64;
+        }
+        let [r, g, b] = cell;
+        let corner = |dr: usize, dg: usize, db: usize| {
+            let [x, y, z] = self.at(r + dr, g + dg, b + db);
+            [x as f64, y as f64, z as f64]
+        };
+        let [fr, fg, fb] = frac;
+        match interpolation {
+            Interpolation::Trilinear => {
+                let lerp = |a: [f64; 3], b: [f64; 3], t: f64| {
+                    [
+                        a[0] + (b[0] - a[0]) * t,
+                        a[1] + (b[1] - a[1]) * t,
+                        a[2] + (b[2] - a[2]) * t,
+                    ]
+                };
+                let c00 = lerp(corner(0, 0, 0), corner(0, 0, 1), fb);
+                let c01 = lerp(corner(0, 1, 0), corner(0, 1, 1), fb);
+                let c10 = lerp(corner(1, 0, 0), corner(1, 0, 1), fb);
+                let c11 = lerp(corner(1, 1, 0), corner(1, 1, 1), fb);
+                lerp(lerp(c00, c01, fg), lerp(c10, c11, fg), fr)
+            }
+            Interpolation::Tetrahedral => {
+                // Walks from the cell's origin to its far corner along the
+                // axes in order of their fractions.
+                let (first, second, third, weights) = if fr > fg {
+                    if fg > fb {
+                        ((1, 0, 0), (1, 1, 0), (1, 1, 1), [fr, fg, fb])
+                    } else if fr > fb {
+                        ((1, 0, 0), (1, 0, 1), (1, 1, 1), [fr, fb, fg])
+                    } else {
+                        ((0, 0, 1), (1, 0, 1), (1, 1, 1), [fb, fr, fg])
+                    }
+                } else if fb > fg {
+                    ((0, 0, 1), (0, 1, 1), (1, 1, 1), [fb, fg, fr])
+                } else if fb > fr {
+                    ((0, 1, 0), (0, 1, 1), (1, 1, 1), [fg, fb, fr])
+                } else {
+                    ((0, 1, 0), (1, 1, 0), (1, 1, 1), [fg, fr, fb])
+                };
+                let origin = corner(0, 0, 0);
+                let a = corner(first.0, first.1, first.2);
+                let b = corner(second.0, second.1, second.2);
+                let c = corner(third.0, third.1, third.2);
+                let [w1, w2, w3] = weights;
+                std::array::from_fn(|i| {
+                    origin[i] * (1.0 - w1) + a[i] * (w1 - w2) + b[i] * (w2 - w3) + c[i] * w3
+                })
+            }
+        }
+    }
+
+    /// Turns a ProPhoto (gamma 1.8) LUT into an sRGB one, rounding each
+    /// sample to 16 bits like `bakeLutToSRGB`.
+    pub fn baked_from_prophoto(&self) -> Self {
+        let size = self.size;
+        let max = (size - 1) as f64;
+        let mut data = Vec::with_capacity(self.data.len());
+        for r in 0..size {
+            for g in 0..size {
+                for b in 0..size {
+                    let srgb = [r as f64 / max, g as f64 / max, b as f64 / max];
+                    let prophoto = srgb_to_prophoto(srgb).map(|value| value.clamp(0.0, 1.0));
+                    let mapped = prophoto_to_srgb(self.sample(prophoto, Interpolation::Trilinear));
+                    data.extend(mapped.map(|value| {
+                        ((value * MAX_16).round().clamp(0.0, MAX_16) / MAX_16) as f32
+                    }));
+                }
+            }
+        }
+        Self {
+            size,
+            data,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+        }
+    }
+}
+
+// D50 matrices from `silvercore/engine/ColorSpace.js`.
+const SRGB_TO_XYZ: [f64; 9] = [
+    0.4360747, 0.3850649, 0.1430804, 0.2225045, 0.7168786, 0.0606169, 0.0139322, 0.0971045,
+    0.7141733,
+];
+const XYZ_TO_SRGB: [f64; 9] = [
+    3.1338561, -1.6168667, -0.4906146, -0.9787684, 1.9161415, 0.033454, 0.0719453, -0.2289914,
+    1.4052427,
+];
+const PROPHOTO_TO_XYZ: [f64; 9] = [
+    0.7976749, 0.1351917, 0.0313534, 0.2880402, 0.7118741, 0.0000857, 0.0, 0.0, 0.82521,
+];
+const XYZ_TO_PROPHOTO: [f64; 9] = [
+    1.3459433, -0.2556075, -0.0511118, -0.5445989, 1.5081673, 0.0205351, 0.0, 0.0, 1.2118128,
+];
+
+fn mul(m: &[f64; 9], v: [f64; 3]) -> [f64; 3] {
+    [
+        m[0] * v[0] + m[1] * v[1] + m[2] * v[2],
+        m[3] * v[0] + m[4] * v[1] + m[5] * v[2],
+        m[6] * v[0] + m[7] * v[1] + m[8] * v[2],
+    ]
+}
+
+fn srgb_to_linear(v: f64) -> f64 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn srgb_from_linear(v: f64) -> f64 {
+    if v <= 0.0031308 {
+        12.92 * v
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn gamma18_to_linear(v: f64) -> f64 {
+    if v <= 0.0 {
+        0.0
+    } else {
+        v.powf(1.8)
+    }
+}
+
+fn gamma18_from_linear(v: f64) -> f64 {
+    if v <= 0.0 {
+        0.0
+    } else {
+        v.powf(1.0 / 1.8)
+    }
+}
+
+fn srgb_to_prophoto(rgb: [f64; 3]) -> [f64; 3] {
+    let xyz = mul(&SRGB_TO_XYZ, rgb.map(srgb_to_linear));
+    mul(&XYZ_TO_PROPHOTO, xyz).map(gamma18_from_linear)
+}
+
+fn prophoto_to_srgb(rgb: [f64; 3]) -> [f64; 3] {
+    let xyz = mul(&PROPHOTO_TO_XYZ, rgb.map(gamma18_to_linear));
+    mul(&XYZ_TO_SRGB, xyz).map(srgb_from_linear)
+}
+
+/// A built-in profile baked for sRGB, or `None` for `none` and unknown
+/// names. Each is baked once per process.
+pub fn builtin_profile(name: &str) -> Option<Arc<Lut3D>> {
+    static BAKED: OnceLock<BTreeMap<&'static str, Arc<Lut3D>>> = OnceLock::new();
+    BAKED
+        .get_or_init(|| {
+            BUILTIN_PROFILES
+                .iter()
+                .filter_map(|(name, bytes)| {
+                    let lut = Lut3D::from_bin(bytes).ok()?;
+                    Some((*name, Arc::new(lut.baked_from_prophoto())))
+                })
+                .collect()
+        })
+        .get(name)
+        .cloned()
+}
+
+/// Blends the LUT's output with each pixel: 0 = off, 100 = full effect,
+/// 200 = twice the change, as `applyLut3D`'s `strength`.
+pub fn apply_lut3d(image: &mut Image16, lut: &Lut3D, strength: f64, interpolation: Interpolation) {
+    if strength <= 0.0 {
+        return;
+    }
+    let amount = strength / 100.0;
+    for_each_pixel(image, |px| {
+        let input = [px[0] as f64, px[1] as f64, px[2] as f64];
+        let mapped = lut.sample(input.map(|value| value / MAX_16), interpolation);
+        for c in 0..3 {
+            let value = input[c] * (1.0 - amount) + mapped[c] * MAX_16 * amount;
+            px[c] = (value + 0.5).clamp(0.0, MAX_16) as u16;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_lut3d, builtin_profile, Interpolation, Lut3D, BUILTIN_SIZE};
+    use crate::engine::Image16;
+
+    fn close(a: [f64; 3], b: [f64; 3]) -> bool {
+        (0..3).all(|c| (a[c] - b[c]).abs() < 1e-5)
+    }
+
+    #[test]
+    fn identity_luts_pass_colors_through_either_way() {
+        let lut = Lut3D::identity(17).unwrap();
+        for rgb in [
+            [0.0, 0.0, 0.0],
+            [0.2, 0.7, 0.41],
+            [1.0, 0.5, 0.0],
+            [1.0, 1.0, 1.0],
+        ] {
+            assert!(close(lut.sample(rgb, Interpolation::Trilinear), rgb));
+            assert!(close(lut.sample(rgb, Interpolation::Tetrahedral), rgb));
+        }
+    }
+
+    #[test]
+    fn tetrahedral_differs_from_trilinear_inside_a_cell() {
+        // A 2³ LUT that only lights the white corner.
+        let mut data = vec![0.0f32; 8 * 3];
+        data[7 * 3..].copy_from_slice(&[1.0, 1.0, 1.0]);
+        let lut = Lut3D::new(2, data).unwrap();
+        let rgb = [0.5, 0.5, 0.5];
+        assert!(close(lut.sample(rgb, Interpolation::Trilinear), [0.125; 3]));
+        assert!(close(lut.sample(rgb, Interpolation::Tetrahedral), [0.5; 3]));
+    }
+
+    #[test]
+    fn cube_files_round_trip_and_list_red_fastest() {
+        let text = "# comment\nTITLE \"Lab\"\nLUT_3D_SIZE 2\n\
+            0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n";
+        let lut = Lut3D::parse_cube(text).unwrap();
+        assert_eq!(lut, Lut3D::identity(2).unwrap());
+        assert!(close(
+            lut.sample([1.0, 0.0, 0.0], Interpolation::Trilinear),
+            [1.0, 0.0, 0.0]
+        ));
+        assert_eq!(Lut3D::parse_cube(&lut.to_cube("Lab")).unwrap(), lut);
+
+        let scaled =
+            Lut3D::parse_cube(&text.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MAX 2 2 2"))
+                .unwrap();
+        assert!(close(
+            scaled.sample([1.0, 1.0, 1.0], Interpolation::Trilinear),
+            [0.5; 3]
+        ));
+        assert_eq!(Lut3D::parse_cube(&scaled.to_cube("x")).unwrap(), scaled);
+
+        assert!(Lut3D::parse_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut3D::parse_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+    }
+
+    #[test]
+    fn oversized_cube_headers_are_rejected_before_reading_samples() {
+        for size in ["1", "130", "4294967296", "18446744073709551615"] {
+            let error = Lut3D::parse_cube(&format!("LUT_3D_SIZE {size}\n0 0 0\n")).unwrap_err();
+            assert!(error.contains("outside 2..=129"), "{size}: {error}");
+        }
+        assert!(Lut3D::from_red_fastest(usize::MAX, &[]).is_err());
+    }
+
+    #[test]
+    fn three_dl_files_scale_by_their_bit_depth() {
+        let text = "3DMESH\nMesh 1 12\n0 4095\n\
+            0 0 0\n0 0 4095\n0 4095 0\n0 4095 4095\n\
+            4095 0 0\n4095 0 4095\n4095 4095 0\n4095 4095 4095\n";
+        assert_eq!(Lut3D::parse_3dl(text).unwrap(), Lut3D::identity(2).unwrap());
+        let ten_bit = "0 1023\n0 0 0\n0 0 1023\n0 1023 0\n0 1023 1023\n\
+            1023 0 0\n1023 0 1023\n1023 1023 0\n1023 1023 1023\n";
+        assert_eq!(
+            Lut3D::parse_3dl(ten_bit).unwrap(),
+            Lut3D::identity(2).unwrap()
+        );
+        assert!(Lut3D::parse_3dl("0 1023\n0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn builtin_profiles_are_baked_for_srgb() {
+        for name in ["frontier", "crystal", "natural", "pakon", "noritsu"] {
+            let lut = builtin_profile(name).unwrap();
+            assert_eq!(lut.size(), BUILTIN_SIZE);
+            let black = lut.sample([0.0; 3], Interpolation::Trilinear);
+            let white = lut.sample([1.0; 3], Interpolation::Trilinear);
+            assert!(
+                black.iter().sum::<f64>() < white.iter().sum::<f64>(),
+                "{name}"
+            );
+        }
+        assert!(builtin_profile("none").is_none());
+        assert!(Lut3D::from_bin(&[0u8; 10]).is_err());
+
+        let identity = Lut3D::identity(4).unwrap();
+        assert_eq!(
+            Lut3D::from_bin(&identity.to_le_u16_bytes()).unwrap(),
+            identity
+        );
+    }
+
+    #[test]
+    fn strength_blends_towards_the_lut() {
+        // Inverts every channel.
+        let mut data = Vec::new();
+        for r in 0..2 {
+            for g in 0..2 {
+                for b in 0..2 {
+                    data.extend([1.0 - r as f32, 1.0 - g as f32, 1.0 - b as f32]);
+                }
+            }
+        }
+        let lut = Lut3D::new(2, data).unwrap();
+        let pixel = vec![65535, 0, 16384, 1234];
+        let mut off = Image16::new(1, 1, pixel.clone()).unwrap();
+        apply_lut3d(&mut off, &lut, 0.0, Interpolation::Trilinear);
+        assert_eq!(off.data, pixel);
+        let mut full = Image16::new(1, 1, pixel.clone()).unwrap();
+        apply_lut3d(&mut full, &lut, 100.0, Interpolation::Tetrahedral);
+        assert_eq!(full.data, vec![0, 65535, 49151, 1234]);
+        let mut half = Image16::new(1, 1, pixel).unwrap();
+        apply_lut3d(&mut half, &lut, 50.0, Interpolation::Trilinear);
+        assert_eq!(half.data, vec![32768, 32768, 32768, 1234]);
+    }
+}
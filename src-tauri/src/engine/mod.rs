@@ -0,0 +1,589 @@
+//! Native negative-to-positive conversion. Mirrors the SilverCore pipeline
+//! (`pipeline/silverAdapter.js` + `silvercore/engine/Engine.js`):
+//! film base compensation → analyze → generate curves → apply LUTs → HSL →
+//! enhanced profile (3D LUT) → saturation → optional grayscale mix.
+
+pub mod adjust;
+pub mod analysis;
+pub mod auto_frame;
+pub mod curves;
+pub mod film_base;
+pub mod geometry;
+pub mod lut3d;
+pub mod presets;
+
+use adjust::{FilmBase, FilmBaseMethod};
+use analysis::{AutoColor, ChannelLevels};
+use curves::{CurveSettings, LayerOrder, WbMethod, WbTonality};
+use lut3d::{Interpolation, Lut3D};
+use presets::FilmPresetSettings;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// 16-bit RGBA image, the same layout as `Image16` in `silvercore/util/image16.js`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image16 {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u16>,
+}
+
+impl Image16 {
+    pub fn new(width: u32, height: u32, data: Vec<u16>) -> Result<Self, String> {
+        let expected = width as usize * height as usize * 4;
+        if width == 0 || height == 0 || data.len() != expected {
+            return Err(format!(
+                "pixel buffer length {} does not match RGBA {width}x{height}",
+                data.len()
+            ));
+        }
+        Ok(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Little-endian u16 samples, the byte order of a JS `Uint16Array` on
+    /// every platform the app ships on.
+    pub fn from_le_bytes(width: u32, height: u32, bytes: &[u8]) -> Result<Self, String> {
+        let samples = bytes.chunks_exact(2);
+        if !samples.remainder().is_empty() {
+            return Err("pixel buffer has an odd number of bytes".to_string());
+        }
+        let data = samples
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        Self::new(width, height, data)
+    }
+
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.data
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+}
+
+/// The subset of a `deepCopySanitizedSettings` snapshot the converter reads.
+/// Unknown fields are ignored so snapshots can grow without breaking this.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FrameSettings {
+    pub film_type: String,
+    pub film_base: Option<FilmBase>,
+    pub film_base_compensation: Option<String>,
+    pub film_base_method: Option<String>,
+    pub film_base_strength: Option<f64>,
+    pub core_film_preset: String,
+    pub core_color_model: String,
+    pub core_enhanced_profile: String,
+    pub core_profile_strength: f64,
+    /// `trilinear` (what the webview uses) or `tetrahedral`.
+    pub core_lut_interpolation: String,
+    pub core_pre_saturation: f64,
+    pub core_border_buffer: f64,
+    pub core_brightness: f64,
+    pub core_exposure: f64,
+    pub core_contrast: f64,
+    pub core_highlights: f64,
+    pub core_shadows: f64,
+    pub core_whites: f64,
+    pub core_blacks: f64,
+    pub core_wb_mode: String,
+    pub core_temperature: f64,
+    pub core_tint: f64,
+    pub core_saturation: f64,
+    pub core_glow: f64,
+    pub core_fade: f64,
+    pub core_curve_precision: String,
+    /// Degrees, clockwise; see [`geometry::apply_frame_geometry`].
+    pub rotation_angle: f64,
+    #[serde(deserialize_with = "geometry::deserialize_crop")]
+    pub crop_region: Option<geometry::FrameCrop>,
+}
+
+impl Default for FrameSettings {
+    fn default() -> Self {
+        Self {
+            film_type: "color".to_string(),
+            film_base: None,
+            film_base_compensation: None,
+            film_base_method: None,
+            film_base_strength: None,
+            core_film_preset: "none".to_string(),
+            core_color_model: "standard".to_string(),
+            core_enhanced_profile: "none".to_string(),
+            core_profile_strength: 100.0,
+            core_lut_interpolation: "trilinear".to_string(),
+            core_pre_saturation: 100.0,
+            core_border_buffer: 10.0,
+            core_brightness: 0.0,
+            core_exposure: 0.0,
+            core_contrast: 0.0,
+            core_highlights: 0.0,
+            core_shadows: 0.0,
+            core_whites: 0.0,
+            core_blacks: 0.0,
+            core_wb_mode: "auto".to_string(),
+            core_temperature: 0.0,
+            core_tint: 0.0,
+            core_saturation: 100.0,
+            core_glow: 0.0,
+            core_fade: 0.0,
+            core_curve_precision: "auto".to_string(),
+            rotation_angle: 0.0,
+            crop_region: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionMode {
+    Color,
+    Bw,
+    Positive,
+}
+
+impl ConversionMode {
+    /// Mirrors `resolveConversionMode` in `pipeline/conversionRouter.js`.
+    pub fn from_film_type(film_type: &str) -> Self {
+        match film_type {
+            "positive" => Self::Positive,
+            "bw" => Self::Bw,
+            _ => Self::Color,
+        }
+    }
+}
+
+/// Engine parameters after the film preset is merged in, as produced by
+/// `buildSilverCoreParams`.
+#[derive(Debug, Clone)]
+pub struct CoreParams {
+    pub color_model: String,
+    pub is_negative: bool,
+    pub border_buffer: f64,
+    pub brightness: f64,
+    pub exposure: f64,
+    pub contrast: f64,
+    pub highlights: f64,
+    pub shadows: f64,
+    pub whites: f64,
+    pub blacks: f64,
+    pub wb_mode: String,
+    pub temperature: f64,
+    pub tint: f64,
+    pub saturation: f64,
+    pub glow: f64,
+    pub fade: f64,
+    pub curve_precision: String,
+    pub enhanced_profile: String,
+    pub profile_strength: f64,
+    pub lut_interpolation: Interpolation,
+    pub mid_cyan: f64,
+    pub mid_tint: f64,
+    pub mid_temp: f64,
+    pub shadow_range: f64,
+    pub highlight_range: f64,
+    pub wb_tonality: String,
+    pub shadow_cyan: f64,
+    pub shadow_tint: f64,
+    pub shadow_temp: f64,
+    pub highlight_cyan: f64,
+    pub highlight_tint: f64,
+    pub highlight_temp: f64,
+    pub layer_order: String,
+    pub auto_tone_level: f64,
+    pub auto_color_level: f64,
+    pub film_wb: String,
+    pub bw_mix: String,
+}
+
+fn sanitize(value: f64, fallback: f64, min: f64, max: f64) -> f64 {
+    let base = if value.is_finite() { value } else { fallback };
+    base.clamp(min, max)
+}
+
+impl CoreParams {
+    pub fn from_settings(settings: &FrameSettings, mode: ConversionMode) -> Self {
+        Self::with_film_preset(
+            settings,
+            mode,
+            Builtins.film_preset(&settings.core_film_preset),
+        )
+    }
+
+    /// Like [`CoreParams::from_settings`] with `preset` standing in for the
+    /// film preset the settings name.
+    pub fn with_film_preset(
+        settings: &FrameSettings,
+        mode: ConversionMode,
+        preset: Option<FilmPresetSettings>,
+    ) -> Self {
+        let pick = |value: Option<f64>, base: f64| value.unwrap_or(base);
+        let pick_str =
+            |value: Option<&String>, base: &str| value.cloned().unwrap_or_else(|| base.to_string());
+
+        let color_model = if mode == ConversionMode::Bw {
+            "mono".to_string()
+        } else {
+            settings.core_color_model.clone()
+        };
+        let preset = preset.unwrap_or_default();
+
+        Self {
+            color_model,
+            is_negative: mode != ConversionMode::Positive,
+            border_buffer: sanitize(settings.core_border_buffer, 10.0, 0.0, 30.0).round(),
+            brightness: sanitize(settings.core_brightness, 0.0, -100.0, 100.0),
+            exposure: sanitize(settings.core_exposure, 0.0, -300.0, 300.0),
+            contrast: sanitize(settings.core_contrast, 0.0, -100.0, 100.0),
+            highlights: sanitize(
+                pick(preset.highlights, settings.core_highlights),
+                0.0,
+                -100.0,
+                100.0,
+            ),
+            shadows: sanitize(
+                pick(preset.shadows, settings.core_shadows),
+                0.0,
+                -100.0,
+                100.0,
+            ),
+            whites: sanitize(
+                pick(preset.whites, settings.core_whites),
+                0.0,
+                -100.0,
+                100.0,
+            ),
+            blacks: sanitize(
+                pick(preset.blacks, settings.core_blacks),
+                0.0,
+                -100.0,
+                100.0,
+            ),
+            wb_mode: settings.core_wb_mode.clone(),
+            temperature: sanitize(settings.core_temperature, 0.0, -100.0, 100.0),
+            tint: sanitize(settings.core_tint, 0.0, -100.0, 100.0),
+            saturation: sanitize(
+                pick(preset.saturation, settings.core_saturation),
+                100.0,
+                0.0,
+                200.0,
+            )
+            .round(),
+            glow: sanitize(pick(preset.glow, settings.core_glow), 0.0, 0.0, 100.0),
+            fade: sanitize(pick(preset.fade, settings.core_fade), 0.0, 0.0, 100.0),
+            curve_precision: match settings.core_curve_precision.as_str() {
+                "smooth" | "precise" => settings.core_curve_precision.clone(),
+                _ => "auto".to_string(),
+            },
+            enhanced_profile: pick_str(
+                preset.enhanced_profile.as_ref(),
+                &settings.core_enhanced_profile,
+            ),
+            profile_strength: sanitize(settings.core_profile_strength, 100.0, 0.0, 200.0).round(),
+            lut_interpolation: Interpolation::parse(&settings.core_lut_interpolation),
+            mid_cyan: sanitize(pick(preset.mid_cyan, 0.0), 0.0, -100.0, 100.0),
+            mid_tint: sanitize(pick(preset.mid_tint, 0.0), 0.0, -100.0, 100.0),
+            mid_temp: sanitize(pick(preset.mid_temp, 0.0), 0.0, -100.0, 100.0),
+            shadow_range: sanitize(pick(preset.shadow_range, 5.0), 5.0, 0.0, 10.0),
+            highlight_range: sanitize(pick(preset.highlight_range, 5.0), 5.0, 0.0, 10.0),
+            wb_tonality: pick_str(preset.wb_tonality.as_ref(), "addDensity"),
+            shadow_cyan: sanitize(pick(preset.shadow_cyan, 0.0), 0.0, -100.0, 100.0),
+            shadow_tint: sanitize(pick(preset.shadow_tint, 0.0), 0.0, -100.0, 100.0),
+            shadow_temp: sanitize(pick(preset.shadow_temp, 0.0), 0.0, -100.0, 100.0),
+            highlight_cyan: sanitize(pick(preset.highlight_cyan, 0.0), 0.0, -100.0, 100.0),
+            highlight_tint: sanitize(pick(preset.highlight_tint, 0.0), 0.0, -100.0, 100.0),
+            highlight_temp: sanitize(pick(preset.highlight_temp, 0.0), 0.0, -100.0, 100.0),
+            layer_order: pick_str(preset.layer_order.as_ref(), "colorFirst"),
+            auto_tone_level: 100.0,
+            auto_color_level: 100.0,
+            film_wb: "none".to_string(),
+            bw_mix: "standard".to_string(),
+        }
+    }
+
+    /// Mirrors `Engine.buildSettings`.
+    pub fn curve_settings(&self, auto_color: &AutoColor) -> CurveSettings {
+        let tone_profile = presets::color_model_tone_profile(&self.color_model);
+        let profile = presets::tone_profile(tone_profile);
+        let model = presets::color_model(&self.color_model);
+        let strength = self.profile_strength / 100.0;
+        let auto_color_level = self.auto_color_level / 100.0;
+        let (film_temp, film_tint, film_cyan) = presets::film_wb_offsets(&self.film_wb);
+
+        let temp = self.temperature
+            + auto_color.temp_correction * auto_color_level
+            + model.default_temp * strength
+            + film_temp;
+        let tint = self.tint
+            + auto_color.tint_correction * auto_color_level
+            + model.default_tint * strength
+            + film_tint;
+        let cyan = auto_color.cyan_correction * auto_color_level
+            + model.default_cyan * strength
+            + film_cyan;
+
+        CurveSettings {
+            tone_profile,
+            is_negative: self.is_negative,
+            brightness: self.brightness,
+            exposure: self.exposure,
+            contrast: self.contrast,
+            highlights: self.highlights,
+            shadows: self.shadows,
+            whites: self.whites,
+            blacks: self.blacks,
+            glow: self.glow,
+            fade: self.fade,
+            temp,
+            tint,
+            temperature: self.temperature,
+            wb_cyan: cyan,
+            wb_temp: temp,
+            wb_tint: tint,
+            wb_tonality: WbTonality::parse(&self.wb_tonality),
+            wb_method: WbMethod::parse(&self.wb_mode),
+            layer_order: LayerOrder::parse(&self.layer_order),
+            soft_highlights: profile.soft_highlights,
+            soft_shadows: profile.soft_shadows,
+            shadow_range: self.shadow_range,
+            highlight_range: self.highlight_range,
+            shadow_cyan: self.shadow_cyan,
+            shadow_tint: self.shadow_tint,
+            shadow_temp: self.shadow_temp + model.default_shadows_temp * strength,
+            highlight_cyan: self.highlight_cyan,
+            highlight_tint: self.highlight_tint,
+            highlight_temp: self.highlight_temp,
+            mid_cyan: self.mid_cyan,
+            mid_tint: self.mid_tint,
+            mid_temp: self.mid_temp,
+            curve_precision: self.curve_precision.clone(),
+            auto_tone_level: self.auto_tone_level / 100.0,
+        }
+    }
+}
+
+/// What a conversion measured, returned alongside the pixels so callers can
+/// show or reuse the analysis.
+#[derive(Debug, Clone)]
+pub struct ConversionReport {
+    pub mode: ConversionMode,
+    pub levels: [ChannelLevels; 3],
+    pub auto_color: AutoColor,
+    pub film_base_gains: Option<[f64; 3]>,
+}
+
+/// Where a conversion finds the film presets and enhanced profiles that a
+/// settings snapshot names. The defaults only know the built-in ones.
+pub trait Library {
+    fn film_preset(&self, id: &str) -> Option<FilmPresetSettings> {
+        presets::film_preset(id).map(|preset| preset.settings.clone())
+    }
+
+    fn enhanced_profile(&self, id: &str) -> Option<Arc<Lut3D>> {
+        lut3d::builtin_profile(id)
+    }
+}
+
+/// The presets and profiles that ship with the app.
+pub struct Builtins;
+
+impl Library for Builtins {}
+
+/// Converts `image` in place using a sanitized settings snapshot. Only the
+/// built-in presets and profiles are available; see [`convert_frame_with`].
+pub fn convert_frame(image: &mut Image16, settings: &FrameSettings) -> ConversionReport {
+    convert_frame_with(image, settings, &Builtins)
+}
+
+/// Like [`convert_frame`], looking presets and profiles up in `library`.
+/// A profile that cannot be found turns the profile strength down to 0, as
+/// `silverAdapter.js` does when loading one fails.
+pub fn convert_frame_with(
+    image: &mut Image16,
+    settings: &FrameSettings,
+    library: &impl Library,
+) -> ConversionReport {
+    let mode = ConversionMode::from_film_type(&settings.film_type);
+    let preset = match settings.core_film_preset.as_str() {
+        "none" => None,
+        id => library.film_preset(id),
+    };
+    let mut params = CoreParams::with_film_preset(settings, mode, preset);
+    let lut = match params.enhanced_profile.as_str() {
+        "none" => None,
+        name => library.enhanced_profile(name),
+    };
+    if lut.is_none() && params.enhanced_profile != "none" {
+        params.profile_strength = 0.0;
+    }
+
+    let film_base_gains = match (&settings.film_base, mode) {
+        (Some(base), ConversionMode::Color | ConversionMode::Bw) => {
+            let method = settings
+                .film_base_compensation
+                .as_deref()
+                .or(settings.film_base_method.as_deref())
+                .map(FilmBaseMethod::parse)
+                .unwrap_or(if mode == ConversionMode::Color {
+                    FilmBaseMethod::Density
+                } else {
+                    FilmBaseMethod::Linear
+                });
+            adjust::compute_film_base_gains(
+                base,
+                method,
+                settings.film_base_strength.unwrap_or(1.0),
+            )
+        }
+        _ => None,
+    };
+    if let Some(gains) = film_base_gains {
+        adjust::apply_channel_gains(image, gains);
+    }
+
+    let levels = analysis::analyze_image(
+        image,
+        params.border_buffer,
+        &params.color_model,
+        params.is_negative,
+    );
+    let auto_color = analysis::compute_auto_color(&levels);
+    let luts = curves::generate_curves(&levels, &params.curve_settings(&auto_color));
+
+    adjust::apply_luts(image, &luts);
+    if let Some(hsl) = presets::color_model(&params.color_model).hsl_adjustments {
+        adjust::apply_hsl_adjustments(image, &hsl.scaled(params.profile_strength / 100.0));
+    }
+    if let Some(lut) = &lut {
+        lut3d::apply_lut3d(
+            image,
+            lut,
+            params.profile_strength,
+            params.lut_interpolation,
+        );
+    }
+    adjust::adjust_saturation(image, params.saturation);
+    if mode == ConversionMode::Bw {
+        adjust::to_grayscale(image, presets::bw_mix_weights(&params.bw_mix));
+    }
+
+    ConversionReport {
+        mode,
+        levels,
+        auto_color,
+        film_base_gains,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert_frame, ConversionMode, CoreParams, FrameSettings, Image16};
+
+    fn orange_negative() -> Image16 {
+        let width = 48u32;
+        let height = 32u32;
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let density = ((x + y) * 600) as u16;
+                data.extend_from_slice(&[
+                    52000 - density,
+                    36000 - density / 2,
+                    24000 - density / 3,
+                    65535,
+                ]);
+            }
+        }
+        Image16::new(width, height, data).unwrap()
+    }
+
+    #[test]
+    fn image16_round_trips_little_endian_bytes() {
+        let image = Image16::new(1, 1, vec![1, 256, 65535, 0]).unwrap();
+        let bytes = image.to_le_bytes();
+        assert_eq!(bytes, vec![1, 0, 0, 1, 255, 255, 0, 0]);
+        assert_eq!(Image16::from_le_bytes(1, 1, &bytes).unwrap(), image);
+        assert!(Image16::from_le_bytes(2, 1, &bytes).is_err());
+    }
+
+    #[test]
+    fn settings_snapshot_deserializes_with_unknown_fields() {
+        let settings: FrameSettings = serde_json::from_value(serde_json::json!({
+            "filmType": "bw",
+            "coreFilmPreset": "frontier-lab",
+            "coreContrast": 12,
+            "filmBase": { "r": 210, "g": 140, "b": 90, "method": "auto" },
+            "cropRegion": { "x": 0, "y": 0, "width": 10, "height": 10 },
+            "curves": { "r": [0, 1, 2] }
+        }))
+        .unwrap();
+        assert_eq!(settings.core_contrast, 12.0);
+        assert_eq!(settings.core_saturation, 100.0);
+        assert_eq!(settings.crop_region, None);
+
+        let params = CoreParams::from_settings(&settings, ConversionMode::Bw);
+        assert_eq!(params.color_model, "mono");
+        assert_eq!(params.shadows, -24.0);
+        assert_eq!(params.enhanced_profile, "frontier");
+    }
+
+    #[test]
+    fn color_negative_is_inverted_and_keeps_alpha() {
+        let mut image = orange_negative();
+        let settings = FrameSettings {
+            film_base: Some(super::adjust::FilmBase {
+                r: Some(210.0),
+                g: Some(140.0),
+                b: Some(90.0),
+                ..Default::default()
+            }),
+            ..FrameSettings::default()
+        };
+        let report = convert_frame(&mut image, &settings);
+        assert_eq!(report.mode, ConversionMode::Color);
+        assert!(report.film_base_gains.is_some());
+
+        let first = &image.data[0..4];
+        let last = &image.data[image.data.len() - 4..];
+        let luma = |px: &[u16]| px[0] as u32 + px[1] as u32 + px[2] as u32;
+        assert!(
+            luma(first) < luma(last),
+            "thin negative areas should become dark"
+        );
+        assert!(image.data.chunks_exact(4).all(|px| px[3] == 65535));
+    }
+
+    #[test]
+    fn enhanced_profiles_change_the_result_and_missing_ones_turn_off() {
+        let convert = |profile: &str, strength: f64| {
+            let mut image = orange_negative();
+            let settings = FrameSettings {
+                core_enhanced_profile: profile.to_string(),
+                core_profile_strength: strength,
+                ..FrameSettings::default()
+            };
+            convert_frame(&mut image, &settings);
+            image
+        };
+        assert_ne!(convert("frontier", 100.0), convert("none", 100.0));
+        // As in the webview, a profile that fails to load zeroes the strength.
+        assert_eq!(convert("user:gone.cube", 100.0), convert("none", 0.0));
+    }
+
+    #[test]
+    fn bw_mode_produces_neutral_pixels() {
+        let mut image = orange_negative();
+        let settings = FrameSettings {
+            film_type: "bw".to_string(),
+            ..FrameSettings::default()
+        };
+        convert_frame(&mut image, &settings);
+        assert!(image
+            .data
+            .chunks_exact(4)
+            .all(|px| px[0] == px[1] && px[1] == px[2]));
+    }
+}
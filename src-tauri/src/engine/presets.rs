@@ -0,0 +1,281 @@
+//! Tone profiles, color models, and film presets.
+//! Mirrors `silvercore/engine/Presets.js` and `FilmPresets.js`.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+const FILM_PRESETS_JSON: &str = include_str!("../../resources/film-presets.json");
+
+#[derive(Debug, Clone, Copy)]
+pub struct ToneProfile {
+    pub default_blacks: f64,
+    pub default_whites: f64,
+    pub default_shadows: f64,
+    pub default_highlights: f64,
+    pub default_gamma: f64,
+    pub default_contrast: f64,
+    pub default_soft_high: f64,
+    pub default_soft_low: f64,
+    pub auto_tone: bool,
+    pub soft_highlights: bool,
+    pub soft_shadows: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+const fn tone(
+    blacks: f64,
+    whites: f64,
+    shadows: f64,
+    highlights: f64,
+    gamma: f64,
+    contrast: f64,
+    soft_high: f64,
+    soft_low: f64,
+    auto_tone: bool,
+) -> ToneProfile {
+    ToneProfile {
+        default_blacks: blacks,
+        default_whites: whites,
+        default_shadows: shadows,
+        default_highlights: highlights,
+        default_gamma: gamma,
+        default_contrast: contrast,
+        default_soft_high: soft_high,
+        default_soft_low: soft_low,
+        auto_tone,
+        soft_highlights: false,
+        soft_shadows: false,
+    }
+}
+
+pub fn tone_profile(key: &str) -> ToneProfile {
+    match key {
+        "autotone" => tone(5.0, -1.0, 0.0, 0.0, 1.0, 10.0, 0.0, 0.0, true),
+        "base" => tone(0.0, 0.0, 0.0, 0.0, 1.0, 0.0, -3.0, -3.0, false),
+        "base_gamma" => tone(0.0, 0.0, 0.0, 0.0, 0.66, 0.0, -3.0, -3.0, false),
+        "base_flat" => tone(0.0, 0.0, 0.0, 0.0, 1.0, 0.0, -15.0, -10.0, false),
+        "base_deep" => tone(0.0, 0.0, -12.0, 0.0, 1.0, 0.0, -3.0, -3.0, false),
+        "filmic" => tone(-10.0, 0.0, -10.0, -25.0, 1.0, 0.0, -3.0, -3.0, false),
+        "filmic_rich" => tone(-20.0, 0.0, -10.0, -25.0, 1.0, 5.0, -3.0, -3.0, false),
+        "filmic_flat" => tone(-10.0, 0.0, -10.0, -25.0, 1.0, 0.0, -9.0, -6.0, false),
+        "all_hard" => tone(2.0, -2.0, 0.0, 0.0, 1.0, 25.0, 0.0, 0.0, true),
+        "all_soft" => tone(10.0, -10.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, true),
+        "highlight_hard" => tone(2.0, -2.0, 0.0, 10.0, 1.0, 10.0, 0.0, 0.0, true),
+        "highlight_soft" => ToneProfile {
+            soft_highlights: true,
+            ..tone(2.0, -10.0, -10.0, 0.0, 1.0, 0.0, 0.0, 0.0, true)
+        },
+        "shadow_hard" => tone(2.0, 0.0, -10.0, 0.0, 1.0, 10.0, 0.0, 0.0, true),
+        "shadow_soft" => ToneProfile {
+            soft_shadows: true,
+            ..tone(9.0, -2.0, 0.0, 10.0, 1.0, 0.0, 0.0, 0.0, true)
+        },
+        _ => tone(2.0, -2.0, 0.0, 0.0, 1.0, 10.0, 0.0, 0.0, true),
+    }
+}
+
+pub fn color_model_tone_profile(color_model: &str) -> &'static str {
+    match color_model {
+        "cine-log" => "filmic",
+        "cine-rich" => "filmic_rich",
+        "cine-flat" => "filmic_flat",
+        "neutral" => "base",
+        _ => "standard",
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HslAdjustments {
+    pub red_hue: f64,
+    pub red_saturation: f64,
+    pub green_hue: f64,
+    pub green_saturation: f64,
+    pub blue_hue: f64,
+    pub blue_saturation: f64,
+}
+
+impl HslAdjustments {
+    pub fn scaled(self, factor: f64) -> Self {
+        Self {
+            red_hue: self.red_hue * factor,
+            red_saturation: self.red_saturation * factor,
+            green_hue: self.green_hue * factor,
+            green_saturation: self.green_saturation * factor,
+            blue_hue: self.blue_hue * factor,
+            blue_saturation: self.blue_saturation * factor,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColorModel {
+    pub default_temp: f64,
+    pub default_tint: f64,
+    pub default_cyan: f64,
+    pub default_shadows_temp: f64,
+    pub black_threshold: f64,
+    pub white_threshold: f64,
+    pub hsl_adjustments: Option<HslAdjustments>,
+}
+
+/// Model keys the UI offers, matching `CORE_COLOR_MODEL_OPTIONS` in `main.js`.
+pub const COLOR_MODELS: &[&str] = &[
+    "frontier",
+    "standard",
+    "warm",
+    "mono",
+    "noritsu",
+    "cine-log",
+    "cine-rich",
+    "cine-flat",
+    "neutral",
+];
+
+/// Unknown models fall back to `basic`, like `colorModels[...] || colorModels.basic`.
+pub fn color_model(key: &str) -> ColorModel {
+    match key {
+        "none" => ColorModel {
+            black_threshold: 0.001,
+            white_threshold: 0.001,
+            ..ColorModel::default()
+        },
+        "frontier" => ColorModel {
+            default_temp: 1.0,
+            default_cyan: -1.0,
+            default_shadows_temp: 3.0,
+            black_threshold: 0.002,
+            white_threshold: 0.002,
+            hsl_adjustments: Some(HslAdjustments {
+                red_hue: 15.0,
+                red_saturation: -10.0,
+                blue_hue: -15.0,
+                ..HslAdjustments::default()
+            }),
+            ..ColorModel::default()
+        },
+        "mono" => ColorModel {
+            black_threshold: 0.002,
+            white_threshold: 0.002,
+            ..ColorModel::default()
+        },
+        "noritsu" => ColorModel {
+            black_threshold: 0.002,
+            white_threshold: 0.002,
+            hsl_adjustments: Some(HslAdjustments {
+                red_hue: 15.0,
+                red_saturation: -4.0,
+                blue_hue: -10.0,
+                ..HslAdjustments::default()
+            }),
+            ..ColorModel::default()
+        },
+        _ => ColorModel {
+            black_threshold: 0.002,
+            white_threshold: 0.001,
+            hsl_adjustments: Some(HslAdjustments {
+                blue_hue: -10.0,
+                ..HslAdjustments::default()
+            }),
+            ..ColorModel::default()
+        },
+    }
+}
+
+/// Film-specific white balance base offsets as `(temp, tint, cyan)`.
+pub fn film_wb_offsets(key: &str) -> (f64, f64, f64) {
+    match key {
+        "kodak-gold" => (-3.0, 2.0, -1.0),
+        "kodak-portra" => (-2.0, 1.0, 0.0),
+        "kodak-ektar" => (-1.0, 0.0, 1.0),
+        "kodak-colorplus" => (-2.0, 2.0, -1.0),
+        "fuji-c200" => (2.0, -1.0, 1.0),
+        "fuji-pro400h" => (1.0, 0.0, 1.0),
+        "fuji-superia" => (1.0, -1.0, 0.0),
+        "cinestill-50d" => (-4.0, 1.0, -1.0),
+        "cinestill-800t" => (-8.0, 3.0, -2.0),
+        _ => (0.0, 0.0, 0.0),
+    }
+}
+
+/// B&W channel mixing weights as `(r, g, b)`.
+pub fn bw_mix_weights(key: &str) -> (f64, f64, f64) {
+    match key {
+        "red" => (0.6, 0.3, 0.1),
+        "green" => (0.2, 0.7, 0.1),
+        "blue" => (0.1, 0.3, 0.6),
+        "orange" => (0.5, 0.4, 0.1),
+        _ => (0.299, 0.587, 0.114),
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilmPresetSettings {
+    pub tone_profile: Option<String>,
+    pub enhanced_profile: Option<String>,
+    pub shadows: Option<f64>,
+    pub highlights: Option<f64>,
+    pub blacks: Option<f64>,
+    pub whites: Option<f64>,
+    pub saturation: Option<f64>,
+    pub glow: Option<f64>,
+    pub fade: Option<f64>,
+    pub shadow_range: Option<f64>,
+    pub highlight_range: Option<f64>,
+    pub shadow_cyan: Option<f64>,
+    pub shadow_tint: Option<f64>,
+    pub shadow_temp: Option<f64>,
+    pub highlight_cyan: Option<f64>,
+    pub highlight_tint: Option<f64>,
+    pub highlight_temp: Option<f64>,
+    pub mid_cyan: Option<f64>,
+    pub mid_tint: Option<f64>,
+    pub mid_temp: Option<f64>,
+    pub layer_order: Option<String>,
+    pub wb_tonality: Option<String>,
+    pub curve_resolution_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilmPreset {
+    pub name: String,
+    pub category: String,
+    pub settings: FilmPresetSettings,
+}
+
+pub fn film_presets() -> &'static BTreeMap<String, FilmPreset> {
+    static PRESETS: OnceLock<BTreeMap<String, FilmPreset>> = OnceLock::new();
+    PRESETS.get_or_init(|| {
+        serde_json::from_str(FILM_PRESETS_JSON).expect("bundled film presets are valid JSON")
+    })
+}
+
+pub fn film_preset(id: &str) -> Option<&'static FilmPreset> {
+    film_presets().get(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{color_model, color_model_tone_profile, film_preset, film_presets, tone_profile};
+
+    #[test]
+    fn bundled_film_presets_parse() {
+        let presets = film_presets();
+        assert_eq!(presets.len(), 37);
+        let frontier = film_preset("frontier-lab").expect("frontier-lab preset");
+        assert_eq!(frontier.category, "color");
+        assert_eq!(frontier.settings.shadows, Some(-24.0));
+        assert_eq!(
+            frontier.settings.enhanced_profile.as_deref(),
+            Some("frontier")
+        );
+    }
+
+    #[test]
+    fn unknown_keys_fall_back_like_the_js_tables() {
+        assert_eq!(color_model_tone_profile("mystery"), "standard");
+        assert_eq!(tone_profile("mystery").default_contrast, 10.0);
+        assert_eq!(color_model("mystery").white_threshold, 0.001);
+    }
+}
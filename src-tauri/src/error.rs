@@ -0,0 +1,148 @@
+//! The error every command returns. It reaches the webview as
+//! `{ "code": "DiskFull", "message": "…", "retryable": false }`, so the UI can
+//! pick an actionable message and decide whether retrying is worthwhile
+//! without parsing English text.
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io::ErrorKind;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    NotFound(String),
+    PermissionDenied(String),
+    DiskFull(String),
+    InvalidPath(String),
+    /// The target exists and replacing it was not confirmed.
+    AlreadyExists(String),
+    DecodeFailed(String),
+    Cancelled(String),
+    /// Other I/O failures, such as a network share dropping out mid-write.
+    Io(String),
+    /// Everything else: malformed arguments, encoder failures, bugs.
+    Internal(String),
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+impl CommandError {
+    /// Classifies an I/O error; `context` says what was being attempted, e.g.
+    /// "write file".
+    pub fn io(context: &str, err: std::io::Error) -> Self {
+        let message = format!("{context} failed: {err}");
+        match err.kind() {
+            ErrorKind::NotFound => Self::NotFound(message),
+            ErrorKind::AlreadyExists => Self::AlreadyExists(message),
+            ErrorKind::PermissionDenied | ErrorKind::ReadOnlyFilesystem => {
+                Self::PermissionDenied(message)
+            }
+            ErrorKind::StorageFull | ErrorKind::QuotaExceeded | ErrorKind::FileTooLarge => {
+                Self::DiskFull(message)
+            }
+            ErrorKind::InvalidFilename | ErrorKind::NotADirectory | ErrorKind::IsADirectory => {
+                Self::InvalidPath(message)
+            }
+            _ => Self::Io(message),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "NotFound",
+            Self::PermissionDenied(_) => "PermissionDenied",
+            Self::DiskFull(_) => "DiskFull",
+            Self::InvalidPath(_) => "InvalidPath",
+            Self::AlreadyExists(_) => "AlreadyExists",
+            Self::DecodeFailed(_) => "DecodeFailed",
+            Self::Cancelled(_) => "Cancelled",
+            Self::Io(_) => "Io",
+            Self::Internal(_) => "Internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NotFound(message)
+            | Self::PermissionDenied(message)
+            | Self::DiskFull(message)
+            | Self::InvalidPath(message)
+            | Self::AlreadyExists(message)
+            | Self::DecodeFailed(message)
+            | Self::Cancelled(message)
+            | Self::Io(message)
+            | Self::Internal(message) => message,
+        }
+    }
+
+    /// Whether trying the same operation again might succeed without the
+    /// user changing anything first.
+    pub fn retryable(&self) -> bool {
+        matches!(self, Self::Io(_))
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Helpers that still report plain strings (encoders, argument parsing) map
+/// to `Internal`; callers that know better map them explicitly.
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::Internal(message)
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("CommandError", 3)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", self.message())?;
+        error.serialize_field("retryable", &self.retryable())?;
+        error.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandError;
+    use std::io::{Error, ErrorKind};
+
+    #[test]
+    fn io_errors_are_classified_by_kind() {
+        let cases = [
+            (ErrorKind::NotFound, "NotFound"),
+            (ErrorKind::PermissionDenied, "PermissionDenied"),
+            (ErrorKind::ReadOnlyFilesystem, "PermissionDenied"),
+            (ErrorKind::StorageFull, "DiskFull"),
+            (ErrorKind::NotADirectory, "InvalidPath"),
+            (ErrorKind::TimedOut, "Io"),
+        ];
+        for (kind, code) in cases {
+            let error = CommandError::io("write file", Error::new(kind, "boom"));
+            assert_eq!(error.code(), code, "{kind:?}");
+            assert_eq!(error.message(), "write file failed: boom");
+        }
+    }
+
+    #[test]
+    fn serializes_code_message_and_retryable() {
+        let error = CommandError::io("write file", Error::new(ErrorKind::TimedOut, "share gone"));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "Io",
+                "message": "write file failed: share gone",
+                "retryable": true
+            })
+        );
+        let error = CommandError::from("bad header".to_string());
+        assert_eq!(error.code(), "Internal");
+        assert!(!error.retryable());
+    }
+}
@@ -0,0 +1,304 @@
+//! What a directory export does when its target file already exists.
+//! Re-exporting a corrected roll into the same folder should be able to
+//! replace the old frames instead of leaving `_1` copies next to them.
+
+use crate::error::CommandResult;
+use crate::sidecar;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// Keeps the existing file and writes `name_1`, `name_2`, ...
+    #[default]
+    Rename,
+    Overwrite,
+    Skip,
+    /// Replaces the existing file only when the source frame (or its
+    /// settings sidecar) changed after it was written.
+    OverwriteIfOlder,
+    /// Asks in a native dialog, which can apply the answer to the rest of
+    /// the batch.
+    Ask,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportConflict {
+    pub policy: ConflictPolicy,
+    /// Files left in the batch after this one; `ask` only offers to apply
+    /// its answer to all of them when there are any.
+    pub remaining: u32,
+    /// The scan the export came from, for `overwrite-if-older`.
+    pub source_path: Option<String>,
+}
+
+/// The answer given in the `ask` dialog. The webview passes `policy` with
+/// the remaining files when `apply_to_all` is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictAnswer {
+    pub policy: ConflictPolicy,
+    pub apply_to_all: bool,
+}
+
+#[derive(Debug)]
+pub struct ConflictDecision {
+    pub path: PathBuf,
+    /// Nothing should be written; `path` is the file that was kept.
+    pub skip: bool,
+    pub answer: Option<ConflictAnswer>,
+}
+
+impl ConflictDecision {
+    pub fn write(path: PathBuf) -> Self {
+        Self {
+            path,
+            skip: false,
+            answer: None,
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// When the frame last changed: the scan itself or its settings sidecar,
+/// whichever is newer. `None` when the scan is unknown or unreadable.
+fn source_modified(source_path: &str) -> Option<SystemTime> {
+    let source = Path::new(source_path.trim());
+    let scanned = modified(source)?;
+    Some(match modified(&sidecar::sidecar_path(source)) {
+        Some(edited) => scanned.max(edited),
+        None => scanned,
+    })
+}
+
+/// Decides where `target` gets written. `rename` yields a free name next to
+/// it and `ask` shows the dialog; both are only called when `target` exists.
+pub fn decide(
+    target: PathBuf,
+    conflict: &ExportConflict,
+    rename: impl FnOnce() -> PathBuf,
+    ask: impl FnOnce(&Path, u32) -> CommandResult<ConflictAnswer>,
+) -> CommandResult<ConflictDecision> {
+    if std::fs::symlink_metadata(&target).is_err() {
+        return Ok(ConflictDecision::write(target));
+    }
+    let (policy, answer) = match conflict.policy {
+        ConflictPolicy::Ask => {
+            let answer = ask(&target, conflict.remaining)?;
+            (answer.policy, Some(answer))
+        }
+        policy => (policy, None),
+    };
+    let mut decision = match policy {
+        ConflictPolicy::Rename => ConflictDecision::write(rename()),
+        ConflictPolicy::Overwrite => ConflictDecision::write(target),
+        ConflictPolicy::OverwriteIfOlder => {
+            let source = conflict.source_path.as_deref().and_then(source_modified);
+            // An unknown source counts as newer, like a plain overwrite.
+            let newer = match (source, modified(&target)) {
+                (Some(source), Some(exported)) => source > exported,
+                _ => true,
+            };
+            ConflictDecision {
+                skip: !newer,
+                ..ConflictDecision::write(target)
+            }
+        }
+        ConflictPolicy::Skip | ConflictPolicy::Ask => ConflictDecision {
+            skip: true,
+            ..ConflictDecision::write(target)
+        },
+    };
+    decision.answer = answer;
+    Ok(decision)
+}
+
+const REPLACE_LABEL: &str = "Replace";
+const KEEP_BOTH_LABEL: &str = "Keep Both";
+const SKIP_LABEL: &str = "Skip";
+
+/// Asks what to do about `target`. Closing the dialog skips the file.
+pub fn ask_native(target: &Path, remaining: u32) -> CommandResult<ConflictAnswer> {
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let folder = target
+        .parent()
+        .map(|parent| parent.display().to_string())
+        .unwrap_or_default();
+    let choice = rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Warning)
+        .set_title("File already exists")
+        .set_description(format!("\"{name}\" already exists in {folder}."))
+        .set_buttons(rfd::MessageButtons::YesNoCancelCustom(
+            REPLACE_LABEL.to_string(),
+            KEEP_BOTH_LABEL.to_string(),
+            SKIP_LABEL.to_string(),
+        ))
+        .show();
+    let policy = match choice {
+        rfd::MessageDialogResult::Custom(label) if label == REPLACE_LABEL => {
+            ConflictPolicy::Overwrite
+        }
+        rfd::MessageDialogResult::Custom(label) if label == KEEP_BOTH_LABEL => {
+            ConflictPolicy::Rename
+        }
+        rfd::MessageDialogResult::Yes => ConflictPolicy::Overwrite,
+        rfd::MessageDialogResult::No => ConflictPolicy::Rename,
+        _ => ConflictPolicy::Skip,
+    };
+    let apply_to_all = remaining > 0
+        && rfd::MessageDialog::new()
+            .set_title("File already exists")
+            .set_description(format!(
+                "Do the same for existing files among the {remaining} remaining?"
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show()
+            == rfd::MessageDialogResult::Yes;
+    Ok(ConflictAnswer {
+        policy,
+        apply_to_all,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decide, ConflictAnswer, ConflictPolicy, ExportConflict};
+    use std::time::{Duration, SystemTime};
+
+    fn policy(policy: ConflictPolicy) -> ExportConflict {
+        ExportConflict {
+            policy,
+            ..ExportConflict::default()
+        }
+    }
+
+    #[test]
+    fn existing_targets_follow_the_policy() {
+        let dir = std::env::temp_dir().join(format!("nc-conflict-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("frame.tif");
+        let renamed = dir.join("frame_1.tif");
+        let no_dialog = |_: &std::path::Path, _| -> crate::error::CommandResult<ConflictAnswer> {
+            panic!("asked about a free name")
+        };
+
+        let free = decide(
+            target.clone(),
+            &policy(ConflictPolicy::Ask),
+            || unreachable!(),
+            no_dialog,
+        )
+        .unwrap();
+        assert_eq!((free.path, free.skip), (target.clone(), false));
+
+        std::fs::write(&target, b"old").unwrap();
+        let rename = decide(
+            target.clone(),
+            &policy(ConflictPolicy::Rename),
+            || renamed.clone(),
+            no_dialog,
+        )
+        .unwrap();
+        assert_eq!((rename.path, rename.skip), (renamed.clone(), false));
+        let overwrite = decide(
+            target.clone(),
+            &policy(ConflictPolicy::Overwrite),
+            || unreachable!(),
+            no_dialog,
+        )
+        .unwrap();
+        assert_eq!((overwrite.path, overwrite.skip), (target.clone(), false));
+        let skip = decide(
+            target.clone(),
+            &policy(ConflictPolicy::Skip),
+            || unreachable!(),
+            no_dialog,
+        )
+        .unwrap();
+        assert_eq!((skip.path, skip.skip), (target.clone(), true));
+
+        let asked = decide(
+            target.clone(),
+            &ExportConflict {
+                policy: ConflictPolicy::Ask,
+                remaining: 4,
+                source_path: None,
+            },
+            || renamed.clone(),
+            |path, remaining| {
+                assert_eq!((path, remaining), (target.as_path(), 4));
+                Ok(ConflictAnswer {
+                    policy: ConflictPolicy::Rename,
+                    apply_to_all: true,
+                })
+            },
+        )
+        .unwrap();
+        assert_eq!(asked.path, renamed);
+        assert_eq!(asked.answer.map(|answer| answer.apply_to_all), Some(true));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn overwrite_if_older_compares_against_the_source_and_its_sidecar() {
+        let dir = std::env::temp_dir().join(format!("nc-conflict-age-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("scan.dng");
+        let target = dir.join("scan.tif");
+        std::fs::write(&source, b"scan").unwrap();
+        std::fs::write(&target, b"export").unwrap();
+        let now = SystemTime::now();
+        let set_age = |path: &std::path::Path, age: u64| {
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(now - Duration::from_secs(age))
+                .unwrap();
+        };
+        set_age(&source, 3600);
+        set_age(&target, 60);
+
+        let conflict = ExportConflict {
+            policy: ConflictPolicy::OverwriteIfOlder,
+            remaining: 0,
+            source_path: Some(source.to_string_lossy().to_string()),
+        };
+        let ask = |_: &std::path::Path, _| unreachable!();
+        assert!(
+            decide(target.clone(), &conflict, || unreachable!(), ask)
+                .unwrap()
+                .skip
+        );
+
+        // Editing the frame's settings rewrites its sidecar.
+        let sidecar = crate::sidecar::sidecar_path(&source);
+        std::fs::write(&sidecar, b"{}").unwrap();
+        let replaced = decide(target.clone(), &conflict, || unreachable!(), ask).unwrap();
+        assert_eq!((replaced.path, replaced.skip), (target.clone(), false));
+
+        let unknown = ExportConflict {
+            source_path: None,
+            ..conflict
+        };
+        assert!(
+            !decide(target, &unknown, || unreachable!(), ask)
+                .unwrap()
+                .skip
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
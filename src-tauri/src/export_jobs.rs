@@ -0,0 +1,516 @@
+//! Background export jobs. The webview queues files (encoded bytes, or a
+//! frame to convert natively) and hears back through `export://…` events,
+//! so a long roll export neither blocks a command per file nor the UI, and
+//! can be cancelled part way through.
+//!
+//! Files run one at a time, in the order they were queued, on a single
+//! worker thread; the conversion engine already spreads each frame across
+//! cores. Running them in order also keeps `rename` conflict names and
+//! `ask` dialogs predictable.
+
+use crate::error::{CommandError, CommandResult};
+use crate::export_conflict::ConflictPolicy;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+
+pub const PROGRESS_EVENT: &str = "export://progress";
+pub const DONE_EVENT: &str = "export://done";
+pub const ERROR_EVENT: &str = "export://error";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportStage {
+    Queued,
+    Converting,
+    Encoding,
+    Writing,
+    Done,
+    Failed,
+}
+
+/// The payload of every export event. `result` is set on `export://done`,
+/// `error` on `export://error`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportEvent<R> {
+    pub job_id: u64,
+    pub index: usize,
+    pub total: usize,
+    /// Files of the job that are done or failed so far.
+    pub completed: usize,
+    pub file_name: String,
+    pub stage: ExportStage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<R>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<CommandError>,
+}
+
+/// Where events go; the app forwards them to the webview.
+pub type EventSink<R> = Arc<dyn Fn(&'static str, ExportEvent<R>) + Send + Sync>;
+
+pub struct Job {
+    id: u64,
+    total: usize,
+    cancelled: AtomicBool,
+    /// Indices of the files that are done or failed; a file queued twice
+    /// counts once.
+    completed: Mutex<HashSet<usize>>,
+    conflict: Mutex<Option<ConflictPolicy>>,
+}
+
+impl Job {
+    fn completed(&self) -> usize {
+        self.completed.lock().map(|done| done.len()).unwrap_or(0)
+    }
+
+    /// Marks file `index` done and returns how many distinct files are.
+    fn complete(&self, index: usize) -> usize {
+        match self.completed.lock() {
+            Ok(mut done) => {
+                done.insert(index);
+                done.len()
+            }
+            Err(_) => self.total,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// The policy an `ask` answer settled on for the rest of the job.
+    pub fn settled_conflict(&self) -> Option<ConflictPolicy> {
+        self.conflict.lock().ok().and_then(|policy| *policy)
+    }
+
+    pub fn settle_conflict(&self, policy: ConflictPolicy) {
+        if let Ok(mut settled) = self.conflict.lock() {
+            *settled = Some(policy);
+        }
+    }
+}
+
+/// One queued file while it runs: reports stages and notices cancellation.
+pub struct JobFile<'a, R> {
+    job: &'a Job,
+    index: usize,
+    file_name: &'a str,
+    sink: &'a EventSink<R>,
+}
+
+impl<R> JobFile<'_, R> {
+    pub fn job(&self) -> &Job {
+        self.job
+    }
+
+    /// Reports that the file reached `stage`, or fails with `Cancelled` when
+    /// the job was cancelled in the meantime.
+    pub fn stage(&self, stage: ExportStage) -> CommandResult<()> {
+        if self.job.is_cancelled() {
+            return Err(cancelled(self.job.id));
+        }
+        (self.sink)(
+            PROGRESS_EVENT,
+            event(self.job, self.index, self.file_name, stage),
+        );
+        Ok(())
+    }
+}
+
+fn cancelled(job_id: u64) -> CommandError {
+    CommandError::Cancelled(format!("export job {job_id} was cancelled"))
+}
+
+fn event<R>(job: &Job, index: usize, file_name: &str, stage: ExportStage) -> ExportEvent<R> {
+    ExportEvent {
+        job_id: job.id,
+        index,
+        total: job.total,
+        completed: job.completed(),
+        file_name: file_name.to_string(),
+        stage,
+        result: None,
+        error: None,
+    }
+}
+
+type Task = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct Inner {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<u64, Arc<Job>>>,
+    /// Files queued after a cancel are refused as `Cancelled`, not unknown.
+    /// Forgotten once a later job completes.
+    cancelled: Mutex<HashSet<u64>>,
+    worker: Mutex<Option<Sender<Task>>>,
+}
+
+impl Inner {
+    fn lock_jobs(&self) -> CommandResult<std::sync::MutexGuard<'_, HashMap<u64, Arc<Job>>>> {
+        self.jobs
+            .lock()
+            .map_err(|_| CommandError::Internal("export job state is poisoned".to_string()))
+    }
+
+    fn job(&self, job_id: u64) -> CommandResult<Arc<Job>> {
+        if let Some(job) = self.lock_jobs()?.get(&job_id) {
+            return Ok(job.clone());
+        }
+        let cancelled = self
+            .cancelled
+            .lock()
+            .map(|cancelled| cancelled.contains(&job_id))
+            .unwrap_or(false);
+        if cancelled {
+            Err(self::cancelled(job_id))
+        } else {
+            Err(CommandError::Internal(format!(
+                "unknown export job: {job_id}"
+            )))
+        }
+    }
+
+    /// Forgets a job whose files have all been reported.
+    fn finish(&self, job: &Job) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.remove(&job.id);
+        }
+        if let Ok(mut cancelled) = self.cancelled.lock() {
+            cancelled.retain(|id| *id > job.id);
+        }
+    }
+
+    /// Hands `task` to the worker thread, starting it on first use and again
+    /// if it ever went away.
+    fn spawn(&self, task: Task) -> CommandResult<()> {
+        let mut worker = self
+            .worker
+            .lock()
+            .map_err(|_| CommandError::Internal("export worker state is poisoned".to_string()))?;
+        let task = match worker.as_ref() {
+            Some(sender) => match sender.send(task) {
+                Ok(()) => return Ok(()),
+                Err(mpsc::SendError(task)) => task,
+            },
+            None => task,
+        };
+        let (sender, receiver) = mpsc::channel::<Task>();
+        std::thread::Builder::new()
+            .name("export-jobs".to_string())
+            .spawn(move || {
+                for task in receiver {
+                    task();
+                }
+            })
+            .map_err(|err| CommandError::Internal(format!("start export worker failed: {err}")))?;
+        sender
+            .send(task)
+            .map_err(|_| CommandError::Internal("export worker stopped".to_string()))?;
+        *worker = Some(sender);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct ExportJobs {
+    inner: Arc<Inner>,
+}
+
+impl ExportJobs {
+    /// Opens a job of `total` files. It ends once that many have been
+    /// reported done or failed or were settled, or when it is cancelled.
+    pub fn begin(&self, total: usize) -> CommandResult<u64> {
+        if total == 0 {
+            return Err(CommandError::Internal(
+                "export job has no files".to_string(),
+            ));
+        }
+        let job_id = self.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = Job {
+            id: job_id,
+            total,
+            cancelled: AtomicBool::new(false),
+            completed: Mutex::new(HashSet::new()),
+            conflict: Mutex::new(None),
+        };
+        self.inner.lock_jobs()?.insert(job_id, Arc::new(job));
+        Ok(job_id)
+    }
+
+    /// Queues file `index` of a job. `work` runs on the worker thread and
+    /// its outcome becomes an `export://done` or `export://error` event.
+    pub fn queue<R, F>(
+        &self,
+        job_id: u64,
+        index: usize,
+        file_name: String,
+        sink: EventSink<R>,
+        work: F,
+    ) -> CommandResult<()>
+    where
+        R: Send + 'static,
+        F: FnOnce(&JobFile<'_, R>) -> CommandResult<R> + Send + 'static,
+    {
+        let job = self.inner.job(job_id)?;
+        if index >= job.total {
+            return Err(CommandError::Internal(format!(
+                "export job {job_id} has no file {index}"
+            )));
+        }
+        sink(
+            PROGRESS_EVENT,
+            event(&job, index, &file_name, ExportStage::Queued),
+        );
+        let inner = self.inner.clone();
+        self.inner.spawn(Box::new(move || {
+            let file = JobFile {
+                job: &job,
+                index,
+                file_name: &file_name,
+                sink: &sink,
+            };
+            let outcome = if job.is_cancelled() {
+                Err(cancelled(job.id))
+            } else {
+                std::panic::catch_unwind(AssertUnwindSafe(|| work(&file))).unwrap_or_else(|_| {
+                    Err(CommandError::Internal(format!(
+                        "exporting {file_name} panicked"
+                    )))
+                })
+            };
+            let completed = job.complete(index);
+            let mut report = event(&job, index, &file_name, ExportStage::Done);
+            let name = match outcome {
+                Ok(result) => {
+                    report.result = Some(result);
+                    DONE_EVENT
+                }
+                Err(error) => {
+                    report.stage = ExportStage::Failed;
+                    report.error = Some(error);
+                    ERROR_EVENT
+                }
+            };
+            // Forgotten before the last event, so whoever receives it sees
+            // the job as finished.
+            if completed >= job.total {
+                inner.finish(&job);
+            }
+            sink(name, report);
+        }))
+    }
+
+    /// Counts file `index` as finished without running anything, for files
+    /// the webview gave up on before queueing them. It waits behind the
+    /// files already queued, and ends the job when it was the last one.
+    pub fn settle(&self, job_id: u64, index: usize) -> CommandResult<()> {
+        let job = self.inner.job(job_id)?;
+        if index >= job.total {
+            return Err(CommandError::Internal(format!(
+                "export job {job_id} has no file {index}"
+            )));
+        }
+        let inner = self.inner.clone();
+        self.inner.spawn(Box::new(move || {
+            if job.complete(index) >= job.total {
+                inner.finish(&job);
+            }
+        }))
+    }
+
+    /// Stops a job: the file being written fails with `Cancelled` at its
+    /// next stage and files still queued fail without running. Returns
+    /// whether the job was still running.
+    pub fn cancel(&self, job_id: u64) -> CommandResult<bool> {
+        let Some(job) = self.inner.lock_jobs()?.remove(&job_id) else {
+            return Ok(false);
+        };
+        job.cancelled.store(true, Ordering::Relaxed);
+        if let Ok(mut cancelled) = self.inner.cancelled.lock() {
+            cancelled.insert(job_id);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventSink, ExportEvent, ExportJobs, ExportStage, DONE_EVENT, ERROR_EVENT};
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn collector() -> (
+        EventSink<u32>,
+        mpsc::Receiver<(&'static str, ExportEvent<u32>)>,
+    ) {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let sink: EventSink<u32> = Arc::new(move |name, event| {
+            sender.lock().unwrap().send((name, event)).unwrap();
+        });
+        (sink, receiver)
+    }
+
+    fn next_final(
+        events: &mpsc::Receiver<(&'static str, ExportEvent<u32>)>,
+    ) -> (&'static str, ExportEvent<u32>) {
+        loop {
+            let (name, event) = events.recv_timeout(Duration::from_secs(5)).unwrap();
+            if name == DONE_EVENT || name == ERROR_EVENT {
+                return (name, event);
+            }
+        }
+    }
+
+    #[test]
+    fn files_run_in_order_and_report_each_stage() {
+        let jobs = ExportJobs::default();
+        let (sink, events) = collector();
+        let job_id = jobs.begin(2).unwrap();
+        for index in 0..2 {
+            jobs.queue(
+                job_id,
+                index,
+                format!("{index}.tif"),
+                sink.clone(),
+                move |file| {
+                    file.stage(ExportStage::Writing)?;
+                    Ok(index as u32 * 10)
+                },
+            )
+            .unwrap();
+        }
+
+        let mut seen = Vec::new();
+        while seen
+            .iter()
+            .filter(|(name, _, _)| *name == DONE_EVENT)
+            .count()
+            < 2
+        {
+            let (name, event) = events.recv_timeout(Duration::from_secs(5)).unwrap();
+            seen.push((name, event.index, event.stage));
+            if name == DONE_EVENT {
+                assert_eq!(event.result, Some(event.index as u32 * 10));
+                assert_eq!(event.completed, event.index + 1);
+            }
+        }
+        let stages: Vec<_> = seen
+            .iter()
+            .filter(|(_, index, _)| *index == 0)
+            .map(|(_, _, stage)| *stage)
+            .collect();
+        assert_eq!(
+            stages,
+            [ExportStage::Queued, ExportStage::Writing, ExportStage::Done]
+        );
+        assert!(!jobs.cancel(job_id).unwrap(), "finished jobs are forgotten");
+    }
+
+    #[test]
+    fn cancelling_fails_the_running_and_queued_files() {
+        let jobs = ExportJobs::default();
+        let (sink, events) = collector();
+        let job_id = jobs.begin(3).unwrap();
+        let (started_tx, started) = mpsc::channel();
+        let (release, release_rx) = mpsc::channel::<()>();
+        jobs.queue(job_id, 0, "0.tif".into(), sink.clone(), move |file| {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+            file.stage(ExportStage::Writing)?;
+            Ok(0)
+        })
+        .unwrap();
+        jobs.queue(job_id, 1, "1.tif".into(), sink.clone(), |_| {
+            panic!("cancelled files must not run")
+        })
+        .unwrap();
+
+        started.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(jobs.cancel(job_id).unwrap());
+        release.send(()).unwrap();
+        for index in 0..2 {
+            let (name, event) = next_final(&events);
+            assert_eq!((name, event.index), (ERROR_EVENT, index));
+            assert_eq!(event.error.unwrap().code(), "Cancelled");
+        }
+        let late = jobs.queue(job_id, 2, "2.tif".into(), sink.clone(), |_| Ok(2));
+        assert_eq!(late.unwrap_err().code(), "Cancelled");
+
+        let next_id = jobs.begin(1).unwrap();
+        jobs.queue(next_id, 0, "next.tif".into(), sink, |_| Ok(3))
+            .unwrap();
+        assert_eq!(next_final(&events).0, DONE_EVENT);
+        assert!(
+            jobs.inner.cancelled.lock().unwrap().is_empty(),
+            "a later finished job forgets earlier cancels"
+        );
+    }
+
+    #[test]
+    fn a_panicking_file_fails_alone() {
+        let jobs = ExportJobs::default();
+        let (sink, events) = collector();
+        let job_id = jobs.begin(2).unwrap();
+        jobs.queue(job_id, 0, "0.tif".into(), sink.clone(), |_| panic!("boom"))
+            .unwrap();
+        jobs.queue(job_id, 1, "1.tif".into(), sink, |_| Ok(1))
+            .unwrap();
+        let (name, event) = next_final(&events);
+        assert_eq!(
+            (name, event.error.unwrap().code()),
+            (ERROR_EVENT, "Internal")
+        );
+        assert_eq!(next_final(&events).0, DONE_EVENT);
+    }
+
+    #[test]
+    fn a_file_queued_twice_counts_once() {
+        let jobs = ExportJobs::default();
+        let (sink, events) = collector();
+        let job_id = jobs.begin(2).unwrap();
+        for _ in 0..2 {
+            jobs.queue(job_id, 0, "0.tif".into(), sink.clone(), |_| Ok(0))
+                .unwrap();
+        }
+        assert_eq!(next_final(&events).1.completed, 1);
+        assert_eq!(next_final(&events).1.completed, 1);
+        jobs.queue(job_id, 1, "1.tif".into(), sink, |_| Ok(1))
+            .unwrap();
+        assert_eq!(next_final(&events).1.completed, 2);
+        assert!(
+            !jobs.cancel(job_id).unwrap(),
+            "the job ended with its last file"
+        );
+    }
+
+    #[test]
+    fn files_never_queued_still_end_the_job() {
+        let jobs = ExportJobs::default();
+        let (sink, events) = collector();
+        let job_id = jobs.begin(3).unwrap();
+        jobs.settle(job_id, 0).unwrap();
+        jobs.queue(job_id, 1, "1.tif".into(), sink.clone(), |_| Ok(1))
+            .unwrap();
+        assert_eq!(next_final(&events).1.completed, 2);
+        assert!(jobs.settle(job_id, 3).is_err());
+        jobs.settle(job_id, 2).unwrap();
+
+        // The worker runs in order, so the next job's file runs after it.
+        let next_id = jobs.begin(1).unwrap();
+        jobs.queue(next_id, 0, "next.tif".into(), sink, |_| Ok(3))
+            .unwrap();
+        assert_eq!(next_final(&events).0, DONE_EVENT);
+        assert!(
+            !jobs.cancel(job_id).unwrap(),
+            "the job ended with its last file"
+        );
+    }
+}
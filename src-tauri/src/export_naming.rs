@@ -0,0 +1,469 @@
+//! Export file names built from templates such as `{roll}_{frame:03}_{preset}.tif`.
+//!
+//! Names follow the strictest rules of the filesystems scans end up on
+//! (NTFS, exFAT card readers, SMB shares), whatever the host OS, so a roll
+//! exported on macOS can be copied to a Windows NAS unchanged. Collisions
+//! are still resolved by `build_unique_export_path` afterwards.
+//!
+//! A subfolder pattern such as `{year}/{roll}/{format}` sorts exports into
+//! folders under the chosen export root, which they can never leave.
+
+use crate::error::{CommandError, CommandResult};
+use crate::image_io;
+use crate::metadata::ExportMetadata;
+use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
+
+const MAX_FILE_NAME_BYTES: usize = 255;
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+const EXPORT_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff"];
+/// Left over around tokens that rendered empty, e.g. `roll_001_` without a preset.
+const EDGE_SEPARATORS: &[char] = &['_', '-', ' ', '.'];
+
+/// What the webview sends to name a batch export.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportNaming {
+    pub template: String,
+    /// Folders under the export root, separated by `/`.
+    pub subfolder: String,
+    pub roll: String,
+    /// 1-based position of the frame in the roll.
+    pub frame: Option<u32>,
+    pub bit_depth: Option<u8>,
+    /// Source path, film preset and film type; the capture date is read from
+    /// the source's EXIF.
+    pub metadata: Option<ExportMetadata>,
+}
+
+/// Token values for one export.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NamingContext {
+    pub stem: String,
+    pub roll: String,
+    pub frame: Option<u32>,
+    pub preset: String,
+    pub film: String,
+    /// `YYYY-MM-DD`.
+    pub date: Option<String>,
+    pub format: String,
+    pub bit_depth: Option<u8>,
+}
+
+impl ExportNaming {
+    /// The subfolder (relative to the export root) and file name for an
+    /// export the webview would have called `suggested_name`, whose extension
+    /// decides the format. Without a template the suggested name is kept.
+    pub fn resolve(&self, suggested_name: &str) -> Result<(PathBuf, String), String> {
+        let context = self.context(suggested_name);
+        let subfolder = render_subfolder(&self.subfolder, &context)?;
+        let file_name = if self.template.trim().is_empty() {
+            suggested_name.to_string()
+        } else {
+            render_file_name(&self.template, &context, suggested_name)?
+        };
+        Ok((subfolder, file_name))
+    }
+
+    fn context(&self, suggested_name: &str) -> NamingContext {
+        let metadata = self.metadata.as_ref();
+        let source_stem = metadata
+            .and_then(|metadata| metadata.source_path.as_deref())
+            .and_then(|path| Path::new(path.trim()).file_stem())
+            .or_else(|| Path::new(suggested_name).file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let film = metadata.and_then(|metadata| metadata.film.as_ref());
+        let preset = film
+            .map(|film| film.core_film_preset.trim())
+            .filter(|preset| !preset.eq_ignore_ascii_case("none"))
+            .unwrap_or_default();
+        NamingContext {
+            stem: source_stem,
+            roll: self.roll.trim().to_string(),
+            frame: self.frame,
+            preset: preset.to_string(),
+            film: film
+                .map(|film| film.film_type.trim().to_string())
+                .unwrap_or_default(),
+            date: metadata.and_then(ExportMetadata::capture_date),
+            format: image_io::lowercase_extension(Path::new(suggested_name)),
+            bit_depth: self.bit_depth,
+        }
+    }
+}
+
+fn token_value(
+    name: &str,
+    width: Option<usize>,
+    context: &NamingContext,
+) -> Result<String, String> {
+    let date_part = |range: std::ops::Range<usize>| {
+        context
+            .date
+            .as_deref()
+            .and_then(|date| date.get(range))
+            .unwrap_or_default()
+            .to_string()
+    };
+    let value = match name {
+        "stem" => context.stem.clone(),
+        "roll" => context.roll.clone(),
+        "frame" => {
+            return Ok(context
+                .frame
+                .map(|frame| format!("{frame:0width$}", width = width.unwrap_or(0)))
+                .unwrap_or_default())
+        }
+        "preset" => context.preset.clone(),
+        "film" => context.film.clone(),
+        "date" => context.date.clone().unwrap_or_default(),
+        "year" => date_part(0..4),
+        "month" => date_part(5..7),
+        "day" => date_part(8..10),
+        "format" => context.format.clone(),
+        "bits" => context
+            .bit_depth
+            .map(|bits| bits.to_string())
+            .unwrap_or_default(),
+        _ => return Err(format!("unknown filename token {{{name}}}")),
+    };
+    if width.is_some() {
+        return Err(format!("filename token {{{name}}} does not take a width"));
+    }
+    Ok(value)
+}
+
+/// Expands `{token}` and `{token:03}` placeholders. Each value is sanitized
+/// on its own so a preset named `Portra 400/160` cannot add a folder level.
+pub fn render_template(template: &str, context: &NamingContext) -> Result<String, String> {
+    let mut output = String::with_capacity(template.len() + 16);
+    let mut rest = template;
+    while let Some(open) = rest.find(['{', '}']) {
+        output.push_str(&rest[..open]);
+        if rest[open..].starts_with('}') {
+            return Err(format!("unmatched '}}' in filename template: {template}"));
+        }
+        let close = rest[open..]
+            .find('}')
+            .map(|offset| open + offset)
+            .ok_or_else(|| format!("unclosed '{{' in filename template: {template}"))?;
+        let token = &rest[open + 1..close];
+        let (name, width) = match token.split_once(':') {
+            Some((name, spec)) => {
+                let width = spec
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|width| *width <= 9)
+                    .ok_or_else(|| format!("invalid width in filename token {{{token}}}"))?;
+                (name.trim(), Some(width))
+            }
+            None => (token.trim(), None),
+        };
+        output.push_str(&sanitize_component(&token_value(name, width, context)?));
+        rest = &rest[close + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Treats `jpg`/`jpeg` and `tif`/`tiff` as the same format.
+fn same_format(left: &str, right: &str) -> bool {
+    let canonical = |extension: &str| match extension {
+        "jpeg" => "jpg".to_string(),
+        "tiff" => "tif".to_string(),
+        other => other.to_string(),
+    };
+    canonical(left) == canonical(right)
+}
+
+/// Renders `template` into a complete file name. The export's own extension
+/// wins over one written in the template, so `{roll}.tif` still produces a
+/// `.png` when PNG is selected.
+pub fn render_file_name(
+    template: &str,
+    context: &NamingContext,
+    suggested_name: &str,
+) -> Result<String, String> {
+    let rendered = render_template(template.trim(), context)?;
+    let template_extension = image_io::lowercase_extension(Path::new(&rendered));
+    let stem = if EXPORT_EXTENSIONS.contains(&template_extension.as_str()) {
+        &rendered[..rendered.len() - template_extension.len() - 1]
+    } else {
+        rendered.as_str()
+    };
+    let extension = if same_format(&template_extension, &context.format) {
+        template_extension.clone()
+    } else {
+        context.format.clone()
+    };
+
+    let stem = stem.trim_matches(EDGE_SEPARATORS);
+    let stem = if stem.is_empty() {
+        Path::new(suggested_name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "converted_negative".to_string())
+    } else {
+        stem.to_string()
+    };
+    let name = if extension.is_empty() {
+        stem
+    } else {
+        format!("{stem}.{extension}")
+    };
+    Ok(sanitize_file_name(&name))
+}
+
+/// Renders a subfolder pattern into a relative path. Absolute patterns and
+/// `.`/`..` segments are refused; segments whose tokens all rendered empty
+/// are dropped rather than creating oddly named folders.
+pub fn render_subfolder(pattern: &str, context: &NamingContext) -> Result<PathBuf, String> {
+    let pattern = pattern.trim();
+    let bytes = pattern.as_bytes();
+    if pattern.starts_with(['/', '\\']) || (bytes.len() > 1 && bytes[1] == b':') {
+        return Err(format!("subfolder pattern must be relative: {pattern}"));
+    }
+    let mut subfolder = PathBuf::new();
+    for segment in pattern.split(['/', '\\']) {
+        if matches!(segment.trim(), "." | "..") {
+            return Err(format!(
+                "subfolder pattern must not leave the export folder: {pattern}"
+            ));
+        }
+        let rendered = sanitize_file_name(&render_template(segment, context)?);
+        let rendered = rendered.trim_matches(EDGE_SEPARATORS);
+        if !rendered.is_empty() {
+            subfolder.push(rendered);
+        }
+    }
+    Ok(subfolder)
+}
+
+/// Creates `subfolder` under `root` and returns it. Folders are made one at
+/// a time and each existing one is checked after following symlinks, so a
+/// link inside the export root cannot redirect exports, or the folders made
+/// for them, elsewhere.
+pub fn create_subfolder(root: &Path, subfolder: &Path) -> CommandResult<PathBuf> {
+    if subfolder
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(CommandError::InvalidPath(format!(
+            "export subfolder must stay inside the export folder: {}",
+            subfolder.display()
+        )));
+    }
+    let canonical_root = std::fs::canonicalize(root)
+        .map_err(|err| CommandError::io("resolve export folder", err))?;
+    let mut directory = root.to_path_buf();
+    for component in subfolder.components() {
+        directory.push(component);
+        if std::fs::symlink_metadata(&directory).is_err() {
+            match std::fs::create_dir(&directory) {
+                Ok(()) => continue,
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(CommandError::io("create export folder", err)),
+            }
+        }
+        let canonical = std::fs::canonicalize(&directory)
+            .map_err(|err| CommandError::io("resolve export folder", err))?;
+        if !canonical.starts_with(&canonical_root) {
+            return Err(CommandError::InvalidPath(format!(
+                "export subfolder leads outside the export folder: {}",
+                directory.display()
+            )));
+        }
+        if !canonical.is_dir() {
+            return Err(CommandError::InvalidPath(format!(
+                "export subfolder is not a folder: {}",
+                directory.display()
+            )));
+        }
+    }
+    Ok(directory)
+}
+
+/// Replaces characters no common filesystem accepts in a single name
+/// component, including both path separators.
+fn sanitize_component(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| match ch {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            ch if ch.is_control() => '_',
+            ch => ch,
+        })
+        .collect()
+}
+
+/// Makes `name` valid as a file name on Windows, macOS and Linux: no
+/// separators or reserved characters, no trailing dots or spaces, no device
+/// names such as `CON`, and at most 255 bytes with the extension kept.
+pub fn sanitize_file_name(name: &str) -> String {
+    let name = sanitize_component(name.trim());
+    let name = name.trim_end_matches(['.', ' ']);
+
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot > 0 && name.len() - dot <= 16 => (&name[..dot], &name[dot..]),
+        _ => (name, ""),
+    };
+    let device = stem.split('.').next().unwrap_or_default();
+    let mut stem = if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(device.trim_end()))
+    {
+        format!("_{stem}")
+    } else {
+        stem.to_string()
+    };
+
+    let budget = MAX_FILE_NAME_BYTES.saturating_sub(extension.len());
+    if stem.len() > budget {
+        let mut end = budget;
+        while !stem.is_char_boundary(end) {
+            end -= 1;
+        }
+        stem.truncate(end);
+    }
+    format!("{stem}{extension}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        create_subfolder, render_file_name, render_subfolder, render_template, sanitize_file_name,
+        NamingContext,
+    };
+    use std::path::{Path, PathBuf};
+
+    fn context() -> NamingContext {
+        NamingContext {
+            stem: "DSC_0042".to_string(),
+            roll: "2024-05 Kyoto".to_string(),
+            frame: Some(7),
+            preset: "Portra 400/160".to_string(),
+            film: "color".to_string(),
+            date: Some("2024-05-01".to_string()),
+            format: "tiff".to_string(),
+            bit_depth: Some(16),
+        }
+    }
+
+    #[test]
+    fn tokens_expand_with_widths_and_sanitized_values() {
+        assert_eq!(
+            render_template("{roll}_{frame:03}_{preset}", &context()).unwrap(),
+            "2024-05 Kyoto_007_Portra 400_160"
+        );
+        assert_eq!(
+            render_template(
+                "{year}{month}{day}-{stem}-{film}-{bits}bit.{format}",
+                &context()
+            )
+            .unwrap(),
+            "20240501-DSC_0042-color-16bit.tiff"
+        );
+        assert!(render_template("{nope}", &context()).is_err());
+        assert!(render_template("{roll:03}", &context()).is_err());
+        assert!(render_template("{frame", &context()).is_err());
+        assert!(render_template("frame}", &context()).is_err());
+    }
+
+    #[test]
+    fn file_names_keep_the_export_format_and_drop_empty_edges() {
+        let ctx = context();
+        assert_eq!(
+            render_file_name("{roll}_{frame:03}.tif", &ctx, "a.tiff").unwrap(),
+            "2024-05 Kyoto_007.tif"
+        );
+        let png = NamingContext {
+            format: "png".to_string(),
+            preset: String::new(),
+            ..context()
+        };
+        assert_eq!(
+            render_file_name("{roll}_{frame:03}_{preset}.tif", &png, "a.png").unwrap(),
+            "2024-05 Kyoto_007.png"
+        );
+        assert_eq!(
+            render_file_name("{preset}", &png, "DSC_0042_converted.png").unwrap(),
+            "DSC_0042_converted.png"
+        );
+        assert_eq!(
+            render_file_name("roll {frame} v1.2", &png, "a.png").unwrap(),
+            "roll 7 v1.2.png"
+        );
+    }
+
+    #[test]
+    fn names_are_valid_on_every_filesystem() {
+        assert_eq!(sanitize_file_name("a/b\\c:d*?.png"), "a_b_c_d__.png");
+        assert_eq!(sanitize_file_name("frame. . "), "frame");
+        assert_eq!(sanitize_file_name("con.tif"), "_con.tif");
+        assert_eq!(sanitize_file_name("LPT1.old.png"), "_LPT1.old.png");
+        assert_eq!(sanitize_file_name("console.png"), "console.png");
+        let long = format!("{}.tiff", "é".repeat(200));
+        let sanitized = sanitize_file_name(&long);
+        assert_eq!(sanitized.len(), 255);
+        assert!(sanitized.ends_with("é.tiff"));
+    }
+
+    #[test]
+    fn subfolders_render_per_segment_and_refuse_traversal() {
+        let ctx = context();
+        assert_eq!(
+            render_subfolder("{year}/{roll}/{format}", &ctx).unwrap(),
+            PathBuf::from("2024").join("2024-05 Kyoto").join("tiff")
+        );
+        let undated = NamingContext {
+            date: None,
+            ..context()
+        };
+        assert_eq!(
+            render_subfolder("{year}\\{preset}", &undated).unwrap(),
+            PathBuf::from("Portra 400_160")
+        );
+        assert_eq!(render_subfolder("", &ctx).unwrap(), PathBuf::new());
+        assert!(render_subfolder("../{roll}", &ctx).is_err());
+        assert!(render_subfolder("{roll}/./x", &ctx).is_err());
+        assert!(render_subfolder("/etc", &ctx).is_err());
+        assert!(render_subfolder("C:/scans", &ctx).is_err());
+    }
+
+    #[test]
+    fn subfolders_are_created_inside_the_root_only() {
+        let root = std::env::temp_dir().join(format!("nc-subfolder-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let created = create_subfolder(&root, Path::new("2024/roll")).unwrap();
+        assert!(created.is_dir());
+        assert_eq!(created, root.join("2024/roll"));
+        assert_eq!(
+            create_subfolder(&root, Path::new("../escape"))
+                .unwrap_err()
+                .code(),
+            "InvalidPath"
+        );
+
+        #[cfg(unix)]
+        {
+            let outside = std::env::temp_dir().join(format!("nc-outside-{}", std::process::id()));
+            std::fs::create_dir_all(&outside).unwrap();
+            std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+            assert_eq!(
+                create_subfolder(&root, Path::new("link/roll"))
+                    .unwrap_err()
+                    .code(),
+                "InvalidPath"
+            );
+            assert!(!outside.join("roll").exists());
+            std::fs::remove_dir_all(&outside).unwrap();
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
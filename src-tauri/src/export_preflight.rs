@@ -0,0 +1,410 @@
+//! Checks a batch export's target folder before the first frame is written,
+//! so a roll that cannot fit, or lands on a volume that will mangle its
+//! names, is caught up front instead of dying part way through.
+
+use crate::error::CommandError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+/// FAT32 cannot hold a file of 4 GiB or more.
+const FAT_MAX_FILE_BYTES: u64 = u32::MAX as u64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreflightWarning {
+    InsufficientSpace,
+    NotWritable,
+    /// The average file is larger than the filesystem allows.
+    FileTooLarge,
+    NameCollisions,
+    NameTooLong,
+}
+
+/// A planned export whose name differs only by case from an existing file or
+/// an earlier file of the same batch. Identical names are left to the
+/// conflict policy.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameCollision {
+    pub path: String,
+    pub existing: String,
+    pub within_batch: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreflight {
+    pub directory: String,
+    pub estimated_bytes: u64,
+    pub count: u32,
+    /// Space available to this user; `None` when the volume does not say.
+    pub free_bytes: Option<u64>,
+    pub writable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write_error: Option<CommandError>,
+    /// Such as `ext4`, `apfs`, `NTFS` or `exfat`, as the OS names it.
+    pub file_system: Option<String>,
+    /// Longest file name the volume accepts, in bytes (UTF-16 units on
+    /// Windows).
+    pub max_file_name_length: Option<u64>,
+    pub max_file_bytes: Option<u64>,
+    pub collisions: Vec<NameCollision>,
+    pub long_names: Vec<String>,
+    pub warnings: Vec<PreflightWarning>,
+}
+
+#[derive(Debug, Default)]
+struct VolumeInfo {
+    free_bytes: Option<u64>,
+    file_system: Option<String>,
+    max_file_name_length: Option<u64>,
+}
+
+/// Checks `directory` (already validated and in scope) for a batch of
+/// `count` files totalling about `estimated_bytes`, to be written at
+/// `planned` paths inside it.
+pub fn check(
+    directory: &Path,
+    estimated_bytes: u64,
+    count: u32,
+    planned: &[PathBuf],
+) -> ExportPreflight {
+    let volume = volume_info(directory);
+    let write_error = probe_writable(directory).err();
+    let max_file_bytes = volume
+        .file_system
+        .as_deref()
+        .filter(|name| is_fat(name))
+        .map(|_| FAT_MAX_FILE_BYTES);
+    let collisions = find_collisions(planned);
+    let long_names = planned
+        .iter()
+        .filter(|path| {
+            let length = path
+                .file_name()
+                .map(|name| name_length(&name.to_string_lossy()))
+                .unwrap_or(0);
+            volume
+                .max_file_name_length
+                .is_some_and(|limit| length > limit)
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+
+    let mut warnings = Vec::new();
+    if volume.free_bytes.is_some_and(|free| free < estimated_bytes) {
+        warnings.push(PreflightWarning::InsufficientSpace);
+    }
+    if write_error.is_some() {
+        warnings.push(PreflightWarning::NotWritable);
+    }
+    if count > 0 && max_file_bytes.is_some_and(|limit| estimated_bytes / u64::from(count) > limit) {
+        warnings.push(PreflightWarning::FileTooLarge);
+    }
+    if !collisions.is_empty() {
+        warnings.push(PreflightWarning::NameCollisions);
+    }
+    if !long_names.is_empty() {
+        warnings.push(PreflightWarning::NameTooLong);
+    }
+
+    ExportPreflight {
+        directory: directory.to_string_lossy().to_string(),
+        estimated_bytes,
+        count,
+        free_bytes: volume.free_bytes,
+        writable: write_error.is_none(),
+        write_error,
+        file_system: volume.file_system,
+        max_file_name_length: volume.max_file_name_length,
+        max_file_bytes,
+        collisions,
+        long_names,
+        warnings,
+    }
+}
+
+fn is_fat(file_system: &str) -> bool {
+    matches!(
+        file_system.to_ascii_lowercase().as_str(),
+        "vfat" | "fat" | "fat32" | "msdos"
+    )
+}
+
+#[cfg(windows)]
+fn name_length(name: &str) -> u64 {
+    name.encode_utf16().count() as u64
+}
+
+#[cfg(not(windows))]
+fn name_length(name: &str) -> u64 {
+    name.len() as u64
+}
+
+/// Creates and removes a scratch file, which catches read-only mounts, ACLs
+/// and full quotas that the folder's permission bits do not show.
+fn probe_writable(directory: &Path) -> Result<(), CommandError> {
+    let probe = directory.join(format!(
+        ".negative-converter-preflight-{}",
+        std::process::id()
+    ));
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .map_err(|err| CommandError::io("write to export folder", err))?;
+    std::fs::remove_file(&probe).map_err(|err| CommandError::io("remove preflight file", err))
+}
+
+/// Names that differ only by case overwrite each other on case-insensitive
+/// volumes (macOS and Windows defaults, exFAT cards, most NAS shares) and
+/// break when a roll is copied to one later.
+fn find_collisions(planned: &[PathBuf]) -> Vec<NameCollision> {
+    let mut existing_by_folder: HashMap<PathBuf, HashMap<String, String>> = HashMap::new();
+    let mut planned_names: HashMap<String, (String, String)> = HashMap::new();
+    let mut collisions = Vec::new();
+    for path in planned {
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        let folder = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let key = name.to_lowercase();
+        let display = path.to_string_lossy().to_string();
+
+        let folder_key = format!("{}/{key}", folder.to_string_lossy().to_lowercase());
+        if let Some((earlier, earlier_name)) = planned_names.get(&folder_key) {
+            if *earlier_name != name {
+                collisions.push(NameCollision {
+                    path: display,
+                    existing: earlier.clone(),
+                    within_batch: true,
+                });
+            }
+            continue;
+        }
+        planned_names.insert(folder_key, (display.clone(), name.clone()));
+
+        let existing = existing_by_folder
+            .entry(folder.clone())
+            .or_insert_with(|| list_folder(&folder));
+        if let Some(existing) = existing.get(&key).filter(|existing| **existing != name) {
+            collisions.push(NameCollision {
+                path: display,
+                existing: folder.join(existing).to_string_lossy().to_string(),
+                within_batch: false,
+            });
+        }
+    }
+    collisions
+}
+
+/// Existing entries of `folder` by lowercased name. A folder that does not
+/// exist yet (a subfolder the batch will create) is empty.
+fn list_folder(folder: &Path) -> HashMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return HashMap::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            (name.to_lowercase(), name)
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn volume_info(directory: &Path) -> VolumeInfo {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = CString::new(directory.as_os_str().as_bytes()) else {
+        return VolumeInfo::default();
+    };
+    let mut info = VolumeInfo::default();
+    // SAFETY: `path` is NUL-terminated and `stats` is a plain C struct that
+    // statvfs fills in; it is only read after the call succeeds.
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } == 0 {
+        info.free_bytes = Some((stats.f_bavail as u64).saturating_mul(stats.f_frsize as u64));
+        info.max_file_name_length = Some(stats.f_namemax as u64);
+    }
+    info.file_system = file_system_name(&path);
+    info
+}
+
+#[cfg(target_os = "linux")]
+fn file_system_name(path: &std::ffi::CStr) -> Option<String> {
+    // SAFETY: as for statvfs above.
+    let mut stats: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    // Magic numbers from linux/magic.h and the filesystems' own headers.
+    let name = match stats.f_type as u32 {
+        0xEF53 => "ext4",
+        0x9123_683E => "btrfs",
+        0x5846_5342 => "xfs",
+        0x2FC1_2FC1 => "zfs",
+        0x4D44 => "vfat",
+        0x2011_BAB0 => "exfat",
+        0x5346_544E | 0x7366_746E => "ntfs",
+        0x6969 => "nfs",
+        0xFF53_4D42 | 0xFE53_4D42 | 0x517B => "smb",
+        0x0102_1994 => "tmpfs",
+        0x6573_5546 => "fuse",
+        0x794C_7630 => "overlay",
+        0xF15F => "ecryptfs",
+        0x9660 => "iso9660",
+        other => return Some(format!("0x{other:x}")),
+    };
+    Some(name.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn file_system_name(path: &std::ffi::CStr) -> Option<String> {
+    // SAFETY: as for statvfs above.
+    let mut stats: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    // SAFETY: statfs NUL-terminates `f_fstypename` within its buffer.
+    let name = unsafe { std::ffi::CStr::from_ptr(stats.f_fstypename.as_ptr()) };
+    Some(name.to_string_lossy().to_string())
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn file_system_name(_path: &std::ffi::CStr) -> Option<String> {
+    None
+}
+
+#[cfg(windows)]
+fn volume_info(directory: &Path) -> VolumeInfo {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetDiskFreeSpaceExW, GetVolumeInformationW, GetVolumePathNameW,
+    };
+
+    let path: Vec<u16> = directory
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut info = VolumeInfo::default();
+
+    let mut free = 0u64;
+    // SAFETY: `path` is NUL-terminated; the out pointers are valid or null.
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            path.as_ptr(),
+            &mut free,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    if ok != 0 {
+        info.free_bytes = Some(free);
+    }
+
+    let mut root = [0u16; 261];
+    // SAFETY: `root` is as long as the length passed.
+    if unsafe { GetVolumePathNameW(path.as_ptr(), root.as_mut_ptr(), root.len() as u32) } == 0 {
+        return info;
+    }
+    let mut max_component = 0u32;
+    let mut file_system = [0u16; 261];
+    // SAFETY: `root` was NUL-terminated by GetVolumePathNameW and the
+    // buffers are as long as the lengths passed.
+    let ok = unsafe {
+        GetVolumeInformationW(
+            root.as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            &mut max_component,
+            std::ptr::null_mut(),
+            file_system.as_mut_ptr(),
+            file_system.len() as u32,
+        )
+    };
+    if ok != 0 {
+        let length = file_system.iter().position(|&unit| unit == 0).unwrap_or(0);
+        info.file_system = Some(String::from_utf16_lossy(&file_system[..length]));
+        info.max_file_name_length = Some(u64::from(max_component));
+    }
+    info
+}
+
+#[cfg(not(any(unix, windows)))]
+fn volume_info(_directory: &Path) -> VolumeInfo {
+    VolumeInfo::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, PreflightWarning};
+
+    #[test]
+    fn reports_space_and_case_insensitive_collisions() {
+        let dir = std::env::temp_dir().join(format!("nc-preflight-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Roll_001.tif"), b"old").unwrap();
+        std::fs::write(dir.join("roll_003.tif"), b"old").unwrap();
+        let planned = [
+            dir.join("roll_001.tif"),
+            dir.join("roll_002.tif"),
+            dir.join("ROLL_002.TIF"),
+            dir.join("roll_002.tif"),
+            dir.join("roll_003.tif"),
+            dir.join("new").join("roll_001.tif"),
+        ];
+
+        let report = check(&dir, 1024, 6, &planned);
+        assert!(report.writable);
+        assert!(report.free_bytes.is_some_and(|free| free > 0));
+        assert!(report
+            .max_file_name_length
+            .is_some_and(|limit| limit >= 255));
+        assert!(report.file_system.is_some());
+        let collisions = report
+            .collisions
+            .iter()
+            .map(|collision| {
+                (
+                    collision.path.clone(),
+                    collision.existing.clone(),
+                    collision.within_batch,
+                )
+            })
+            .collect::<Vec<_>>();
+        let show = |path: std::path::PathBuf| path.to_string_lossy().to_string();
+        assert_eq!(
+            collisions,
+            vec![
+                (
+                    show(dir.join("roll_001.tif")),
+                    show(dir.join("Roll_001.tif")),
+                    false
+                ),
+                (
+                    show(dir.join("ROLL_002.TIF")),
+                    show(dir.join("roll_002.tif")),
+                    true
+                ),
+            ]
+        );
+        assert_eq!(report.warnings, vec![PreflightWarning::NameCollisions]);
+        // The writability probe cleans up after itself.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        let huge = check(&dir, u64::MAX, 1, &[]);
+        assert_eq!(huge.warnings, vec![PreflightWarning::InsufficientSpace]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,255 @@
+use crate::atomic_write::{PartialFile, WriteReport};
+use crate::error::{CommandError, CommandResult};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// An upload that has not received a chunk for this long is taken to be
+/// abandoned (e.g. the webview reloaded mid-export) and its temp file removed.
+const ABANDONED_AFTER: Duration = Duration::from_secs(10 * 60);
+
+struct Upload {
+    file: PartialFile,
+    touched: Instant,
+}
+
+/// Open export files that the webview fills with raw-body chunks, so large
+/// payloads never have to exist as one base64 string on either side. Chunks
+/// land in a temp file; the target only appears once the upload finishes.
+#[derive(Default)]
+pub struct ExportUploads {
+    next_id: AtomicU64,
+    sessions: Mutex<HashMap<u64, Upload>>,
+    /// Chunks still in flight when the webview aborts an upload are reported
+    /// as `Cancelled` rather than as an unknown upload. Forgotten once a later
+    /// upload finishes, by which time nothing can still be in flight for them.
+    aborted: Mutex<HashSet<u64>>,
+}
+
+impl ExportUploads {
+    pub fn begin(&self, path: PathBuf) -> CommandResult<u64> {
+        self.abort_abandoned(Instant::now());
+        let file = PartialFile::create(path)?;
+        let upload_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.sessions
+            .lock()
+            .map_err(|_| CommandError::Internal("export upload state is poisoned".to_string()))?
+            .insert(
+                upload_id,
+                Upload {
+                    file,
+                    touched: Instant::now(),
+                },
+            );
+        Ok(upload_id)
+    }
+
+    pub fn append(&self, upload_id: u64, chunk: &[u8]) -> CommandResult<u64> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .map_err(|_| CommandError::Internal("export upload state is poisoned".to_string()))?;
+        let upload = sessions
+            .get_mut(&upload_id)
+            .ok_or_else(|| self.missing(upload_id))?;
+        upload.touched = Instant::now();
+        upload.file.write(chunk)
+    }
+
+    pub fn target_path(&self, upload_id: u64) -> CommandResult<PathBuf> {
+        self.sessions
+            .lock()
+            .map_err(|_| CommandError::Internal("export upload state is poisoned".to_string()))?
+            .get(&upload_id)
+            .map(|upload| upload.file.final_path().to_path_buf())
+            .ok_or_else(|| self.missing(upload_id))
+    }
+
+    /// Moves the finished upload into place.
+    pub fn finish(&self, upload_id: u64, verify: bool) -> CommandResult<(PathBuf, WriteReport)> {
+        let upload = self.take(upload_id)?;
+        self.forget_aborted_before(upload_id);
+        let path = upload.final_path().to_path_buf();
+        Ok((path, upload.commit(verify)?))
+    }
+
+    /// Ends the upload without moving it into place and returns its bytes,
+    /// for callers that still have to rewrite them (e.g. to embed metadata).
+    pub fn finish_into_bytes(&self, upload_id: u64) -> CommandResult<(PathBuf, Vec<u8>)> {
+        let upload = self.take(upload_id)?;
+        self.forget_aborted_before(upload_id);
+        upload.into_bytes()
+    }
+
+    pub fn abort(&self, upload_id: u64) -> CommandResult<()> {
+        let upload = self.take(upload_id)?;
+        if let Ok(mut aborted) = self.aborted.lock() {
+            aborted.insert(upload_id);
+        }
+        upload.abort()
+    }
+
+    /// Removes the temp files of every upload still open; run when the app
+    /// exits so abandoned uploads leave nothing behind.
+    pub fn abort_all(&self) {
+        let uploads: Vec<Upload> = match self.sessions.lock() {
+            Ok(mut sessions) => sessions.drain().map(|(_, upload)| upload).collect(),
+            Err(_) => return,
+        };
+        for upload in uploads {
+            let _ = upload.file.abort();
+        }
+    }
+
+    fn abort_abandoned(&self, now: Instant) {
+        let abandoned: Vec<(u64, Upload)> = match self.sessions.lock() {
+            Ok(mut sessions) => {
+                let ids: Vec<u64> = sessions
+                    .iter()
+                    .filter(|(_, upload)| now.duration_since(upload.touched) >= ABANDONED_AFTER)
+                    .map(|(id, _)| *id)
+                    .collect();
+                ids.into_iter()
+                    .filter_map(|id| sessions.remove(&id).map(|upload| (id, upload)))
+                    .collect()
+            }
+            Err(_) => return,
+        };
+        for (upload_id, upload) in abandoned {
+            if let Ok(mut aborted) = self.aborted.lock() {
+                aborted.insert(upload_id);
+            }
+            let _ = upload.file.abort();
+        }
+    }
+
+    fn forget_aborted_before(&self, upload_id: u64) {
+        if let Ok(mut aborted) = self.aborted.lock() {
+            aborted.retain(|id| *id > upload_id);
+        }
+    }
+
+    fn take(&self, upload_id: u64) -> CommandResult<PartialFile> {
+        self.sessions
+            .lock()
+            .map_err(|_| CommandError::Internal("export upload state is poisoned".to_string()))?
+            .remove(&upload_id)
+            .map(|upload| upload.file)
+            .ok_or_else(|| self.missing(upload_id))
+    }
+
+    fn missing(&self, upload_id: u64) -> CommandError {
+        let aborted = self
+            .aborted
+            .lock()
+            .map(|aborted| aborted.contains(&upload_id))
+            .unwrap_or(false);
+        if aborted {
+            CommandError::Cancelled(format!("export upload {upload_id} was cancelled"))
+        } else {
+            CommandError::Internal(format!("unknown export upload: {upload_id}"))
+        }
+    }
+}
+
+impl Drop for ExportUploads {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportUploads, ABANDONED_AFTER};
+    use std::time::Instant;
+
+    fn scratch_path(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "negative-converter-upload-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn chunks_are_appended_in_order() {
+        let uploads = ExportUploads::default();
+        let path = scratch_path("chunks.bin");
+        let id = uploads.begin(path.clone()).unwrap();
+        assert_eq!(uploads.append(id, b"abc").unwrap(), 3);
+        assert_eq!(uploads.append(id, b"def").unwrap(), 6);
+        assert!(!path.exists());
+        let (finished_path, report) = uploads.finish(id, true).unwrap();
+        assert_eq!(finished_path, path);
+        assert_eq!(report.bytes_written, 6);
+        assert_eq!(std::fs::read(&path).unwrap(), b"abcdef");
+        assert!(uploads.append(id, b"late").is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn abort_removes_partial_file() {
+        let uploads = ExportUploads::default();
+        let path = scratch_path("aborted.bin");
+        let id = uploads.begin(path.clone()).unwrap();
+        uploads.append(id, b"partial").unwrap();
+        uploads.abort(id).unwrap();
+        assert!(!path.exists());
+        assert_eq!(uploads.finish(id, false).unwrap_err().code(), "Cancelled");
+        assert_eq!(uploads.append(id + 1, b"x").unwrap_err().code(), "Internal");
+    }
+
+    #[test]
+    fn unfinished_bytes_can_be_taken_for_rewriting() {
+        let uploads = ExportUploads::default();
+        let path = scratch_path("rewrite.png");
+        let id = uploads.begin(path.clone()).unwrap();
+        uploads.append(id, b"png bytes").unwrap();
+        let (target, bytes) = uploads.finish_into_bytes(id).unwrap();
+        assert_eq!(target, path);
+        assert_eq!(bytes, b"png bytes");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn tombstones_are_forgotten_once_a_later_upload_finishes() {
+        let uploads = ExportUploads::default();
+        let aborted = uploads.begin(scratch_path("tombstone-a.bin")).unwrap();
+        uploads.abort(aborted).unwrap();
+        let path = scratch_path("tombstone-b.bin");
+        let finished = uploads.begin(path.clone()).unwrap();
+        assert_eq!(
+            uploads.append(aborted, b"x").unwrap_err().code(),
+            "Cancelled"
+        );
+        uploads.finish(finished, false).unwrap();
+        assert!(uploads.aborted.lock().unwrap().is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn abandoned_uploads_leave_no_temp_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "negative-converter-upload-abandoned-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let uploads = ExportUploads::default();
+        let stale = uploads.begin(dir.join("stale.bin")).unwrap();
+        uploads.append(stale, b"stale").unwrap();
+        uploads.abort_abandoned(Instant::now() + ABANDONED_AFTER);
+        assert_eq!(uploads.append(stale, b"x").unwrap_err().code(), "Cancelled");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let open = uploads.begin(dir.join("open.bin")).unwrap();
+        uploads.append(open, b"open").unwrap();
+        drop(uploads);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
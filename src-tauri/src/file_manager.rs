@@ -0,0 +1,175 @@
+//! Showing an exported file in Finder, Explorer or the Linux file manager,
+//! and opening it in the app the OS associates with it.
+
+use crate::error::{CommandError, CommandResult};
+use std::path::Path;
+use std::process::Command;
+
+/// Files the webview may open with their default app: exports only, so a
+/// compromised webview cannot launch anything executable.
+const OPENABLE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "zip"];
+
+/// Starts `command` without waiting for it. A background thread waits on
+/// the child so it does not linger as a zombie once it exits.
+fn launch(command: &mut Command, what: &str) -> CommandResult<()> {
+    let mut child = command
+        .spawn()
+        .map_err(|err| CommandError::Io(format!("failed to launch {what}: {err}")))?;
+    let reaper = std::thread::Builder::new()
+        .name("file-manager-child".to_string())
+        .spawn(move || {
+            let _ = child.wait();
+        });
+    if let Err(err) = reaper {
+        eprintln!("[file-manager] Could not wait on {what}: {err}");
+    }
+    Ok(())
+}
+
+/// Opens `path` (a file or folder) in whatever the OS associates with it.
+fn open_with_system(path: &Path) -> CommandResult<()> {
+    #[cfg(target_os = "macos")]
+    {
+        return launch(Command::new("open").arg(path), "open");
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Explorer hands files to their default app without cmd's quoting.
+        return launch(Command::new("explorer").arg(path), "Explorer");
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        return launch(Command::new("xdg-open").arg(path), "xdg-open");
+    }
+
+    #[allow(unreachable_code)]
+    Err(CommandError::Internal(
+        "unsupported platform for opening files".to_string(),
+    ))
+}
+
+/// Opens the folder holding `path` with `path` selected. On Linux this goes
+/// through the FileManager1 D-Bus interface (Nautilus, Dolphin, Nemo, ...);
+/// without one it opens the folder instead.
+pub fn reveal(path: &Path) -> CommandResult<()> {
+    #[cfg(target_os = "macos")]
+    {
+        return launch(Command::new("open").arg("-R").arg(path), "Finder");
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Explorer exits non-zero even when it worked, so only launching is checked.
+        return launch(
+            Command::new("explorer").arg("/select,").arg(path),
+            "Explorer",
+        );
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        if show_items_over_dbus(path) {
+            return Ok(());
+        }
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(path);
+        return open_with_system(parent);
+    }
+
+    #[allow(unreachable_code)]
+    Err(CommandError::Internal(
+        "unsupported platform for revealing files".to_string(),
+    ))
+}
+
+/// Opens an exported file in its default app.
+pub fn open_with_default_app(path: &Path) -> CommandResult<()> {
+    let openable = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            OPENABLE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+        });
+    if !openable {
+        return Err(CommandError::InvalidPath(format!(
+            "not an exported file: {}",
+            path.display()
+        )));
+    }
+    open_with_system(path)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show_items_over_dbus(path: &Path) -> bool {
+    let status = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--reply-timeout=5000",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+        ])
+        .arg(format!("array:string:{}", file_uri(path)))
+        .arg("string:")
+        .output();
+    matches!(status, Ok(output) if output.status.success())
+}
+
+#[cfg(all(unix, any(not(target_os = "macos"), test)))]
+fn file_uri(path: &Path) -> String {
+    use percent_encoding::{percent_encode, AsciiSet, NON_ALPHANUMERIC};
+    use std::os::unix::ffi::OsStrExt;
+
+    // Unreserved characters and `/` stay as they are; dbus-send would also
+    // split an `array:string:` argument at commas.
+    const PATH: &AsciiSet = &NON_ALPHANUMERIC
+        .remove(b'/')
+        .remove(b'-')
+        .remove(b'_')
+        .remove(b'.')
+        .remove(b'~');
+    format!(
+        "file://{}",
+        percent_encode(path.as_os_str().as_bytes(), PATH)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::open_with_default_app;
+    use std::path::Path;
+
+    #[cfg(unix)]
+    #[test]
+    fn file_uris_escape_everything_but_the_path_structure() {
+        assert_eq!(
+            super::file_uri(Path::new("/home/me/Roll 12, 2024/frame_01.tif")),
+            "file:///home/me/Roll%2012%2C%202024/frame_01.tif"
+        );
+        assert_eq!(
+            super::file_uri(Path::new("/tmp/フィルム.png")),
+            "file:///tmp/%E3%83%95%E3%82%A3%E3%83%AB%E3%83%A0.png"
+        );
+    }
+
+    #[test]
+    fn only_exports_open_with_their_default_app() {
+        for path in [
+            "/out/run.sh",
+            "/out/setup.exe",
+            "/out/App.app",
+            "/out/frame",
+        ] {
+            assert_eq!(
+                open_with_default_app(Path::new(path)).unwrap_err().code(),
+                "InvalidPath"
+            );
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Reading and writing frames outside the webview, for the headless paths.
+
+use crate::engine::Image16;
+use std::path::Path;
+
+const RAW_LIKE_EXTENSIONS: &[&str] = &[
+    "cr2", "cr3", "crw", "nef", "nrw", "arw", "dng", "raf", "raw", "rw2", "pef", "srw", "3fr",
+    "mef", "orf", "rwl", "iiq", "x3f", "mrw", "kdc", "dcr", "tif", "tiff",
+];
+
+/// Everything the file picker offers: RAW-like files plus the formats the
+/// webview decodes itself.
+pub const INPUT_IMAGE_EXTENSIONS: &[&str] = &[
+    "cr2", "cr3", "crw", "nef", "nrw", "arw", "dng", "raf", "raw", "rw2", "pef", "srw", "3fr",
+    "mef", "orf", "rwl", "iiq", "x3f", "mrw", "kdc", "dcr", "tif", "tiff", "png", "jpg", "jpeg",
+    "gif", "webp", "bmp",
+];
+
+pub fn lowercase_extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+pub fn is_raw_like_path(path: &Path) -> bool {
+    RAW_LIKE_EXTENSIONS.contains(&lowercase_extension(path).as_str())
+}
+
+/// Decodes an 8- or 16-bit PNG of any color type into 16-bit RGBA.
+pub fn decode_png(bytes: &[u8]) -> Result<Image16, String> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|err| format!("read PNG header failed: {err}"))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|err| format!("decode PNG failed: {err}"))?;
+    let buffer = &buffer[..info.buffer_size()];
+
+    let samples: Vec<u16> = match info.bit_depth {
+        png::BitDepth::Sixteen => buffer
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect(),
+        png::BitDepth::Eight => buffer.iter().map(|value| *value as u16 * 257).collect(),
+        depth => return Err(format!("unsupported PNG bit depth: {depth:?}")),
+    };
+
+    let pixel_count = info.width as usize * info.height as usize;
+    let mut data = Vec::with_capacity(pixel_count * 4);
+    match info.color_type {
+        png::ColorType::Rgba => data = samples,
+        png::ColorType::Rgb => {
+            for px in samples.chunks_exact(3) {
+                data.extend_from_slice(&[px[0], px[1], px[2], u16::MAX]);
+            }
+        }
+        png::ColorType::GrayscaleAlpha => {
+            for px in samples.chunks_exact(2) {
+                data.extend_from_slice(&[px[0], px[0], px[0], px[1]]);
+            }
+        }
+        png::ColorType::Grayscale => {
+            for value in samples {
+                data.extend_from_slice(&[value, value, value, u16::MAX]);
+            }
+        }
+        png::ColorType::Indexed => return Err("indexed PNG was not expanded".to_string()),
+    }
+
+    Image16::new(info.width, info.height, data)
+}
+
+/// Decodes an 8- or 16-bit gray/RGB(A) TIFF into 16-bit RGBA.
+pub fn decode_tiff(bytes: &[u8]) -> Result<Image16, String> {
+    use tiff::decoder::{Decoder, DecodingResult, Limits};
+
+    let mut decoder = Decoder::new(std::io::Cursor::new(bytes))
+        .map_err(|err| format!("read TIFF header failed: {err}"))?
+        .with_limits(Limits::unlimited());
+    let (width, height) = decoder
+        .dimensions()
+        .map_err(|err| format!("read TIFF header failed: {err}"))?;
+    let color_type = decoder
+        .colortype()
+        .map_err(|err| format!("read TIFF header failed: {err}"))?;
+    let samples: Vec<u16> = match decoder
+        .read_image()
+        .map_err(|err| format!("decode TIFF failed: {err}"))?
+    {
+        DecodingResult::U8(values) => values.into_iter().map(|value| value as u16 * 257).collect(),
+        DecodingResult::U16(values) => values,
+        _ => return Err(format!("unsupported TIFF sample format: {color_type:?}")),
+    };
+
+    let channels = match color_type {
+        tiff::ColorType::Gray(8 | 16) => 1,
+        tiff::ColorType::GrayA(8 | 16) => 2,
+        tiff::ColorType::RGB(8 | 16) => 3,
+        tiff::ColorType::RGBA(8 | 16) => 4,
+        other => return Err(format!("unsupported TIFF color type: {other:?}")),
+    };
+    let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+    for px in samples.chunks_exact(channels) {
+        match channels {
+            1 => data.extend_from_slice(&[px[0], px[0], px[0], u16::MAX]),
+            2 => data.extend_from_slice(&[px[0], px[0], px[0], px[1]]),
+            3 => data.extend_from_slice(&[px[0], px[1], px[2], u16::MAX]),
+            _ => data.extend_from_slice(px),
+        }
+    }
+    Image16::new(width, height, data)
+}
+
+/// Decodes a gray or RGB JPEG into 16-bit RGBA.
+pub fn decode_jpeg(bytes: &[u8]) -> Result<Image16, String> {
+    use jpeg_decoder::{Decoder, PixelFormat};
+
+    let mut decoder = Decoder::new(bytes);
+    let pixels = decoder
+        .decode()
+        .map_err(|err| format!("decode JPEG failed: {err}"))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| "read JPEG header failed".to_string())?;
+    let widen = |value: u8| value as u16 * 257;
+    let mut data = Vec::with_capacity(info.width as usize * info.height as usize * 4);
+    match info.pixel_format {
+        PixelFormat::L8 => {
+            for value in pixels {
+                let value = widen(value);
+                data.extend_from_slice(&[value, value, value, u16::MAX]);
+            }
+        }
+        PixelFormat::L16 => {
+            for pair in pixels.chunks_exact(2) {
+                let value = u16::from_ne_bytes([pair[0], pair[1]]);
+                data.extend_from_slice(&[value, value, value, u16::MAX]);
+            }
+        }
+        PixelFormat::RGB24 => {
+            for px in pixels.chunks_exact(3) {
+                data.extend_from_slice(&[widen(px[0]), widen(px[1]), widen(px[2]), u16::MAX]);
+            }
+        }
+        PixelFormat::CMYK32 => return Err("CMYK JPEGs are not supported".to_string()),
+    }
+    Image16::new(info.width as u32, info.height as u32, data)
+}
+
+/// Encodes as 16-bit RGB PNG. Alpha is dropped; converted frames are opaque.
+pub fn encode_png16(image: &Image16) -> Result<Vec<u8>, String> {
+    let mut output = Vec::new();
+    let mut encoder = png::Encoder::new(&mut output, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|err| format!("write PNG header failed: {err}"))?;
+
+    let mut bytes = Vec::with_capacity(image.data.len() / 4 * 6);
+    for px in image.data.chunks_exact(4) {
+        for value in &px[..3] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    writer
+        .write_image_data(&bytes)
+        .map_err(|err| format!("encode PNG failed: {err}"))?;
+    writer
+        .finish()
+        .map_err(|err| format!("encode PNG failed: {err}"))?;
+    Ok(output)
+}
+
+/// Loads a scanned frame from disk as 16-bit RGBA.
+pub fn read_input_image(path: &Path) -> Result<Image16, String> {
+    let extension = lowercase_extension(path);
+    if matches!(extension.as_str(), "png" | "jpg" | "jpeg") {
+        let bytes = std::fs::read(path).map_err(|err| format!("read file failed: {err}"))?;
+        return if extension == "png" {
+            decode_png(&bytes)
+        } else {
+            decode_jpeg(&bytes)
+        };
+    }
+    if is_raw_like_path(path) {
+        return crate::raw::decode_raw_file(path)?.to_display_image16();
+    }
+    Err(format!("unsupported input format: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_png, decode_tiff, encode_png16, is_raw_like_path, read_input_image};
+    use crate::engine::Image16;
+    use std::path::Path;
+
+    #[test]
+    fn png16_round_trips_rgb_and_fills_alpha() {
+        let image = Image16::new(2, 1, vec![1, 2, 3, 9, 65535, 32768, 0, 9]).unwrap();
+        let decoded = decode_png(&encode_png16(&image).unwrap()).unwrap();
+        assert_eq!(decoded.data, vec![1, 2, 3, 65535, 65535, 32768, 0, 65535]);
+    }
+
+    #[test]
+    fn tiff16_rgb_decodes_with_opaque_alpha() {
+        let mut bytes = Vec::new();
+        tiff::encoder::TiffEncoder::new(std::io::Cursor::new(&mut bytes))
+            .unwrap()
+            .write_image::<tiff::encoder::colortype::RGB16>(1, 2, &[1, 2, 3, 40000, 50000, 60000])
+            .unwrap();
+        let decoded = decode_tiff(&bytes).unwrap();
+        assert_eq!((decoded.width, decoded.height), (1, 2));
+        assert_eq!(
+            decoded.data,
+            vec![1, 2, 3, 65535, 40000, 50000, 60000, 65535]
+        );
+    }
+
+    #[test]
+    fn raw_like_detection_is_case_insensitive() {
+        assert!(is_raw_like_path(Path::new("/rolls/frame_01.NEF")));
+        assert!(is_raw_like_path(Path::new("scan.tif")));
+        assert!(!is_raw_like_path(Path::new("scan.png")));
+    }
+
+    #[test]
+    fn jpeg_scans_decode_to_opaque_16_bit() {
+        let bytes = include_bytes!("../../negative2positive/test-fixtures/negative-sample.jpg");
+        let path = std::env::temp_dir().join(format!("nc-scan-{}.JPG", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let decoded = read_input_image(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((decoded.width, decoded.height), (1400, 1284));
+        assert!(decoded.data.chunks_exact(4).all(|px| px[3] == u16::MAX));
+        assert!(decoded.data.iter().all(|value| value % 257 == 0));
+    }
+}
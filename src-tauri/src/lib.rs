@@ -1,16 +1,131 @@
+mod atomic_write;
+mod autosave;
+mod cli;
+pub mod engine;
+mod error;
+mod export_conflict;
+mod export_jobs;
+mod export_naming;
+mod export_preflight;
+mod export_upload;
+mod file_manager;
+mod image_io;
+mod metadata;
+mod preset_store;
+mod profiles;
+mod project;
+mod raw;
+mod roll_film_base;
+mod sidecar;
+mod tiff_writer;
+mod write_scope;
+mod zip_export;
+
+use atomic_write::WriteReport;
+use autosave::Autosave;
 use base64::Engine;
-use serde::Serialize;
+pub use cli::run_cli;
+use engine::adjust::FilmBaseMethod;
+use engine::auto_frame::{self, AutoFrameSettings, FrameDetection};
+use engine::film_base::{self, FilmBaseDetection};
+use engine::{FrameSettings, Image16};
+use error::{CommandError, CommandResult};
+use export_conflict::{ConflictAnswer, ConflictDecision, ConflictPolicy, ExportConflict};
+use export_jobs::{EventSink, ExportJobs, ExportStage, JobFile};
+use export_naming::ExportNaming;
+use export_preflight::ExportPreflight;
+use export_upload::ExportUploads;
+use metadata::ExportMetadata;
+use preset_store::{PresetDraft, PresetImport, PresetStore, UserPreset};
+use profiles::{ProfileInfo, ProfileStore};
+use roll_film_base::{RollFilmBase, RollFrame};
+use serde::{Deserialize, Serialize};
 #[cfg(target_os = "linux")]
 use std::io::ErrorKind;
-use std::path::PathBuf;
-#[cfg(target_os = "linux")]
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-
-#[derive(Serialize)]
+use std::sync::Arc;
+use tauri::ipc::{InvokeBody, Request, Response};
+use tauri::{AppHandle, Emitter, Manager, State};
+use write_scope::WriteScopes;
+use zip_export::ZipExports;
+
+const UPLOAD_ID_HEADER: &str = "x-upload-id";
+const FRAME_WIDTH_HEADER: &str = "x-frame-width";
+const FRAME_HEIGHT_HEADER: &str = "x-frame-height";
+const FRAME_SETTINGS_HEADER: &str = "x-frame-settings";
+const BORDER_BUFFER_HEADER: &str = "x-border-buffer";
+const AUTO_FRAME_SETTINGS_HEADER: &str = "x-auto-frame-settings";
+const TIFF_OPTIONS_HEADER: &str = "x-tiff-options";
+const ZIP_ENTRY_HEADER: &str = "x-zip-entry";
+const EXPORT_ITEM_HEADER: &str = "x-export-item";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 struct SaveResult {
     saved: bool,
     path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes_written: Option<u64>,
+    /// SHA-256 of the written file, as lowercase hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<String>,
+    /// The conflict policy kept the existing file at `path`.
+    skipped: bool,
+    /// What the user answered when the policy asked about an existing file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflict: Option<ConflictAnswer>,
+}
+
+impl SaveResult {
+    fn cancelled() -> Self {
+        Self {
+            saved: false,
+            path: None,
+            bytes_written: None,
+            checksum: None,
+            skipped: false,
+            conflict: None,
+        }
+    }
+
+    fn saved(path: &Path) -> Self {
+        Self {
+            saved: true,
+            path: Some(path.to_string_lossy().to_string()),
+            ..Self::cancelled()
+        }
+    }
+
+    fn skipped(decision: &ConflictDecision) -> Self {
+        Self {
+            path: Some(decision.path.to_string_lossy().to_string()),
+            skipped: true,
+            conflict: decision.answer,
+            ..Self::cancelled()
+        }
+    }
+
+    fn with_conflict(self, conflict: Option<ConflictAnswer>) -> Self {
+        Self { conflict, ..self }
+    }
+
+    fn written(path: &Path, report: WriteReport) -> Self {
+        Self {
+            bytes_written: Some(report.bytes_written),
+            checksum: Some(report.checksum),
+            ..Self::saved(path)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InputFileEntry {
+    path: String,
+    name: String,
+    size: u64,
+    last_modified: u64,
 }
 
 #[tauri::command]
@@ -33,29 +148,57 @@ fn normalize_export_path(mut path: PathBuf, suggested_name: &str) -> PathBuf {
     path
 }
 
-fn decode_export_bytes(bytes_base64: &str) -> Result<Vec<u8>, String> {
+fn decode_export_bytes(bytes_base64: &str) -> CommandResult<Vec<u8>> {
     base64::engine::general_purpose::STANDARD
         .decode(bytes_base64)
-        .map_err(|err| format!("decode base64 failed: {err}"))
+        .map_err(|err| CommandError::DecodeFailed(format!("decode base64 failed: {err}")))
 }
 
-fn write_export_bytes(path: &PathBuf, bytes_base64: &str) -> Result<SaveResult, String> {
-    let bytes = decode_export_bytes(bytes_base64)?;
-    std::fs::write(path, bytes).map_err(|err| format!("write file failed: {err}"))?;
+/// Exports are read back and checked before they get their final name unless
+/// the webview opts out (e.g. for a fast local scratch disk).
+fn should_verify(verify: Option<bool>) -> bool {
+    verify.unwrap_or(true)
+}
 
-    Ok(SaveResult {
-        saved: true,
-        path: Some(path.to_string_lossy().to_string()),
-    })
+fn write_export_bytes(
+    path: &Path,
+    bytes_base64: &str,
+    metadata: Option<&ExportMetadata>,
+    verify: bool,
+) -> CommandResult<SaveResult> {
+    write_export_with_metadata(path, decode_export_bytes(bytes_base64)?, metadata, verify)
+}
+
+fn write_export_with_metadata(
+    path: &Path,
+    mut bytes: Vec<u8>,
+    metadata: Option<&ExportMetadata>,
+    verify: bool,
+) -> CommandResult<SaveResult> {
+    if let Some(metadata) = metadata {
+        let extension = image_io::lowercase_extension(path);
+        bytes = metadata::embed_metadata(bytes, &extension, &metadata.resolve())
+            .map_err(CommandError::DecodeFailed)?;
+    }
+    write_export_raw_bytes(path, &bytes, verify)
+}
+
+fn write_export_raw_bytes(path: &Path, bytes: &[u8], verify: bool) -> CommandResult<SaveResult> {
+    let report = atomic_write::write_atomic(path, bytes, verify)?;
+    Ok(SaveResult::written(path, report))
 }
 
-fn build_unique_export_path(directory: &PathBuf, suggested_name: &str) -> PathBuf {
+fn export_target_path(directory: &Path, suggested_name: &str) -> PathBuf {
     let base_name = if suggested_name.trim().is_empty() {
         "converted_negative"
     } else {
         suggested_name.trim()
     };
-    let base_path = normalize_export_path(directory.join(base_name), base_name);
+    normalize_export_path(directory.join(base_name), base_name)
+}
+
+fn build_unique_export_path(directory: &Path, suggested_name: &str) -> PathBuf {
+    let base_path = export_target_path(directory, suggested_name);
     if !base_path.exists() {
         return base_path;
     }
@@ -86,64 +229,1273 @@ fn build_unique_export_path(directory: &PathBuf, suggested_name: &str) -> PathBu
 }
 
 #[tauri::command]
-fn pick_export_file_path(suggested_name: String) -> Option<String> {
+fn pick_export_file_path(scopes: State<'_, WriteScopes>, suggested_name: String) -> Option<String> {
     let path = rfd::FileDialog::new()
         .set_file_name(&suggested_name)
         .save_file()?;
     let normalized = normalize_export_path(path, &suggested_name);
+    scopes.allow_file(&normalized);
     Some(normalized.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-fn pick_export_directory() -> Option<String> {
+fn pick_export_directory(scopes: State<'_, WriteScopes>) -> Option<String> {
     let path = rfd::FileDialog::new().pick_folder()?;
+    scopes.allow_directory(&path);
     Some(path.to_string_lossy().to_string())
 }
 
+fn input_file_entry(path: &Path) -> CommandResult<InputFileEntry> {
+    let metadata =
+        std::fs::metadata(path).map_err(|err| CommandError::io("read file info", err))?;
+    if !metadata.is_file() {
+        return Err(CommandError::InvalidPath(format!(
+            "not a file: {}",
+            path.display()
+        )));
+    }
+    let last_modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0);
+    Ok(InputFileEntry {
+        path: path.to_string_lossy().to_string(),
+        name: path
+            .file_name()
+            .map(|value| value.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        size: metadata.len(),
+        last_modified,
+    })
+}
+
+#[tauri::command]
+fn open_input_files(scopes: State<'_, WriteScopes>) -> Vec<InputFileEntry> {
+    let Some(paths) = rfd::FileDialog::new()
+        .add_filter("Images", image_io::INPUT_IMAGE_EXTENSIONS)
+        .pick_files()
+    else {
+        return Vec::new();
+    };
+    for path in &paths {
+        scopes.allow_file(&sidecar::sidecar_path(path));
+    }
+    paths
+        .iter()
+        .filter_map(|path| input_file_entry(path).ok())
+        .collect()
+}
+
+#[tauri::command]
+async fn read_input_file(path: String) -> CommandResult<Response> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(CommandError::InvalidPath("input path is empty".to_string()));
+    }
+    let path = Path::new(trimmed);
+    if !path.is_file() {
+        return Err(CommandError::NotFound(format!(
+            "input file not found: {trimmed}"
+        )));
+    }
+    let bytes = std::fs::read(path).map_err(|err| CommandError::io("read file", err))?;
+    Ok(Response::new(bytes))
+}
+
+fn resolve_source_path(path: &str) -> CommandResult<PathBuf> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(CommandError::InvalidPath(
+            "source path is empty".to_string(),
+        ));
+    }
+    Ok(PathBuf::from(trimmed))
+}
+
+#[tauri::command]
+fn read_settings_sidecar(path: String) -> CommandResult<Option<serde_json::Value>> {
+    sidecar::read_settings(&resolve_source_path(&path)?)
+}
+
+#[tauri::command]
+fn write_settings_sidecar(
+    scopes: State<'_, WriteScopes>,
+    path: String,
+    settings: serde_json::Value,
+) -> CommandResult<String> {
+    let source = resolve_source_path(&path)?;
+    scopes.check_file(&sidecar::sidecar_path(&source))?;
+    let sidecar_path = sidecar::write_settings(&source, &settings)?;
+    Ok(sidecar_path.to_string_lossy().to_string())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenedProjectFrame {
+    file: InputFileEntry,
+    selected: bool,
+    settings: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenedProject {
+    path: String,
+    project: project::RollProject,
+    frames: Vec<OpenedProjectFrame>,
+    missing_frames: Vec<String>,
+}
+
+fn app_config_dir(app: &AppHandle) -> CommandResult<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .map_err(|err| CommandError::Internal(format!("resolve config directory failed: {err}")))
+}
+
+/// Recents are a convenience; failing to record one must not fail the save.
+fn remember_recent_project(app: &AppHandle, path: &Path, project: &project::RollProject) {
+    if let Ok(config_dir) = app_config_dir(app) {
+        let _ = project::remember_recent_project(&config_dir, path, project);
+    }
+}
+
+fn project_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new().add_filter("Negative Converter Roll", &[project::PROJECT_EXTENSION])
+}
+
+/// The save dialog opened on `path`, to confirm saving a roll there.
+fn project_save_dialog_at(path: &Path) -> rfd::FileDialog {
+    let dialog = match path.parent().filter(|parent| parent.is_dir()) {
+        Some(parent) => project_dialog().set_directory(parent),
+        None => project_dialog(),
+    };
+    match path.file_name() {
+        Some(file_name) => dialog.set_file_name(file_name.to_string_lossy()),
+        None => dialog,
+    }
+}
+
+#[tauri::command]
+fn save_project(
+    app: AppHandle,
+    scopes: State<'_, WriteScopes>,
+    path: Option<String>,
+    project: project::RollProject,
+) -> CommandResult<SaveResult> {
+    let requested = path
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|path| project::normalize_project_path(PathBuf::from(path)));
+    // A path the webview passes back is reused only while it is writable,
+    // i.e. it was picked in a dialog this session. Otherwise (e.g. after
+    // recovering an autosave) the user confirms it in the save dialog.
+    let target = match requested {
+        Some(requested) if scopes.check_file(&requested).is_ok() => requested,
+        requested => {
+            let dialog = match &requested {
+                Some(requested) => project_save_dialog_at(requested),
+                None => {
+                    let suggested = if project.name.trim().is_empty() {
+                        "roll".to_string()
+                    } else {
+                        project.name.trim().to_string()
+                    };
+                    project_dialog()
+                        .set_file_name(format!("{suggested}.{}", project::PROJECT_EXTENSION))
+                }
+            };
+            let Some(picked) = dialog.save_file() else {
+                return Ok(SaveResult::cancelled());
+            };
+            let picked = project::normalize_project_path(picked);
+            scopes.allow_file(&picked);
+            picked
+        }
+    };
+    scopes.check_file(&target)?;
+    let saved = project::save_project(&target, project)?;
+    remember_recent_project(&app, &target, &saved);
+    Ok(SaveResult::saved(&target))
+}
+
+#[tauri::command]
+async fn open_project(
+    app: AppHandle,
+    scopes: State<'_, WriteScopes>,
+    path: Option<String>,
+) -> CommandResult<Option<OpenedProject>> {
+    // Only a roll picked in the dialog may be saved over; one opened by path
+    // (e.g. from the recents list) asks where to save.
+    let source = match path
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        Some(path) => PathBuf::from(path),
+        None => match project_dialog().pick_file() {
+            Some(picked) => {
+                scopes.allow_file(&picked);
+                picked
+            }
+            None => return Ok(None),
+        },
+    };
+    let (project, missing_frames) = project::open_project(&source)?;
+    remember_recent_project(&app, &source, &project);
+    let (frames, missing_frames) = resolve_project_frames(&project, missing_frames);
+    Ok(Some(OpenedProject {
+        path: source.to_string_lossy().to_string(),
+        project,
+        frames,
+        missing_frames,
+    }))
+}
+
+/// Stats each frame still on disk; the rest join `missing_frames`. Frame
+/// paths come from the project file, not a dialog, so their sidecars do not
+/// become writable.
+fn resolve_project_frames(
+    project: &project::RollProject,
+    mut missing_frames: Vec<String>,
+) -> (Vec<OpenedProjectFrame>, Vec<String>) {
+    let mut frames = Vec::with_capacity(project.frames.len());
+    for frame in &project.frames {
+        if missing_frames.contains(&frame.path) {
+            continue;
+        }
+        match input_file_entry(Path::new(&frame.path)) {
+            Ok(file) => {
+                frames.push(OpenedProjectFrame {
+                    file,
+                    selected: frame.selected,
+                    settings: frame.settings.clone(),
+                });
+            }
+            Err(_) => missing_frames.push(frame.path.clone()),
+        }
+    }
+    (frames, missing_frames)
+}
+
+#[tauri::command]
+fn list_recent_projects(app: AppHandle) -> Vec<project::RecentProject> {
+    app_config_dir(&app)
+        .map(|config_dir| project::list_recent_projects(&config_dir))
+        .unwrap_or_default()
+}
+
+fn app_data_dir(app: &AppHandle) -> CommandResult<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map_err(|err| CommandError::Internal(format!("resolve data directory failed: {err}")))
+}
+
 #[tauri::command]
-fn save_export_file(suggested_name: String, bytes_base64: String) -> Result<SaveResult, String> {
+fn autosave_session(
+    app: AppHandle,
+    autosave: State<'_, Autosave>,
+    session_id: String,
+    sequence: u64,
+    session: autosave::AutosaveSession,
+) -> CommandResult<bool> {
+    autosave.save(&app_data_dir(&app)?, &session_id, sequence, session)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecoveredSession {
+    saved_at: u64,
+    project_path: Option<String>,
+    current_settings: Option<serde_json::Value>,
+    project: project::RollProject,
+    frames: Vec<OpenedProjectFrame>,
+    missing_frames: Vec<String>,
+}
+
+/// The autosaved session with its frames resolved like `open_project`, or
+/// `None` when there is nothing to recover. Its `project_path` came from the
+/// webview, so it gains no write access; saving there asks first.
+#[tauri::command]
+async fn recover_last_session(app: AppHandle) -> CommandResult<Option<RecoveredSession>> {
+    let Some(snapshot) = autosave::recover(&app_data_dir(&app)?)? else {
+        return Ok(None);
+    };
+    let session = snapshot.session;
+    if session.project.frames.is_empty() {
+        return Ok(None);
+    }
+    let (frames, missing_frames) = resolve_project_frames(&session.project, Vec::new());
+    Ok(Some(RecoveredSession {
+        saved_at: snapshot.saved_at,
+        project_path: session.project_path,
+        current_settings: session.current_settings,
+        project: session.project,
+        frames,
+        missing_frames,
+    }))
+}
+
+#[tauri::command]
+fn discard_autosave_session(app: AppHandle, autosave: State<'_, Autosave>) -> CommandResult<()> {
+    autosave.discard(&app_data_dir(&app)?)
+}
+
+#[tauri::command]
+fn save_export_file(
+    scopes: State<'_, WriteScopes>,
+    suggested_name: String,
+    bytes_base64: String,
+    metadata: Option<ExportMetadata>,
+    verify: Option<bool>,
+) -> CommandResult<SaveResult> {
     let Some(path) = rfd::FileDialog::new()
         .set_file_name(&suggested_name)
         .save_file()
     else {
-        return Ok(SaveResult {
-            saved: false,
-            path: None,
-        });
+        return Ok(SaveResult::cancelled());
     };
 
     let normalized = normalize_export_path(path, &suggested_name);
-    write_export_bytes(&normalized, &bytes_base64)
+    scopes.allow_file(&normalized);
+    write_export_bytes(
+        &normalized,
+        &bytes_base64,
+        metadata.as_ref(),
+        should_verify(verify),
+    )
 }
 
-#[tauri::command]
-fn write_export_file_to_path(path: String, bytes_base64: String) -> Result<SaveResult, String> {
+fn resolve_export_path(path: &str) -> CommandResult<PathBuf> {
     let trimmed = path.trim();
     if trimmed.is_empty() {
-        return Err("export path is empty".to_string());
+        return Err(CommandError::InvalidPath(
+            "export path is empty".to_string(),
+        ));
+    }
+
+    Ok(PathBuf::from(trimmed))
+}
+
+/// Names the export from `naming`'s template and sorts it into `naming`'s
+/// subfolder when given. An existing file is handled by `conflict`, which
+/// renames by default. `directory` must be inside a folder picked for export.
+fn resolve_directory_export_path(
+    scopes: &WriteScopes,
+    directory: &str,
+    suggested_name: &str,
+    naming: Option<&ExportNaming>,
+    conflict: Option<&ExportConflict>,
+) -> CommandResult<ConflictDecision> {
+    decide_directory_export(
+        scopes,
+        directory,
+        suggested_name,
+        naming,
+        conflict,
+        export_conflict::ask_native,
+    )
+}
+
+/// Replacing an existing file the user never confirmed, through `overwrite`
+/// or `overwrite-if-older`, asks first as `ask` would. A `Replace` answer
+/// for all remaining files lets the rest of the batch replace files in the
+/// picked folder without asking again.
+fn decide_directory_export(
+    scopes: &WriteScopes,
+    directory: &str,
+    suggested_name: &str,
+    naming: Option<&ExportNaming>,
+    conflict: Option<&ExportConflict>,
+    ask: impl Fn(&Path, u32) -> CommandResult<ConflictAnswer>,
+) -> CommandResult<ConflictDecision> {
+    let root = validate_export_directory(scopes, directory)?;
+    let (directory_path, file_name) = match naming {
+        Some(naming) => {
+            let (subfolder, file_name) = naming
+                .resolve(suggested_name)
+                .map_err(CommandError::InvalidPath)?;
+            (
+                export_naming::create_subfolder(&root, &subfolder)?,
+                file_name,
+            )
+        }
+        None => (root.clone(), suggested_name.to_string()),
+    };
+    let conflict = conflict.cloned().unwrap_or_default();
+    let rename = || build_unique_export_path(&directory_path, &file_name);
+    let mut decision = export_conflict::decide(
+        export_target_path(&directory_path, &file_name),
+        &conflict,
+        rename,
+        &ask,
+    )?;
+    if !decision.skip && decision.answer.is_none() {
+        match scopes.check_file(&decision.path) {
+            Err(CommandError::AlreadyExists(_)) => {
+                let confirm = ExportConflict {
+                    policy: ConflictPolicy::Ask,
+                    ..conflict
+                };
+                decision = export_conflict::decide(decision.path, &confirm, rename, &ask)?;
+            }
+            result => result?,
+        }
+    }
+    if let Some(ConflictAnswer {
+        policy: ConflictPolicy::Overwrite,
+        apply_to_all: true,
+    }) = decision.answer
+    {
+        scopes.allow_replacing(&root);
+    }
+    Ok(decision)
+}
+
+/// An existing folder the user picked, which batch exports write into.
+fn validate_export_directory(scopes: &WriteScopes, directory: &str) -> CommandResult<PathBuf> {
+    let trimmed = directory.trim();
+    if trimmed.is_empty() {
+        return Err(CommandError::InvalidPath(
+            "export directory is empty".to_string(),
+        ));
+    }
+
+    let directory_path = PathBuf::from(trimmed);
+    if !directory_path.exists() {
+        return Err(CommandError::NotFound(format!(
+            "export directory not found: {trimmed}"
+        )));
+    }
+    if !directory_path.is_dir() {
+        return Err(CommandError::InvalidPath(format!(
+            "export directory is invalid: {trimmed}"
+        )));
+    }
+    scopes.check_directory(&directory_path)?;
+    Ok(directory_path)
+}
+
+/// A path the webview already holds, usually from `pick_export_file_path`
+/// whose dialog confirmed replacing it. A conflict policy may pick a new name
+/// inside a picked folder, but replacing an existing file still needs that
+/// file to have been picked itself or a `Replace` answer in the dialog.
+fn resolve_path_export(
+    scopes: &WriteScopes,
+    path: &str,
+    conflict: Option<&ExportConflict>,
+) -> CommandResult<ConflictDecision> {
+    let path = resolve_export_path(path)?;
+    let Some(conflict) = conflict else {
+        scopes.check_file(&path)?;
+        return Ok(ConflictDecision::write(path));
+    };
+    scopes.check_within(&path)?;
+    let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let decision = export_conflict::decide(
+        path,
+        conflict,
+        || build_unique_export_path(&directory, &file_name),
+        export_conflict::ask_native,
+    )?;
+    if !decision.skip && decision.answer.is_none() {
+        scopes.check_file(&decision.path)?;
     }
+    Ok(decision)
+}
 
-    write_export_bytes(&PathBuf::from(trimmed), &bytes_base64)
+#[tauri::command]
+fn write_export_file_to_path(
+    scopes: State<'_, WriteScopes>,
+    path: String,
+    bytes_base64: String,
+    metadata: Option<ExportMetadata>,
+    verify: Option<bool>,
+    conflict: Option<ExportConflict>,
+) -> CommandResult<SaveResult> {
+    let decision = resolve_path_export(&scopes, &path, conflict.as_ref())?;
+    write_decided_export(
+        &decision,
+        &bytes_base64,
+        metadata.as_ref(),
+        should_verify(verify),
+    )
+}
+
+fn write_decided_export(
+    decision: &ConflictDecision,
+    bytes_base64: &str,
+    metadata: Option<&ExportMetadata>,
+    verify: bool,
+) -> CommandResult<SaveResult> {
+    if decision.skip {
+        return Ok(SaveResult::skipped(decision));
+    }
+    Ok(
+        write_export_bytes(&decision.path, bytes_base64, metadata, verify)?
+            .with_conflict(decision.answer),
+    )
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 fn write_export_file_to_directory(
+    scopes: State<'_, WriteScopes>,
     directory: String,
     suggested_name: String,
     bytes_base64: String,
-) -> Result<SaveResult, String> {
-    let trimmed = directory.trim();
-    if trimmed.is_empty() {
-        return Err("export directory is empty".to_string());
+    metadata: Option<ExportMetadata>,
+    verify: Option<bool>,
+    naming: Option<ExportNaming>,
+    conflict: Option<ExportConflict>,
+) -> CommandResult<SaveResult> {
+    let decision = resolve_directory_export_path(
+        &scopes,
+        &directory,
+        &suggested_name,
+        naming.as_ref(),
+        conflict.as_ref(),
+    )?;
+    write_decided_export(
+        &decision,
+        &bytes_base64,
+        metadata.as_ref(),
+        should_verify(verify),
+    )
+}
+
+/// A file a batch is about to write, named as `write_export_file_to_directory`
+/// would name it.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PreflightFile {
+    suggested_name: String,
+    #[serde(default)]
+    naming: Option<ExportNaming>,
+}
+
+/// Checks a batch's target folder before exporting: free space against
+/// `estimated_bytes`, whether it can be written, its filesystem and name
+/// limits, and `files` whose names differ only by case from existing ones.
+#[tauri::command]
+fn preflight_export(
+    scopes: State<'_, WriteScopes>,
+    directory: String,
+    estimated_bytes: u64,
+    count: u32,
+    files: Option<Vec<PreflightFile>>,
+) -> CommandResult<ExportPreflight> {
+    let directory_path = validate_export_directory(&scopes, &directory)?;
+    let planned = files
+        .unwrap_or_default()
+        .iter()
+        .map(|file| {
+            let (subfolder, file_name) = match &file.naming {
+                Some(naming) => naming
+                    .resolve(&file.suggested_name)
+                    .map_err(CommandError::InvalidPath)?,
+                None => (PathBuf::new(), file.suggested_name.clone()),
+            };
+            Ok(export_target_path(
+                &directory_path.join(subfolder),
+                &file_name,
+            ))
+        })
+        .collect::<CommandResult<Vec<_>>>()?;
+    Ok(export_preflight::check(
+        &directory_path,
+        estimated_bytes,
+        count,
+        &planned,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum ExportUploadTarget {
+    Path {
+        path: String,
+        #[serde(default)]
+        conflict: Option<ExportConflict>,
+    },
+    Directory {
+        directory: String,
+        suggested_name: String,
+        #[serde(default)]
+        naming: Option<Box<ExportNaming>>,
+        #[serde(default)]
+        conflict: Option<ExportConflict>,
+    },
+}
+
+impl ExportUploadTarget {
+    fn resolve(&self, scopes: &WriteScopes) -> CommandResult<ConflictDecision> {
+        match self {
+            Self::Path { path, conflict } => resolve_path_export(scopes, path, conflict.as_ref()),
+            Self::Directory {
+                directory,
+                suggested_name,
+                naming,
+                conflict,
+            } => resolve_directory_export_path(
+                scopes,
+                directory,
+                suggested_name,
+                naming.as_deref(),
+                conflict.as_ref(),
+            ),
+        }
     }
 
-    let directory_path = PathBuf::from(trimmed);
-    if !directory_path.is_dir() {
-        return Err(format!("export directory is invalid: {trimmed}"));
+    /// Within an export job, an `ask` answered for all files stands in for
+    /// the dialog on every later file.
+    fn apply_settled_conflict(&mut self, settled: Option<ConflictPolicy>) {
+        let (Self::Path { conflict, .. } | Self::Directory { conflict, .. }) = self;
+        if let (Some(settled), Some(conflict)) = (settled, conflict.as_mut()) {
+            if conflict.policy == ConflictPolicy::Ask {
+                conflict.policy = settled;
+            }
+        }
     }
 
-    let target_path = build_unique_export_path(&directory_path, &suggested_name);
-    write_export_bytes(&target_path, &bytes_base64)
+    /// The name shown in progress events before the target is resolved.
+    fn display_name(&self) -> String {
+        match self {
+            Self::Path { path, .. } => Path::new(path.trim())
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            Self::Directory { suggested_name, .. } => suggested_name.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadStart {
+    /// `None` when the conflict policy kept the existing file; there is
+    /// nothing to upload then.
+    upload_id: Option<u64>,
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflict: Option<ConflictAnswer>,
+}
+
+#[tauri::command]
+fn begin_export_upload(
+    uploads: State<'_, ExportUploads>,
+    scopes: State<'_, WriteScopes>,
+    target: ExportUploadTarget,
+) -> CommandResult<UploadStart> {
+    let decision = target.resolve(&scopes)?;
+    let upload_id = if decision.skip {
+        None
+    } else {
+        Some(uploads.begin(decision.path.clone())?)
+    };
+    Ok(UploadStart {
+        upload_id,
+        path: decision.path.to_string_lossy().to_string(),
+        conflict: decision.answer,
+    })
+}
+
+fn request_header<'a>(request: &'a Request<'_>, name: &str) -> Option<&'a str> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+}
+
+fn parse_numeric_header<T: std::str::FromStr>(
+    request: &Request<'_>,
+    name: &str,
+) -> Result<T, String> {
+    request_header(request, name)
+        .and_then(|value| value.parse::<T>().ok())
+        .ok_or_else(|| format!("missing or invalid {name} header"))
+}
+
+/// Reads a URI-encoded JSON header, the way the webview sends structured
+/// options alongside a raw body.
+fn parse_json_header<T: serde::de::DeserializeOwned>(
+    request: &Request<'_>,
+    name: &str,
+    what: &str,
+) -> Result<T, String> {
+    let encoded = request_header(request, name).ok_or_else(|| format!("missing {name} header"))?;
+    let json = percent_encoding::percent_decode_str(encoded)
+        .decode_utf8()
+        .map_err(|err| format!("decode {what} failed: {err}"))?;
+    serde_json::from_str(&json).map_err(|err| format!("parse {what} failed: {err}"))
+}
+
+fn raw_request_body<'a>(request: &'a Request<'_>, what: &str) -> Result<&'a [u8], String> {
+    match request.body() {
+        InvokeBody::Raw(bytes) => Ok(bytes),
+        _ => Err(format!("{what} must be sent as raw bytes")),
+    }
+}
+
+#[tauri::command]
+fn append_export_chunk(
+    uploads: State<'_, ExportUploads>,
+    request: Request<'_>,
+) -> CommandResult<u64> {
+    let upload_id = parse_numeric_header::<u64>(&request, UPLOAD_ID_HEADER)?;
+    let chunk = raw_request_body(&request, "export chunk")?;
+    uploads.append(upload_id, chunk)
+}
+
+#[tauri::command]
+fn finish_export_upload(
+    uploads: State<'_, ExportUploads>,
+    upload_id: u64,
+    metadata: Option<ExportMetadata>,
+    verify: Option<bool>,
+) -> CommandResult<SaveResult> {
+    let verify = should_verify(verify);
+    let extension = image_io::lowercase_extension(&uploads.target_path(upload_id)?);
+    match metadata {
+        // Metadata goes into the streamed bytes before they get their final name
+        Some(metadata) if matches!(extension.as_str(), "png" | "jpg" | "jpeg" | "tif" | "tiff") => {
+            let (path, bytes) = uploads.finish_into_bytes(upload_id)?;
+            let bytes = metadata::embed_metadata(bytes, &extension, &metadata.resolve())
+                .map_err(CommandError::DecodeFailed)?;
+            write_export_raw_bytes(&path, &bytes, verify)
+        }
+        _ => {
+            let (path, report) = uploads.finish(upload_id, verify)?;
+            Ok(SaveResult::written(&path, report))
+        }
+    }
+}
+
+#[tauri::command]
+fn abort_export_upload(uploads: State<'_, ExportUploads>, upload_id: u64) -> CommandResult<()> {
+    uploads.abort(upload_id)
+}
+
+/// Starts a streamed ZIP archive at `target`. Entries are deflated when
+/// `deflate` is set, which only pays off for uncompressed formats.
+#[tauri::command]
+fn begin_zip_export(
+    zips: State<'_, ZipExports>,
+    scopes: State<'_, WriteScopes>,
+    target: ExportUploadTarget,
+    deflate: Option<bool>,
+) -> CommandResult<u64> {
+    let decision = target.resolve(&scopes)?;
+    if decision.skip {
+        return Err(CommandError::AlreadyExists(format!(
+            "{} already exists",
+            decision.path.display()
+        )));
+    }
+    zips.begin(decision.path, deflate.unwrap_or(false))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ZipEntryChunk {
+    zip_id: u64,
+    name: String,
+    /// Closes the entry after this chunk.
+    #[serde(default)]
+    last: bool,
+    /// Names the entry (and its folder inside the archive) from a template.
+    #[serde(default)]
+    naming: Option<ExportNaming>,
+}
+
+/// Appends one raw-body chunk of an entry; the URI-encoded `x-zip-entry`
+/// header says which archive and entry it belongs to.
+#[tauri::command]
+fn add_zip_entry(zips: State<'_, ZipExports>, request: Request<'_>) -> CommandResult<u64> {
+    let entry: ZipEntryChunk = parse_json_header(&request, ZIP_ENTRY_HEADER, "ZIP entry")?;
+    let chunk = raw_request_body(&request, "ZIP entry chunk")?;
+    let name = match entry.naming {
+        Some(naming) => {
+            let (subfolder, file_name) = naming
+                .resolve(&entry.name)
+                .map_err(CommandError::InvalidPath)?;
+            let mut segments: Vec<String> = subfolder
+                .iter()
+                .map(|segment| segment.to_string_lossy().to_string())
+                .collect();
+            segments.push(file_name);
+            segments.join("/")
+        }
+        None => entry.name,
+    };
+    zips.add(entry.zip_id, &name, chunk, entry.last)
+}
+
+#[tauri::command]
+fn finish_zip_export(
+    zips: State<'_, ZipExports>,
+    zip_id: u64,
+    verify: Option<bool>,
+) -> CommandResult<SaveResult> {
+    let (path, report) = zips.finish(zip_id, should_verify(verify))?;
+    Ok(SaveResult::written(&path, report))
+}
+
+#[tauri::command]
+fn abort_zip_export(zips: State<'_, ZipExports>, zip_id: u64) -> CommandResult<()> {
+    zips.abort(zip_id)
+}
+
+/// The built-in presets and profiles plus the user's own.
+pub(crate) struct AppLibrary<'a> {
+    pub(crate) config_dir: Option<PathBuf>,
+    pub(crate) data_dir: Option<PathBuf>,
+    pub(crate) presets: &'a PresetStore,
+    pub(crate) profiles: &'a ProfileStore,
+}
+
+impl engine::Library for AppLibrary<'_> {
+    fn film_preset(&self, id: &str) -> Option<engine::presets::FilmPresetSettings> {
+        match (
+            id.strip_prefix(preset_store::USER_PRESET_PREFIX),
+            &self.config_dir,
+        ) {
+            (Some(_), Some(config_dir)) => self
+                .presets
+                .get(config_dir, id)
+                .map(|preset| preset.film_preset_settings()),
+            (Some(_), None) => None,
+            (None, _) => engine::Builtins.film_preset(id),
+        }
+    }
+
+    fn enhanced_profile(&self, id: &str) -> Option<Arc<engine::lut3d::Lut3D>> {
+        match &self.data_dir {
+            Some(data_dir) => self.profiles.resolve(data_dir, id),
+            None => engine::lut3d::builtin_profile(id),
+        }
+    }
+}
+
+fn convert_with_library(app: &AppHandle, image: &mut Image16, settings: &FrameSettings) {
+    let presets = app.state::<PresetStore>();
+    let profiles = app.state::<ProfileStore>();
+    let library = AppLibrary {
+        config_dir: app_config_dir(app).ok(),
+        data_dir: app_data_dir(app).ok(),
+        presets: &presets,
+        profiles: &profiles,
+    };
+    engine::convert_frame_with(image, settings, &library);
+}
+
+/// Converts one frame natively. The body carries little-endian 16-bit RGBA
+/// pixels; dimensions and the URI-encoded settings snapshot travel in headers.
+#[tauri::command]
+async fn convert_frame(app: AppHandle, request: Request<'_>) -> CommandResult<Response> {
+    let width = parse_numeric_header::<u32>(&request, FRAME_WIDTH_HEADER)?;
+    let height = parse_numeric_header::<u32>(&request, FRAME_HEIGHT_HEADER)?;
+    let settings: FrameSettings =
+        parse_json_header(&request, FRAME_SETTINGS_HEADER, "frame settings")?;
+
+    let pixels = raw_request_body(&request, "frame pixels")?;
+    let mut image =
+        Image16::from_le_bytes(width, height, pixels).map_err(CommandError::DecodeFailed)?;
+    convert_with_library(&app, &mut image, &settings);
+    Ok(Response::new(image.to_le_bytes()))
+}
+
+/// Finds the film base on a full-resolution 16-bit frame sent like
+/// `convert_frame`'s. `x-border-buffer` is the `coreBorderBuffer` setting.
+#[tauri::command]
+async fn detect_film_base(request: Request<'_>) -> CommandResult<FilmBaseDetection> {
+    let width = parse_numeric_header::<u32>(&request, FRAME_WIDTH_HEADER)?;
+    let height = parse_numeric_header::<u32>(&request, FRAME_HEIGHT_HEADER)?;
+    let border_buffer = parse_numeric_header::<f64>(&request, BORDER_BUFFER_HEADER)?;
+    let pixels = raw_request_body(&request, "frame pixels")?;
+    let image =
+        Image16::from_le_bytes(width, height, pixels).map_err(CommandError::DecodeFailed)?;
+    Ok(film_base::detect_film_base(&image, border_buffer))
+}
+
+/// Samples the film base of every frame in a roll and combines them into one,
+/// reporting how far each frame is from it. `method` is `filmBaseMethod`.
+#[tauri::command]
+async fn analyze_roll_film_base(
+    frames: Vec<RollFrame>,
+    method: Option<String>,
+) -> CommandResult<RollFilmBase> {
+    let method = FilmBaseMethod::parse(method.as_deref().unwrap_or("density"));
+    Ok(roll_film_base::analyze_roll(&frames, method))
+}
+
+/// Finds the crop and straightening angle of a full-resolution 16-bit frame
+/// sent like `convert_frame`'s, without OpenCV.js. `x-auto-frame-settings` is
+/// `state.autoFrame` with `filmType`. `None` when no border is reliable.
+#[tauri::command]
+async fn detect_frame(request: Request<'_>) -> CommandResult<Option<FrameDetection>> {
+    let width = parse_numeric_header::<u32>(&request, FRAME_WIDTH_HEADER)?;
+    let height = parse_numeric_header::<u32>(&request, FRAME_HEIGHT_HEADER)?;
+    let settings: AutoFrameSettings =
+        parse_json_header(&request, AUTO_FRAME_SETTINGS_HEADER, "auto frame settings")?;
+    let pixels = raw_request_body(&request, "frame pixels")?;
+    let image =
+        Image16::from_le_bytes(width, height, pixels).map_err(CommandError::DecodeFailed)?;
+    Ok(auto_frame::detect_frame(&image, &settings))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TiffEncoding {
+    width: u32,
+    height: u32,
+    channels: usize,
+    sample_bits: u8,
+    bit_depth: u8,
+    #[serde(default)]
+    compression: tiff_writer::TiffCompression,
+    #[serde(default)]
+    predictor: bool,
+    #[serde(default)]
+    dpi: Option<f64>,
+    #[serde(default)]
+    icc_profile: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TiffExportOptions {
+    target: ExportUploadTarget,
+    #[serde(flatten)]
+    encoding: TiffEncoding,
+    #[serde(default)]
+    metadata: Option<ExportMetadata>,
+    #[serde(default)]
+    verify: Option<bool>,
+}
+
+/// Encodes interleaved RGB(A) samples: bytes, or little-endian `u16` when
+/// `sampleBits` is 16.
+fn encode_tiff_samples(
+    encoding: &TiffEncoding,
+    body: &[u8],
+    metadata: Option<&ExportMetadata>,
+) -> CommandResult<Vec<u8>> {
+    let wide_samples;
+    let samples = match encoding.sample_bits {
+        8 => tiff_writer::TiffSamples::U8(body),
+        16 => {
+            if !body.len().is_multiple_of(2) {
+                return Err(CommandError::DecodeFailed(
+                    "16-bit TIFF samples have odd length".to_string(),
+                ));
+            }
+            wide_samples = body
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect::<Vec<u16>>();
+            tiff_writer::TiffSamples::U16(&wide_samples)
+        }
+        bits => return Err(format!("unsupported TIFF sample bits: {bits}").into()),
+    };
+    let icc_profile = encoding
+        .icc_profile
+        .as_deref()
+        .map(decode_export_bytes)
+        .transpose()?;
+    let tiff_options = tiff_writer::TiffOptions {
+        bit_depth: encoding.bit_depth,
+        compression: encoding.compression,
+        predictor: encoding.predictor,
+        dpi: encoding.dpi,
+        icc_profile,
+        metadata: metadata.map(ExportMetadata::resolve),
+    };
+    Ok(tiff_writer::encode_tiff(
+        encoding.width,
+        encoding.height,
+        encoding.channels,
+        samples,
+        &tiff_options,
+    )?)
+}
+
+/// Encodes and writes a TIFF natively. The body carries interleaved RGB(A)
+/// samples (bytes, or little-endian `u16` when `sampleBits` is 16); the
+/// URI-encoded options header names the target like `begin_export_upload`.
+#[tauri::command]
+async fn write_tiff_export(
+    scopes: State<'_, WriteScopes>,
+    request: Request<'_>,
+) -> CommandResult<SaveResult> {
+    let options: TiffExportOptions =
+        parse_json_header(&request, TIFF_OPTIONS_HEADER, "TIFF options")?;
+    let decision = options.target.resolve(&scopes)?;
+    if decision.skip {
+        return Ok(SaveResult::skipped(&decision));
+    }
+    let body = raw_request_body(&request, "TIFF samples")?;
+    let bytes = encode_tiff_samples(&options.encoding, body, options.metadata.as_ref())?;
+    Ok(
+        write_export_raw_bytes(&decision.path, &bytes, should_verify(options.verify))?
+            .with_conflict(decision.answer),
+    )
+}
+
+#[tauri::command]
+fn begin_export_job(jobs: State<'_, ExportJobs>, total: usize) -> CommandResult<u64> {
+    jobs.begin(total)
+}
+
+fn export_event_sink(app: AppHandle) -> EventSink<SaveResult> {
+    Arc::new(move |name, event| {
+        let _ = app.emit(name, event);
+    })
+}
+
+/// Resolves a job file's target only when its turn comes, so conflict names
+/// and dialogs follow queue order, then hands the final path to `write`.
+fn write_job_file(
+    app: &AppHandle,
+    file: &JobFile<'_, SaveResult>,
+    mut target: ExportUploadTarget,
+    write: impl FnOnce(&Path) -> CommandResult<SaveResult>,
+) -> CommandResult<SaveResult> {
+    file.stage(ExportStage::Writing)?;
+    target.apply_settled_conflict(file.job().settled_conflict());
+    let decision = target.resolve(&app.state::<WriteScopes>())?;
+    if let Some(answer) = decision.answer.filter(|answer| answer.apply_to_all) {
+        file.job().settle_conflict(answer.policy);
+    }
+    if decision.skip {
+        return Ok(SaveResult::skipped(&decision));
+    }
+    Ok(write(&decision.path)?.with_conflict(decision.answer))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportJobItem {
+    job_id: u64,
+    index: usize,
+    target: ExportUploadTarget,
+    #[serde(default)]
+    metadata: Option<ExportMetadata>,
+    #[serde(default)]
+    verify: Option<bool>,
+    /// The body is raw samples for the job to encode as TIFF, as
+    /// `write_tiff_export` takes them, rather than a finished file.
+    #[serde(default)]
+    tiff: Option<TiffEncoding>,
+}
+
+/// Queues a file for an export job and returns once it is queued. The raw
+/// body is the file (or TIFF samples); the URI-encoded `x-export-item`
+/// header says which job, which file of it and where it goes.
+#[tauri::command]
+fn queue_export_file(
+    app: AppHandle,
+    jobs: State<'_, ExportJobs>,
+    request: Request<'_>,
+) -> CommandResult<()> {
+    let item: ExportJobItem = parse_json_header(&request, EXPORT_ITEM_HEADER, "export item")?;
+    let bytes = raw_request_body(&request, "export file")?.to_vec();
+    let sink = export_event_sink(app.clone());
+    jobs.queue(
+        item.job_id,
+        item.index,
+        item.target.display_name(),
+        sink,
+        move |file| {
+            let verify = should_verify(item.verify);
+            let Some(tiff) = &item.tiff else {
+                return write_job_file(&app, file, item.target, |path| {
+                    write_export_with_metadata(path, bytes, item.metadata.as_ref(), verify)
+                });
+            };
+            file.stage(ExportStage::Encoding)?;
+            let encoded = encode_tiff_samples(tiff, &bytes, item.metadata.as_ref())?;
+            drop(bytes);
+            write_job_file(&app, file, item.target, |path| {
+                write_export_raw_bytes(path, &encoded, verify)
+            })
+        },
+    )
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FrameExportFormat {
+    /// Always 16-bit.
+    Png,
+    Tiff,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportJobFrame {
+    job_id: u64,
+    index: usize,
+    /// The scan on disk, converted natively with `settings`.
+    source_path: String,
+    settings: FrameSettings,
+    target: ExportUploadTarget,
+    format: FrameExportFormat,
+    #[serde(default)]
+    bit_depth: Option<u8>,
+    #[serde(default)]
+    compression: tiff_writer::TiffCompression,
+    #[serde(default)]
+    predictor: bool,
+    #[serde(default)]
+    metadata: Option<ExportMetadata>,
+    #[serde(default)]
+    verify: Option<bool>,
+    /// `state.autoFrame` with `filmType`, as `detect_frame` takes it. When
+    /// set, the frame's detected crop and angle replace the snapshot's.
+    #[serde(default)]
+    auto_frame: Option<AutoFrameSettings>,
+}
+
+fn encode_job_frame(frame: &ExportJobFrame, image: &Image16) -> CommandResult<Vec<u8>> {
+    match frame.format {
+        FrameExportFormat::Png => Ok(image_io::encode_png16(image)?),
+        FrameExportFormat::Tiff => {
+            let options = tiff_writer::TiffOptions {
+                bit_depth: frame.bit_depth.unwrap_or(16),
+                compression: frame.compression,
+                predictor: frame.predictor,
+                dpi: None,
+                icc_profile: None,
+                metadata: frame.metadata.as_ref().map(ExportMetadata::resolve),
+            };
+            Ok(tiff_writer::encode_tiff(
+                image.width,
+                image.height,
+                4,
+                tiff_writer::TiffSamples::U16(&image.data),
+                &options,
+            )?)
+        }
+    }
+}
+
+/// Reads a queued frame from disk and rotates and crops it as its settings
+/// ask, after auto frame has updated them if `auto_frame` is set.
+fn read_job_frame(
+    source: &Path,
+    settings: &mut FrameSettings,
+    auto_frame: Option<&AutoFrameSettings>,
+) -> CommandResult<Image16> {
+    let image = image_io::read_input_image(source).map_err(CommandError::DecodeFailed)?;
+    if let Some(auto) = auto_frame {
+        engine::geometry::auto_frame(&image, settings, auto);
+    }
+    Ok(engine::geometry::apply_frame_geometry(image, settings))
+}
+
+/// Queues a frame that the export job reads from disk, converts and
+/// encodes natively, so the webview never holds its pixels.
+#[tauri::command]
+fn queue_export_frame(
+    app: AppHandle,
+    jobs: State<'_, ExportJobs>,
+    mut frame: ExportJobFrame,
+) -> CommandResult<()> {
+    let source = resolve_source_path(&frame.source_path)?;
+    let sink = export_event_sink(app.clone());
+    jobs.queue(
+        frame.job_id,
+        frame.index,
+        frame.target.display_name(),
+        sink,
+        move |file| {
+            file.stage(ExportStage::Converting)?;
+            let mut image =
+                read_job_frame(&source, &mut frame.settings, frame.auto_frame.as_ref())?;
+            convert_with_library(&app, &mut image, &frame.settings);
+            file.stage(ExportStage::Encoding)?;
+            let bytes = encode_job_frame(&frame, &image)?;
+            drop(image);
+            // TIFF carries its metadata from the encoder.
+            let metadata = match frame.format {
+                FrameExportFormat::Png => frame.metadata.as_ref(),
+                FrameExportFormat::Tiff => None,
+            };
+            let verify = should_verify(frame.verify);
+            write_job_file(&app, file, frame.target, |path| {
+                write_export_with_metadata(path, bytes, metadata, verify)
+            })
+        },
+    )
+}
+
+#[tauri::command]
+fn settle_export_file(jobs: State<'_, ExportJobs>, job_id: u64, index: usize) -> CommandResult<()> {
+    jobs.settle(job_id, index)
+}
+
+#[tauri::command]
+fn cancel_export_job(jobs: State<'_, ExportJobs>, job_id: u64) -> CommandResult<bool> {
+    jobs.cancel(job_id)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DecodedRawHeader<'a> {
+    width: u32,
+    height: u32,
+    channels: u32,
+    linear: bool,
+    metadata: &'a raw::RawLensMetadata,
+}
+
+/// Decodes a RAW (or TIFF) scan natively. The response is a little-endian
+/// `u32` header length, a JSON header with dimensions and lens metadata, then
+/// little-endian 16-bit RGB samples.
+#[tauri::command]
+async fn decode_raw_file(path: String) -> CommandResult<Response> {
+    let path = PathBuf::from(path.trim());
+    if !image_io::is_raw_like_path(&path) {
+        return Err(CommandError::InvalidPath(format!(
+            "not a RAW file: {}",
+            path.display()
+        )));
+    }
+    if !path.is_file() {
+        return Err(CommandError::NotFound(format!(
+            "RAW file not found: {}",
+            path.display()
+        )));
+    }
+    let decoded = raw::decode_raw_file(&path).map_err(CommandError::DecodeFailed)?;
+    let header = serde_json::to_vec(&DecodedRawHeader {
+        width: decoded.width,
+        height: decoded.height,
+        channels: 3,
+        linear: decoded.linear,
+        metadata: &decoded.metadata,
+    })
+    .map_err(|err| CommandError::Internal(format!("encode RAW header failed: {err}")))?;
+
+    let mut body = Vec::with_capacity(4 + header.len() + decoded.rgb.len() * 2);
+    body.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    body.extend_from_slice(&header);
+    for value in &decoded.rgb {
+        body.extend_from_slice(&value.to_le_bytes());
+    }
+    Ok(Response::new(body))
 }
 
 #[cfg(any(target_os = "linux", test))]
@@ -170,7 +1522,7 @@ fn looks_like_legacy_appimage_name(value: &str) -> bool {
 
 #[cfg(target_os = "linux")]
 fn detect_appimage_variant() -> AppImageVariant {
-    if let Some(appimage) = std::env::var("APPIMAGE").ok() {
+    if let Ok(appimage) = std::env::var("APPIMAGE") {
         if looks_like_legacy_appimage_name(&appimage) {
             return AppImageVariant::LegacyCompat;
         }
@@ -532,38 +1884,283 @@ fn open_url_with_system_browser(url: &str) -> Result<(), String> {
 }
 
 #[tauri::command]
-fn open_external_url(url: String) -> Result<(), String> {
+fn open_external_url(url: String) -> CommandResult<()> {
     let trimmed = url.trim();
     if !trimmed.to_ascii_lowercase().starts_with("https://") {
-        return Err("only https URLs are allowed".to_string());
+        return Err(CommandError::InvalidPath(
+            "only https URLs are allowed".to_string(),
+        ));
+    }
+    Ok(open_url_with_system_browser(trimmed)?)
+}
+
+/// An exported file the webview wants to show or open. Like writes, it must
+/// sit inside a folder the user picked or be a file they picked or opened.
+fn scoped_existing_file(scopes: &WriteScopes, path: &str) -> CommandResult<PathBuf> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(CommandError::InvalidPath("file path is empty".to_string()));
+    }
+    let path = PathBuf::from(trimmed);
+    if !path.is_file() {
+        return Err(CommandError::NotFound(format!("file not found: {trimmed}")));
     }
-    open_url_with_system_browser(trimmed)
+    scopes.check_within(&path)?;
+    Ok(path)
+}
+
+/// Async so the D-Bus round trip on Linux runs off the main thread.
+#[tauri::command]
+async fn reveal_in_file_manager(scopes: State<'_, WriteScopes>, path: String) -> CommandResult<()> {
+    file_manager::reveal(&scoped_existing_file(&scopes, &path)?)
+}
+
+#[tauri::command]
+fn open_with_default_app(scopes: State<'_, WriteScopes>, path: String) -> CommandResult<()> {
+    file_manager::open_with_default_app(&scoped_existing_file(&scopes, &path)?)
+}
+
+#[tauri::command]
+fn list_profiles(
+    app: AppHandle,
+    profiles: State<'_, ProfileStore>,
+) -> CommandResult<Vec<ProfileInfo>> {
+    Ok(profiles.list(&app_data_dir(&app)?))
+}
+
+/// Copies a `.cube`, `.3dl` or `.bin` LUT picked in a native dialog into the
+/// profiles folder. `None` when the dialog is cancelled.
+#[tauri::command]
+fn import_profile(
+    app: AppHandle,
+    profiles: State<'_, ProfileStore>,
+) -> CommandResult<Option<ProfileInfo>> {
+    let Some(source) = rfd::FileDialog::new()
+        .add_filter("3D LUT", profiles::PROFILE_EXTENSIONS)
+        .pick_file()
+    else {
+        return Ok(None);
+    };
+    profiles.import(&app_data_dir(&app)?, &source).map(Some)
+}
+
+fn resolve_profile(
+    app: &AppHandle,
+    profiles: &ProfileStore,
+    id: &str,
+) -> CommandResult<Arc<engine::lut3d::Lut3D>> {
+    profiles
+        .resolve(&app_data_dir(app)?, id)
+        .ok_or_else(|| CommandError::NotFound(format!("profile not found: {id}")))
+}
+
+/// Saves a profile as a `.cube` file, built-in ones already converted for
+/// sRGB input so other apps render them like this one does.
+#[tauri::command]
+fn export_profile_as_cube(
+    app: AppHandle,
+    scopes: State<'_, WriteScopes>,
+    profiles: State<'_, ProfileStore>,
+    id: String,
+) -> CommandResult<SaveResult> {
+    let lut = resolve_profile(&app, &profiles, &id)?;
+    let title = id.strip_prefix("user:").unwrap_or(&id);
+    let title = Path::new(title)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| id.clone());
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("Cube LUT", &["cube"])
+        .set_file_name(format!("{title}.cube"))
+        .save_file()
+    else {
+        return Ok(SaveResult::cancelled());
+    };
+    scopes.allow_file(&path);
+    let report = atomic_write::write_atomic(&path, lut.to_cube(&title).as_bytes(), false)?;
+    Ok(SaveResult::written(&path, report))
+}
+
+/// A profile as the webview applies it: little-endian `u16` RGB samples of
+/// an sRGB-to-sRGB cube, blue fastest.
+#[tauri::command]
+fn read_profile(
+    app: AppHandle,
+    profiles: State<'_, ProfileStore>,
+    id: String,
+) -> CommandResult<Response> {
+    Ok(Response::new(
+        resolve_profile(&app, &profiles, &id)?.to_le_u16_bytes(),
+    ))
+}
+
+#[tauri::command]
+fn list_film_presets(
+    app: AppHandle,
+    presets: State<'_, PresetStore>,
+) -> CommandResult<Vec<UserPreset>> {
+    presets.list(&app_config_dir(&app)?)
+}
+
+#[tauri::command]
+fn save_film_preset(
+    app: AppHandle,
+    presets: State<'_, PresetStore>,
+    preset: PresetDraft,
+) -> CommandResult<UserPreset> {
+    presets.save(&app_config_dir(&app)?, preset)
+}
+
+#[tauri::command]
+fn rename_film_preset(
+    app: AppHandle,
+    presets: State<'_, PresetStore>,
+    id: String,
+    name: String,
+) -> CommandResult<UserPreset> {
+    presets.rename(&app_config_dir(&app)?, &id, &name)
+}
+
+#[tauri::command]
+fn delete_film_preset(
+    app: AppHandle,
+    presets: State<'_, PresetStore>,
+    id: String,
+) -> CommandResult<bool> {
+    presets.delete(&app_config_dir(&app)?, &id)
+}
+
+fn preset_bundle_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new().add_filter(
+        "Negative Converter Presets",
+        &[preset_store::PRESET_BUNDLE_EXTENSION, "json"],
+    )
+}
+
+/// Saves the presets with `ids`, or all user presets, as a bundle to share.
+#[tauri::command]
+fn export_film_presets(
+    app: AppHandle,
+    scopes: State<'_, WriteScopes>,
+    presets: State<'_, PresetStore>,
+    ids: Option<Vec<String>>,
+) -> CommandResult<SaveResult> {
+    let bytes = presets.export_bundle(&app_config_dir(&app)?, ids.as_deref())?;
+    let Some(path) = preset_bundle_dialog()
+        .set_file_name(format!(
+            "film-presets.{}",
+            preset_store::PRESET_BUNDLE_EXTENSION
+        ))
+        .save_file()
+    else {
+        return Ok(SaveResult::cancelled());
+    };
+    scopes.allow_file(&path);
+    let report = atomic_write::write_atomic(&path, &bytes, false)?;
+    Ok(SaveResult::written(&path, report))
+}
+
+/// Adds the presets of a bundle picked in a native dialog. `None` when the
+/// dialog is cancelled.
+#[tauri::command]
+fn import_film_presets(
+    app: AppHandle,
+    presets: State<'_, PresetStore>,
+) -> CommandResult<Option<PresetImport>> {
+    let Some(source) = preset_bundle_dialog().pick_file() else {
+        return Ok(None);
+    };
+    let json = std::fs::read_to_string(&source)
+        .map_err(|err| CommandError::io("read preset bundle", err))?;
+    presets
+        .import_bundle(&app_config_dir(&app)?, &json)
+        .map(Some)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     apply_linux_appimage_compat_env();
     tauri::Builder::default()
+        .manage(ExportUploads::default())
+        .manage(Autosave::default())
+        .manage(ZipExports::default())
+        .manage(WriteScopes::default())
+        .manage(ExportJobs::default())
+        .manage(ProfileStore::default())
+        .manage(PresetStore::default())
         .invoke_handler(tauri::generate_handler![
             save_export_file,
             pick_export_file_path,
             pick_export_directory,
+            open_input_files,
+            read_input_file,
+            read_settings_sidecar,
+            write_settings_sidecar,
+            save_project,
+            open_project,
+            list_recent_projects,
+            autosave_session,
+            recover_last_session,
+            discard_autosave_session,
             write_export_file_to_path,
             write_export_file_to_directory,
+            preflight_export,
+            begin_export_upload,
+            append_export_chunk,
+            finish_export_upload,
+            abort_export_upload,
+            begin_zip_export,
+            add_zip_entry,
+            finish_zip_export,
+            abort_zip_export,
+            convert_frame,
+            detect_film_base,
+            analyze_roll_film_base,
+            detect_frame,
+            write_tiff_export,
+            begin_export_job,
+            queue_export_file,
+            queue_export_frame,
+            settle_export_file,
+            cancel_export_job,
+            decode_raw_file,
             get_app_version,
-            open_external_url
+            open_external_url,
+            reveal_in_file_manager,
+            open_with_default_app,
+            list_profiles,
+            import_profile,
+            export_profile_as_cube,
+            read_profile,
+            list_film_presets,
+            save_film_preset,
+            rename_film_preset,
+            delete_film_preset,
+            export_film_presets,
+            import_film_presets
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<ExportUploads>().abort_all();
+                app.state::<ZipExports>().abort_all();
+            }
+        });
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        decide_dmabuf_policy, looks_like_legacy_appimage_name, normalize_export_path,
-        parse_bool_flag, AppImageVariant, DmabufDecision, DmabufDisableReason,
-        DmabufKeepReason, DmabufProbeKind,
+        decide_directory_export, decide_dmabuf_policy, encode_job_frame, input_file_entry,
+        looks_like_legacy_appimage_name, normalize_export_path, parse_bool_flag, read_job_frame,
+        resolve_path_export, AppImageVariant, ConflictAnswer, ConflictPolicy, DmabufDecision,
+        DmabufDisableReason, DmabufKeepReason, DmabufProbeKind, ExportConflict, ExportJobFrame,
+        WriteScopes,
     };
+    use crate::engine::auto_frame::{tests::scan, AutoFrameSettings};
+    use crate::engine::Image16;
+    use crate::image_io;
     use std::path::PathBuf;
 
     #[test]
@@ -600,6 +2197,25 @@ mod tests {
         assert!(!looks_like_legacy_appimage_name("legacy-build.AppImage"));
     }
 
+    #[test]
+    fn input_file_entry_reports_name_and_size() {
+        let dir = std::env::temp_dir().join(format!(
+            "negative-converter-input-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("frame_01.nef");
+        std::fs::write(&path, [0u8; 12]).unwrap();
+
+        let entry = input_file_entry(&path).unwrap();
+        assert_eq!(entry.name, "frame_01.nef");
+        assert_eq!(entry.size, 12);
+        assert!(entry.last_modified > 0);
+        assert!(input_file_entry(&dir).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn normalize_export_path_adds_missing_extension_from_suggested_name() {
         let path = normalize_export_path(PathBuf::from("/tmp/converted_negatives"), "converted.zip");
@@ -679,4 +2295,133 @@ mod tests {
             DmabufDecision::Disable(DmabufDisableReason::PermissionDenied)
         );
     }
+
+    #[test]
+    fn path_exports_replace_only_files_picked_themselves() {
+        let dir = std::env::temp_dir().join(format!("nc-path-export-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let existing = dir.join("frame.tif");
+        std::fs::write(&existing, b"keep").unwrap();
+        let path = existing.to_string_lossy().to_string();
+        let conflict = |policy| ExportConflict {
+            policy,
+            ..ExportConflict::default()
+        };
+
+        let scopes = WriteScopes::default();
+        scopes.allow_directory(&dir);
+        let overwrite =
+            resolve_path_export(&scopes, &path, Some(&conflict(ConflictPolicy::Overwrite)));
+        assert_eq!(overwrite.unwrap_err().code(), "AlreadyExists");
+        let renamed =
+            resolve_path_export(&scopes, &path, Some(&conflict(ConflictPolicy::Rename))).unwrap();
+        assert_eq!(renamed.path, dir.join("frame_1.tif"));
+
+        scopes.allow_file(&existing);
+        let overwrite =
+            resolve_path_export(&scopes, &path, Some(&conflict(ConflictPolicy::Overwrite)))
+                .unwrap();
+        assert_eq!(overwrite.path, existing);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn directory_exports_ask_before_replacing_files() {
+        let dir = std::env::temp_dir().join(format!("nc-dir-export-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.tif"), b"keep").unwrap();
+        std::fs::write(dir.join("b.tif"), b"keep").unwrap();
+        let directory = dir.to_string_lossy().to_string();
+        let overwrite = ExportConflict {
+            policy: ConflictPolicy::Overwrite,
+            remaining: 1,
+            ..ExportConflict::default()
+        };
+        let answer = |policy, apply_to_all| {
+            move |_: &std::path::Path, _: u32| {
+                Ok(ConflictAnswer {
+                    policy,
+                    apply_to_all,
+                })
+            }
+        };
+        let never = |_: &std::path::Path, _: u32| -> crate::CommandResult<ConflictAnswer> {
+            panic!("asked again")
+        };
+
+        let scopes = WriteScopes::default();
+        scopes.allow_directory(&dir);
+        let fresh =
+            decide_directory_export(&scopes, &directory, "c.tif", None, Some(&overwrite), never)
+                .unwrap();
+        assert_eq!((fresh.path, fresh.skip), (dir.join("c.tif"), false));
+
+        let skipped = decide_directory_export(
+            &scopes,
+            &directory,
+            "a.tif",
+            None,
+            Some(&overwrite),
+            answer(ConflictPolicy::Skip, false),
+        )
+        .unwrap();
+        assert!(skipped.skip);
+
+        let replaced = decide_directory_export(
+            &scopes,
+            &directory,
+            "a.tif",
+            None,
+            Some(&overwrite),
+            answer(ConflictPolicy::Overwrite, true),
+        )
+        .unwrap();
+        assert_eq!((replaced.path, replaced.skip), (dir.join("a.tif"), false));
+        let rest =
+            decide_directory_export(&scopes, &directory, "b.tif", None, Some(&overwrite), never)
+                .unwrap();
+        assert_eq!((rest.path, rest.skip), (dir.join("b.tif"), false));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn queued_frames_are_framed_before_encoding() {
+        let dir = std::env::temp_dir().join(format!("nc-job-frame-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("frame.png");
+        let plain = Image16::new(60, 40, vec![20000; 60 * 40 * 4]).unwrap();
+        std::fs::write(&source, image_io::encode_png16(&plain).unwrap()).unwrap();
+
+        let mut frame: ExportJobFrame = serde_json::from_value(serde_json::json!({
+            "jobId": 1,
+            "index": 0,
+            "sourcePath": source.to_string_lossy(),
+            "settings": {
+                "rotationAngle": -90,
+                "cropRegion": { "left": 5, "top": 10, "width": 30, "height": 45 }
+            },
+            "target": { "kind": "path", "path": dir.join("out.png").to_string_lossy() },
+            "format": "png"
+        }))
+        .unwrap();
+        let mut settings = frame.settings.clone();
+        let image = read_job_frame(&source, &mut settings, None).unwrap();
+        let encoded = encode_job_frame(&frame, &image).unwrap();
+        let output = dir.join("out.png");
+        std::fs::write(&output, encoded).unwrap();
+        let written = image_io::read_input_image(&output).unwrap();
+        assert_eq!((written.width, written.height), (30, 45));
+
+        // With auto frame the detected frame replaces the snapshot's crop.
+        let framed = scan(1200, 800, 900.0, 600.0, 0.0);
+        std::fs::write(&source, image_io::encode_png16(&framed).unwrap()).unwrap();
+        let auto = AutoFrameSettings::default();
+        let image = read_job_frame(&source, &mut frame.settings, Some(&auto)).unwrap();
+        assert!(image.width.abs_diff(900) <= 24, "{}", image.width);
+        assert!(image.height.abs_diff(600) <= 24, "{}", image.height);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
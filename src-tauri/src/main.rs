@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<std::ffi::OsString> = std::env::args_os().skip(1).collect();
+    if let Some(exit_code) = negative_converter_lib::run_cli(&args) {
+        std::process::exit(exit_code);
+    }
     negative_converter_lib::run()
 }
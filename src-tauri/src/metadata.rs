@@ -0,0 +1,577 @@
+//! Metadata carried into exports: the source frame's EXIF (shoot date,
+//! camera, lens) and an XMP packet recording the film stock, profile, and
+//! film base, so scanned rolls can be sorted in a DAM.
+
+use crate::raw::{exif_number, exif_text};
+use crate::tiff_writer;
+use serde::Deserialize;
+use std::path::Path;
+
+pub const XMP_NAMESPACE_FILM: &str = "https://negative-converter.tokugai.com/ns/film/1.0/";
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+const PNG_XMP_KEYWORD: &[u8] = b"XML:com.adobe.xmp";
+const JPEG_EXIF_HEADER: &[u8] = b"Exif\0\0";
+const JPEG_XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const JPEG_MAX_SEGMENT_PAYLOAD: usize = 65533;
+
+const TAG_MAKE: u16 = 271;
+const TAG_MODEL: u16 = 272;
+const TAG_DATE_TIME: u16 = 306;
+pub const TAG_XMP: u16 = 700;
+pub const TAG_EXIF_IFD: u16 = 34665;
+const TAG_EXPOSURE_F_NUMBER: u16 = 33437;
+const TAG_EXIF_VERSION: u16 = 36864;
+const TAG_DATE_TIME_ORIGINAL: u16 = 36867;
+const TAG_FOCAL_LENGTH: u16 = 37386;
+const TAG_LENS_MAKE: u16 = 42035;
+const TAG_LENS_MODEL: u16 = 42036;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagValue {
+    Ascii(String),
+    Rational(u32, u32),
+    Undefined(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataTag {
+    pub tag: u16,
+    pub value: TagValue,
+}
+
+/// Ready-to-embed metadata: TIFF/EXIF tags for IFD0 and the Exif sub-IFD,
+/// plus the XMP packet.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedMetadata {
+    pub ifd0: Vec<MetadataTag>,
+    pub exif: Vec<MetadataTag>,
+    pub xmp: String,
+}
+
+/// Camera fields in the shape `state.rawMetadata` uses, plus the shoot date
+/// as an EXIF `YYYY:MM:DD HH:MM:SS` string.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SourceMetadata {
+    pub date_time_original: Option<String>,
+    pub camera_maker: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_maker: Option<String>,
+    pub lens_model: Option<String>,
+    pub focal: Option<f64>,
+    pub aperture: Option<f64>,
+}
+
+impl SourceMetadata {
+    /// Reads EXIF from any container kamadak-exif understands (JPEG, PNG,
+    /// TIFF-based RAWs). Unreadable files yield empty metadata.
+    pub fn read(path: &Path) -> Self {
+        let Some(exif) = std::fs::File::open(path).ok().and_then(|file| {
+            exif::Reader::new()
+                .read_from_container(&mut std::io::BufReader::new(file))
+                .ok()
+        }) else {
+            return Self::default();
+        };
+        Self {
+            date_time_original: exif_text(&exif, exif::Tag::DateTimeOriginal)
+                .or_else(|| exif_text(&exif, exif::Tag::DateTime)),
+            camera_maker: exif_text(&exif, exif::Tag::Make),
+            camera_model: exif_text(&exif, exif::Tag::Model),
+            lens_maker: exif_text(&exif, exif::Tag::LensMake),
+            lens_model: exif_text(&exif, exif::Tag::LensModel),
+            focal: exif_number(&exif, exif::Tag::FocalLength),
+            aperture: exif_number(&exif, exif::Tag::FNumber),
+        }
+    }
+
+    /// Keeps fields read from the file and fills the gaps from `fallback`.
+    fn or(self, fallback: &Self) -> Self {
+        let text = |value: Option<String>, other: &Option<String>| {
+            non_empty(value).or_else(|| non_empty(other.clone()))
+        };
+        let number = |value: Option<f64>, other: Option<f64>| {
+            value
+                .filter(|v| v.is_finite() && *v > 0.0)
+                .or(other.filter(|v| v.is_finite() && *v > 0.0))
+        };
+        Self {
+            date_time_original: text(self.date_time_original, &fallback.date_time_original),
+            camera_maker: text(self.camera_maker, &fallback.camera_maker),
+            camera_model: text(self.camera_model, &fallback.camera_model),
+            lens_maker: text(self.lens_maker, &fallback.lens_maker),
+            lens_model: text(self.lens_model, &fallback.lens_model),
+            focal: number(self.focal, fallback.focal),
+            aperture: number(self.aperture, fallback.aperture),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct FilmBaseRgb {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+/// The conversion settings worth recording, named as in the settings snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FilmMetadata {
+    pub core_film_preset: String,
+    pub core_enhanced_profile: String,
+    pub film_type: String,
+    pub film_base: Option<FilmBaseRgb>,
+}
+
+/// What the webview sends alongside an export. EXIF is re-read from
+/// `source_path` when there is one; `source` fills in what the file lacks.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportMetadata {
+    pub source_path: Option<String>,
+    pub source: SourceMetadata,
+    pub film: Option<FilmMetadata>,
+}
+
+impl ExportMetadata {
+    pub fn resolve(&self) -> EmbeddedMetadata {
+        build_embedded(&self.source_metadata(), self.film.as_ref())
+    }
+
+    /// The shoot date as `YYYY-MM-DD`, for naming exports.
+    pub fn capture_date(&self) -> Option<String> {
+        let (_, xmp_date) =
+            normalize_exif_date(self.source_metadata().date_time_original?.as_str())?;
+        Some(xmp_date[..10].to_string())
+    }
+
+    fn source_metadata(&self) -> SourceMetadata {
+        match non_empty(self.source_path.clone()) {
+            Some(path) => SourceMetadata::read(Path::new(&path)).or(&self.source),
+            None => SourceMetadata::default().or(&self.source),
+        }
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn to_rational(value: f64) -> TagValue {
+    TagValue::Rational(
+        (value * 100.0).round().clamp(0.0, u32::MAX as f64) as u32,
+        100,
+    )
+}
+
+/// Accepts EXIF `YYYY:MM:DD HH:MM:SS` (what cameras write) and returns it
+/// alongside the ISO 8601 form XMP wants.
+fn normalize_exif_date(value: &str) -> Option<(String, String)> {
+    let value = value.trim();
+    let bytes = value.as_bytes();
+    if bytes.len() < 19 || !value.is_char_boundary(19) {
+        return None;
+    }
+    let digits = [0, 1, 2, 3, 5, 6, 8, 9, 11, 12, 14, 15, 17, 18];
+    if !digits.iter().all(|&i| bytes[i].is_ascii_digit()) {
+        return None;
+    }
+    let date = &value[..10];
+    let time = &value[11..19];
+    if date.starts_with("0000") {
+        return None;
+    }
+    let exif_date = format!("{}:{}:{} {time}", &date[..4], &date[5..7], &date[8..10]);
+    let xmp_date = format!("{}-{}-{}T{time}", &date[..4], &date[5..7], &date[8..10]);
+    Some((exif_date, xmp_date))
+}
+
+pub fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            ch if (ch as u32) < 0x20 && !matches!(ch, '\t' | '\n' | '\r') => {}
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn build_embedded(source: &SourceMetadata, film: Option<&FilmMetadata>) -> EmbeddedMetadata {
+    let date = source
+        .date_time_original
+        .as_deref()
+        .and_then(normalize_exif_date);
+
+    let mut ifd0 = Vec::new();
+    let mut exif = Vec::new();
+    let ascii = |tag, value: &Option<String>| {
+        value.as_ref().map(|text| MetadataTag {
+            tag,
+            value: TagValue::Ascii(text.clone()),
+        })
+    };
+    ifd0.extend(ascii(TAG_MAKE, &source.camera_maker));
+    ifd0.extend(ascii(TAG_MODEL, &source.camera_model));
+    if let Some((exif_date, _)) = &date {
+        ifd0.push(MetadataTag {
+            tag: TAG_DATE_TIME,
+            value: TagValue::Ascii(exif_date.clone()),
+        });
+        exif.push(MetadataTag {
+            tag: TAG_DATE_TIME_ORIGINAL,
+            value: TagValue::Ascii(exif_date.clone()),
+        });
+    }
+    if let Some(aperture) = source.aperture {
+        exif.push(MetadataTag {
+            tag: TAG_EXPOSURE_F_NUMBER,
+            value: to_rational(aperture),
+        });
+    }
+    if let Some(focal) = source.focal {
+        exif.push(MetadataTag {
+            tag: TAG_FOCAL_LENGTH,
+            value: to_rational(focal),
+        });
+    }
+    exif.extend(ascii(TAG_LENS_MAKE, &source.lens_maker));
+    exif.extend(ascii(TAG_LENS_MODEL, &source.lens_model));
+    if !exif.is_empty() {
+        exif.push(MetadataTag {
+            tag: TAG_EXIF_VERSION,
+            value: TagValue::Undefined(b"0232".to_vec()),
+        });
+    }
+
+    EmbeddedMetadata {
+        ifd0,
+        exif,
+        xmp: build_xmp(source, date.as_ref().map(|(_, xmp)| xmp.as_str()), film),
+    }
+}
+
+/// `nc:` attributes describing the film settings, shared with sidecars.
+pub fn film_xmp_attributes(film: &FilmMetadata) -> Vec<(&'static str, String)> {
+    let mut attributes = Vec::new();
+    let texts = [
+        ("nc:FilmStock", &film.core_film_preset),
+        ("nc:EnhancedProfile", &film.core_enhanced_profile),
+        ("nc:FilmType", &film.film_type),
+    ];
+    for (name, value) in texts {
+        let value = value.trim();
+        if !value.is_empty() {
+            attributes.push((name, value.to_string()));
+        }
+    }
+    if let Some(base) = film.film_base {
+        attributes.push(("nc:FilmBaseR", format!("{}", base.r.round())));
+        attributes.push(("nc:FilmBaseG", format!("{}", base.g.round())));
+        attributes.push(("nc:FilmBaseB", format!("{}", base.b.round())));
+    }
+    attributes
+}
+
+fn build_xmp(
+    source: &SourceMetadata,
+    xmp_date: Option<&str>,
+    film: Option<&FilmMetadata>,
+) -> String {
+    let mut attributes = vec![(
+        "xmp:CreatorTool",
+        concat!("Negative Converter ", env!("CARGO_PKG_VERSION")).to_string(),
+    )];
+    if let Some(date) = xmp_date {
+        attributes.push(("xmp:CreateDate", date.to_string()));
+        attributes.push(("exif:DateTimeOriginal", date.to_string()));
+    }
+    let texts = [
+        ("tiff:Make", &source.camera_maker),
+        ("tiff:Model", &source.camera_model),
+        ("exifEX:LensMake", &source.lens_maker),
+        ("exifEX:LensModel", &source.lens_model),
+    ];
+    for (name, value) in texts {
+        if let Some(value) = value {
+            attributes.push((name, value.clone()));
+        }
+    }
+    if let Some(focal) = source.focal {
+        attributes.push((
+            "exif:FocalLength",
+            format!("{}/100", (focal * 100.0).round()),
+        ));
+    }
+    if let Some(aperture) = source.aperture {
+        attributes.push((
+            "exif:FNumber",
+            format!("{}/100", (aperture * 100.0).round()),
+        ));
+    }
+
+    let mut subjects = Vec::new();
+    if let Some(film) = film {
+        attributes.extend(film_xmp_attributes(film));
+        let stock = film.core_film_preset.trim();
+        if !stock.is_empty() && stock != "none" {
+            subjects.push(stock.to_string());
+        }
+    }
+
+    let mut xmp = String::from(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+         <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+         <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+         <rdf:Description rdf:about=\"\"\n \
+         xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n \
+         xmlns:tiff=\"http://ns.adobe.com/tiff/1.0/\"\n \
+         xmlns:exif=\"http://ns.adobe.com/exif/1.0/\"\n \
+         xmlns:exifEX=\"http://cipa.jp/exif/1.0/\"\n \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n",
+    );
+    xmp.push_str(&format!(" xmlns:nc=\"{XMP_NAMESPACE_FILM}\""));
+    for (name, value) in &attributes {
+        xmp.push_str(&format!("\n {name}=\"{}\"", escape_xml(value)));
+    }
+    if subjects.is_empty() {
+        xmp.push_str("/>\n");
+    } else {
+        xmp.push_str(">\n<dc:subject>\n<rdf:Bag>\n");
+        for subject in &subjects {
+            xmp.push_str(&format!("<rdf:li>{}</rdf:li>\n", escape_xml(subject)));
+        }
+        xmp.push_str("</rdf:Bag>\n</dc:subject>\n</rdf:Description>\n");
+    }
+    xmp.push_str("</rdf:RDF>\n</x:xmpmeta>\n<?xpacket end=\"w\"?>");
+    xmp
+}
+
+fn png_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut crc = flate2::Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    let mut chunk = Vec::with_capacity(data.len() + 12);
+    chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(kind);
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(&crc.sum().to_be_bytes());
+    chunk
+}
+
+/// Inserts `eXIf` and an XMP `iTXt` chunk after IHDR, replacing any that
+/// the encoder already wrote.
+fn embed_png(bytes: &[u8], metadata: &EmbeddedMetadata) -> Result<Vec<u8>, String> {
+    if bytes.len() < 8 || &bytes[..8] != PNG_SIGNATURE {
+        return Err("export is not a PNG".to_string());
+    }
+    let mut out = Vec::with_capacity(bytes.len() + metadata.xmp.len() + 1024);
+    out.extend_from_slice(PNG_SIGNATURE);
+
+    let mut offset = 8;
+    while offset < bytes.len() {
+        if offset + 12 > bytes.len() {
+            return Err("PNG chunk is truncated".to_string());
+        }
+        let length = u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        let end = offset + 12 + length;
+        if end > bytes.len() {
+            return Err("PNG chunk is truncated".to_string());
+        }
+        let kind = &bytes[offset + 4..offset + 8];
+        let data = &bytes[offset + 8..offset + 8 + length];
+        let is_xmp = kind == b"iTXt" && data.starts_with(PNG_XMP_KEYWORD);
+        if kind != b"eXIf" && !is_xmp {
+            out.extend_from_slice(&bytes[offset..end]);
+        }
+        if kind == b"IHDR" {
+            if !metadata.ifd0.is_empty() || !metadata.exif.is_empty() {
+                let exif = tiff_writer::encode_exif_block(metadata)?;
+                out.extend_from_slice(&png_chunk(b"eXIf", &exif));
+            }
+            if !metadata.xmp.is_empty() {
+                let mut text = PNG_XMP_KEYWORD.to_vec();
+                // Null separator, uncompressed, no language or translated keyword.
+                text.extend_from_slice(&[0, 0, 0, 0, 0]);
+                text.extend_from_slice(metadata.xmp.as_bytes());
+                out.extend_from_slice(&png_chunk(b"iTXt", &text));
+            }
+        }
+        offset = end;
+    }
+    Ok(out)
+}
+
+fn jpeg_segment(payload_header: &[u8], payload: &[u8]) -> Result<Vec<u8>, String> {
+    let length = payload_header.len() + payload.len();
+    if length > JPEG_MAX_SEGMENT_PAYLOAD {
+        return Err("metadata is too large for a JPEG APP1 segment".to_string());
+    }
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&((length + 2) as u16).to_be_bytes());
+    segment.extend_from_slice(payload_header);
+    segment.extend_from_slice(payload);
+    Ok(segment)
+}
+
+/// Inserts Exif and XMP APP1 segments after SOI (and JFIF APP0, if present),
+/// dropping any existing ones.
+fn embed_jpeg(bytes: &[u8], metadata: &EmbeddedMetadata) -> Result<Vec<u8>, String> {
+    if bytes.len() < 4 || bytes[0] != 0xFF || bytes[1] != 0xD8 {
+        return Err("export is not a JPEG".to_string());
+    }
+    let mut head = Vec::new();
+    let mut segments = Vec::new();
+    let mut offset = 2;
+    loop {
+        if offset + 4 > bytes.len() || bytes[offset] != 0xFF {
+            return Err("JPEG segment is malformed".to_string());
+        }
+        let marker = bytes[offset + 1];
+        if marker == 0xDA {
+            break;
+        }
+        let length = u16::from_be_bytes([bytes[offset + 2], bytes[offset + 3]]) as usize;
+        let end = offset + 2 + length;
+        if length < 2 || end > bytes.len() {
+            return Err("JPEG segment is truncated".to_string());
+        }
+        let payload = &bytes[offset + 4..end];
+        let is_metadata = marker == 0xE1
+            && (payload.starts_with(JPEG_EXIF_HEADER) || payload.starts_with(JPEG_XMP_HEADER));
+        if marker == 0xE0 && segments.is_empty() {
+            head.extend_from_slice(&bytes[offset..end]);
+        } else if !is_metadata {
+            segments.extend_from_slice(&bytes[offset..end]);
+        }
+        offset = end;
+    }
+
+    let mut out = Vec::with_capacity(bytes.len() + metadata.xmp.len() + 1024);
+    out.extend_from_slice(&bytes[..2]);
+    out.extend_from_slice(&head);
+    if !metadata.ifd0.is_empty() || !metadata.exif.is_empty() {
+        let exif = tiff_writer::encode_exif_block(metadata)?;
+        out.extend_from_slice(&jpeg_segment(JPEG_EXIF_HEADER, &exif)?);
+    }
+    if !metadata.xmp.is_empty() {
+        out.extend_from_slice(&jpeg_segment(JPEG_XMP_HEADER, metadata.xmp.as_bytes())?);
+    }
+    out.extend_from_slice(&segments);
+    out.extend_from_slice(&bytes[offset..]);
+    Ok(out)
+}
+
+/// Embeds metadata into encoded PNG, JPEG or TIFF bytes, picked by
+/// extension. Other formats pass through unchanged.
+pub fn embed_metadata(
+    bytes: Vec<u8>,
+    extension: &str,
+    metadata: &EmbeddedMetadata,
+) -> Result<Vec<u8>, String> {
+    match extension {
+        "png" => embed_png(&bytes, metadata),
+        "jpg" | "jpeg" => embed_jpeg(&bytes, metadata),
+        "tif" | "tiff" => tiff_writer::embed_tiff_metadata(bytes, metadata),
+        _ => Ok(bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_embedded, embed_metadata, normalize_exif_date, FilmBaseRgb, FilmMetadata,
+        SourceMetadata,
+    };
+
+    fn sample_metadata() -> super::EmbeddedMetadata {
+        let source = SourceMetadata {
+            date_time_original: Some("2024:05:01 12:30:00".to_string()),
+            camera_maker: Some("Nikon".to_string()),
+            camera_model: Some("Z 7".to_string()),
+            lens_model: Some("Micro 60mm f/2.8 & <ES-2>".to_string()),
+            focal: Some(60.0),
+            aperture: Some(8.0),
+            ..SourceMetadata::default()
+        };
+        let film = FilmMetadata {
+            core_film_preset: "portra-400".to_string(),
+            core_enhanced_profile: "none".to_string(),
+            film_type: "color".to_string(),
+            film_base: Some(FilmBaseRgb {
+                r: 210.0,
+                g: 140.0,
+                b: 90.0,
+            }),
+        };
+        build_embedded(&source, Some(&film))
+    }
+
+    #[test]
+    fn exif_dates_normalize_for_exif_and_xmp() {
+        assert_eq!(
+            normalize_exif_date("2024:05:01 12:30:00"),
+            Some((
+                "2024:05:01 12:30:00".to_string(),
+                "2024-05-01T12:30:00".to_string()
+            ))
+        );
+        assert_eq!(normalize_exif_date("0000:00:00 00:00:00"), None);
+        assert_eq!(normalize_exif_date("yesterday"), None);
+    }
+
+    #[test]
+    fn xmp_records_film_settings_and_escapes_text() {
+        let xmp = sample_metadata().xmp;
+        assert!(xmp.contains("nc:FilmStock=\"portra-400\""));
+        assert!(xmp.contains("nc:FilmBaseR=\"210\""));
+        assert!(xmp.contains("xmp:CreateDate=\"2024-05-01T12:30:00\""));
+        assert!(xmp.contains("Micro 60mm f/2.8 &amp; &lt;ES-2&gt;"));
+        assert!(xmp.contains("<rdf:li>portra-400</rdf:li>"));
+    }
+
+    #[test]
+    fn png_exports_carry_readable_exif() {
+        let image = crate::engine::Image16::new(1, 1, vec![1, 2, 3, 65535]).unwrap();
+        let png = crate::image_io::encode_png16(&image).unwrap();
+        let tagged = embed_metadata(png, "png", &sample_metadata()).unwrap();
+
+        let exif = exif::Reader::new()
+            .read_from_container(&mut std::io::Cursor::new(&tagged))
+            .unwrap();
+        let model = exif.get_field(exif::Tag::Model, exif::In::PRIMARY).unwrap();
+        assert_eq!(model.display_value().to_string(), "\"Z 7\"");
+        let date = exif
+            .get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)
+            .unwrap();
+        assert_eq!(date.display_value().to_string(), "2024-05-01 12:30:00");
+        assert!(crate::image_io::decode_png(&tagged).is_ok());
+    }
+
+    #[test]
+    fn jpeg_exports_keep_jfif_first_and_gain_app1_segments() {
+        // SOI, JFIF APP0, SOS with one byte of scan data, EOI.
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x07];
+        jpeg.extend_from_slice(b"JFIF\0");
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x42, 0xFF, 0xD9]);
+        let tagged = embed_metadata(jpeg, "jpg", &sample_metadata()).unwrap();
+
+        assert_eq!(&tagged[2..4], &[0xFF, 0xE0]);
+        assert_eq!(&tagged[11..13], &[0xFF, 0xE1]);
+        assert!(tagged.ends_with(&[0xFF, 0xDA, 0x00, 0x02, 0x42, 0xFF, 0xD9]));
+        let exif = exif::Reader::new()
+            .read_from_container(&mut std::io::Cursor::new(&tagged))
+            .unwrap();
+        let focal = exif
+            .get_field(exif::Tag::FocalLength, exif::In::PRIMARY)
+            .unwrap();
+        assert_eq!(focal.display_value().to_string(), "60");
+    }
+}
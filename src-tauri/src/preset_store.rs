@@ -0,0 +1,652 @@
+//! The user's own film presets, kept in `<app config>/film-presets.json`
+//! next to the bundled ones from `FilmPresets.js`.
+//!
+//! User presets have `user:<slug>` ids so they can never shadow a bundled
+//! preset, and their `settings` take the same keys as a bundled preset's.
+//! The store file doubles as the bundle format users share presets in; an
+//! object of presets keyed by id, the shape of `film-presets.json`, imports
+//! too.
+
+use crate::atomic_write::write_atomic;
+use crate::engine::lut3d;
+use crate::engine::presets::FilmPresetSettings;
+use crate::error::{CommandError, CommandResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+pub const USER_PRESET_PREFIX: &str = "user:";
+pub const PRESET_BUNDLE_EXTENSION: &str = "ncpresets";
+const STORE_FILE: &str = "film-presets.json";
+const BUNDLE_FORMAT: &str = "negative-converter-film-presets";
+const BUNDLE_SCHEMA_VERSION: u32 = 1;
+const CATEGORIES: &[&str] = &["color", "bw", "positive"];
+const MAX_NAME_CHARS: usize = 80;
+
+/// Accepted ranges of the numeric settings, as `buildSilverCoreParams`
+/// clamps them.
+const NUMERIC_SETTINGS: &[(&str, f64, f64)] = &[
+    ("shadows", -100.0, 100.0),
+    ("highlights", -100.0, 100.0),
+    ("blacks", -100.0, 100.0),
+    ("whites", -100.0, 100.0),
+    ("saturation", 0.0, 200.0),
+    ("glow", 0.0, 100.0),
+    ("fade", 0.0, 100.0),
+    ("shadowRange", 0.0, 10.0),
+    ("highlightRange", 0.0, 10.0),
+    ("shadowCyan", -100.0, 100.0),
+    ("shadowTint", -100.0, 100.0),
+    ("shadowTemp", -100.0, 100.0),
+    ("highlightCyan", -100.0, 100.0),
+    ("highlightTint", -100.0, 100.0),
+    ("highlightTemp", -100.0, 100.0),
+    ("midCyan", -100.0, 100.0),
+    ("midTint", -100.0, 100.0),
+    ("midTemp", -100.0, 100.0),
+];
+
+const TONE_PROFILES: &[&str] = &[
+    "standard",
+    "autotone",
+    "base",
+    "base_gamma",
+    "base_flat",
+    "base_deep",
+    "filmic",
+    "filmic_rich",
+    "filmic_flat",
+    "all_hard",
+    "all_soft",
+    "highlight_hard",
+    "highlight_soft",
+    "shadow_hard",
+    "shadow_soft",
+];
+const LAYER_ORDERS: &[&str] = &["colorFirst", "tonesFirst"];
+const WB_TONALITIES: &[&str] = &[
+    "addDensity",
+    "neutralDensity",
+    "subtractDensity",
+    "tempTintDensity",
+];
+const CURVE_RESOLUTION_TYPES: &[&str] = &["auto_curve_pts"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserPreset {
+    pub id: String,
+    pub name: String,
+    /// `color`, `bw` or `positive`, like the bundled presets.
+    pub category: String,
+    pub settings: Map<String, Value>,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+impl UserPreset {
+    /// The settings as the native engine reads them.
+    pub fn film_preset_settings(&self) -> FilmPresetSettings {
+        serde_json::from_value(Value::Object(self.settings.clone())).unwrap_or_default()
+    }
+}
+
+/// A preset as the webview sends it to be saved. Without an `id` a new
+/// preset is created.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetDraft {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub category: String,
+    pub settings: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PresetBundle {
+    format: String,
+    schema_version: u32,
+    presets: Vec<UserPreset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetImport {
+    pub imported: Vec<UserPreset>,
+    /// Names of presets already in the library with the same settings.
+    pub unchanged: Vec<String>,
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn invalid(message: String) -> CommandError {
+    CommandError::DecodeFailed(message)
+}
+
+/// Checks `settings` against the keys and values bundled presets use.
+pub fn validate_settings(settings: &Map<String, Value>) -> Result<(), String> {
+    for (key, value) in settings {
+        if let Some((_, min, max)) = NUMERIC_SETTINGS.iter().find(|(name, ..)| name == key) {
+            let number = value
+                .as_f64()
+                .ok_or_else(|| format!("{key} must be a number"))?;
+            if !(*min..=*max).contains(&number) {
+                return Err(format!("{key} must be between {min} and {max}"));
+            }
+            continue;
+        }
+        let text = value
+            .as_str()
+            .ok_or_else(|| format!("{key} must be a string"))?;
+        let allowed = match key.as_str() {
+            "toneProfile" => TONE_PROFILES,
+            "layerOrder" => LAYER_ORDERS,
+            "wbTonality" => WB_TONALITIES,
+            "curveResolutionType" => CURVE_RESOLUTION_TYPES,
+            "enhancedProfile" => {
+                let known = text == "none"
+                    || text.starts_with("user:")
+                    || lut3d::BUILTIN_PROFILES
+                        .iter()
+                        .any(|(name, _)| *name == text);
+                if !known {
+                    return Err(format!("unknown enhancedProfile {text:?}"));
+                }
+                continue;
+            }
+            _ => return Err(format!("unknown preset setting {key:?}")),
+        };
+        if !allowed.contains(&text) {
+            return Err(format!("unknown {key} {text:?}"));
+        }
+    }
+    Ok(())
+}
+
+/// Mirrors `inferFilmTypeFromLegacyPreset` in `main.js`, for presets from
+/// older bundles that carry only their id.
+fn infer_category(id: &str) -> &'static str {
+    let id = id.trim().to_lowercase();
+    let any = |needles: &[&str]| needles.iter().any(|needle| id.contains(needle));
+    if id.ends_with("_positive") || any(&["positive", "provia", "velvia", "ektachrome", "slide"]) {
+        "positive"
+    } else if id.ends_with("_bw")
+        || any(&[
+            "bw", "ilford", "trix", "tri-x", "tmax", "acros", "hp5", "fp4", "panf", "delta", "sfx",
+            "xp2", "neopan",
+        ])
+    {
+        "bw"
+    } else {
+        "color"
+    }
+}
+
+fn clean_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("preset name is empty".to_string());
+    }
+    Ok(name.chars().take(MAX_NAME_CHARS).collect())
+}
+
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "preset".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+fn unique_id(presets: &[UserPreset], name: &str) -> String {
+    let base = format!("{USER_PRESET_PREFIX}{}", slug(name));
+    (1..)
+        .map(|n| match n {
+            1 => base.clone(),
+            n => format!("{base}-{n}"),
+        })
+        .find(|id| presets.iter().all(|preset| preset.id != *id))
+        .expect("unbounded candidates")
+}
+
+fn validate_draft(
+    name: &str,
+    category: &str,
+    settings: &Map<String, Value>,
+) -> CommandResult<String> {
+    let name = clean_name(name).map_err(invalid)?;
+    if !CATEGORIES.contains(&category) {
+        return Err(invalid(format!("unknown preset category {category:?}")));
+    }
+    validate_settings(settings).map_err(|err| invalid(format!("{name}: {err}")))?;
+    Ok(name)
+}
+
+/// Reads a bundle: the store format, or an object keyed by preset id with
+/// `name`, `category` and `settings` like `film-presets.json`. A missing
+/// category is inferred from the id.
+fn parse_bundle(json: &str) -> CommandResult<Vec<UserPreset>> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|err| invalid(format!("parse preset bundle failed: {err}")))?;
+    let Value::Object(mut object) = value else {
+        return Err(invalid("preset bundle must be a JSON object".to_string()));
+    };
+    let presets = if object.get("format").and_then(Value::as_str) == Some(BUNDLE_FORMAT) {
+        let version = object
+            .get("schemaVersion")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        if version == 0 || version > BUNDLE_SCHEMA_VERSION as u64 {
+            return Err(invalid(format!(
+                "preset bundle schema {version} is not supported (latest is \
+                 {BUNDLE_SCHEMA_VERSION}); it may have been saved by a newer version"
+            )));
+        }
+        match object.remove("presets") {
+            Some(Value::Array(presets)) => presets,
+            _ => return Err(invalid("preset bundle has no presets".to_string())),
+        }
+    } else {
+        object
+            .into_iter()
+            .map(|(id, mut preset)| {
+                if let Value::Object(fields) = &mut preset {
+                    fields.entry("id").or_insert(Value::String(id));
+                }
+                preset
+            })
+            .collect()
+    };
+    presets
+        .into_iter()
+        .map(|mut preset| {
+            if let Value::Object(fields) = &mut preset {
+                if !fields.contains_key("category") {
+                    let id = fields.get("id").and_then(Value::as_str).unwrap_or("");
+                    fields.insert("category".to_string(), infer_category(id).into());
+                }
+            }
+            let preset: UserPreset = serde_json::from_value(preset)
+                .map_err(|err| invalid(format!("read preset failed: {err}")))?;
+            let name = validate_draft(&preset.name, &preset.category, &preset.settings)?;
+            Ok(UserPreset { name, ..preset })
+        })
+        .collect()
+}
+
+fn bundle_json(presets: Vec<UserPreset>) -> CommandResult<Vec<u8>> {
+    let bundle = PresetBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        schema_version: BUNDLE_SCHEMA_VERSION,
+        presets,
+    };
+    serde_json::to_vec_pretty(&bundle)
+        .map_err(|err| CommandError::Internal(format!("serialize presets failed: {err}")))
+}
+
+fn store_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(STORE_FILE)
+}
+
+/// Moves a store that no longer parses to `film-presets.json.bad`, so the
+/// library starts empty instead of failing every preset command and the
+/// user's presets can still be recovered by hand. A copy set aside earlier
+/// is kept; later ones get a ` (2)`, ` (3)`, ... suffix.
+fn set_aside(path: &Path, err: &CommandError) -> CommandResult<()> {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let aside = (1..)
+        .map(|n| match n {
+            1 => directory.join(format!("{stem}{extension}.bad")),
+            n => directory.join(format!("{stem} ({n}){extension}.bad")),
+        })
+        .find(|candidate| std::fs::symlink_metadata(candidate).is_err())
+        .expect("unbounded candidates");
+    std::fs::rename(path, &aside)
+        .map_err(|err| CommandError::io("move unreadable film presets aside", err))?;
+    eprintln!(
+        "[presets] {} is unreadable ({err}); moved it to {} and started empty.",
+        path.display(),
+        aside.display()
+    );
+    Ok(())
+}
+
+/// The user presets, read from disk on first use and written back after
+/// each change.
+#[derive(Default)]
+pub struct PresetStore {
+    presets: Mutex<Option<Vec<UserPreset>>>,
+}
+
+impl PresetStore {
+    fn lock(&self, config_dir: &Path) -> CommandResult<MutexGuard<'_, Option<Vec<UserPreset>>>> {
+        let mut presets = self
+            .presets
+            .lock()
+            .map_err(|_| CommandError::Internal("preset store is poisoned".to_string()))?;
+        if presets.is_none() {
+            let path = store_path(config_dir);
+            *presets = Some(match std::fs::read_to_string(&path) {
+                Ok(json) => match parse_bundle(&json) {
+                    Ok(presets) => presets,
+                    Err(err) => {
+                        set_aside(&path, &err)?;
+                        Vec::new()
+                    }
+                },
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(err) => return Err(CommandError::io("read film presets", err)),
+            });
+        }
+        Ok(presets)
+    }
+
+    fn change<T>(
+        &self,
+        config_dir: &Path,
+        edit: impl FnOnce(&mut Vec<UserPreset>) -> CommandResult<T>,
+    ) -> CommandResult<T> {
+        let mut guard = self.lock(config_dir)?;
+        let mut presets = guard.clone().unwrap_or_default();
+        let result = edit(&mut presets)?;
+        std::fs::create_dir_all(config_dir)
+            .map_err(|err| CommandError::io("create config directory", err))?;
+        write_atomic(
+            &store_path(config_dir),
+            &bundle_json(presets.clone())?,
+            false,
+        )?;
+        *guard = Some(presets);
+        Ok(result)
+    }
+
+    pub fn list(&self, config_dir: &Path) -> CommandResult<Vec<UserPreset>> {
+        Ok(self.lock(config_dir)?.clone().unwrap_or_default())
+    }
+
+    pub fn get(&self, config_dir: &Path, id: &str) -> Option<UserPreset> {
+        let presets = self.lock(config_dir).ok()?;
+        presets
+            .as_ref()?
+            .iter()
+            .find(|preset| preset.id == id)
+            .cloned()
+    }
+
+    /// Creates a preset, or replaces the one with the draft's id.
+    pub fn save(&self, config_dir: &Path, draft: PresetDraft) -> CommandResult<UserPreset> {
+        let name = validate_draft(&draft.name, &draft.category, &draft.settings)?;
+        self.change(config_dir, |presets| {
+            let existing = draft
+                .id
+                .as_ref()
+                .and_then(|id| presets.iter().position(|preset| preset.id == *id));
+            if let (Some(id), None) = (&draft.id, existing) {
+                return Err(CommandError::NotFound(format!("preset not found: {id}")));
+            }
+            let preset = UserPreset {
+                id: match existing {
+                    Some(index) => presets[index].id.clone(),
+                    None => unique_id(presets, &name),
+                },
+                name,
+                category: draft.category,
+                settings: draft.settings,
+                updated_at: now_millis(),
+            };
+            match existing {
+                Some(index) => presets[index] = preset.clone(),
+                None => presets.push(preset.clone()),
+            }
+            Ok(preset)
+        })
+    }
+
+    pub fn rename(&self, config_dir: &Path, id: &str, name: &str) -> CommandResult<UserPreset> {
+        let name = clean_name(name).map_err(invalid)?;
+        self.change(config_dir, |presets| {
+            let preset = presets
+                .iter_mut()
+                .find(|preset| preset.id == id)
+                .ok_or_else(|| CommandError::NotFound(format!("preset not found: {id}")))?;
+            preset.name = name;
+            preset.updated_at = now_millis();
+            Ok(preset.clone())
+        })
+    }
+
+    /// Returns whether there was a preset to delete.
+    pub fn delete(&self, config_dir: &Path, id: &str) -> CommandResult<bool> {
+        self.change(config_dir, |presets| {
+            let before = presets.len();
+            presets.retain(|preset| preset.id != id);
+            Ok(presets.len() != before)
+        })
+    }
+
+    /// The presets with the given ids, or all of them, as a bundle file.
+    pub fn export_bundle(
+        &self,
+        config_dir: &Path,
+        ids: Option<&[String]>,
+    ) -> CommandResult<Vec<u8>> {
+        let presets = self
+            .list(config_dir)?
+            .into_iter()
+            .filter(|preset| ids.is_none_or(|ids| ids.contains(&preset.id)))
+            .collect();
+        bundle_json(presets)
+    }
+
+    /// Adds every preset of a bundle. Ids from other libraries, or bundled
+    /// ids from `film-presets.json`, become `user:` ids; a preset identical
+    /// to one already here is left out.
+    pub fn import_bundle(&self, config_dir: &Path, json: &str) -> CommandResult<PresetImport> {
+        let incoming = parse_bundle(json)?;
+        self.change(config_dir, |presets| {
+            let mut report = PresetImport {
+                imported: Vec::new(),
+                unchanged: Vec::new(),
+            };
+            for preset in incoming {
+                let same = presets.iter().any(|existing| {
+                    existing.name == preset.name
+                        && existing.category == preset.category
+                        && existing.settings == preset.settings
+                });
+                if same {
+                    report.unchanged.push(preset.name);
+                    continue;
+                }
+                let taken = presets.iter().any(|existing| existing.id == preset.id);
+                let id = if preset.id.starts_with(USER_PRESET_PREFIX)
+                    && preset.id.len() > USER_PRESET_PREFIX.len()
+                    && !taken
+                {
+                    preset.id.clone()
+                } else {
+                    unique_id(presets, &preset.name)
+                };
+                let preset = UserPreset {
+                    id,
+                    updated_at: now_millis(),
+                    ..preset
+                };
+                presets.push(preset.clone());
+                report.imported.push(preset);
+            }
+            Ok(report)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{infer_category, validate_settings, PresetDraft, PresetStore};
+    use serde_json::{json, Map, Value};
+
+    fn settings(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn draft(name: &str, shadows: i32) -> PresetDraft {
+        PresetDraft {
+            id: None,
+            name: name.to_string(),
+            category: "color".to_string(),
+            settings: settings(json!({ "enhancedProfile": "frontier", "shadows": shadows })),
+        }
+    }
+
+    #[test]
+    fn settings_follow_the_bundled_preset_shape() {
+        let bundled: Value =
+            serde_json::from_str(include_str!("../resources/film-presets.json")).unwrap();
+        for preset in bundled.as_object().unwrap().values() {
+            validate_settings(preset["settings"].as_object().unwrap()).unwrap();
+        }
+        for bad in [
+            json!({ "shadows": 140 }),
+            json!({ "saturation": "high" }),
+            json!({ "layerOrder": "sideways" }),
+            json!({ "enhancedProfile": "kodak" }),
+            json!({ "exposure": 10 }),
+        ] {
+            assert!(validate_settings(&settings(bad.clone())).is_err(), "{bad}");
+        }
+        assert!(
+            validate_settings(&settings(json!({ "enhancedProfile": "user:Look.cube" }))).is_ok()
+        );
+    }
+
+    #[test]
+    fn legacy_ids_fall_back_to_a_film_type() {
+        assert_eq!(infer_category("portra400_color"), "color");
+        assert_eq!(infer_category("HP5_bw"), "bw");
+        assert_eq!(infer_category("velvia50"), "positive");
+    }
+
+    #[test]
+    fn presets_persist_and_round_trip_through_bundles() {
+        let dir = std::env::temp_dir().join(format!("nc-presets-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let store = PresetStore::default();
+        let portra = store
+            .save(&dir, draft("Portra 400 — Noritsu", -20))
+            .unwrap();
+        assert_eq!(portra.id, "user:portra-400-noritsu");
+        let again = store
+            .save(&dir, draft("Portra 400 — Noritsu", -10))
+            .unwrap();
+        assert_eq!(again.id, "user:portra-400-noritsu-2");
+        assert_eq!(
+            store
+                .rename(&dir, &again.id, " Portra 400 — SP3000 ")
+                .unwrap()
+                .name,
+            "Portra 400 — SP3000"
+        );
+        let updated = store
+            .save(
+                &dir,
+                PresetDraft {
+                    id: Some(portra.id.clone()),
+                    ..draft("Portra 400 — Noritsu", -25)
+                },
+            )
+            .unwrap();
+        assert_eq!(updated.id, portra.id);
+        assert_eq!(updated.film_preset_settings().shadows, Some(-25.0));
+        assert_eq!(
+            store.save(&dir, draft("Bad", 500)).unwrap_err().code(),
+            "DecodeFailed"
+        );
+
+        // A fresh store reads what the first one wrote.
+        let reopened = PresetStore::default();
+        assert_eq!(reopened.list(&dir).unwrap(), store.list(&dir).unwrap());
+
+        let bundle = store
+            .export_bundle(&dir, Some(std::slice::from_ref(&portra.id)))
+            .unwrap();
+        assert!(store.delete(&dir, &portra.id).unwrap());
+        assert!(!store.delete(&dir, &portra.id).unwrap());
+        let report = store
+            .import_bundle(&dir, std::str::from_utf8(&bundle).unwrap())
+            .unwrap();
+        assert_eq!(report.imported.len(), 1);
+        assert_eq!(report.imported[0].id, portra.id);
+        let report = store
+            .import_bundle(&dir, std::str::from_utf8(&bundle).unwrap())
+            .unwrap();
+        assert_eq!(report.unchanged, vec!["Portra 400 — Noritsu".to_string()]);
+
+        // The bundled presets' own format imports as user presets.
+        let legacy = json!({
+            "tri-x_bw": { "name": "Tri-X Push", "settings": { "shadows": -10 } }
+        });
+        let report = store.import_bundle(&dir, &legacy.to_string()).unwrap();
+        assert_eq!(report.imported[0].id, "user:tri-x-push");
+        assert_eq!(report.imported[0].category, "bw");
+        assert!(store.get(&dir, "user:tri-x-push").is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_corrupt_store_is_set_aside_and_the_library_starts_empty() {
+        let dir = std::env::temp_dir().join(format!("nc-presets-corrupt-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("film-presets.json"), b"{ \"user:half\": ").unwrap();
+
+        let store = PresetStore::default();
+        assert!(store.list(&dir).unwrap().is_empty());
+        assert_eq!(
+            std::fs::read(dir.join("film-presets.json.bad")).unwrap(),
+            b"{ \"user:half\": "
+        );
+        store.save(&dir, draft("Gold 200", 5)).unwrap();
+        assert_eq!(PresetStore::default().list(&dir).unwrap().len(), 1);
+
+        // A second corruption keeps the first copy.
+        std::fs::write(dir.join("film-presets.json"), b"[").unwrap();
+        assert!(PresetStore::default().list(&dir).unwrap().is_empty());
+        assert_eq!(
+            std::fs::read(dir.join("film-presets.json.bad")).unwrap(),
+            b"{ \"user:half\": "
+        );
+        assert_eq!(
+            std::fs::read(dir.join("film-presets (2).json.bad")).unwrap(),
+            b"["
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,248 @@
+//! Enhanced scanner profiles: the 3D LUTs built into the engine plus the
+//! `.cube`, `.3dl` and `.bin` files imported into `<app data>/profiles`.
+//!
+//! Built-in profiles keep their names (`frontier`, ...); imported ones are
+//! `user:<file name>`, so settings snapshots, sidecars and roll projects can
+//! refer to either the same way through `coreEnhancedProfile`.
+
+use crate::atomic_write::write_atomic;
+use crate::engine::lut3d::{self, Lut3D};
+use crate::error::{CommandError, CommandResult};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const PROFILES_DIR: &str = "profiles";
+const USER_PREFIX: &str = "user:";
+pub const PROFILE_EXTENSIONS: &[&str] = &["cube", "3dl", "bin"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    pub id: String,
+    /// File name without its extension for imported profiles.
+    pub name: String,
+    pub builtin: bool,
+    /// `bin`, `cube` or `3dl`.
+    pub format: String,
+    /// Grid points per axis.
+    pub size: usize,
+}
+
+pub fn profiles_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(PROFILES_DIR)
+}
+
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .filter(|extension| PROFILE_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// Reads a LUT file by its extension. `.bin` files are in the built-in
+/// profiles' format, sampled in ProPhoto RGB, and are baked for sRGB.
+pub fn load_lut_file(path: &Path) -> CommandResult<Lut3D> {
+    let extension = extension_of(path).ok_or_else(|| {
+        CommandError::InvalidPath(format!("not a .cube, .3dl or .bin LUT: {}", path.display()))
+    })?;
+    let bytes = std::fs::read(path).map_err(|err| CommandError::io("read LUT", err))?;
+    let parsed = match extension.as_str() {
+        "bin" => Lut3D::from_bin(&bytes).map(|lut| lut.baked_from_prophoto()),
+        _ => {
+            let text = String::from_utf8_lossy(&bytes);
+            if extension == "cube" {
+                Lut3D::parse_cube(&text)
+            } else {
+                Lut3D::parse_3dl(&text)
+            }
+        }
+    };
+    parsed.map_err(|err| CommandError::DecodeFailed(format!("{}: {err}", path.display())))
+}
+
+/// The file behind a `user:` id. Ids naming anything but a plain file in
+/// `dir` (separators, `..`) resolve to nothing.
+fn user_profile_path(dir: &Path, id: &str) -> Option<PathBuf> {
+    let name = id.strip_prefix(USER_PREFIX)?;
+    let file_name = Path::new(name).file_name()?;
+    if file_name != name || name.starts_with('.') {
+        return None;
+    }
+    extension_of(Path::new(name))?;
+    Some(dir.join(file_name))
+}
+
+fn user_profile_info(path: &Path, lut: &Lut3D) -> Option<ProfileInfo> {
+    let file_name = path.file_name()?.to_str()?;
+    Some(ProfileInfo {
+        id: format!("{USER_PREFIX}{file_name}"),
+        name: path.file_stem()?.to_string_lossy().to_string(),
+        builtin: false,
+        format: extension_of(path)?,
+        size: lut.size(),
+    })
+}
+
+/// A parsed LUT and the modification time of the file it came from.
+type LoadedProfile = (Option<SystemTime>, Arc<Lut3D>);
+
+/// Imported LUTs parsed so far, keyed by id and reloaded when their file
+/// changes.
+#[derive(Default)]
+pub struct ProfileStore {
+    loaded: Mutex<HashMap<String, LoadedProfile>>,
+}
+
+impl ProfileStore {
+    /// The LUT for `id`, or `None` for `none`, unknown names and files that
+    /// are gone or no longer parse.
+    pub fn resolve(&self, data_dir: &Path, id: &str) -> Option<Arc<Lut3D>> {
+        if !id.starts_with(USER_PREFIX) {
+            return lut3d::builtin_profile(id);
+        }
+        let path = user_profile_path(&profiles_dir(data_dir), id)?;
+        let modified = std::fs::metadata(&path).ok()?.modified().ok();
+        let mut loaded = self.loaded.lock().ok()?;
+        if let Some((stamp, lut)) = loaded.get(id) {
+            if *stamp == modified {
+                return Some(Arc::clone(lut));
+            }
+        }
+        let lut = Arc::new(load_lut_file(&path).ok()?);
+        loaded.insert(id.to_string(), (modified, Arc::clone(&lut)));
+        Some(lut)
+    }
+
+    /// Built-in profiles first, then imported ones by name. Files that do
+    /// not parse are left out.
+    pub fn list(&self, data_dir: &Path) -> Vec<ProfileInfo> {
+        let mut profiles = lut3d::BUILTIN_PROFILES
+            .iter()
+            .filter_map(|(name, _)| {
+                let lut = lut3d::builtin_profile(name)?;
+                Some(ProfileInfo {
+                    id: name.to_string(),
+                    name: name.to_string(),
+                    builtin: true,
+                    format: "bin".to_string(),
+                    size: lut.size(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut imported = std::fs::read_dir(profiles_dir(data_dir))
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let id = format!("{USER_PREFIX}{}", path.file_name()?.to_str()?);
+                let lut = self.resolve(data_dir, &id)?;
+                user_profile_info(&path, &lut)
+            })
+            .collect::<Vec<_>>();
+        imported.sort_by_key(|info| info.name.to_lowercase());
+        profiles.extend(imported);
+        profiles
+    }
+
+    /// Copies a LUT file into the profiles folder after checking it parses.
+    /// A name already taken gets a ` (2)`, ` (3)`, ... suffix.
+    pub fn import(&self, data_dir: &Path, source: &Path) -> CommandResult<ProfileInfo> {
+        let lut = load_lut_file(source)?;
+        let extension = extension_of(source).unwrap_or_default();
+        let stem = source
+            .file_stem()
+            .map(|stem| {
+                stem.to_string_lossy()
+                    .trim()
+                    .trim_start_matches('.')
+                    .to_string()
+            })
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or_else(|| "profile".to_string());
+        let dir = profiles_dir(data_dir);
+        std::fs::create_dir_all(&dir)
+            .map_err(|err| CommandError::io("create profiles directory", err))?;
+        let target = (1..)
+            .map(|n| match n {
+                1 => dir.join(format!("{stem}.{extension}")),
+                n => dir.join(format!("{stem} ({n}).{extension}")),
+            })
+            .find(|candidate| !candidate.exists())
+            .expect("unbounded candidates");
+        let bytes = std::fs::read(source).map_err(|err| CommandError::io("read LUT", err))?;
+        write_atomic(&target, &bytes, false)?;
+        user_profile_info(&target, &lut)
+            .ok_or_else(|| CommandError::Internal("imported profile has no name".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{user_profile_path, ProfileStore};
+    use crate::engine::lut3d::Lut3D;
+    use std::path::Path;
+
+    #[test]
+    fn user_ids_cannot_leave_the_profiles_folder() {
+        let dir = Path::new("/data/profiles");
+        assert_eq!(
+            user_profile_path(dir, "user:Portra Look.cube"),
+            Some(dir.join("Portra Look.cube"))
+        );
+        for id in [
+            "user:../settings.cube",
+            "user:sub/look.cube",
+            "user:.hidden.cube",
+            "user:look.png",
+            "frontier",
+        ] {
+            assert_eq!(user_profile_path(dir, id), None, "{id}");
+        }
+    }
+
+    #[test]
+    fn imported_luts_are_listed_and_resolved() {
+        let data = std::env::temp_dir().join(format!("nc-profiles-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&data);
+        std::fs::create_dir_all(&data).unwrap();
+        let source = data.join("Warm Look.cube");
+        std::fs::write(&source, Lut3D::identity(3).unwrap().to_cube("Warm Look")).unwrap();
+        let broken = data.join("broken.cube");
+        std::fs::write(&broken, "LUT_3D_SIZE 4\n0 0 0\n").unwrap();
+
+        let store = ProfileStore::default();
+        let first = store.import(&data, &source).unwrap();
+        assert_eq!(first.id, "user:Warm Look.cube");
+        assert_eq!(first.size, 3);
+        let second = store.import(&data, &source).unwrap();
+        assert_eq!(second.id, "user:Warm Look (2).cube");
+        assert_eq!(
+            store.import(&data, &broken).unwrap_err().code(),
+            "DecodeFailed"
+        );
+
+        let listed = store.list(&data);
+        assert!(listed
+            .iter()
+            .any(|info| info.builtin && info.id == "frontier"));
+        let imported = listed
+            .iter()
+            .filter(|info| !info.builtin)
+            .map(|info| info.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            imported,
+            vec!["user:Warm Look.cube", "user:Warm Look (2).cube"]
+        );
+
+        let lut = store.resolve(&data, "user:Warm Look.cube").unwrap();
+        assert_eq!(*lut, Lut3D::identity(3).unwrap());
+        assert!(store.resolve(&data, "user:gone.cube").is_none());
+        assert!(store.resolve(&data, "frontier").is_some());
+        assert!(store.resolve(&data, "none").is_none());
+        let _ = std::fs::remove_dir_all(&data);
+    }
+}
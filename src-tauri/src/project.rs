@@ -0,0 +1,390 @@
+//! `.ncroll` roll projects: the source files, each frame's settings snapshot,
+//! the roll reference, and export preferences, so a roll can be worked on
+//! across several sessions.
+//!
+//! Settings snapshots are stored as the webview sends them; its
+//! `sanitizeSettings` fills in fields newer than the project. The envelope
+//! around them is versioned and migrated here.
+
+use crate::atomic_write;
+use crate::error::{CommandError, CommandResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+pub const PROJECT_EXTENSION: &str = "ncroll";
+pub const PROJECT_SCHEMA_VERSION: u32 = 1;
+const PROJECT_FORMAT: &str = "negative-converter-roll";
+const RECENT_PROJECTS_FILE: &str = "recent-projects.json";
+const MAX_RECENT_PROJECTS: usize = 12;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[i]` upgrades a project from schema `i + 1` to `i + 2`.
+const MIGRATIONS: &[Migration] = &[];
+const _: () = assert!(MIGRATIONS.len() as u32 + 1 == PROJECT_SCHEMA_VERSION);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectFrame {
+    pub path: String,
+    /// Path from the project's folder, tried when `path` no longer exists
+    /// (a roll folder copied to another drive or machine).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_path: Option<String>,
+    #[serde(default = "default_selected")]
+    pub selected: bool,
+    #[serde(default)]
+    pub settings: Option<Value>,
+}
+
+fn default_selected() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollReference {
+    #[serde(default)]
+    pub source_path: Option<String>,
+    pub settings: Value,
+    #[serde(default)]
+    pub apply_lock: bool,
+    #[serde(default)]
+    pub apply_crop: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollProject {
+    #[serde(default)]
+    pub format: String,
+    #[serde(default)]
+    pub schema_version: u32,
+    #[serde(default)]
+    pub app_version: String,
+    #[serde(default)]
+    pub saved_at: u64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub frames: Vec<ProjectFrame>,
+    #[serde(default)]
+    pub current_frame: usize,
+    #[serde(default)]
+    pub roll_reference: Option<RollReference>,
+    #[serde(default)]
+    pub export_preferences: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentProject {
+    pub path: String,
+    pub name: String,
+    pub frame_count: usize,
+    pub saved_at: u64,
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Brings a parsed project up to `PROJECT_SCHEMA_VERSION`.
+fn migrate(value: Value, migrations: &[Migration]) -> Result<Value, String> {
+    let Value::Object(mut object) = value else {
+        return Err("roll project must be a JSON object".to_string());
+    };
+    if object.get("format").and_then(Value::as_str) != Some(PROJECT_FORMAT) {
+        return Err("not a Negative Converter roll project".to_string());
+    }
+    let version = object
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| "roll project has no schema version".to_string())?;
+    let latest = migrations.len() as u64 + 1;
+    if version == 0 || version > latest {
+        return Err(format!(
+            "roll project schema {version} is not supported (latest is {latest}); \
+             it may have been saved by a newer version"
+        ));
+    }
+    for migration in &migrations[version as usize - 1..] {
+        migration(&mut object);
+    }
+    object.insert("schemaVersion".to_string(), Value::from(latest));
+    Ok(Value::Object(object))
+}
+
+pub fn parse_project(json: &str) -> CommandResult<RollProject> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|err| CommandError::DecodeFailed(format!("parse roll project failed: {err}")))?;
+    let value = migrate(value, MIGRATIONS).map_err(CommandError::DecodeFailed)?;
+    serde_json::from_value(value)
+        .map_err(|err| CommandError::DecodeFailed(format!("read roll project failed: {err}")))
+}
+
+/// Ensures the `.ncroll` extension, keeping whatever the user typed before it.
+pub fn normalize_project_path(path: PathBuf) -> PathBuf {
+    let has_extension = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .is_some_and(|value| value.eq_ignore_ascii_case(PROJECT_EXTENSION));
+    if has_extension {
+        return path;
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(PROJECT_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// Stamps the envelope fields and relative paths, then writes `project`.
+pub fn save_project(path: &Path, mut project: RollProject) -> CommandResult<RollProject> {
+    project.format = PROJECT_FORMAT.to_string();
+    project.schema_version = PROJECT_SCHEMA_VERSION;
+    project.app_version = env!("CARGO_PKG_VERSION").to_string();
+    project.saved_at = now_millis();
+    if project.name.trim().is_empty() {
+        project.name = path
+            .file_stem()
+            .map(|value| value.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    let project_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    for frame in &mut project.frames {
+        frame.relative_path = Path::new(&frame.path)
+            .strip_prefix(project_dir)
+            .ok()
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map(|relative| relative.to_string_lossy().to_string());
+    }
+
+    let json = serde_json::to_string_pretty(&project)
+        .map_err(|err| format!("serialize roll project failed: {err}"))?;
+    atomic_write::write_atomic(path, json.as_bytes(), false)?;
+    Ok(project)
+}
+
+/// Reads a project and points frames at their files, falling back to the
+/// path relative to the project. Returns the project and the frames whose
+/// files could not be found.
+pub fn open_project(path: &Path) -> CommandResult<(RollProject, Vec<String>)> {
+    let json =
+        std::fs::read_to_string(path).map_err(|err| CommandError::io("read roll project", err))?;
+    let mut project = parse_project(&json)?;
+    let project_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut missing = Vec::new();
+    for frame in &mut project.frames {
+        if Path::new(&frame.path).is_file() {
+            continue;
+        }
+        let relocated = frame
+            .relative_path
+            .as_deref()
+            .map(|relative| project_dir.join(relative))
+            .filter(|candidate| candidate.is_file());
+        match relocated {
+            Some(candidate) => {
+                let old_path =
+                    std::mem::replace(&mut frame.path, candidate.to_string_lossy().to_string());
+                if let Some(reference) = &mut project.roll_reference {
+                    if reference.source_path.as_deref() == Some(old_path.as_str()) {
+                        reference.source_path = Some(frame.path.clone());
+                    }
+                }
+            }
+            None => missing.push(frame.path.clone()),
+        }
+    }
+    Ok((project, missing))
+}
+
+fn recent_projects_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(RECENT_PROJECTS_FILE)
+}
+
+fn read_recent_projects(config_dir: &Path) -> Vec<RecentProject> {
+    std::fs::read_to_string(recent_projects_path(config_dir))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Recently saved or opened projects, newest first, skipping deleted files.
+pub fn list_recent_projects(config_dir: &Path) -> Vec<RecentProject> {
+    read_recent_projects(config_dir)
+        .into_iter()
+        .filter(|entry| Path::new(&entry.path).is_file())
+        .collect()
+}
+
+pub fn remember_recent_project(
+    config_dir: &Path,
+    path: &Path,
+    project: &RollProject,
+) -> CommandResult<()> {
+    let path_text = path.to_string_lossy().to_string();
+    let mut recent = read_recent_projects(config_dir);
+    recent.retain(|entry| entry.path != path_text);
+    recent.insert(
+        0,
+        RecentProject {
+            path: path_text,
+            name: project.name.clone(),
+            frame_count: project.frames.len(),
+            saved_at: project.saved_at,
+        },
+    );
+    recent.truncate(MAX_RECENT_PROJECTS);
+
+    std::fs::create_dir_all(config_dir)
+        .map_err(|err| CommandError::io("create config directory", err))?;
+    let json = serde_json::to_string_pretty(&recent)
+        .map_err(|err| format!("serialize recent projects failed: {err}"))?;
+    std::fs::write(recent_projects_path(config_dir), json)
+        .map_err(|err| CommandError::io("write recent projects", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        list_recent_projects, migrate, normalize_project_path, open_project, parse_project,
+        remember_recent_project, save_project, ProjectFrame, RollProject, RollReference,
+        PROJECT_SCHEMA_VERSION,
+    };
+    use serde_json::{json, Map, Value};
+    use std::path::{Path, PathBuf};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nc-project-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn project_paths_gain_the_ncroll_extension() {
+        assert_eq!(
+            normalize_project_path(PathBuf::from("/rolls/portra_0612")),
+            Path::new("/rolls/portra_0612.ncroll")
+        );
+        assert_eq!(
+            normalize_project_path(PathBuf::from("/rolls/roll.v2.NCROLL")),
+            Path::new("/rolls/roll.v2.NCROLL")
+        );
+    }
+
+    #[test]
+    fn foreign_and_future_files_are_refused() {
+        assert!(parse_project("{\"frames\": []}").is_err());
+        let future = json!({
+            "format": "negative-converter-roll",
+            "schemaVersion": PROJECT_SCHEMA_VERSION + 1
+        });
+        let err = parse_project(&future.to_string()).unwrap_err();
+        assert!(err.message().contains("newer version"), "{err}");
+        assert_eq!(err.code(), "DecodeFailed");
+    }
+
+    #[test]
+    fn migrations_run_from_the_saved_version() {
+        fn rename_preset(object: &mut Map<String, Value>) {
+            if let Some(value) = object.remove("filmPreset") {
+                object.insert("exportPreferences".to_string(), json!({ "preset": value }));
+            }
+        }
+        let old = json!({
+            "format": "negative-converter-roll",
+            "schemaVersion": 1,
+            "filmPreset": "portra-400"
+        });
+        let migrated = migrate(old, &[rename_preset as super::Migration]).unwrap();
+        assert_eq!(migrated["schemaVersion"], 2);
+        assert_eq!(migrated["exportPreferences"]["preset"], "portra-400");
+    }
+
+    #[test]
+    fn saved_projects_reopen_from_a_moved_folder() {
+        let dir = temp_dir("moved");
+        std::fs::create_dir_all(dir.join("scans")).unwrap();
+        let frame_path = dir.join("scans").join("frame_01.dng");
+        std::fs::write(&frame_path, b"raw").unwrap();
+        let project = RollProject {
+            frames: vec![
+                ProjectFrame {
+                    path: frame_path.to_string_lossy().to_string(),
+                    relative_path: None,
+                    selected: true,
+                    settings: Some(json!({ "rotationAngle": 1.5 })),
+                },
+                ProjectFrame {
+                    path: "/nowhere/frame_02.dng".to_string(),
+                    relative_path: None,
+                    selected: false,
+                    settings: None,
+                },
+            ],
+            roll_reference: Some(RollReference {
+                source_path: Some(frame_path.to_string_lossy().to_string()),
+                settings: json!({ "filmType": "color" }),
+                apply_lock: true,
+                apply_crop: false,
+            }),
+            export_preferences: json!({ "exportFormat": "tiff" }),
+            ..RollProject::default()
+        };
+        let project_path = dir.join("roll.ncroll");
+        let saved = save_project(&project_path, project).unwrap();
+        assert_eq!(saved.name, "roll");
+        assert_eq!(
+            saved.frames[0].relative_path.as_deref(),
+            Some(Path::new("scans").join("frame_01.dng").to_str().unwrap())
+        );
+
+        let moved = dir.with_extension("moved");
+        let _ = std::fs::remove_dir_all(&moved);
+        std::fs::rename(&dir, &moved).unwrap();
+        let (reopened, missing) = open_project(&moved.join("roll.ncroll")).unwrap();
+        let new_frame_path = moved.join("scans").join("frame_01.dng");
+        assert_eq!(reopened.frames[0].path, new_frame_path.to_string_lossy());
+        assert_eq!(
+            reopened.roll_reference.unwrap().source_path.as_deref(),
+            new_frame_path.to_str()
+        );
+        assert_eq!(
+            reopened.frames[0].settings,
+            Some(json!({ "rotationAngle": 1.5 }))
+        );
+        assert_eq!(missing, vec!["/nowhere/frame_02.dng".to_string()]);
+        assert_eq!(reopened.export_preferences["exportFormat"], "tiff");
+
+        std::fs::remove_dir_all(&moved).unwrap();
+    }
+
+    #[test]
+    fn recent_projects_are_newest_first_and_deduplicated() {
+        let dir = temp_dir("recent");
+        let first = dir.join("a.ncroll");
+        let second = dir.join("b.ncroll");
+        std::fs::write(&first, b"{}").unwrap();
+        std::fs::write(&second, b"{}").unwrap();
+        let config = dir.join("config");
+        let project = RollProject::default();
+
+        remember_recent_project(&config, &first, &project).unwrap();
+        remember_recent_project(&config, &second, &project).unwrap();
+        remember_recent_project(&config, &first, &project).unwrap();
+        std::fs::remove_file(&second).unwrap();
+
+        let recent = list_recent_projects(&config);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].path, first.to_string_lossy());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
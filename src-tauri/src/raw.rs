@@ -0,0 +1,454 @@
+//! Native RAW decoding: rawloader unpacks the sensor data, and we scale,
+//! white balance, demosaic, and convert camera RGB to linear sRGB here.
+//! Replaces the LibRaw-Wasm path in `rawFileLoader.js` on desktop.
+
+use crate::engine::Image16;
+use crate::image_io;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::Path;
+
+const MAX_16: f32 = 65535.0;
+
+const SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412453, 0.357580, 0.180423],
+    [0.212671, 0.715160, 0.072169],
+    [0.019334, 0.119193, 0.950227],
+];
+
+/// Camera and lens fields, the same shape `extractRawLensMetadata` returns.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawLensMetadata {
+    pub lens_model: String,
+    pub lens_maker: String,
+    pub camera_model: String,
+    pub camera_maker: String,
+    pub focal: Option<f64>,
+    pub aperture: Option<f64>,
+}
+
+/// Decoded frame: interleaved RGB, 3 samples per pixel. `linear` is false
+/// for TIFF scans, which are passed through with their own encoding.
+#[derive(Debug, Clone)]
+pub struct DecodedRaw {
+    pub width: u32,
+    pub height: u32,
+    pub linear: bool,
+    pub rgb: Vec<u16>,
+    pub metadata: RawLensMetadata,
+}
+
+impl DecodedRaw {
+    /// Expands to RGBA. Linear data gets the BT.709 transfer curve LibRaw
+    /// applies by default, so the engine sees the same input as the webview path.
+    pub fn to_display_image16(&self) -> Result<Image16, String> {
+        if !self.linear {
+            let mut data = Vec::with_capacity(self.rgb.len() / 3 * 4);
+            for px in self.rgb.chunks_exact(3) {
+                data.extend_from_slice(&[px[0], px[1], px[2], u16::MAX]);
+            }
+            return Image16::new(self.width, self.height, data);
+        }
+        let curve: Vec<u16> = (0..=u16::MAX)
+            .map(|value| {
+                let linear = value as f32 / MAX_16;
+                let encoded = if linear < 0.018 {
+                    4.5 * linear
+                } else {
+                    1.099 * linear.powf(0.45) - 0.099
+                };
+                (encoded * MAX_16 + 0.5).clamp(0.0, MAX_16) as u16
+            })
+            .collect();
+        let mut data = Vec::with_capacity(self.rgb.len() / 3 * 4);
+        for px in self.rgb.chunks_exact(3) {
+            data.extend_from_slice(&[
+                curve[px[0] as usize],
+                curve[px[1] as usize],
+                curve[px[2] as usize],
+                u16::MAX,
+            ]);
+        }
+        Image16::new(self.width, self.height, data)
+    }
+}
+
+fn invert_3x3(m: [[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    if !det.is_finite() || det.abs() < 1e-9 {
+        return None;
+    }
+    let inv = 1.0 / det;
+    Some([
+        [
+            (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv,
+            (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv,
+            (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv,
+        ],
+        [
+            (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv,
+            (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv,
+            (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv,
+        ],
+        [
+            (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv,
+            (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv,
+            (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv,
+        ],
+    ])
+}
+
+/// Camera RGB → linear sRGB, dcraw style: rows of `xyz_to_cam · sRGB_to_XYZ`
+/// are normalized so white maps to white, then inverted. Falls back to the
+/// identity for cameras without a matrix.
+fn camera_to_srgb(xyz_to_cam: &[[f32; 3]; 4]) -> [[f32; 3]; 3] {
+    const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let mut rgb_to_cam = [[0.0f32; 3]; 3];
+    for (i, row) in rgb_to_cam.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| xyz_to_cam[i][k] * SRGB_TO_XYZ[k][j]).sum();
+        }
+        let sum: f32 = row.iter().sum();
+        if !sum.is_finite() || sum.abs() < 1e-9 {
+            return IDENTITY;
+        }
+        row.iter_mut().for_each(|cell| *cell /= sum);
+    }
+    invert_3x3(rgb_to_cam).unwrap_or(IDENTITY)
+}
+
+/// CFA color index folded into R/G/B; the fourth color (a second green or
+/// emerald) is treated as green.
+fn cfa_channel(color: usize) -> usize {
+    match color {
+        0 => 0,
+        2 => 2,
+        _ => 1,
+    }
+}
+
+/// Bilinear demosaic for any CFA pattern: missing channels are averaged from
+/// the 3×3 neighbourhood, widening to 5×5 where a pattern (X-Trans edges)
+/// leaves the small window without a sample.
+fn demosaic(
+    mosaic: &[u16],
+    width: usize,
+    height: usize,
+    color_at: impl Fn(usize, usize) -> usize + Sync,
+    matrix: [[f32; 3]; 3],
+) -> Vec<u16> {
+    let mut rgb = vec![0u16; width * height * 3];
+    rgb.par_chunks_mut(width * 3)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for x in 0..width {
+                let own = color_at(y, x);
+                let mut cam = [0.0f32; 3];
+                cam[own] = mosaic[y * width + x] as f32;
+                for (channel, value) in cam.iter_mut().enumerate() {
+                    if channel == own {
+                        continue;
+                    }
+                    for radius in 1..=2usize {
+                        let (mut sum, mut count) = (0.0f32, 0u32);
+                        for ny in y.saturating_sub(radius)..=(y + radius).min(height - 1) {
+                            for nx in x.saturating_sub(radius)..=(x + radius).min(width - 1) {
+                                if color_at(ny, nx) == channel {
+                                    sum += mosaic[ny * width + nx] as f32;
+                                    count += 1;
+                                }
+                            }
+                        }
+                        if count > 0 {
+                            *value = sum / count as f32;
+                            break;
+                        }
+                    }
+                }
+                for (out, coefficients) in row[x * 3..x * 3 + 3].iter_mut().zip(matrix.iter()) {
+                    let linear: f32 = coefficients
+                        .iter()
+                        .zip(cam.iter())
+                        .map(|(m, v)| m * v)
+                        .sum();
+                    *out = (linear + 0.5).clamp(0.0, MAX_16) as u16;
+                }
+            }
+        });
+    rgb
+}
+
+/// Applies EXIF orientation the way rawloader describes it: flip, then transpose.
+fn apply_orientation(
+    rgb: Vec<u16>,
+    width: usize,
+    height: usize,
+    orientation: rawloader::Orientation,
+) -> (Vec<u16>, usize, usize) {
+    let (transpose, flip_x, flip_y) = orientation.to_flips();
+    if !transpose && !flip_x && !flip_y {
+        return (rgb, width, height);
+    }
+    let (out_width, out_height) = if transpose {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    let mut out = vec![0u16; rgb.len()];
+    out.par_chunks_mut(out_width * 3)
+        .enumerate()
+        .for_each(|(oy, row)| {
+            for ox in 0..out_width {
+                let (mut sx, mut sy) = if transpose { (oy, ox) } else { (ox, oy) };
+                if flip_x {
+                    sx = width - 1 - sx;
+                }
+                if flip_y {
+                    sy = height - 1 - sy;
+                }
+                let src = (sy * width + sx) * 3;
+                row[ox * 3..ox * 3 + 3].copy_from_slice(&rgb[src..src + 3]);
+            }
+        });
+    (out, out_width, out_height)
+}
+
+pub(crate) fn exif_text(exif: &exif::Exif, tag: exif::Tag) -> Option<String> {
+    match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+        exif::Value::Ascii(values) => values
+            .first()
+            .map(|bytes| {
+                String::from_utf8_lossy(bytes)
+                    .trim_matches(char::from(0))
+                    .trim()
+                    .to_string()
+            })
+            .filter(|text| !text.is_empty()),
+        _ => None,
+    }
+}
+
+pub(crate) fn exif_number(exif: &exif::Exif, tag: exif::Tag) -> Option<f64> {
+    let value = match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+        exif::Value::Rational(values) => values.first()?.to_f64(),
+        exif::Value::SRational(values) => values.first()?.to_f64(),
+        other => other.get_uint(0)? as f64,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// Lens and camera fields from the TIFF/EXIF container. Formats with a
+/// non-standard container (RW2, ORF, ...) fall back to the decoder's names.
+fn read_lens_metadata(path: &Path, raw: &rawloader::RawImage) -> RawLensMetadata {
+    let exif = std::fs::File::open(path).ok().and_then(|file| {
+        exif::Reader::new()
+            .read_from_container(&mut std::io::BufReader::new(file))
+            .ok()
+    });
+    let text = |tag| exif.as_ref().and_then(|exif| exif_text(exif, tag));
+    let number = |tag| exif.as_ref().and_then(|exif| exif_number(exif, tag));
+    let fallback = |value: &str| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+
+    RawLensMetadata {
+        lens_model: text(exif::Tag::LensModel).unwrap_or_default(),
+        lens_maker: text(exif::Tag::LensMake).unwrap_or_default(),
+        camera_model: fallback(&raw.clean_model)
+            .or_else(|| text(exif::Tag::Model))
+            .unwrap_or_default(),
+        camera_maker: fallback(&raw.clean_make)
+            .or_else(|| text(exif::Tag::Make))
+            .unwrap_or_default(),
+        focal: number(exif::Tag::FocalLength),
+        aperture: number(exif::Tag::FNumber),
+    }
+}
+
+fn decode_tiff_file(path: &Path) -> Result<DecodedRaw, String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("read file failed: {err}"))?;
+    let image = image_io::decode_tiff(&bytes)?;
+    let rgb = image
+        .data
+        .chunks_exact(4)
+        .flat_map(|px| [px[0], px[1], px[2]])
+        .collect();
+    Ok(DecodedRaw {
+        width: image.width,
+        height: image.height,
+        linear: false,
+        rgb,
+        metadata: RawLensMetadata::default(),
+    })
+}
+
+/// Decodes any `RAW_LIKE_EXTENSIONS` file. Camera RAWs come back as 16-bit
+/// linear sRGB with camera white balance; TIFF scans are passed through.
+pub fn decode_raw_file(path: &Path) -> Result<DecodedRaw, String> {
+    if matches!(image_io::lowercase_extension(path).as_str(), "tif" | "tiff") {
+        return decode_tiff_file(path);
+    }
+
+    let raw = rawloader::decode_file(path)
+        .map_err(|err| format!("decode RAW {} failed: {err}", path.display()))?;
+    let [top, right, bottom, left] = raw.crops;
+    let width = raw.width.saturating_sub(left + right);
+    let height = raw.height.saturating_sub(top + bottom);
+    if width == 0 || height == 0 {
+        return Err(format!("RAW {} has no usable image area", path.display()));
+    }
+
+    let cpp = raw.cpp.max(1);
+    let wb = if raw
+        .wb_coeffs
+        .iter()
+        .take(3)
+        .all(|value| value.is_finite() && *value > 0.0)
+    {
+        raw.wb_coeffs
+    } else {
+        raw.neutralwb()
+    };
+    let wb = [wb[0] / wb[1], 1.0, wb[2] / wb[1]];
+    let cfa = raw.cropped_cfa();
+    let mosaic_color = |y: usize, x: usize| {
+        if cpp == 1 && cfa.is_valid() {
+            cfa_channel(cfa.color_at(y, x))
+        } else {
+            1
+        }
+    };
+
+    // Black/white scaling and white balance, clipped like dcraw's highlight mode 0.
+    let scale = |sample: f32, channel: usize| -> u16 {
+        let black = raw.blacklevels[channel] as f32;
+        let white = raw.whitelevels[channel].max(1) as f32;
+        let normalized = ((sample - black) / (white - black).max(1.0)) * wb[channel];
+        (normalized.clamp(0.0, 1.0) * MAX_16 + 0.5) as u16
+    };
+    let sample_at = |index: usize| -> f32 {
+        match &raw.data {
+            rawloader::RawImageData::Integer(data) => data[index] as f32,
+            rawloader::RawImageData::Float(data) => data[index],
+        }
+    };
+
+    let matrix = if cpp == 1 && !cfa.is_valid() {
+        [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+    } else {
+        camera_to_srgb(&raw.xyz_to_cam)
+    };
+
+    let rgb = if cpp >= 3 {
+        let mut rgb = vec![0u16; width * height * 3];
+        rgb.par_chunks_mut(width * 3)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for x in 0..width {
+                    let src = ((y + top) * raw.width + x + left) * cpp;
+                    let cam = [0, 1, 2].map(|c| scale(sample_at(src + c), c) as f32);
+                    for (out, coefficients) in row[x * 3..x * 3 + 3].iter_mut().zip(matrix.iter()) {
+                        let linear: f32 = coefficients
+                            .iter()
+                            .zip(cam.iter())
+                            .map(|(m, v)| m * v)
+                            .sum();
+                        *out = (linear + 0.5).clamp(0.0, MAX_16) as u16;
+                    }
+                }
+            });
+        rgb
+    } else {
+        let mut mosaic = vec![0u16; width * height];
+        mosaic
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, value) in row.iter_mut().enumerate() {
+                    let src = (y + top) * raw.width + x + left;
+                    *value = scale(sample_at(src), mosaic_color(y, x));
+                }
+            });
+        if cfa.is_valid() {
+            demosaic(&mosaic, width, height, mosaic_color, matrix)
+        } else {
+            mosaic.iter().flat_map(|value| [*value; 3]).collect()
+        }
+    };
+
+    let (rgb, width, height) = apply_orientation(rgb, width, height, raw.orientation);
+    Ok(DecodedRaw {
+        width: width as u32,
+        height: height as u32,
+        linear: true,
+        rgb,
+        metadata: read_lens_metadata(path, &raw),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_orientation, camera_to_srgb, demosaic, DecodedRaw, RawLensMetadata};
+
+    const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    fn rggb(y: usize, x: usize) -> usize {
+        match (y % 2, x % 2) {
+            (0, 0) => 0,
+            (1, 1) => 2,
+            _ => 1,
+        }
+    }
+
+    #[test]
+    fn demosaic_reconstructs_flat_color_fields() {
+        let (width, height) = (6, 4);
+        let mosaic: Vec<u16> = (0..width * height)
+            .map(|i| [40000, 20000, 10000][rggb(i / width, i % width)])
+            .collect();
+        let rgb = demosaic(&mosaic, width, height, rggb, IDENTITY);
+        for px in rgb.chunks_exact(3) {
+            assert_eq!(px, [40000, 20000, 10000]);
+        }
+    }
+
+    #[test]
+    fn camera_matrix_maps_white_to_white() {
+        let xyz_to_cam = [
+            [0.7, -0.1, -0.1],
+            [-0.4, 1.2, 0.2],
+            [-0.1, 0.2, 0.6],
+            [0.0, 0.0, 0.0],
+        ];
+        let matrix = camera_to_srgb(&xyz_to_cam);
+        for row in matrix {
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+        }
+        assert_eq!(camera_to_srgb(&[[0.0; 3]; 4]), IDENTITY);
+    }
+
+    #[test]
+    fn rotate_90_swaps_dimensions() {
+        // 2x1 image: red then blue.
+        let rgb = vec![1, 0, 0, 0, 0, 1];
+        let (out, width, height) = apply_orientation(rgb, 2, 1, rawloader::Orientation::Rotate90);
+        assert_eq!((width, height), (1, 2));
+        assert_eq!(out, vec![1, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn display_image_applies_bt709_curve() {
+        let decoded = DecodedRaw {
+            width: 1,
+            height: 1,
+            linear: true,
+            rgb: vec![0, 65535, 16384],
+            metadata: RawLensMetadata::default(),
+        };
+        let image = decoded.to_display_image16().unwrap();
+        assert_eq!(&image.data[..2], &[0, 65535]);
+        assert!(image.data[2] > 16384);
+        assert_eq!(image.data[3], 65535);
+    }
+}
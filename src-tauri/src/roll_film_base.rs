@@ -0,0 +1,213 @@
+//! Roll-wide film base: every frame's rebate and inter-frame gaps are sampled
+//! again on the full-resolution source and combined into one consensus base,
+//! so mask drift between frames of the same roll can be seen and removed.
+
+use crate::engine::adjust::{FilmBase, FilmBaseMethod};
+use crate::engine::film_base::{self, FilmBaseResult, FrameDeviation, RollConsensus};
+use crate::image_io;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Confidence given to a settings snapshot's base when it does not record
+/// one, e.g. bases set before detection reported confidence.
+const SNAPSHOT_CONFIDENCE: f64 = 0.5;
+
+/// Frames decoded at once. Each holds a full-resolution 16-bit scan until
+/// its base is detected, so a long roll must not decode on every core.
+const CONCURRENT_DECODES: usize = 4;
+
+/// A `filmBase` from a settings snapshot, which may carry its detection's
+/// confidence next to the color.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SnapshotFilmBase {
+    #[serde(flatten)]
+    pub base: FilmBase,
+    pub confidence: Option<f64>,
+}
+
+/// One frame of the roll, as the queue knows it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RollFrame {
+    pub source_path: Option<String>,
+    /// The frame's `coreBorderBuffer` setting.
+    pub border_buffer: Option<f64>,
+    /// Used when the source cannot be read.
+    pub film_base: Option<SnapshotFilmBase>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FrameBaseSource {
+    /// Detected on the frame's source file.
+    Detected,
+    /// Taken from the frame's settings snapshot.
+    Settings,
+    /// Neither was available.
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameBaseReport {
+    pub index: usize,
+    pub source: FrameBaseSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub film_base: Option<FilmBaseResult>,
+    #[serde(flatten)]
+    pub deviation: Option<FrameDeviation>,
+    /// Why the source could not be sampled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollFilmBase {
+    pub consensus: Option<FilmBaseResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gains: Option<[f64; 3]>,
+    pub frames: Vec<FrameBaseReport>,
+}
+
+fn sample_frame(frame: &RollFrame) -> (FrameBaseSource, Option<FilmBaseResult>, Option<String>) {
+    let detected = frame
+        .source_path
+        .as_deref()
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(|path| {
+            // Only the detected base leaves this closure, so the scan is
+            // freed as soon as it has been sampled.
+            let image = image_io::read_input_image(Path::new(path))?;
+            let border_buffer = frame.border_buffer.unwrap_or(10.0);
+            Ok::<_, String>(film_base::detect_film_base(&image, border_buffer).result)
+        });
+    let error = match detected {
+        Some(Ok(base)) => return (FrameBaseSource::Detected, Some(base), None),
+        Some(Err(err)) => Some(err),
+        None => None,
+    };
+    let snapshot = frame.film_base.as_ref().and_then(|snapshot| {
+        let confidence = snapshot.confidence.unwrap_or(SNAPSHOT_CONFIDENCE);
+        FilmBaseResult::from_film_base(&snapshot.base, confidence)
+    });
+    match snapshot {
+        Some(base) => (FrameBaseSource::Settings, Some(base), error),
+        None => (FrameBaseSource::Missing, None, error),
+    }
+}
+
+/// Samples every frame, [`CONCURRENT_DECODES`] at a time, and builds the
+/// roll's consensus base. `method` is the `filmBaseMethod` setting used to
+/// report gains.
+pub fn analyze_roll(frames: &[RollFrame], method: FilmBaseMethod) -> RollFilmBase {
+    let sampled = frames
+        .chunks(CONCURRENT_DECODES)
+        .flat_map(|chunk| chunk.par_iter().map(sample_frame).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let bases = sampled
+        .iter()
+        .map(|(_, base, _)| base.clone())
+        .collect::<Vec<_>>();
+    let RollConsensus {
+        consensus,
+        gains,
+        frames: deviations,
+    } = film_base::roll_consensus(&bases, method);
+    let frames = sampled
+        .into_iter()
+        .zip(deviations)
+        .enumerate()
+        .map(
+            |(index, ((source, film_base, error), deviation))| FrameBaseReport {
+                index,
+                source,
+                film_base,
+                deviation,
+                error,
+            },
+        )
+        .collect();
+    RollFilmBase {
+        consensus,
+        gains,
+        frames,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze_roll, FrameBaseSource, RollFrame, SnapshotFilmBase};
+    use crate::engine::adjust::{FilmBase, FilmBaseMethod};
+    use crate::engine::Image16;
+    use crate::image_io;
+
+    fn snapshot(rgb16: [f64; 3], confidence: Option<f64>) -> Option<SnapshotFilmBase> {
+        Some(SnapshotFilmBase {
+            base: FilmBase {
+                r16: Some(rgb16[0]),
+                g16: Some(rgb16[1]),
+                b16: Some(rgb16[2]),
+                ..FilmBase::default()
+            },
+            confidence,
+        })
+    }
+
+    #[test]
+    fn frames_are_detected_or_fall_back_to_their_snapshot() {
+        let dir = std::env::temp_dir().join(format!("nc-roll-base-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let orange = Image16::new(40, 30, [52000, 33000, 20000, 65535].repeat(40 * 30)).unwrap();
+        let scan = dir.join("frame-01.png");
+        std::fs::write(&scan, image_io::encode_png16(&orange).unwrap()).unwrap();
+
+        let frames = vec![
+            RollFrame {
+                source_path: Some(scan.to_string_lossy().into_owned()),
+                ..RollFrame::default()
+            },
+            RollFrame {
+                source_path: Some(dir.join("gone.png").to_string_lossy().into_owned()),
+                film_base: snapshot([52100.0, 33050.0, 20020.0], None),
+                ..RollFrame::default()
+            },
+            RollFrame {
+                film_base: snapshot([51900.0, 32950.0, 19980.0], Some(0.8)),
+                ..RollFrame::default()
+            },
+            RollFrame::default(),
+        ];
+        let roll = analyze_roll(&frames, FilmBaseMethod::Density);
+        let sources = roll
+            .frames
+            .iter()
+            .map(|frame| frame.source)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec![
+                FrameBaseSource::Detected,
+                FrameBaseSource::Settings,
+                FrameBaseSource::Settings,
+                FrameBaseSource::Missing,
+            ]
+        );
+        assert!(roll.frames[1].error.is_some());
+        assert_eq!(roll.frames[1].film_base.as_ref().unwrap().confidence, 0.5);
+        assert!(roll.frames[3].deviation.is_none());
+
+        let consensus = roll.consensus.unwrap();
+        assert_eq!(consensus.selected, Some(3));
+        assert!(consensus.r16.abs_diff(52000) < 100);
+
+        let json = serde_json::to_value(&roll.frames[0]).unwrap();
+        assert_eq!(json["source"], "detected");
+        assert!(json["densityOffset"].is_array());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,213 @@
+//! `.xmp` sidecars holding a frame's full settings snapshot, written next to
+//! the source file the way Lightroom does (`frame.NEF` -> `frame.xmp`).
+//!
+//! Our data lives in its own `rdf:Description`, so sidecars written by other
+//! tools keep their content when we update ours.
+
+use crate::atomic_write::write_atomic;
+use crate::error::{CommandError, CommandResult};
+use crate::metadata::{self, FilmMetadata};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Bumped when the snapshot layout changes in a way readers must know about.
+const SETTINGS_VERSION: u32 = 1;
+const SETTINGS_VERSION_ATTRIBUTE: &str = "nc:SettingsVersion=";
+const SETTINGS_OPEN: &str = "<nc:Settings>";
+const SETTINGS_CLOSE: &str = "</nc:Settings>";
+const DESCRIPTION_OPEN: &str = "<rdf:Description";
+const DESCRIPTION_CLOSE: &str = "</rdf:Description>";
+const RDF_CLOSE: &str = "</rdf:RDF>";
+
+pub fn sidecar_path(source: &Path) -> PathBuf {
+    source.with_extension("xmp")
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Byte range of our `rdf:Description` inside an existing packet.
+fn find_settings_description(xmp: &str) -> Option<(usize, usize)> {
+    let marker = xmp.find(SETTINGS_VERSION_ATTRIBUTE)?;
+    let start = xmp[..marker].rfind(DESCRIPTION_OPEN)?;
+    let end = marker + xmp[marker..].find(DESCRIPTION_CLOSE)? + DESCRIPTION_CLOSE.len();
+    Some((start, end))
+}
+
+fn build_settings_description(settings: &Value) -> Result<String, String> {
+    let json = serde_json::to_string(settings)
+        .map_err(|err| format!("serialize settings failed: {err}"))?;
+    let film: FilmMetadata = serde_json::from_value(settings.clone()).unwrap_or_default();
+
+    let mut description = format!(
+        "<rdf:Description rdf:about=\"\"\n xmlns:nc=\"{}\"\n {SETTINGS_VERSION_ATTRIBUTE}\"{SETTINGS_VERSION}\"",
+        metadata::XMP_NAMESPACE_FILM
+    );
+    for (name, value) in metadata::film_xmp_attributes(&film) {
+        description.push_str(&format!("\n {name}=\"{}\"", metadata::escape_xml(&value)));
+    }
+    description.push_str(&format!(
+        ">\n{SETTINGS_OPEN}{}{SETTINGS_CLOSE}\n{DESCRIPTION_CLOSE}",
+        metadata::escape_xml(&json)
+    ));
+    Ok(description)
+}
+
+/// Replaces our description in `existing` (or appends it to the RDF), or
+/// starts a fresh packet when there is nothing usable to merge into.
+fn merge_sidecar(existing: Option<&str>, settings: &Value) -> Result<String, String> {
+    let description = build_settings_description(settings)?;
+    if let Some(existing) = existing {
+        if let Some((start, end)) = find_settings_description(existing) {
+            return Ok(format!(
+                "{}{description}{}",
+                &existing[..start],
+                &existing[end..]
+            ));
+        }
+        if let Some(position) = existing.rfind(RDF_CLOSE) {
+            return Ok(format!(
+                "{}{description}\n{}",
+                &existing[..position],
+                &existing[position..]
+            ));
+        }
+    }
+    Ok(format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+         <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+         <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+         {description}\n\
+         </rdf:RDF>\n\
+         </x:xmpmeta>\n\
+         <?xpacket end=\"w\"?>\n"
+    ))
+}
+
+fn parse_settings(xmp: &str) -> CommandResult<Option<Value>> {
+    let Some((start, end)) = find_settings_description(xmp) else {
+        return Ok(None);
+    };
+    let description = &xmp[start..end];
+    let Some(open) = description.find(SETTINGS_OPEN) else {
+        return Ok(None);
+    };
+    let body_start = open + SETTINGS_OPEN.len();
+    let body_end = body_start
+        + description[body_start..]
+            .find(SETTINGS_CLOSE)
+            .ok_or_else(|| {
+                CommandError::DecodeFailed("sidecar settings are not closed".to_string())
+            })?;
+    let json = unescape_xml(&description[body_start..body_end]);
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|err| CommandError::DecodeFailed(format!("parse sidecar settings failed: {err}")))
+}
+
+/// Returns the settings snapshot stored next to `source`, or `None` when
+/// there is no sidecar or it holds no settings of ours.
+pub fn read_settings(source: &Path) -> CommandResult<Option<Value>> {
+    let path = sidecar_path(source);
+    let xmp = match std::fs::read_to_string(&path) {
+        Ok(xmp) => xmp,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(CommandError::io("read sidecar", err)),
+    };
+    parse_settings(&xmp)
+}
+
+/// Writes the settings snapshot for `source` and returns the sidecar path.
+pub fn write_settings(source: &Path, settings: &Value) -> CommandResult<PathBuf> {
+    if !settings.is_object() {
+        return Err(CommandError::Internal(
+            "sidecar settings must be an object".to_string(),
+        ));
+    }
+    let path = sidecar_path(source);
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(xmp) => Some(xmp),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(CommandError::io("read sidecar", err)),
+    };
+    let xmp = merge_sidecar(existing.as_deref(), settings)?;
+    write_atomic(&path, xmp.as_bytes(), false)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_sidecar, parse_settings, read_settings, sidecar_path, write_settings};
+    use serde_json::json;
+    use std::path::Path;
+
+    fn sample_settings() -> serde_json::Value {
+        json!({
+            "cropRegion": { "x": 10, "y": 20, "width": 300, "height": 200 },
+            "rotationAngle": -1.5,
+            "filmType": "color",
+            "filmBase": { "r": 210, "g": 140, "b": 90 },
+            "coreFilmPreset": "portra-400",
+            "coreEnhancedProfile": "none",
+            "curves": { "rgb": [[0, 0], [128, 140], [255, 255]] },
+            "lensCorrection": { "enabled": true, "selectedLens": { "model": "50mm <\"A&B\">" } }
+        })
+    }
+
+    #[test]
+    fn sidecar_sits_next_to_the_source_with_xmp_extension() {
+        assert_eq!(
+            sidecar_path(Path::new("/rolls/roll01/frame_01.NEF")),
+            Path::new("/rolls/roll01/frame_01.xmp")
+        );
+    }
+
+    #[test]
+    fn settings_round_trip_through_a_fresh_sidecar() {
+        let xmp = merge_sidecar(None, &sample_settings()).unwrap();
+        assert!(xmp.contains("nc:FilmStock=\"portra-400\""));
+        assert_eq!(parse_settings(&xmp).unwrap(), Some(sample_settings()));
+    }
+
+    #[test]
+    fn foreign_sidecar_content_survives_updates() {
+        let lightroom = "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+            <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+            <rdf:Description rdf:about=\"\" xmlns:crs=\"http://ns.adobe.com/camera-raw-settings/1.0/\" crs:Exposure2012=\"+0.50\"/>\n\
+            </rdf:RDF>\n</x:xmpmeta>\n";
+        let first = merge_sidecar(Some(lightroom), &sample_settings()).unwrap();
+        let mut changed = sample_settings();
+        changed["rotationAngle"] = json!(2.0);
+        let second = merge_sidecar(Some(&first), &changed).unwrap();
+
+        assert!(second.contains("crs:Exposure2012=\"+0.50\""));
+        assert_eq!(second.matches("nc:SettingsVersion").count(), 1);
+        assert_eq!(parse_settings(&second).unwrap(), Some(changed));
+    }
+
+    #[test]
+    fn missing_sidecar_reads_as_none_and_writes_create_it() {
+        let dir = std::env::temp_dir().join(format!("nc-sidecar-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("frame_07.dng");
+
+        assert_eq!(read_settings(&source).unwrap(), None);
+        let written = write_settings(&source, &sample_settings()).unwrap();
+        assert_eq!(written, dir.join("frame_07.xmp"));
+        assert_eq!(read_settings(&source).unwrap(), Some(sample_settings()));
+        assert!(write_settings(&source, &json!([1, 2])).is_err());
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            1,
+            "no temp file left"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
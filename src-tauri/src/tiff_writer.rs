@@ -0,0 +1,638 @@
+//! Baseline little-endian TIFF writer for exports: 8- or 16-bit RGB in
+//! strips, uncompressed, LZW, or Deflate, with an optional horizontal
+//! predictor, embedded ICC profile, resolution tags, and EXIF/XMP metadata.
+
+use crate::metadata::{self, EmbeddedMetadata, MetadataTag, TagValue};
+use serde::Deserialize;
+use std::io::Write;
+
+const TARGET_STRIP_BYTES: usize = 256 * 1024;
+
+const TYPE_BYTE: u16 = 1;
+const TYPE_ASCII: u16 = 2;
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_RATIONAL: u16 = 5;
+const TYPE_UNDEFINED: u16 = 7;
+
+const TAG_IMAGE_WIDTH: u16 = 256;
+const TAG_IMAGE_LENGTH: u16 = 257;
+const TAG_BITS_PER_SAMPLE: u16 = 258;
+const TAG_COMPRESSION: u16 = 259;
+const TAG_PHOTOMETRIC: u16 = 262;
+const TAG_STRIP_OFFSETS: u16 = 273;
+const TAG_SAMPLES_PER_PIXEL: u16 = 277;
+const TAG_ROWS_PER_STRIP: u16 = 278;
+const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+const TAG_X_RESOLUTION: u16 = 282;
+const TAG_Y_RESOLUTION: u16 = 283;
+const TAG_PLANAR_CONFIG: u16 = 284;
+const TAG_RESOLUTION_UNIT: u16 = 296;
+const TAG_SOFTWARE: u16 = 305;
+const TAG_PREDICTOR: u16 = 317;
+const TAG_ICC_PROFILE: u16 = 34675;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TiffCompression {
+    #[default]
+    None,
+    Lzw,
+    Deflate,
+}
+
+impl TiffCompression {
+    fn tag_value(self) -> u16 {
+        match self {
+            Self::None => 1,
+            Self::Lzw => 5,
+            Self::Deflate => 8,
+        }
+    }
+}
+
+/// Interleaved samples; 3 (RGB) or 4 (RGBA, alpha dropped) per pixel.
+#[derive(Debug, Clone, Copy)]
+pub enum TiffSamples<'a> {
+    U8(&'a [u8]),
+    U16(&'a [u16]),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TiffOptions {
+    pub bit_depth: u8,
+    pub compression: TiffCompression,
+    pub predictor: bool,
+    pub dpi: Option<f64>,
+    pub icc_profile: Option<Vec<u8>>,
+    pub metadata: Option<EmbeddedMetadata>,
+}
+
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    data: Vec<u8>,
+}
+
+impl IfdEntry {
+    fn shorts(tag: u16, values: &[u16]) -> Self {
+        Self {
+            tag,
+            field_type: TYPE_SHORT,
+            count: values.len() as u32,
+            data: values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        }
+    }
+
+    fn longs(tag: u16, values: &[u32]) -> Self {
+        Self {
+            tag,
+            field_type: TYPE_LONG,
+            count: values.len() as u32,
+            data: values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        }
+    }
+
+    fn rational(tag: u16, numerator: u32, denominator: u32) -> Self {
+        let mut data = numerator.to_le_bytes().to_vec();
+        data.extend_from_slice(&denominator.to_le_bytes());
+        Self {
+            tag,
+            field_type: TYPE_RATIONAL,
+            count: 1,
+            data,
+        }
+    }
+
+    fn ascii(tag: u16, text: &str) -> Self {
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+        Self {
+            tag,
+            field_type: TYPE_ASCII,
+            count: data.len() as u32,
+            data,
+        }
+    }
+
+    fn undefined(tag: u16, bytes: &[u8]) -> Self {
+        Self {
+            tag,
+            field_type: TYPE_UNDEFINED,
+            count: bytes.len() as u32,
+            data: bytes.to_vec(),
+        }
+    }
+}
+
+impl From<&MetadataTag> for IfdEntry {
+    fn from(entry: &MetadataTag) -> Self {
+        match &entry.value {
+            TagValue::Ascii(text) => Self::ascii(entry.tag, text),
+            TagValue::Rational(numerator, denominator) => {
+                Self::rational(entry.tag, *numerator, *denominator)
+            }
+            TagValue::Undefined(bytes) => Self::undefined(entry.tag, bytes),
+        }
+    }
+}
+
+fn to_u32_offset(value: usize) -> Result<u32, String> {
+    u32::try_from(value).map_err(|_| "TIFF output exceeds 4 GB".to_string())
+}
+
+/// Appends `entries` as one IFD at the end of `out`; values over 4 bytes go
+/// into a word-aligned area right after it.
+fn write_ifd(out: &mut Vec<u8>, mut entries: Vec<IfdEntry>) -> Result<(), String> {
+    entries.sort_by_key(|entry| entry.tag);
+    let ifd_offset = out.len();
+    let mut extra_offset = ifd_offset + 2 + entries.len() * 12 + 4;
+    let mut extra = Vec::new();
+
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    for entry in &entries {
+        out.extend_from_slice(&entry.tag.to_le_bytes());
+        out.extend_from_slice(&entry.field_type.to_le_bytes());
+        out.extend_from_slice(&entry.count.to_le_bytes());
+        if entry.data.len() <= 4 {
+            let mut inline = [0u8; 4];
+            inline[..entry.data.len()].copy_from_slice(&entry.data);
+            out.extend_from_slice(&inline);
+        } else {
+            out.extend_from_slice(&to_u32_offset(extra_offset)?.to_le_bytes());
+            extra.extend_from_slice(&entry.data);
+            if extra.len() % 2 == 1 {
+                extra.push(0);
+            }
+            extra_offset = ifd_offset + 2 + entries.len() * 12 + 4 + extra.len();
+        }
+    }
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&extra);
+    Ok(())
+}
+
+/// Writes the Exif sub-IFD (when there is one) and returns the IFD0 entries
+/// that carry the metadata, including the sub-IFD pointer and optional XMP.
+fn write_metadata_ifds(
+    out: &mut Vec<u8>,
+    metadata: &EmbeddedMetadata,
+    include_xmp: bool,
+) -> Result<Vec<IfdEntry>, String> {
+    let mut entries: Vec<IfdEntry> = metadata.ifd0.iter().map(IfdEntry::from).collect();
+    if !metadata.exif.is_empty() {
+        if out.len() % 2 == 1 {
+            out.push(0);
+        }
+        let exif_offset = to_u32_offset(out.len())?;
+        write_ifd(out, metadata.exif.iter().map(IfdEntry::from).collect())?;
+        entries.push(IfdEntry::longs(metadata::TAG_EXIF_IFD, &[exif_offset]));
+    }
+    if include_xmp && !metadata.xmp.is_empty() {
+        entries.push(IfdEntry {
+            tag: metadata::TAG_XMP,
+            field_type: TYPE_BYTE,
+            count: metadata.xmp.len() as u32,
+            data: metadata.xmp.as_bytes().to_vec(),
+        });
+    }
+    Ok(entries)
+}
+
+/// A standalone TIFF-structured EXIF block, as PNG `eXIf` chunks and JPEG
+/// APP1 segments carry it.
+pub fn encode_exif_block(metadata: &EmbeddedMetadata) -> Result<Vec<u8>, String> {
+    let mut out = vec![b'I', b'I', 42, 0, 0, 0, 0, 0];
+    let entries = write_metadata_ifds(&mut out, metadata, false)?;
+    let ifd_offset = to_u32_offset(out.len())?;
+    out[4..8].copy_from_slice(&ifd_offset.to_le_bytes());
+    write_ifd(&mut out, entries)?;
+    Ok(out)
+}
+
+/// Adds `metadata` to a finished little-endian TIFF, e.g. one the webview
+/// encoded: a copy of its first IFD with the metadata entries added is
+/// appended and the header pointed at it. Strips, other IFDs and tags such as
+/// the ICC profile or resolution stay where they are.
+pub fn embed_tiff_metadata(
+    mut bytes: Vec<u8>,
+    metadata: &EmbeddedMetadata,
+) -> Result<Vec<u8>, String> {
+    if bytes.len() < 8 || &bytes[..4] != b"II*\0" {
+        return Err("export is not a little-endian TIFF".to_string());
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let read_u32 = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    let ifd_offset = read_u32(4) as usize;
+    if ifd_offset + 2 > bytes.len() {
+        return Err("TIFF IFD is truncated".to_string());
+    }
+    let count = read_u16(ifd_offset) as usize;
+    let next_ifd_at = ifd_offset + 2 + count * 12;
+    if next_ifd_at + 4 > bytes.len() {
+        return Err("TIFF IFD is truncated".to_string());
+    }
+    let next_ifd = read_u32(next_ifd_at);
+
+    let replaced: Vec<u16> = metadata
+        .ifd0
+        .iter()
+        .map(|entry| entry.tag)
+        .chain([metadata::TAG_EXIF_IFD, metadata::TAG_XMP])
+        .collect();
+    // Copied entries keep their value field as is: inline values stay
+    // inline and offsets still point into the untouched original bytes.
+    let mut entries: Vec<IfdEntry> = (0..count)
+        .map(|index| ifd_offset + 2 + index * 12)
+        .filter(|&at| !replaced.contains(&read_u16(at)))
+        .map(|at| IfdEntry {
+            tag: read_u16(at),
+            field_type: read_u16(at + 2),
+            count: read_u32(at + 4),
+            data: bytes[at + 8..at + 12].to_vec(),
+        })
+        .collect();
+
+    if bytes.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    entries.extend(write_metadata_ifds(&mut bytes, metadata, true)?);
+    if bytes.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    let new_offset = bytes.len();
+    let entry_count = entries.len();
+    write_ifd(&mut bytes, entries)?;
+    let new_next_at = new_offset + 2 + entry_count * 12;
+    bytes[new_next_at..new_next_at + 4].copy_from_slice(&next_ifd.to_le_bytes());
+    bytes[4..8].copy_from_slice(&to_u32_offset(new_offset)?.to_le_bytes());
+    Ok(bytes)
+}
+
+/// One row of output samples, little-endian, with the horizontal predictor
+/// (per-channel differences from the previous pixel) applied when requested.
+fn encode_row(
+    samples: TiffSamples<'_>,
+    row: usize,
+    width: usize,
+    channels: usize,
+    bit_depth: u8,
+    predictor: bool,
+    out: &mut Vec<u8>,
+) {
+    let start = row * width * channels;
+    let sample = |x: usize, c: usize| -> u16 {
+        let index = start + x * channels + c;
+        match (samples, bit_depth) {
+            (TiffSamples::U8(data), 16) => data[index] as u16 * 257,
+            (TiffSamples::U8(data), _) => data[index] as u16,
+            (TiffSamples::U16(data), 16) => data[index],
+            (TiffSamples::U16(data), _) => ((data[index] as u32 * 255 + 32767) / 65535) as u16,
+        }
+    };
+
+    for x in 0..width {
+        for c in 0..3 {
+            let mut value = sample(x, c);
+            if predictor && x > 0 {
+                value = value.wrapping_sub(sample(x - 1, c));
+            }
+            if bit_depth == 16 {
+                out.extend_from_slice(&value.to_le_bytes());
+            } else {
+                out.push(value as u8);
+            }
+        }
+    }
+}
+
+fn compress_strip(raw: &[u8], compression: TiffCompression) -> Result<Vec<u8>, String> {
+    match compression {
+        TiffCompression::None => Ok(raw.to_vec()),
+        TiffCompression::Lzw => {
+            weezl::encode::Encoder::with_tiff_size_switch(weezl::BitOrder::Msb, 8)
+                .encode(raw)
+                .map_err(|err| format!("LZW compression failed: {err}"))
+        }
+        TiffCompression::Deflate => {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(raw)
+                .and_then(|_| encoder.finish())
+                .map_err(|err| format!("Deflate compression failed: {err}"))
+        }
+    }
+}
+
+pub fn encode_tiff(
+    width: u32,
+    height: u32,
+    channels: usize,
+    samples: TiffSamples<'_>,
+    options: &TiffOptions,
+) -> Result<Vec<u8>, String> {
+    if width == 0 || height == 0 {
+        return Err("TIFF image is empty".to_string());
+    }
+    if channels != 3 && channels != 4 {
+        return Err(format!(
+            "TIFF input must be RGB or RGBA, got {channels} channels"
+        ));
+    }
+    let bit_depth = if options.bit_depth == 16 { 16 } else { 8 };
+    let expected = width as usize * height as usize * channels;
+    let actual = match samples {
+        TiffSamples::U8(data) => data.len(),
+        TiffSamples::U16(data) => data.len(),
+    };
+    if actual != expected {
+        return Err(format!(
+            "TIFF sample count {actual} does not match {width}x{height}x{channels}"
+        ));
+    }
+
+    let width_px = width as usize;
+    let row_bytes = width_px * 3 * (bit_depth as usize / 8);
+    let rows_per_strip = (TARGET_STRIP_BYTES / row_bytes).clamp(1, height as usize);
+    let predictor = options.predictor && options.compression != TiffCompression::None;
+
+    let mut out = vec![b'I', b'I', 42, 0, 0, 0, 0, 0];
+    let mut strip_offsets = Vec::new();
+    let mut strip_byte_counts = Vec::new();
+    let mut raw = Vec::with_capacity(row_bytes * rows_per_strip);
+    for strip_start in (0..height as usize).step_by(rows_per_strip) {
+        raw.clear();
+        let strip_end = (strip_start + rows_per_strip).min(height as usize);
+        for row in strip_start..strip_end {
+            encode_row(
+                samples, row, width_px, channels, bit_depth, predictor, &mut raw,
+            );
+        }
+        let encoded = compress_strip(&raw, options.compression)?;
+        strip_offsets.push(to_u32_offset(out.len())?);
+        strip_byte_counts.push(to_u32_offset(encoded.len())?);
+        out.extend_from_slice(&encoded);
+        if out.len() % 2 == 1 {
+            out.push(0);
+        }
+    }
+
+    let mut entries = vec![
+        IfdEntry::longs(TAG_IMAGE_WIDTH, &[width]),
+        IfdEntry::longs(TAG_IMAGE_LENGTH, &[height]),
+        IfdEntry::shorts(TAG_BITS_PER_SAMPLE, &[bit_depth as u16; 3]),
+        IfdEntry::shorts(TAG_COMPRESSION, &[options.compression.tag_value()]),
+        IfdEntry::shorts(TAG_PHOTOMETRIC, &[2]),
+        IfdEntry::longs(TAG_STRIP_OFFSETS, &strip_offsets),
+        IfdEntry::shorts(TAG_SAMPLES_PER_PIXEL, &[3]),
+        IfdEntry::longs(TAG_ROWS_PER_STRIP, &[rows_per_strip as u32]),
+        IfdEntry::longs(TAG_STRIP_BYTE_COUNTS, &strip_byte_counts),
+        IfdEntry::shorts(TAG_PLANAR_CONFIG, &[1]),
+        IfdEntry::ascii(
+            TAG_SOFTWARE,
+            concat!("Negative Converter ", env!("CARGO_PKG_VERSION")),
+        ),
+    ];
+    if predictor {
+        entries.push(IfdEntry::shorts(TAG_PREDICTOR, &[2]));
+    }
+    if let Some(dpi) = options.dpi.filter(|dpi| dpi.is_finite() && *dpi > 0.0) {
+        // Hundredths of a dot keep fractional DPI from scanner metadata.
+        let numerator = (dpi * 100.0).round().min(u32::MAX as f64) as u32;
+        entries.push(IfdEntry::rational(TAG_X_RESOLUTION, numerator, 100));
+        entries.push(IfdEntry::rational(TAG_Y_RESOLUTION, numerator, 100));
+        entries.push(IfdEntry::shorts(TAG_RESOLUTION_UNIT, &[2]));
+    }
+    if let Some(profile) = options.icc_profile.as_deref().filter(|p| !p.is_empty()) {
+        entries.push(IfdEntry::undefined(TAG_ICC_PROFILE, profile));
+    }
+    if let Some(metadata) = &options.metadata {
+        entries.extend(write_metadata_ifds(&mut out, metadata, true)?);
+    }
+
+    let ifd_offset = to_u32_offset(out.len())?;
+    out[4..8].copy_from_slice(&ifd_offset.to_le_bytes());
+    write_ifd(&mut out, entries)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{embed_tiff_metadata, encode_tiff, TiffCompression, TiffOptions, TiffSamples};
+    use tiff::decoder::{Decoder, DecodingResult};
+    use tiff::tags::Tag;
+
+    fn gradient_rgba16(width: usize, height: usize) -> Vec<u16> {
+        (0..width * height)
+            .flat_map(|i| {
+                let v = (i * 997 % 65536) as u16;
+                [v, v / 2, 65535 - v, 65535]
+            })
+            .collect()
+    }
+
+    fn decode_u16(bytes: &[u8]) -> Vec<u16> {
+        match Decoder::new(std::io::Cursor::new(bytes))
+            .unwrap()
+            .read_image()
+            .unwrap()
+        {
+            DecodingResult::U16(values) => values,
+            other => panic!(
+                "unexpected sample type: {:?}",
+                std::mem::discriminant(&other)
+            ),
+        }
+    }
+
+    #[test]
+    fn every_compression_round_trips_16_bit_rgb() {
+        let (width, height) = (37, 900);
+        let rgba = gradient_rgba16(width, height);
+        let expected: Vec<u16> = rgba
+            .chunks_exact(4)
+            .flat_map(|px| [px[0], px[1], px[2]])
+            .collect();
+
+        for (compression, predictor) in [
+            (TiffCompression::None, false),
+            (TiffCompression::Lzw, true),
+            (TiffCompression::Deflate, true),
+            (TiffCompression::Deflate, false),
+        ] {
+            let options = TiffOptions {
+                bit_depth: 16,
+                compression,
+                predictor,
+                ..TiffOptions::default()
+            };
+            let bytes = encode_tiff(
+                width as u32,
+                height as u32,
+                4,
+                TiffSamples::U16(&rgba),
+                &options,
+            )
+            .unwrap();
+            assert_eq!(decode_u16(&bytes), expected, "{compression:?}");
+        }
+    }
+
+    #[test]
+    fn eight_bit_input_widens_to_16_bit() {
+        let rgb = [0u8, 128, 255];
+        let options = TiffOptions {
+            bit_depth: 16,
+            ..TiffOptions::default()
+        };
+        let bytes = encode_tiff(1, 1, 3, TiffSamples::U8(&rgb), &options).unwrap();
+        assert_eq!(decode_u16(&bytes), vec![0, 128 * 257, 65535]);
+    }
+
+    #[test]
+    fn sixteen_bit_input_narrows_to_8_bit() {
+        let rgb = [0u16, 257 * 128, 65535];
+        let options = TiffOptions {
+            bit_depth: 8,
+            ..TiffOptions::default()
+        };
+        let bytes = encode_tiff(1, 1, 3, TiffSamples::U16(&rgb), &options).unwrap();
+        match Decoder::new(std::io::Cursor::new(&bytes))
+            .unwrap()
+            .read_image()
+            .unwrap()
+        {
+            DecodingResult::U8(values) => assert_eq!(values, vec![0, 128, 255]),
+            _ => panic!("expected 8-bit samples"),
+        }
+    }
+
+    #[test]
+    fn icc_profile_and_resolution_tags_are_written() {
+        let rgb = [10u8; 12];
+        let options = TiffOptions {
+            bit_depth: 8,
+            compression: TiffCompression::Lzw,
+            predictor: true,
+            dpi: Some(3200.0),
+            icc_profile: Some(vec![7u8; 131]),
+            metadata: None,
+        };
+        let bytes = encode_tiff(2, 2, 3, TiffSamples::U8(&rgb), &options).unwrap();
+        let mut decoder = Decoder::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(
+            decoder.get_tag_u8_vec(Tag::Unknown(34675)).unwrap(),
+            vec![7u8; 131]
+        );
+        assert_eq!(decoder.get_tag_u32(Tag::ResolutionUnit).unwrap(), 2);
+        assert_eq!(
+            decoder.get_tag_u32_vec(Tag::XResolution).unwrap(),
+            vec![320000, 100]
+        );
+        match decoder.read_image().unwrap() {
+            DecodingResult::U8(values) => assert_eq!(values, vec![10u8; 12]),
+            _ => panic!("expected 8-bit samples"),
+        }
+    }
+
+    #[test]
+    fn metadata_lands_in_ifd0_and_exif_sub_ifd() {
+        use crate::metadata::{EmbeddedMetadata, MetadataTag, TagValue};
+
+        let metadata = EmbeddedMetadata {
+            ifd0: vec![MetadataTag {
+                tag: 271,
+                value: TagValue::Ascii("Nikon".to_string()),
+            }],
+            exif: vec![MetadataTag {
+                tag: 36867,
+                value: TagValue::Ascii("2024:05:01 12:30:00".to_string()),
+            }],
+            xmp: "<x:xmpmeta/>".to_string(),
+        };
+        let options = TiffOptions {
+            bit_depth: 16,
+            compression: TiffCompression::Deflate,
+            predictor: true,
+            metadata: Some(metadata),
+            ..TiffOptions::default()
+        };
+        let bytes = encode_tiff(1, 1, 3, TiffSamples::U8(&[1, 2, 3]), &options).unwrap();
+
+        let exif = exif::Reader::new()
+            .read_from_container(&mut std::io::Cursor::new(&bytes))
+            .unwrap();
+        let make = exif.get_field(exif::Tag::Make, exif::In::PRIMARY).unwrap();
+        assert_eq!(make.display_value().to_string(), "\"Nikon\"");
+        assert!(exif
+            .get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)
+            .is_some());
+        let mut decoder = Decoder::new(std::io::Cursor::new(&bytes)).unwrap();
+        let xmp = decoder.get_tag_u32_vec(Tag::Unknown(700)).unwrap();
+        assert_eq!(
+            xmp.into_iter()
+                .map(|value| value as u8)
+                .collect::<Vec<u8>>(),
+            b"<x:xmpmeta/>".to_vec()
+        );
+        assert_eq!(decode_u16(&bytes), vec![257, 514, 771]);
+    }
+
+    #[test]
+    fn sample_count_mismatch_is_rejected() {
+        let options = TiffOptions::default();
+        assert!(encode_tiff(2, 2, 3, TiffSamples::U8(&[0u8; 11]), &options).is_err());
+        assert!(encode_tiff(1, 1, 2, TiffSamples::U8(&[0u8; 2]), &options).is_err());
+    }
+
+    #[test]
+    fn metadata_is_added_to_a_finished_tiff_without_touching_its_tags() {
+        use crate::metadata::{EmbeddedMetadata, MetadataTag, TagValue};
+
+        let options = TiffOptions {
+            bit_depth: 16,
+            compression: TiffCompression::Lzw,
+            dpi: Some(3200.0),
+            icc_profile: Some(vec![7; 40]),
+            ..TiffOptions::default()
+        };
+        let plain = encode_tiff(1, 1, 3, TiffSamples::U8(&[1, 2, 3]), &options).unwrap();
+        let metadata = EmbeddedMetadata {
+            ifd0: vec![MetadataTag {
+                tag: 272,
+                value: TagValue::Ascii("Z 7".to_string()),
+            }],
+            exif: vec![MetadataTag {
+                tag: 36867,
+                value: TagValue::Ascii("2024:05:01 12:30:00".to_string()),
+            }],
+            xmp: "<x:xmpmeta/>".to_string(),
+        };
+        let tagged = embed_tiff_metadata(plain, &metadata).unwrap();
+
+        let exif = exif::Reader::new()
+            .read_from_container(&mut std::io::Cursor::new(&tagged))
+            .unwrap();
+        let model = exif.get_field(exif::Tag::Model, exif::In::PRIMARY).unwrap();
+        assert_eq!(model.display_value().to_string(), "\"Z 7\"");
+        assert!(exif
+            .get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)
+            .is_some());
+        let mut decoder = Decoder::new(std::io::Cursor::new(&tagged)).unwrap();
+        assert_eq!(
+            decoder.get_tag_u8_vec(Tag::Unknown(34675)).unwrap(),
+            vec![7u8; 40]
+        );
+        assert!(decoder.get_tag_u32_vec(Tag::Unknown(700)).is_ok());
+        assert_eq!(decode_u16(&tagged), vec![257, 514, 771]);
+        assert!(embed_tiff_metadata(b"MM\0*".to_vec(), &metadata).is_err());
+    }
+}
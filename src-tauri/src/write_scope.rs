@@ -0,0 +1,222 @@
+//! Where the webview may write. The app runs with `csp: null` and loads
+//! third-party wasm, so paths arriving over IPC are not trusted on their
+//! own: a write must land inside a folder the user picked in a native dialog,
+//! or be a file the user picked or opened this session. Scopes last until
+//! the app quits.
+
+use crate::error::{CommandError, CommandResult};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct Scopes {
+    directories: Vec<PathBuf>,
+    files: HashSet<PathBuf>,
+    /// Folders where the user agreed to replace every existing export.
+    replaceable: Vec<PathBuf>,
+}
+
+#[derive(Default)]
+pub struct WriteScopes {
+    scopes: Mutex<Scopes>,
+}
+
+/// Resolves symlinks and `..` so a scope check compares real locations. The
+/// file itself may not exist yet, so only its parent is resolved.
+fn canonical_file_path(path: &Path) -> CommandResult<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| CommandError::InvalidPath(format!("invalid path: {}", path.display())))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let parent =
+        std::fs::canonicalize(parent).map_err(|err| CommandError::io("resolve folder", err))?;
+    Ok(parent.join(file_name))
+}
+
+impl WriteScopes {
+    fn lock(&self) -> CommandResult<std::sync::MutexGuard<'_, Scopes>> {
+        self.scopes
+            .lock()
+            .map_err(|_| CommandError::Internal("write scope state is poisoned".to_string()))
+    }
+
+    /// A folder picked in a native dialog: anything inside it may be created.
+    pub fn allow_directory(&self, directory: &Path) {
+        let directory = std::fs::canonicalize(directory).unwrap_or_else(|_| directory.into());
+        if let Ok(mut scopes) = self.lock() {
+            if !scopes.directories.contains(&directory) {
+                scopes.directories.push(directory);
+            }
+        }
+    }
+
+    /// A file picked in a save dialog (which already asked about replacing
+    /// it) or opened by the user: it may be written and replaced.
+    pub fn allow_file(&self, path: &Path) {
+        let path = canonical_file_path(path).unwrap_or_else(|_| path.into());
+        if let Ok(mut scopes) = self.lock() {
+            scopes.files.insert(path);
+        }
+    }
+
+    /// The user answered `Replace` for all remaining files of a batch
+    /// exported into `directory`: existing files inside it may be replaced.
+    pub fn allow_replacing(&self, directory: &Path) {
+        let directory = std::fs::canonicalize(directory).unwrap_or_else(|_| directory.into());
+        if let Ok(mut scopes) = self.lock() {
+            if !scopes.replaceable.contains(&directory) {
+                scopes.replaceable.push(directory);
+            }
+        }
+    }
+
+    fn denied(path: &Path) -> CommandError {
+        CommandError::PermissionDenied(format!(
+            "{} is outside the folders chosen for export",
+            path.display()
+        ))
+    }
+
+    /// Fails unless `directory` is inside a picked folder.
+    pub fn check_directory(&self, directory: &Path) -> CommandResult<()> {
+        let canonical = std::fs::canonicalize(directory)
+            .map_err(|err| CommandError::io("resolve export folder", err))?;
+        let scopes = self.lock()?;
+        if scopes
+            .directories
+            .iter()
+            .any(|allowed| canonical.starts_with(allowed))
+        {
+            Ok(())
+        } else {
+            Err(Self::denied(directory))
+        }
+    }
+
+    /// Fails unless `path` was picked or opened itself or sits inside a
+    /// picked folder. Enough to show or open an export; replacing a file
+    /// still needs [`WriteScopes::check_file`] or the user's confirmation.
+    pub fn check_within(&self, path: &Path) -> CommandResult<()> {
+        let canonical = canonical_file_path(path)?;
+        let scopes = self.lock()?;
+        let parent = canonical.parent().unwrap_or(&canonical);
+        if scopes.files.contains(&canonical)
+            || scopes
+                .directories
+                .iter()
+                .any(|allowed| parent.starts_with(allowed))
+        {
+            Ok(())
+        } else {
+            Err(Self::denied(path))
+        }
+    }
+
+    /// Fails unless `path` may be written: it was picked or opened itself,
+    /// or it is new and inside a picked folder. Replacing an existing file
+    /// that was never confirmed, alone or with the rest of its batch, is
+    /// refused with `AlreadyExists`.
+    pub fn check_file(&self, path: &Path) -> CommandResult<()> {
+        let canonical = canonical_file_path(path)?;
+        let scopes = self.lock()?;
+        if scopes.files.contains(&canonical) {
+            return Ok(());
+        }
+        let parent = canonical.parent().unwrap_or(&canonical);
+        if !scopes
+            .directories
+            .iter()
+            .any(|allowed| parent.starts_with(allowed))
+        {
+            return Err(Self::denied(path));
+        }
+        if std::fs::symlink_metadata(&canonical).is_ok()
+            && !scopes
+                .replaceable
+                .iter()
+                .any(|allowed| parent.starts_with(allowed))
+        {
+            return Err(CommandError::AlreadyExists(format!(
+                "{} already exists",
+                path.display()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WriteScopes;
+
+    #[test]
+    fn writes_stay_inside_picked_folders_and_files() {
+        let root = std::env::temp_dir().join(format!("nc-scope-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let picked = root.join("exports");
+        let elsewhere = root.join("documents");
+        std::fs::create_dir_all(picked.join("2024")).unwrap();
+        std::fs::create_dir_all(&elsewhere).unwrap();
+        std::fs::write(picked.join("old.tif"), b"keep").unwrap();
+        std::fs::write(elsewhere.join("notes.txt"), b"keep").unwrap();
+
+        let scopes = WriteScopes::default();
+        assert_eq!(
+            scopes
+                .check_file(&picked.join("new.tif"))
+                .unwrap_err()
+                .code(),
+            "PermissionDenied"
+        );
+
+        scopes.allow_directory(&picked);
+        scopes.check_directory(&picked.join("2024")).unwrap();
+        scopes
+            .check_file(&picked.join("2024").join("new.tif"))
+            .unwrap();
+        assert_eq!(
+            scopes
+                .check_file(&picked.join("old.tif"))
+                .unwrap_err()
+                .code(),
+            "AlreadyExists"
+        );
+        let escape = picked.join("2024").join("..").join("..").join("documents");
+        assert_eq!(
+            scopes.check_directory(&escape).unwrap_err().code(),
+            "PermissionDenied"
+        );
+        assert_eq!(
+            scopes
+                .check_file(&escape.join("notes.txt"))
+                .unwrap_err()
+                .code(),
+            "PermissionDenied"
+        );
+        scopes.check_within(&picked.join("old.tif")).unwrap();
+        assert_eq!(
+            scopes
+                .check_within(&escape.join("notes.txt"))
+                .unwrap_err()
+                .code(),
+            "PermissionDenied"
+        );
+
+        scopes.allow_file(&picked.join("old.tif"));
+        scopes.check_file(&picked.join("old.tif")).unwrap();
+        scopes.allow_file(&elsewhere.join("notes.txt"));
+        scopes.check_file(&elsewhere.join("notes.txt")).unwrap();
+        assert_eq!(
+            scopes
+                .check_file(&elsewhere.join("other.txt"))
+                .unwrap_err()
+                .code(),
+            "PermissionDenied"
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,704 @@
+//! Streaming ZIP archives for "Export All (ZIP)". The webview sends each
+//! encoded frame in raw-body chunks and they go straight to disk (deflated
+//! or stored), so an archive is bounded by disk space rather than webview
+//! memory. Entries use data descriptors so nothing is rewritten in place,
+//! and ZIP64 records are added once sizes, offsets or the entry count
+//! outgrow the classic format.
+
+use crate::atomic_write::{PartialFile, WriteReport};
+use crate::error::{CommandError, CommandResult};
+use crate::export_naming::sanitize_file_name;
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x0807_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const ZIP64_END_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const END_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+const VERSION_DEFAULT: u16 = 20;
+const VERSION_ZIP64: u16 = 45;
+/// Unix host, so the external attributes below carry file permissions.
+const VERSION_MADE_BY: u16 = (3 << 8) | VERSION_ZIP64;
+/// Sizes and CRC follow in a data descriptor; names are UTF-8.
+const FLAGS: u16 = 0x0008 | 0x0800;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+const REGULAR_FILE_ATTRIBUTES: u32 = 0o100_644 << 16;
+
+const MAX_U16: u64 = u16::MAX as u64;
+const MAX_U32: u64 = u32::MAX as u64;
+
+fn put_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Clamps a value to its 32-bit header field; ZIP64 extras carry the rest.
+fn field_u32(value: u64) -> u32 {
+    value.min(MAX_U32) as u32
+}
+
+/// MS-DOS time and date (UTC) for entry headers.
+fn dos_date_time(unix_seconds: u64) -> (u16, u16) {
+    let days = (unix_seconds / 86_400) as i64;
+    let seconds = unix_seconds % 86_400;
+    // Howard Hinnant's days-to-civil conversion
+    let shifted = days + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let time = ((seconds / 3_600) << 11) | (((seconds % 3_600) / 60) << 5) | ((seconds % 60) / 2);
+    let date = (((year - 1980).min(127) as u16) << 9) | ((month as u16) << 5) | day as u16;
+    (time as u16, date)
+}
+
+/// Turns a webview-supplied entry name into a safe relative path: `/`
+/// separators, no absolute paths or `..` (so extracting cannot escape the
+/// target folder), and each segment valid on every filesystem.
+pub fn sanitize_entry_name(name: &str) -> CommandResult<String> {
+    let mut segments = Vec::new();
+    for segment in name.split(['/', '\\']) {
+        if segment.trim() == ".." {
+            return Err(CommandError::InvalidPath(format!(
+                "ZIP entry must not leave the archive: {name}"
+            )));
+        }
+        let segment = sanitize_file_name(segment);
+        if !segment.is_empty() {
+            segments.push(segment);
+        }
+    }
+    if segments.is_empty() {
+        return Err(CommandError::InvalidPath(format!(
+            "ZIP entry name is empty: {name:?}"
+        )));
+    }
+    Ok(segments.join("/"))
+}
+
+struct CentralEntry {
+    name: String,
+    method: u16,
+    time: u16,
+    date: u16,
+    crc: u32,
+    compressed: u64,
+    uncompressed: u64,
+    header_offset: u64,
+}
+
+struct OpenEntry {
+    central: CentralEntry,
+    crc: Crc,
+    encoder: Option<DeflateEncoder<Vec<u8>>>,
+}
+
+/// Writes a ZIP archive to any `Write`, one entry at a time.
+pub struct ZipWriter<W: Write> {
+    out: W,
+    offset: u64,
+    deflate: bool,
+    entries: Vec<CentralEntry>,
+    names: HashSet<String>,
+    open: Option<OpenEntry>,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(out: W, deflate: bool) -> Self {
+        Self {
+            out,
+            offset: 0,
+            deflate,
+            entries: Vec::new(),
+            names: HashSet::new(),
+            open: None,
+        }
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.offset
+    }
+
+    fn emit(&mut self, bytes: &[u8]) -> CommandResult<()> {
+        self.out
+            .write_all(bytes)
+            .map_err(|err| CommandError::io("write ZIP archive", err))?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Appends `_1`, `_2`, … before the extension until the name is unused.
+    fn unique_name(&self, name: String) -> String {
+        if !self.names.contains(&name) {
+            return name;
+        }
+        let (base, extension) = match name.rfind('.') {
+            Some(dot) if dot > name.rfind('/').map_or(0, |slash| slash + 1) => name.split_at(dot),
+            _ => (name.as_str(), ""),
+        };
+        (1..)
+            .map(|index| format!("{base}_{index}{extension}"))
+            .find(|candidate| !self.names.contains(candidate))
+            .unwrap_or(name)
+    }
+
+    /// Starts a new entry (closing any open one) and returns its final name.
+    pub fn start_entry(&mut self, name: &str) -> CommandResult<String> {
+        self.finish_entry()?;
+        let name = self.unique_name(sanitize_entry_name(name)?);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let (time, date) = dos_date_time(now);
+        let method = if self.deflate {
+            METHOD_DEFLATED
+        } else {
+            METHOD_STORED
+        };
+
+        let mut header = Vec::with_capacity(30 + name.len());
+        put_u32(&mut header, LOCAL_HEADER_SIGNATURE);
+        put_u16(&mut header, VERSION_ZIP64);
+        put_u16(&mut header, FLAGS);
+        put_u16(&mut header, method);
+        put_u16(&mut header, time);
+        put_u16(&mut header, date);
+        put_u32(&mut header, 0); // CRC, sizes: in the data descriptor
+        put_u32(&mut header, 0);
+        put_u32(&mut header, 0);
+        put_u16(&mut header, name.len() as u16);
+        put_u16(&mut header, 0);
+        header.extend_from_slice(name.as_bytes());
+
+        let header_offset = self.offset;
+        self.emit(&header)?;
+        self.names.insert(name.clone());
+        self.open = Some(OpenEntry {
+            central: CentralEntry {
+                name: name.clone(),
+                method,
+                time,
+                date,
+                crc: 0,
+                compressed: 0,
+                uncompressed: 0,
+                header_offset,
+            },
+            crc: Crc::new(),
+            encoder: self
+                .deflate
+                .then(|| DeflateEncoder::new(Vec::new(), Compression::new(6))),
+        });
+        Ok(name)
+    }
+
+    pub fn write_entry(&mut self, bytes: &[u8]) -> CommandResult<()> {
+        let Some(mut entry) = self.open.take() else {
+            return Err(CommandError::Internal("no ZIP entry is open".to_string()));
+        };
+        entry.crc.update(bytes);
+        entry.central.uncompressed += bytes.len() as u64;
+        let result = match entry.encoder.as_mut() {
+            Some(encoder) => {
+                encoder
+                    .write_all(bytes)
+                    .map_err(|err| CommandError::Internal(format!("deflate failed: {err}")))?;
+                let compressed = std::mem::take(encoder.get_mut());
+                entry.central.compressed += compressed.len() as u64;
+                self.emit(&compressed)
+            }
+            None => {
+                entry.central.compressed += bytes.len() as u64;
+                self.emit(bytes)
+            }
+        };
+        self.open = Some(entry);
+        result
+    }
+
+    /// Closes the open entry, if any, with its data descriptor.
+    pub fn finish_entry(&mut self) -> CommandResult<()> {
+        let Some(mut entry) = self.open.take() else {
+            return Ok(());
+        };
+        if let Some(encoder) = entry.encoder.take() {
+            let tail = encoder
+                .finish()
+                .map_err(|err| CommandError::Internal(format!("deflate failed: {err}")))?;
+            entry.central.compressed += tail.len() as u64;
+            self.emit(&tail)?;
+        }
+        entry.central.crc = entry.crc.sum();
+
+        let central = entry.central;
+        let mut descriptor = Vec::with_capacity(24);
+        put_u32(&mut descriptor, DATA_DESCRIPTOR_SIGNATURE);
+        put_u32(&mut descriptor, central.crc);
+        if central.compressed >= MAX_U32 || central.uncompressed >= MAX_U32 {
+            put_u64(&mut descriptor, central.compressed);
+            put_u64(&mut descriptor, central.uncompressed);
+        } else {
+            put_u32(&mut descriptor, central.compressed as u32);
+            put_u32(&mut descriptor, central.uncompressed as u32);
+        }
+        self.emit(&descriptor)?;
+        self.entries.push(central);
+        Ok(())
+    }
+
+    /// Writes the central directory and returns the underlying writer.
+    pub fn finish(mut self) -> CommandResult<W> {
+        self.finish_entry()?;
+        let entries = std::mem::take(&mut self.entries);
+        let directory_offset = self.offset;
+
+        let mut directory = Vec::new();
+        for entry in &entries {
+            let mut extra = Vec::new();
+            if entry.uncompressed >= MAX_U32 {
+                put_u64(&mut extra, entry.uncompressed);
+            }
+            if entry.compressed >= MAX_U32 {
+                put_u64(&mut extra, entry.compressed);
+            }
+            if entry.header_offset >= MAX_U32 {
+                put_u64(&mut extra, entry.header_offset);
+            }
+            let zip64 = !extra.is_empty();
+
+            put_u32(&mut directory, CENTRAL_HEADER_SIGNATURE);
+            put_u16(&mut directory, VERSION_MADE_BY);
+            put_u16(
+                &mut directory,
+                if zip64 {
+                    VERSION_ZIP64
+                } else {
+                    VERSION_DEFAULT
+                },
+            );
+            put_u16(&mut directory, FLAGS);
+            put_u16(&mut directory, entry.method);
+            put_u16(&mut directory, entry.time);
+            put_u16(&mut directory, entry.date);
+            put_u32(&mut directory, entry.crc);
+            put_u32(&mut directory, field_u32(entry.compressed));
+            put_u32(&mut directory, field_u32(entry.uncompressed));
+            put_u16(&mut directory, entry.name.len() as u16);
+            put_u16(
+                &mut directory,
+                if zip64 { 4 + extra.len() as u16 } else { 0 },
+            );
+            put_u16(&mut directory, 0); // comment
+            put_u16(&mut directory, 0); // disk
+            put_u16(&mut directory, 0); // internal attributes
+            put_u32(&mut directory, REGULAR_FILE_ATTRIBUTES);
+            put_u32(&mut directory, field_u32(entry.header_offset));
+            directory.extend_from_slice(entry.name.as_bytes());
+            if zip64 {
+                put_u16(&mut directory, ZIP64_EXTRA_ID);
+                put_u16(&mut directory, extra.len() as u16);
+                directory.extend_from_slice(&extra);
+            }
+        }
+        self.emit(&directory)?;
+
+        let count = entries.len() as u64;
+        let directory_size = directory.len() as u64;
+        let mut end = Vec::new();
+        if count >= MAX_U16 || directory_size >= MAX_U32 || directory_offset >= MAX_U32 {
+            let zip64_end_offset = self.offset;
+            put_u32(&mut end, ZIP64_END_SIGNATURE);
+            put_u64(&mut end, 44);
+            put_u16(&mut end, VERSION_MADE_BY);
+            put_u16(&mut end, VERSION_ZIP64);
+            put_u32(&mut end, 0);
+            put_u32(&mut end, 0);
+            put_u64(&mut end, count);
+            put_u64(&mut end, count);
+            put_u64(&mut end, directory_size);
+            put_u64(&mut end, directory_offset);
+
+            put_u32(&mut end, ZIP64_LOCATOR_SIGNATURE);
+            put_u32(&mut end, 0);
+            put_u64(&mut end, zip64_end_offset);
+            put_u32(&mut end, 1);
+        }
+        put_u32(&mut end, END_SIGNATURE);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, count.min(MAX_U16) as u16);
+        put_u16(&mut end, count.min(MAX_U16) as u16);
+        put_u32(&mut end, field_u32(directory_size));
+        put_u32(&mut end, field_u32(directory_offset));
+        put_u16(&mut end, 0);
+        self.emit(&end)?;
+
+        self.out
+            .flush()
+            .map_err(|err| CommandError::io("write ZIP archive", err))?;
+        Ok(self.out)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// An archive being written and the name the webview used for its open
+/// entry, which may differ from the entry's final (deduplicated) name.
+struct ZipSession {
+    writer: ZipWriter<PartialFile>,
+    open_name: Option<String>,
+}
+
+/// Archives being written, keyed by the id `begin_zip_export` hands out.
+#[derive(Default)]
+pub struct ZipExports {
+    next_id: AtomicU64,
+    sessions: Mutex<HashMap<u64, ZipSession>>,
+    /// Chunks still in flight when the webview aborts an archive are reported
+    /// as `Cancelled` rather than as an unknown archive. Forgotten once a
+    /// later archive finishes.
+    aborted: Mutex<HashSet<u64>>,
+}
+
+impl ZipExports {
+    pub fn begin(&self, path: PathBuf, deflate: bool) -> CommandResult<u64> {
+        let writer = ZipWriter::new(PartialFile::create(path)?, deflate);
+        let zip_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.lock()?.insert(
+            zip_id,
+            ZipSession {
+                writer,
+                open_name: None,
+            },
+        );
+        Ok(zip_id)
+    }
+
+    /// Appends a chunk to entry `name`, starting it if it is not the open
+    /// entry. `last` closes the entry. Returns the archive size so far.
+    pub fn add(&self, zip_id: u64, name: &str, chunk: &[u8], last: bool) -> CommandResult<u64> {
+        let mut sessions = self.lock()?;
+        let session = sessions
+            .get_mut(&zip_id)
+            .ok_or_else(|| self.missing(zip_id))?;
+        // Compared with the requested name, not the final one: a duplicate
+        // name's entry is stored as `name_1`, yet its chunks keep `name`.
+        if session.open_name.as_deref() != Some(name) {
+            session.open_name = None;
+            session.writer.start_entry(name)?;
+            session.open_name = Some(name.to_string());
+        }
+        session.writer.write_entry(chunk)?;
+        if last {
+            session.open_name = None;
+            session.writer.finish_entry()?;
+        }
+        Ok(session.writer.bytes_written())
+    }
+
+    /// Writes the central directory and moves the archive into place.
+    pub fn finish(&self, zip_id: u64, verify: bool) -> CommandResult<(PathBuf, WriteReport)> {
+        let session = self.take(zip_id)?;
+        if let Ok(mut aborted) = self.aborted.lock() {
+            aborted.retain(|id| *id > zip_id);
+        }
+        let file = session.writer.finish()?;
+        let path = file.final_path().to_path_buf();
+        Ok((path, file.commit(verify)?))
+    }
+
+    pub fn abort(&self, zip_id: u64) -> CommandResult<()> {
+        let session = self.take(zip_id)?;
+        if let Ok(mut aborted) = self.aborted.lock() {
+            aborted.insert(zip_id);
+        }
+        session.writer.into_inner().abort()
+    }
+
+    /// Removes the temp files of every archive still open; run when the app
+    /// exits so abandoned exports leave nothing behind.
+    pub fn abort_all(&self) {
+        let sessions: Vec<ZipSession> = match self.sessions.lock() {
+            Ok(mut sessions) => sessions.drain().map(|(_, session)| session).collect(),
+            Err(_) => return,
+        };
+        for session in sessions {
+            let _ = session.writer.into_inner().abort();
+        }
+    }
+
+    fn lock(&self) -> CommandResult<std::sync::MutexGuard<'_, HashMap<u64, ZipSession>>> {
+        self.sessions
+            .lock()
+            .map_err(|_| CommandError::Internal("ZIP export state is poisoned".to_string()))
+    }
+
+    fn take(&self, zip_id: u64) -> CommandResult<ZipSession> {
+        self.lock()?
+            .remove(&zip_id)
+            .ok_or_else(|| self.missing(zip_id))
+    }
+
+    fn missing(&self, zip_id: u64) -> CommandError {
+        let aborted = self
+            .aborted
+            .lock()
+            .map(|aborted| aborted.contains(&zip_id))
+            .unwrap_or(false);
+        if aborted {
+            CommandError::Cancelled(format!("ZIP export {zip_id} was cancelled"))
+        } else {
+            CommandError::Internal(format!("unknown ZIP export: {zip_id}"))
+        }
+    }
+}
+
+impl Drop for ZipExports {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dos_date_time, sanitize_entry_name, ZipExports, ZipWriter};
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], at: usize) -> u64 {
+        u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+    }
+
+    /// Reads an archive through its central directory, the way unzip does.
+    fn read_archive(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let end = bytes.len() - 22;
+        assert_eq!(u32_at(bytes, end), 0x0605_4b50);
+        let mut count = u64::from(u16_at(bytes, end + 10));
+        let mut directory = u64::from(u32_at(bytes, end + 16));
+        if count == 0xFFFF || directory == 0xFFFF_FFFF {
+            let locator = end - 20;
+            assert_eq!(u32_at(bytes, locator), 0x0706_4b50);
+            let zip64_end = u64_at(bytes, locator + 8) as usize;
+            assert_eq!(u32_at(bytes, zip64_end), 0x0606_4b50);
+            count = u64_at(bytes, zip64_end + 32);
+            directory = u64_at(bytes, zip64_end + 48);
+        }
+
+        let mut at = directory as usize;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            assert_eq!(u32_at(bytes, at), 0x0201_4b50);
+            let method = u16_at(bytes, at + 10);
+            let crc = u32_at(bytes, at + 16);
+            let compressed = u32_at(bytes, at + 20) as usize;
+            let name_len = u16_at(bytes, at + 28) as usize;
+            let extra_len = u16_at(bytes, at + 30) as usize;
+            let offset = u32_at(bytes, at + 42) as usize;
+            let name = String::from_utf8(bytes[at + 46..at + 46 + name_len].to_vec()).unwrap();
+
+            assert_eq!(u32_at(bytes, offset), 0x0403_4b50);
+            let data = offset + 30 + u16_at(bytes, offset + 26) as usize;
+            let raw = &bytes[data..data + compressed];
+            let content = if method == 8 {
+                let mut inflated = Vec::new();
+                DeflateDecoder::new(raw).read_to_end(&mut inflated).unwrap();
+                inflated
+            } else {
+                raw.to_vec()
+            };
+            let mut check = flate2::Crc::new();
+            check.update(&content);
+            assert_eq!(check.sum(), crc, "{name}");
+            assert_eq!(u32_at(bytes, data + compressed), 0x0807_4b50);
+
+            entries.push((name, content));
+            at += 46 + name_len + extra_len;
+        }
+        entries
+    }
+
+    #[test]
+    fn stored_and_deflated_entries_round_trip() {
+        for deflate in [false, true] {
+            let mut zip = ZipWriter::new(Vec::new(), deflate);
+            assert_eq!(zip.start_entry("roll/01.tif").unwrap(), "roll/01.tif");
+            zip.write_entry(&[7u8; 70_000]).unwrap();
+            zip.write_entry(b"tail").unwrap();
+            assert_eq!(zip.start_entry("roll\\01.tif").unwrap(), "roll/01_1.tif");
+            zip.write_entry(b"second").unwrap();
+            zip.start_entry("empty.png").unwrap();
+            let bytes = zip.finish().unwrap();
+
+            let entries = read_archive(&bytes);
+            let mut first = vec![7u8; 70_000];
+            first.extend_from_slice(b"tail");
+            assert_eq!(entries[0], ("roll/01.tif".to_string(), first));
+            assert_eq!(
+                entries[1],
+                ("roll/01_1.tif".to_string(), b"second".to_vec())
+            );
+            assert_eq!(entries[2], ("empty.png".to_string(), Vec::new()));
+            if deflate {
+                assert!(bytes.len() < 10_000);
+            }
+        }
+    }
+
+    #[test]
+    fn many_entries_switch_to_zip64_records() {
+        let mut zip = ZipWriter::new(Vec::new(), false);
+        for index in 0..70_000u32 {
+            zip.start_entry(&format!("{index}.png")).unwrap();
+            zip.write_entry(&index.to_le_bytes()).unwrap();
+        }
+        let entries = read_archive(&zip.finish().unwrap());
+        assert_eq!(entries.len(), 70_000);
+        assert_eq!(
+            entries[69_999],
+            ("69999.png".to_string(), 69_999u32.to_le_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn entry_names_cannot_escape_the_archive() {
+        assert_eq!(
+            sanitize_entry_name("/2024/roll/01.tif").unwrap(),
+            "2024/roll/01.tif"
+        );
+        assert_eq!(sanitize_entry_name("a/./b:c.png").unwrap(), "a/b_c.png");
+        assert_eq!(
+            sanitize_entry_name("../x.png").unwrap_err().code(),
+            "InvalidPath"
+        );
+        assert_eq!(sanitize_entry_name("//").unwrap_err().code(), "InvalidPath");
+    }
+
+    #[test]
+    fn dos_timestamps_use_the_civil_calendar() {
+        // 2024-02-29 13:45:30 UTC
+        let (time, date) = dos_date_time(1_709_214_330);
+        assert_eq!(time, (13 << 11) | (45 << 5) | 15);
+        assert_eq!(date, (44 << 9) | (2 << 5) | 29);
+    }
+
+    #[test]
+    fn sessions_stream_chunks_into_an_archive_on_disk() {
+        let dir = std::env::temp_dir().join(format!("nc-zip-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let zips = ZipExports::default();
+
+        let id = zips.begin(dir.join("roll.zip"), true).unwrap();
+        zips.add(id, "01.tif", b"first ", false).unwrap();
+        zips.add(id, "01.tif", b"frame", true).unwrap();
+        zips.add(id, "02.tif", b"second", true).unwrap();
+        let (path, report) = zips.finish(id, true).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(report.bytes_written, bytes.len() as u64);
+        let entries = read_archive(&bytes);
+        assert_eq!(entries[0], ("01.tif".to_string(), b"first frame".to_vec()));
+        assert_eq!(entries[1], ("02.tif".to_string(), b"second".to_vec()));
+
+        let id = zips.begin(dir.join("aborted.zip"), false).unwrap();
+        zips.add(id, "01.tif", b"partial", false).unwrap();
+        zips.abort(id).unwrap();
+        assert_eq!(
+            zips.add(id, "01.tif", b"late", true).unwrap_err().code(),
+            "Cancelled"
+        );
+        assert!(!dir.join("aborted.zip").exists());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn duplicate_names_keep_their_chunks_in_one_entry() {
+        let dir = std::env::temp_dir().join(format!("nc-zip-dup-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let zips = ZipExports::default();
+
+        let id = zips.begin(dir.join("roll.zip"), false).unwrap();
+        for (index, chunk) in [b"one ", b"two ", b"end!"].iter().enumerate() {
+            zips.add(id, "a.tif", *chunk, index == 2).unwrap();
+        }
+        for (index, chunk) in [b"ONE ", b"TWO ", b"END!"].iter().enumerate() {
+            zips.add(id, "a.tif", *chunk, index == 2).unwrap();
+        }
+        let (path, _) = zips.finish(id, false).unwrap();
+        let entries = read_archive(&std::fs::read(&path).unwrap());
+        assert_eq!(
+            entries,
+            vec![
+                ("a.tif".to_string(), b"one two end!".to_vec()),
+                ("a_1.tif".to_string(), b"ONE TWO END!".to_vec()),
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tombstones_are_pruned_and_open_archives_removed_on_drop() {
+        let dir = std::env::temp_dir().join(format!("nc-zip-drop-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let zips = ZipExports::default();
+
+        let aborted = zips.begin(dir.join("aborted.zip"), false).unwrap();
+        zips.abort(aborted).unwrap();
+        let finished = zips.begin(dir.join("finished.zip"), false).unwrap();
+        zips.finish(finished, false).unwrap();
+        assert!(zips.aborted.lock().unwrap().is_empty());
+
+        let open = zips.begin(dir.join("open.zip"), false).unwrap();
+        zips.add(open, "01.tif", b"partial", false).unwrap();
+        drop(zips);
+        let names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("finished.zip")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}